@@ -0,0 +1,230 @@
+//! Audit log: one JSON line per mutating action (push, PR creation, container start/stop,
+//! config change) in `<config dir>/audit.jsonl`. Entries are only ever appended, so the
+//! file answers "who changed what, and from which run" on a shared smith host.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_dir;
+use crate::error::SmithError;
+use crate::events::SmithEvent;
+
+/// Action names written to `audit.jsonl` (and accepted by `smith audit show --action`).
+pub const AUDIT_ACTIONS: &[&str] = &[
+    "push",
+    "pr_create",
+    "container_start",
+    "container_stop",
+    "container_restart",
+    "container_remove",
+    "config_change",
+    "share",
+    "issue_create",
+];
+
+/// Run id of the pipeline run this process is executing, attached to its entries.
+static CURRENT_RUN: Mutex<Option<String>> = Mutex::new(None);
+
+/// One audited action.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AuditEntry {
+    /// Unix seconds.
+    pub at: u64,
+    /// `user@host` that ran smith.
+    pub user: String,
+    pub action: String,
+    /// What was acted on: container name, `project/branch`, PR URL or config file.
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Path of the audit log.
+pub fn audit_path() -> Result<PathBuf, SmithError> {
+    config_dir().map(|dir| dir.join("audit.jsonl"))
+}
+
+/// Attach `run_id` to the entries recorded from now on.
+pub fn set_current_run(run_id: &str) {
+    if let Ok(mut current) = CURRENT_RUN.lock() {
+        *current = Some(run_id.to_string());
+    }
+}
+
+/// Append an entry. Auditing never fails the action itself; write errors are logged.
+/// Nothing is recorded while replaying (`--replay`) or simulating (`--simulate`): the actions
+/// are not real.
+pub fn record(action: &str, target: &str, detail: Option<String>) {
+    if crate::replay::replaying() || crate::simulate::active() {
+        return;
+    }
+    let entry = AuditEntry {
+        at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        user: current_user(),
+        action: action.to_string(),
+        target: target.to_string(),
+        run_id: CURRENT_RUN.lock().ok().and_then(|r| r.clone()),
+        detail,
+    };
+    if let Err(e) = append(&entry) {
+        tracing::warn!("failed to write audit log: {}", e);
+    }
+}
+
+/// Audit the lifecycle events that change a remote: pushes and opened pull requests.
+pub(crate) fn record_event(event: &SmithEvent) {
+    match event {
+        SmithEvent::PushPerformed {
+            project,
+            branch,
+            commit,
+        } => record(
+            "push",
+            &format!("{}/{}", project, branch),
+            Some(format!("commit {}", commit)),
+        ),
+        SmithEvent::PrOpened {
+            project,
+            branch,
+            url,
+        } => record(
+            "pr_create",
+            url,
+            Some(match project {
+                Some(project) => format!("{}/{}", project, branch),
+                None => branch.clone(),
+            }),
+        ),
+        _ => {}
+    }
+}
+
+fn append(entry: &AuditEntry) -> Result<(), SmithError> {
+    let path = audit_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            SmithError::Config(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    let line = serde_json::to_string(entry)
+        .map_err(|e| SmithError::Config(format!("Failed to serialize audit entry: {}", e)))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| SmithError::Config(format!("Failed to open {}: {}", path.display(), e)))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| SmithError::Config(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// All audit entries, oldest first. A missing file is an empty log; unreadable lines are skipped.
+pub fn load() -> Result<Vec<AuditEntry>, SmithError> {
+    let path = audit_path()?;
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(SmithError::Config(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// "2026-10-17 15:04:05 UTC" for unix seconds.
+pub fn format_utc(secs: u64) -> String {
+    let (year, month, day) = crate::budget::civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+/// `user@host`; the invoking user when run under sudo.
+fn current_user() -> String {
+    let user = ["SUDO_USER", "USER", "USERNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut buf = [0u8; 256];
+    // SAFETY: buf is valid for buf.len() bytes; gethostname NUL-terminates on success.
+    let host = if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } == 0 {
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        String::from_utf8_lossy(&buf[..end]).to_string()
+    } else {
+        String::new()
+    };
+    if host.is_empty() {
+        user
+    } else {
+        format!("{}@{}", user, host)
+    }
+}
+
+/// Top-level config keys whose value differs between `old` and `new`; arrays of named tables
+/// (projects, agents, ...) are compared per entry ("projects.web").
+pub fn changed_config_keys(old: &toml::Value, new: &toml::Value) -> Vec<String> {
+    let empty = toml::map::Map::new();
+    let old = old.as_table().unwrap_or(&empty);
+    let new = new.as_table().unwrap_or(&empty);
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let mut changed = Vec::new();
+    for key in keys {
+        let (before, after) = (old.get(key), new.get(key));
+        if before == after {
+            continue;
+        }
+        let named = |v: Option<&toml::Value>| -> Option<Vec<(String, toml::Value)>> {
+            v.map_or(Some(Vec::new()), |v| {
+                v.as_array()?
+                    .iter()
+                    .map(|item| Some((item.get("name")?.as_str()?.to_string(), item.clone())))
+                    .collect()
+            })
+        };
+        match (named(before), named(after)) {
+            (Some(before), Some(after)) => {
+                let mut names: Vec<&String> =
+                    before.iter().chain(after.iter()).map(|(n, _)| n).collect();
+                names.sort();
+                names.dedup();
+                for name in names {
+                    let find = |items: &[(String, toml::Value)]| {
+                        items
+                            .iter()
+                            .find(|(n, _)| n == name)
+                            .map(|(_, v)| v.clone())
+                    };
+                    if find(&before) != find(&after) {
+                        changed.push(format!("{}.{}", key, name));
+                    }
+                }
+            }
+            _ => changed.push(key.clone()),
+        }
+    }
+    changed
+}
@@ -0,0 +1,151 @@
+//! Spend budgets: sums the cost of recorded runs (see `history`) over the current calendar
+//! week or month for each `[[budgets]]` entry that covers a project or provider.
+
+use crate::config::BudgetConfig;
+use crate::history::RunRecord;
+
+/// Accepted values for `budgets[].period`.
+pub const BUDGET_PERIODS: &[&str] = &["weekly", "monthly"];
+
+/// Default warning threshold, in percent of the limit.
+const DEFAULT_WARN_PERCENT: u8 = 80;
+
+/// Spend of one budget in its current period.
+#[derive(Clone)]
+pub struct BudgetStatus {
+    pub budget: BudgetConfig,
+    /// Start of the current period (unix seconds, UTC).
+    pub period_start: u64,
+    /// USD spent since `period_start`.
+    pub spent: f64,
+}
+
+impl BudgetStatus {
+    /// Human label: "project web", "provider anthropic" or "all runs", plus the period.
+    pub fn label(&self) -> String {
+        let scope = match (&self.budget.project, &self.budget.provider) {
+            (Some(project), Some(provider)) => format!("project {} / {}", project, provider),
+            (Some(project), None) => format!("project {}", project),
+            (None, Some(provider)) => format!("provider {}", provider),
+            (None, None) => "all runs".to_string(),
+        };
+        format!("{} budget for {}", self.budget.period, scope)
+    }
+
+    pub fn percent(&self) -> f64 {
+        if self.budget.limit <= 0.0 {
+            return 100.0;
+        }
+        self.spent / self.budget.limit * 100.0
+    }
+
+    pub fn warn_percent(&self) -> u8 {
+        self.budget.warn_percent.unwrap_or(DEFAULT_WARN_PERCENT)
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.spent >= self.budget.limit
+    }
+
+    /// Past the limit with `hard_cap = true`: new runs are refused.
+    pub fn blocks_runs(&self) -> bool {
+        self.exceeded() && self.budget.hard_cap == Some(true)
+    }
+
+    /// Threshold crossed when spend grew from `previous` to the current amount:
+    /// "limit", "warning" or None.
+    pub fn crossed(&self, previous: f64) -> Option<&'static str> {
+        let warn_at = self.budget.limit * f64::from(self.warn_percent()) / 100.0;
+        if previous < self.budget.limit && self.exceeded() {
+            Some("limit")
+        } else if previous < warn_at && self.spent >= warn_at {
+            Some("warning")
+        } else {
+            None
+        }
+    }
+}
+
+/// Provider of a `provider/model` id ("anthropic/claude-sonnet-4-5" -> "anthropic").
+pub fn model_provider(model: &str) -> Option<&str> {
+    model.split_once('/').map(|(provider, _)| provider)
+}
+
+/// Whether `budget` covers a run of `project` on `providers` (an empty list matches no provider budget).
+pub fn budget_applies(budget: &BudgetConfig, project: &str, providers: &[&str]) -> bool {
+    budget.project.as_deref().is_none_or(|p| p == project)
+        && budget
+            .provider
+            .as_deref()
+            .is_none_or(|p| providers.contains(&p))
+}
+
+/// Start of the period containing `now`: Monday 00:00 UTC for "weekly", the 1st 00:00 UTC otherwise.
+pub fn period_start(period: &str, now: u64) -> u64 {
+    let days = now / 86_400;
+    if period == "weekly" {
+        // 1970-01-01 was a Thursday (Monday = 0 -> Thursday = 3).
+        return (days - (days + 3) % 7) * 86_400;
+    }
+    let (year, month, _) = civil_from_days(days as i64);
+    days_from_civil(year, month, 1) as u64 * 86_400
+}
+
+/// Status of every budget that covers a run of `project` on `providers`.
+pub fn budget_statuses(
+    budgets: &[BudgetConfig],
+    project: &str,
+    providers: &[&str],
+    runs: &[RunRecord],
+    now: u64,
+) -> Vec<BudgetStatus> {
+    budgets
+        .iter()
+        .filter(|b| budget_applies(b, project, providers))
+        .map(|b| status(b, runs, now))
+        .collect()
+}
+
+/// Spend of `budget` in the period containing `now`.
+pub fn status(budget: &BudgetConfig, runs: &[RunRecord], now: u64) -> BudgetStatus {
+    let start = period_start(&budget.period, now);
+    let spent = runs
+        .iter()
+        .filter(|r| r.finished_at >= start)
+        .filter(|r| {
+            let providers: Vec<&str> = r.models.iter().filter_map(|m| model_provider(m)).collect();
+            budget_applies(budget, &r.project, &providers)
+        })
+        .map(|r| r.cost)
+        .sum();
+    BudgetStatus {
+        budget: budget.clone(),
+        period_start: start,
+        spent,
+    }
+}
+
+/// Days since 1970-01-01 to (year, month, day) in the proleptic Gregorian calendar.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// (year, month, day) to days since 1970-01-01.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
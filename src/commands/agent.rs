@@ -1,5 +1,6 @@
 use crate::*;
 
+mod expose;
 mod lifecycle;
 
 pub async fn handle(cmd: AgentCommands) {
@@ -0,0 +1,201 @@
+use crate::*;
+use smith_core::proxy;
+use std::net::SocketAddr;
+
+pub async fn handle(cmd: AgentCommands) {
+    let AgentCommands::Expose {
+        project,
+        branch,
+        model,
+        listen,
+        token,
+        tls_cert,
+        tls_key,
+        hosts,
+        insecure,
+    } = cmd
+    else {
+        return;
+    };
+    let cfg = load_config().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let target = match model {
+        Some(name) => model_upstream(&cfg, &name),
+        None => spawned_upstream(&cfg, project, branch),
+    };
+    let (label, upstream) = target.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let listen: SocketAddr = listen.parse().unwrap_or_else(|_| {
+        eprintln!(
+            "Error: Invalid --listen '{}': expected IP:PORT (e.g. 0.0.0.0:8443)",
+            listen
+        );
+        std::process::exit(1);
+    });
+
+    let env_token = std::env::var("SMITH_EXPOSE_TOKEN")
+        .ok()
+        .filter(|t| !t.trim().is_empty());
+    let (token, generated) = match token.or(env_token) {
+        Some(t) => (t.trim().to_string(), false),
+        None => (
+            proxy::generate_token().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }),
+            true,
+        ),
+    };
+
+    let cert = if insecure {
+        None
+    } else {
+        let (cert, key) = match (tls_cert, tls_key) {
+            (Some(cert), Some(key)) => (cert, key),
+            _ => config_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| proxy::self_signed_cert(&dir.join("expose"), &hosts))
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }),
+        };
+        let acceptor = proxy::tls_acceptor(&cert, &key).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        Some((cert, acceptor))
+    };
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error: Failed to listen on {}: {}", listen, e);
+            std::process::exit(1);
+        });
+    let scheme = if cert.is_some() { "https" } else { "http" };
+    println!(
+        "  {} Exposing {} ({}) at {}://{}",
+        BULLET_GREEN, label, upstream, scheme, listen
+    );
+    if generated {
+        println!("       Token: {}", token);
+    } else {
+        println!("       Token: from --token / $SMITH_EXPOSE_TOKEN");
+    }
+    match &cert {
+        Some((path, _)) => {
+            let fingerprint = proxy::cert_fingerprint(path).unwrap_or_else(|| "unknown".into());
+            println!("       Certificate: {}", path.display());
+            println!("       SHA-256: {}", fingerprint);
+        }
+        None => println!(
+            "       {} TLS disabled (--insecure): the token travels in clear text",
+            BULLET_YELLOW
+        ),
+    }
+    println!(
+        "       Clients send `Authorization: Bearer <token>`; browsers open {}://<host>:{}/?token=<token>",
+        scheme,
+        listen.port()
+    );
+    println!("       Press Ctrl+C to stop.");
+    audit::record(
+        "agent_expose",
+        &label,
+        Some(format!("{}://{} -> {}", scheme, listen, upstream)),
+    );
+
+    let options = proxy::ExposeOptions {
+        upstream,
+        token,
+        tls: cert.map(|(_, acceptor)| acceptor),
+    };
+    if let Err(e) = proxy::serve(listener, std::sync::Arc::new(options)).await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// A model profile's running container: its configured port on the profile's bind address.
+fn model_upstream(cfg: &SmithConfig, name: &str) -> Result<(String, SocketAddr), String> {
+    let agents = cfg.agents.as_deref().unwrap_or_default();
+    let (idx, entry) = agents
+        .iter()
+        .enumerate()
+        .find(|(_, a)| a.name == name)
+        .ok_or_else(|| format!("Model profile '{}' not found", name))?;
+    let running = docker::list_running_agent_containers().map_err(|e| e.to_string())?;
+    if !running.iter().any(|r| r == name) {
+        return Err(format!(
+            "Model profile '{}' is not running. Start it with `smith model start`.",
+            name
+        ));
+    }
+    let host = docker::probe_address(docker::agent_bind_address(cfg, Some(entry)));
+    Ok((
+        format!("model profile '{}'", name),
+        SocketAddr::new(host, agent_port(entry, idx)),
+    ))
+}
+
+/// A running spawned agent for project/branch (auto-detected like `smith agent logs`).
+fn spawned_upstream(
+    cfg: &SmithConfig,
+    project: Option<String>,
+    branch: Option<String>,
+) -> Result<(String, SocketAddr), String> {
+    let project = match project {
+        Some(p) => p,
+        None => detect_or_pick_project()?.ok_or(
+            "No project specified and none detected from current directory. Use --project.",
+        )?,
+    };
+    let branch = match branch {
+        Some(b) => b,
+        None => {
+            let out = Command::new("git")
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .ok_or("No branch specified and failed to detect from git. Use --branch.")?;
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        }
+    };
+    let proj = cfg
+        .projects
+        .iter()
+        .find(|p| p.name == project)
+        .ok_or_else(|| format!("Project '{}' not found", project))?;
+    if proj.backend.as_deref() == Some("kubernetes") {
+        return Err(format!(
+            "Project '{}' runs agents on Kubernetes; expose them with kubectl port-forward instead",
+            project
+        ));
+    }
+    if docker::spawned_container_running(&project, &branch).map_err(|e| e.to_string())?
+        != Some(true)
+    {
+        return Err(format!(
+            "No running agent for {}:{}. Start it with `smith agent start`.",
+            project, branch
+        ));
+    }
+    let name = docker::spawn_container_name(&project, &branch);
+    let port = docker::list_spawned_containers()?
+        .into_iter()
+        .find(|c| c.container_name == name)
+        .map(|c| c.port)
+        .filter(|p| *p != 0)
+        .ok_or_else(|| format!("Could not determine the port of {}", name))?;
+    let host = docker::HealthCheck::for_project(&project).host;
+    Ok((
+        format!("agent {}:{}", project, branch),
+        SocketAddr::new(host, port),
+    ))
+}
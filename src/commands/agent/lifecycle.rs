@@ -1,4 +1,5 @@
 use crate::*;
+use std::io::IsTerminal;
 
 pub async fn handle(cmd: AgentCommands) {
     match cmd {
@@ -10,7 +11,7 @@ pub async fn handle(cmd: AgentCommands) {
             // Auto-detect project from cwd if not provided
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     Ok(None) => {
                         eprintln!("Error: No project specified and none detected from current directory. Use --project or run from a git repo that matches a configured project.");
@@ -55,27 +56,18 @@ pub async fn handle(cmd: AgentCommands) {
                 .ok_or_else(|| format!("Project '{}' not found", project))
                 .unwrap();
 
-            let injected_env = if proj.model.is_some() {
-                let model_profile = resolve_project_model_profile(Some(proj)).unwrap_or_else(|e| {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                });
-                resolve_agent_env_bindings(model_profile.env.as_ref()).unwrap_or_else(|e| {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                })
-            } else {
-                Vec::new()
-            };
+            let injected_env = spawned_agent_env(proj).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
 
-            let image = proj
-                .image
-                .clone()
-                .unwrap_or_else(|| DEFAULT_AGENT_IMAGE.to_string());
+            let image = resolve_project_image(proj).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
             let repo = proj.repo.clone();
             let ssh_key = proj.ssh_key.as_ref().map(PathBuf::from);
-            let commit_name = proj.commit_name.clone();
-            let commit_email = proj.commit_email.clone();
+            let (commit_name, commit_email) = resolve_commit_author(Some(proj));
 
             // Determine port
             let final_port = match port {
@@ -90,6 +82,17 @@ pub async fn handle(cmd: AgentCommands) {
             println!("       Image: {}", image);
             println!("       Repo: {}", repo);
             println!("       Port: {}", final_port);
+            if let Some(servers) = injected_env
+                .iter()
+                .find(|(k, _)| k == smith_core::opencode::CONFIG_CONTENT_ENV)
+                .and_then(|(_, v)| serde_json::from_str::<Value>(v).ok())
+            {
+                let names: Vec<&str> = servers["mcp"]
+                    .as_object()
+                    .map(|m| m.keys().map(String::as_str).collect())
+                    .unwrap_or_default();
+                println!("       MCP servers: {}", names.join(", "));
+            }
             if !injected_env.is_empty() {
                 let keys = injected_env
                     .iter()
@@ -118,6 +121,12 @@ pub async fn handle(cmd: AgentCommands) {
                 }
             }
 
+            let run_args = spawn_run_args(&project, &branch, proj.persist_workspace == Some(true))
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
             match docker::start_spawned_container(
                 &project,
                 &branch,
@@ -128,7 +137,17 @@ pub async fn handle(cmd: AgentCommands) {
                 commit_name.as_deref(),
                 commit_email.as_deref(),
                 &injected_env,
-            ) {
+                &run_args,
+            )
+            .await
+            {
+                Ok(_) if proj.backend.as_deref() == Some("kubernetes") => {
+                    println!(
+                        "  {} Agent ready in pod {}",
+                        BULLET_GREEN,
+                        docker::spawn_pod_name(&project, &branch)
+                    );
+                }
                 Ok(actual_port) => {
                     let url = clickable_agent_url(actual_port);
                     println!("  {} Agent ready at {}", BULLET_GREEN, url);
@@ -179,7 +198,7 @@ pub async fn handle(cmd: AgentCommands) {
                 // Stop specific agent - auto-detect project/branch if not provided
                 let project = match project {
                     Some(p) => p,
-                    None => match detect_project_from_cwd() {
+                    None => match detect_or_pick_project() {
                         Ok(Some(name)) => name,
                         _ => {
                             eprintln!("Error: --project required (or use --all to stop all)");
@@ -222,7 +241,7 @@ pub async fn handle(cmd: AgentCommands) {
             // Auto-detect project and branch if not provided
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     _ => {
                         eprintln!("Error: --project required");
@@ -269,13 +288,14 @@ pub async fn handle(cmd: AgentCommands) {
         AgentCommands::Run {
             project,
             branch,
-            verbose,
             prompt,
+            no_index,
         } => {
+            let verbose = logging::verbose();
             // Auto-detect project and branch if not provided
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     _ => {
                         eprintln!("Error: --project required");
@@ -301,6 +321,11 @@ pub async fn handle(cmd: AgentCommands) {
                 }
             };
 
+            let prompt = if no_index {
+                prompt
+            } else {
+                commands::index::augment_with_index(&project, &prompt).await
+            };
             match docker::run_prompt_in_spawned_container(&project, &branch, &prompt, verbose) {
                 Ok(()) => {}
                 Err(e) => {
@@ -309,32 +334,81 @@ pub async fn handle(cmd: AgentCommands) {
                 }
             }
         }
-        AgentCommands::List => match docker::list_spawned_containers() {
-            Ok(containers) => {
+        AgentCommands::List { table } => match docker::list_spawned_containers() {
+            Ok(local) => {
+                let nodes = load_config().map(|cfg| cfg.nodes).unwrap_or_default();
+                let mut containers: Vec<(Option<&str>, docker::SpawnInfo)> =
+                    local.into_iter().map(|c| (None, c)).collect();
+                for node in &nodes {
+                    match smith_core::nodes::list_agents(node) {
+                        Ok(remote) => containers
+                            .extend(remote.into_iter().map(|c| (Some(node.name.as_str()), c))),
+                        Err(e) => eprintln!("  Warning: node {}: {}", node.name, e),
+                    }
+                }
                 if containers.is_empty() {
                     println!("No spawned agents");
                 } else {
-                    println!("Spawned agents:");
-                    for c in containers {
-                        let status_color = if c.status.to_lowercase().contains("up") {
-                            ANSI_GREEN
-                        } else if c.status.to_lowercase().contains("exited") {
-                            ANSI_YELLOW
+                    let mut defaults = vec![
+                        "status", "project", "branch", "state", "health", "port", "image",
+                    ];
+                    if !nodes.is_empty() {
+                        defaults.insert(1, "node");
+                    }
+                    let mut rows = Table::new(&[
+                        "status", "node", "project", "branch", "state", "health", "name", "id",
+                        "port", "image",
+                    ])
+                    .defaults(&defaults);
+                    for (node, c) in containers {
+                        let state = c.status.to_lowercase();
+                        // Remote agents are probed by their own node's `smith agent list`.
+                        let health = if state.contains("up") && node.is_none() {
+                            Some(
+                                docker::HealthCheck::for_project(&c.project)
+                                    .probe(
+                                        &c.container_name,
+                                        c.port,
+                                        std::time::Duration::from_secs(2),
+                                    )
+                                    .await,
+                            )
                         } else {
-                            ANSI_RED
+                            None
                         };
-                        println!(
-                            "  {} {}::{} - {} (name: {}, id: {}, port: {}, image: {})",
-                            status_color,
+                        let bullet = if matches!(health, Some(Err(_))) {
+                            BULLET_YELLOW
+                        } else if state.contains("up") {
+                            BULLET_GREEN
+                        } else if state.contains("exited") {
+                            BULLET_YELLOW
+                        } else {
+                            BULLET_RED
+                        };
+                        rows.row(vec![
+                            bullet.to_string(),
+                            node.unwrap_or("local").to_string(),
                             c.project,
                             c.branch,
                             c.status,
+                            match health {
+                                Some(Ok(())) => "healthy".to_string(),
+                                Some(Err(e)) => format!("unhealthy: {}", e),
+                                None => "-".to_string(),
+                            },
                             c.container_name,
                             c.container_id,
-                            c.port,
-                            c.image
-                        );
+                            c.port.to_string(),
+                            c.image,
+                        ]);
                     }
+                    print!(
+                        "{}",
+                        rows.render(&table).unwrap_or_else(|e| {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        })
+                    );
                 }
             }
             Err(e) => {
@@ -351,7 +425,7 @@ pub async fn handle(cmd: AgentCommands) {
         } => {
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     _ => {
                         eprintln!("Error: --project required");
@@ -474,90 +548,98 @@ pub async fn handle(cmd: AgentCommands) {
         }
         AgentCommands::Logs {
             project,
-            branch,
+            branches,
+            all,
             follow,
+            since,
+            tail,
+            timestamps,
         } => {
-            // Auto-detect project and branch if not provided
-            let project = match project {
-                Some(p) => p,
-                None => match detect_project_from_cwd() {
-                    Ok(Some(name)) => name,
-                    _ => {
-                        eprintln!("Error: --project required");
-                        std::process::exit(1);
-                    }
-                },
-            };
-            let branch = match branch {
-                Some(b) => b,
-                None => {
-                    let output = Command::new("git")
-                        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-                        .output();
-                    match output {
-                        Ok(out) if out.status.success() => {
-                            String::from_utf8_lossy(&out.stdout).trim().to_string()
-                        }
-                        _ => {
-                            eprintln!("Error: --branch required");
-                            std::process::exit(1);
-                        }
-                    }
+            if let Some(tail) = &tail {
+                if tail != "all" && tail.parse::<u32>().is_err() {
+                    eprintln!(
+                        "Error: Invalid --tail '{}': expected a number or \"all\"",
+                        tail
+                    );
+                    std::process::exit(1);
                 }
+            }
+            let options = docker::LogOptions {
+                follow,
+                since,
+                tail,
+                timestamps,
+                ..Default::default()
             };
+            let targets = if all {
+                all_log_targets(project.as_deref(), follow)
+            } else {
+                named_log_targets(project, branches)
+            }
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
 
-            let name = docker::spawn_container_name(&project, &branch);
-
-            if follow {
-                let running = Command::new("docker")
-                    .args(["inspect", &name, "-f", "{{.State.Running}}"])
-                    .output();
-
-                match running {
-                    Ok(out) if out.status.success() => {
-                        let is_running = String::from_utf8_lossy(&out.stdout).trim() == "true";
-                        if !is_running {
-                            eprintln!(
-                                    "Error: container '{}' is not running. Start it with `smith agent start`.",
-                                    name
-                                );
-                            std::process::exit(1);
-                        }
-                    }
-                    Ok(_) => {
+            if let [(project, branch)] = &targets[..] {
+                if follow
+                    && docker::spawned_container_running(project, branch).unwrap_or(None)
+                        != Some(true)
+                {
+                    eprintln!(
+                        "Error: container '{}' is not running. Start it with `smith agent start`.",
+                        docker::spawn_container_name(project, branch)
+                    );
+                    std::process::exit(1);
+                }
+                let status = docker::spawn_logs_command(project, branch, &options)
+                    .logged()
+                    .status();
+                match status {
+                    Ok(s) if s.success() => {}
+                    Ok(s) => {
                         eprintln!("Error: container not found or not accessible");
-                        std::process::exit(1);
+                        std::process::exit(s.code().unwrap_or(1));
                     }
                     Err(e) => {
-                        eprintln!("Error: Failed to inspect container: {}", e);
+                        eprintln!("Error: Failed to get logs: {}", e);
                         std::process::exit(1);
                     }
                 }
+            } else if !multiplex_logs(&targets, &options) {
+                std::process::exit(1);
             }
-
-            let mut cmd = Command::new("docker");
-            cmd.arg("logs");
-            if follow {
-                cmd.arg("-f");
+        }
+        AgentCommands::Exec { name, cmd } => {
+            match docker::list_running_agent_containers() {
+                Ok(running) if running.iter().any(|r| r == &name) => {}
+                Ok(_) => {
+                    eprintln!(
+                        "Error: Model profile '{}' is not running. Start it with `smith model start`.",
+                        name
+                    );
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
             }
-            cmd.arg(&name);
-
-            let status = cmd.status();
+            let tty = io::stdin().is_terminal() && io::stdout().is_terminal();
+            let status = Command::new("docker")
+                .args(docker::agent_exec_args(&name, &cmd, tty))
+                .logged()
+                .status();
             match status {
                 Ok(s) if s.success() => {}
-                Ok(s) => {
-                    eprintln!("Error: container not found or not accessible");
-                    if let Some(code) = s.code() {
-                        std::process::exit(code);
-                    }
-                    std::process::exit(1);
-                }
+                Ok(s) => std::process::exit(s.code().unwrap_or(1)),
                 Err(e) => {
-                    eprintln!("Error: Failed to get logs: {}", e);
+                    eprintln!("Error: Failed to run docker exec: {}", e);
                     std::process::exit(1);
                 }
             }
         }
+        AgentCommands::Expose { .. } => super::expose::handle(cmd).await,
         AgentCommands::Prune => match docker::prune_spawned_containers() {
             Ok(removed) => {
                 if removed.is_empty() {
@@ -576,3 +658,114 @@ pub async fn handle(cmd: AgentCommands) {
         },
     }
 }
+
+/// Agents named by --project/--branch, auto-detecting them like the other agent commands.
+fn named_log_targets(
+    project: Option<String>,
+    branches: Vec<String>,
+) -> Result<Vec<(String, String)>, String> {
+    let project = match project {
+        Some(p) => p,
+        None => match detect_or_pick_project() {
+            Ok(Some(name)) => name,
+            _ => return Err("--project required".to_string()),
+        },
+    };
+    let branches = if branches.is_empty() {
+        let out = Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .ok_or("--branch required")?;
+        vec![String::from_utf8_lossy(&out.stdout).trim().to_string()]
+    } else {
+        branches
+    };
+    Ok(branches
+        .into_iter()
+        .map(|branch| (project.clone(), branch))
+        .collect())
+}
+
+/// Every spawned agent (of `project`), only the running ones when following.
+fn all_log_targets(project: Option<&str>, running: bool) -> Result<Vec<(String, String)>, String> {
+    let targets: Vec<(String, String)> = docker::list_spawned_containers()?
+        .into_iter()
+        .filter(|c| project.is_none_or(|p| c.project == p))
+        .filter(|c| !running || c.status.starts_with("Up") || c.status.starts_with("Running"))
+        .map(|c| (c.project, c.branch))
+        .collect();
+    if targets.is_empty() {
+        return Err(if running {
+            "No running spawned agents".to_string()
+        } else {
+            "No spawned agents".to_string()
+        });
+    }
+    Ok(targets)
+}
+
+/// Stream several agents' logs at once, each line prefixed with its agent in its own color.
+/// Returns false if any stream failed.
+fn multiplex_logs(targets: &[(String, String)], options: &docker::LogOptions) -> bool {
+    let labels: Vec<String> = targets
+        .iter()
+        .map(|(project, branch)| format!("{}:{}", project, branch))
+        .collect();
+    let prefixes = style::aligned_prefixes(&labels);
+    thread::scope(|scope| {
+        let streams: Vec<_> = targets
+            .iter()
+            .zip(prefixes)
+            .map(|((project, branch), prefix)| {
+                scope.spawn(move || {
+                    let child = docker::spawn_logs_command(project, branch, options)
+                        .stdout(std::process::Stdio::piped())
+                        .stderr(std::process::Stdio::piped())
+                        .logged()
+                        .spawn();
+                    let mut child = match child {
+                        Ok(child) => child,
+                        Err(e) => {
+                            eprintln!("{} Failed to get logs: {}", prefix, e);
+                            return false;
+                        }
+                    };
+                    let stdout = child.stdout.take();
+                    let stderr = child.stderr.take();
+                    thread::scope(|inner| {
+                        if let Some(out) = stdout {
+                            inner.spawn(|| copy_prefixed(out, &prefix, false));
+                        }
+                        if let Some(err) = stderr {
+                            inner.spawn(|| copy_prefixed(err, &prefix, true));
+                        }
+                    });
+                    child.wait().map(|s| s.success()).unwrap_or(false)
+                })
+            })
+            .collect();
+        streams
+            .into_iter()
+            .map(|s| s.join().unwrap_or(false))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .all(|ok| ok)
+    })
+}
+
+fn copy_prefixed(stream: impl io::Read, prefix: &str, stderr: bool) {
+    let mut reader = io::BufReader::new(stream);
+    let mut line = Vec::new();
+    while matches!(io::BufRead::read_until(&mut reader, b'\n', &mut line), Ok(n) if n > 0) {
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end_matches(['\n', '\r']);
+        if stderr {
+            eprintln!("{} {}", prefix, text);
+        } else {
+            println!("{} {}", prefix, text);
+        }
+        line.clear();
+    }
+}
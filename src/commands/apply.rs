@@ -0,0 +1,297 @@
+use crate::*;
+
+/// Desired state for `smith apply`: agents and projects keyed by name.
+/// A section left out of the document is not reconciled (and never pruned).
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct BootstrapDoc {
+    agents: Option<Vec<serde_json::Map<String, Value>>>,
+    projects: Option<Vec<serde_json::Map<String, Value>>>,
+}
+
+enum Change {
+    Create(Value),
+    Update(Vec<(String, Value, Value)>),
+    Remove,
+}
+
+pub async fn handle(file: PathBuf, prune: bool, dry_run: bool, yes: bool) {
+    let doc = load_bootstrap(&file).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let mut cfg = load_config().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    let current_agents: Vec<Value> = cfg
+        .agents
+        .iter()
+        .flatten()
+        .map(|a| serde_json::to_value(a).unwrap_or(Value::Null))
+        .collect();
+    let current_projects: Vec<Value> = cfg
+        .projects
+        .iter()
+        .map(|p| serde_json::to_value(p).unwrap_or(Value::Null))
+        .collect();
+    let agent_changes = doc
+        .agents
+        .as_deref()
+        .map(|desired| plan_changes("agent", &current_agents, desired, prune))
+        .transpose();
+    let project_changes = doc
+        .projects
+        .as_deref()
+        .map(|desired| plan_changes("project", &current_projects, desired, prune))
+        .transpose();
+    let (agent_changes, project_changes) = match (agent_changes, project_changes) {
+        (Ok(a), Ok(p)) => (a.unwrap_or_default(), p.unwrap_or_default()),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if agent_changes.is_empty() && project_changes.is_empty() {
+        println!(
+            "  {} Config already matches {}",
+            BULLET_GREEN,
+            file.display()
+        );
+        return;
+    }
+    print_changes("agent", &agent_changes);
+    print_changes("project", &project_changes);
+    println!();
+    // Apply in memory first so invalid entries fail before anything is confirmed or saved.
+    apply_changes(&mut cfg, agent_changes, project_changes).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    if dry_run {
+        println!("  Dry run: no changes saved.");
+        return;
+    }
+    if !yes && !prompt_yn("Apply these changes?", true) {
+        eprintln!("Apply cancelled.");
+        std::process::exit(1);
+    }
+    save_config(&cfg).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    println!("  {} Applied {}", BULLET_GREEN, file.display());
+}
+
+/// Parse a bootstrap document; `.toml` files are read as TOML, anything else as YAML (or JSON).
+fn load_bootstrap(path: &Path) -> Result<BootstrapDoc, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let doc = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str(&raw).map_err(|e| e.to_string())
+    } else {
+        serde_yaml::from_str(&raw).map_err(|e| e.to_string())
+    };
+    doc.map_err(|e| format!("Invalid bootstrap document {}: {}", path.display(), e))
+}
+
+/// Compare desired entries with the current ones by name. Only keys present in a desired
+/// entry are reconciled (`null` clears a key); keys it leaves out keep their current value.
+fn plan_changes(
+    kind: &str,
+    current: &[Value],
+    desired: &[serde_json::Map<String, Value>],
+    prune: bool,
+) -> Result<Vec<(String, Change)>, String> {
+    let name_of = |v: &serde_json::Map<String, Value>| -> Result<String, String> {
+        v.get("name")
+            .and_then(Value::as_str)
+            .filter(|n| !n.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| format!("Every {} in the document needs a name", kind))
+    };
+    let mut changes = Vec::new();
+    let mut seen = Vec::new();
+    for entry in desired {
+        let name = name_of(entry)?;
+        if seen.contains(&name) {
+            return Err(format!("Duplicate {} '{}' in the document", kind, name));
+        }
+        seen.push(name.clone());
+        let existing = current
+            .iter()
+            .find(|c| c.get("name").and_then(Value::as_str) == Some(name.as_str()));
+        match existing {
+            None => changes.push((name, Change::Create(Value::Object(entry.clone())))),
+            Some(existing) => {
+                let fields: Vec<(String, Value, Value)> = entry
+                    .iter()
+                    .filter_map(|(key, want)| {
+                        let have = existing.get(key).cloned().unwrap_or(Value::Null);
+                        (have != *want).then(|| (key.clone(), have, want.clone()))
+                    })
+                    .collect();
+                if !fields.is_empty() {
+                    changes.push((name, Change::Update(fields)));
+                }
+            }
+        }
+    }
+    if prune {
+        for c in current {
+            if let Some(name) = c.get("name").and_then(Value::as_str) {
+                if !seen.iter().any(|s| s == name) {
+                    changes.push((name.to_string(), Change::Remove));
+                }
+            }
+        }
+    }
+    Ok(changes)
+}
+
+fn show_value(key: &str, value: &Value) -> String {
+    match value {
+        Value::Null => "(unset)".to_string(),
+        _ if logging::is_secret_name(key) => "***".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn print_changes(kind: &str, changes: &[(String, Change)]) {
+    for (name, change) in changes {
+        match change {
+            Change::Create(entry) => {
+                println!("  {}+ {} {}{}", ANSI_GREEN, kind, name, ANSI_RESET);
+                for (key, value) in entry.as_object().into_iter().flatten() {
+                    if key != "name" && !value.is_null() {
+                        println!("      {}: {}", key, show_value(key, value));
+                    }
+                }
+            }
+            Change::Update(fields) => {
+                println!("  {}~ {} {}{}", ANSI_YELLOW, kind, name, ANSI_RESET);
+                for (key, have, want) in fields {
+                    println!(
+                        "      {}: {} -> {}",
+                        key,
+                        show_value(key, have),
+                        show_value(key, want)
+                    );
+                }
+            }
+            Change::Remove => println!("  {}- {} {}{}", ANSI_RED, kind, name, ANSI_RESET),
+        }
+    }
+}
+
+/// Merge `fields` over an existing entry and parse the result back into its config type.
+fn patched<T: Serialize + serde::de::DeserializeOwned>(
+    kind: &str,
+    name: &str,
+    existing: &T,
+    fields: Vec<(String, Value, Value)>,
+) -> Result<T, String> {
+    let mut value = serde_json::to_value(existing).map_err(|e| e.to_string())?;
+    if let Value::Object(map) = &mut value {
+        for (key, _, want) in fields {
+            map.insert(key, want);
+        }
+    }
+    serde_json::from_value(value).map_err(|e| format!("Invalid {} '{}': {}", kind, name, e))
+}
+
+fn apply_changes(
+    cfg: &mut SmithConfig,
+    agent_changes: Vec<(String, Change)>,
+    project_changes: Vec<(String, Change)>,
+) -> Result<(), String> {
+    for (name, change) in agent_changes {
+        match change {
+            Change::Create(mut entry) => {
+                if entry.get("image").is_none_or(Value::is_null) {
+                    entry["image"] = Value::from(DEFAULT_AGENT_IMAGE);
+                }
+                let a: AgentEntry = serde_json::from_value(entry)
+                    .map_err(|e| format!("Invalid agent '{}': {}", name, e))?;
+                if let Some(volumes) = &a.volumes {
+                    docker::agent_volume_args(volumes)
+                        .map_err(|e| format!("Invalid agent '{}': {}", name, e))?;
+                }
+                if let Some(bind) = &a.bind_address {
+                    docker::parse_bind_address(bind)
+                        .map_err(|e| format!("Invalid agent '{}': {}", name, e))?;
+                }
+                let (
+                    env_passthrough,
+                    volumes,
+                    bind_address,
+                    health_path,
+                    health_match,
+                    health_command,
+                ) = (
+                    a.env_passthrough,
+                    a.volumes,
+                    a.bind_address,
+                    a.health_path,
+                    a.health_match,
+                    a.health_command,
+                );
+                add_agent_to_config(
+                    cfg,
+                    a.name,
+                    Some(a.image),
+                    a.agent_type,
+                    a.model,
+                    a.small_model,
+                    a.provider,
+                    a.base_url,
+                    a.port,
+                    a.enabled.or(Some(true)),
+                    a.default_role,
+                    a.roles,
+                    a.env,
+                )?;
+                if let Some(added) = cfg.agents.as_mut().and_then(|a| a.last_mut()) {
+                    added.env_passthrough = env_passthrough;
+                    added.volumes = volumes;
+                    added.bind_address = bind_address;
+                    added.health_path = health_path;
+                    added.health_match = health_match;
+                    added.health_command = health_command;
+                }
+            }
+            Change::Update(fields) => {
+                let agents = cfg.agents.get_or_insert_with(Vec::new);
+                if let Some(slot) = agents.iter_mut().find(|a| a.name == name) {
+                    *slot = patched("agent", &name, slot, fields)?;
+                }
+            }
+            Change::Remove => {
+                if let Some(agents) = cfg.agents.as_mut() {
+                    agents.retain(|a| a.name != name);
+                }
+                if cfg.current_agent.as_deref() == Some(name.as_str()) {
+                    cfg.current_agent = None;
+                }
+            }
+        }
+    }
+    for (name, change) in project_changes {
+        match change {
+            Change::Create(entry) => {
+                let project: ProjectConfig = serde_json::from_value(entry)
+                    .map_err(|e| format!("Invalid project '{}': {}", name, e))?;
+                add_project_to_config(cfg, project)?;
+            }
+            Change::Update(fields) => {
+                if let Some(slot) = cfg.projects.iter_mut().find(|p| p.name == name) {
+                    *slot = patched("project", &name, slot, fields)?;
+                }
+            }
+            Change::Remove => cfg.projects.retain(|p| p.name != name),
+        }
+    }
+    Ok(())
+}
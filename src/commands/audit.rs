@@ -0,0 +1,61 @@
+use crate::*;
+use smith_core::audit::{AuditEntry, AUDIT_ACTIONS};
+
+pub async fn handle(cmd: AuditCommands) {
+    match cmd {
+        AuditCommands::Show {
+            since,
+            action,
+            json,
+        } => {
+            show(since.as_deref(), action.as_deref(), json).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+        }
+    }
+}
+
+fn show(since: Option<&str>, action: Option<&str>, json: bool) -> Result<(), String> {
+    if let Some(action) = action.filter(|a| !AUDIT_ACTIONS.contains(a)) {
+        return Err(format!(
+            "Unknown audit action '{}' (expected one of: {})",
+            action,
+            AUDIT_ACTIONS.join(", ")
+        ));
+    }
+    let min_at = since
+        .map(commands::cache::parse_age)
+        .transpose()?
+        .map(|age| now_unix().saturating_sub(age));
+    let entries: Vec<AuditEntry> = audit::load()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|e| min_at.is_none_or(|t| e.at >= t))
+        .filter(|e| action.is_none_or(|a| e.action == a))
+        .collect();
+
+    if json {
+        for entry in &entries {
+            println!("{}", serde_json::to_string(entry).unwrap_or_default());
+        }
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("  {} No audit entries", BULLET_GREEN);
+        return Ok(());
+    }
+    let mut table = Table::new(&["time", "user", "action", "target", "run", "detail"]);
+    for entry in entries {
+        table.row(vec![
+            audit::format_utc(entry.at),
+            entry.user,
+            entry.action,
+            entry.target,
+            entry.run_id.unwrap_or_default(),
+            entry.detail.unwrap_or_default(),
+        ]);
+    }
+    print!("{}", table.render(&TableArgs::default())?);
+    Ok(())
+}
@@ -0,0 +1,240 @@
+use crate::*;
+
+/// Cache categories in display order.
+const CATEGORIES: &[&str] = &["deps", "git", "images", "workspaces"];
+
+pub async fn handle(cmd: CacheCommands) {
+    match cmd {
+        CacheCommands::Stats { table } => {
+            let mut rows = Table::new(&["category", "name", "size", "age", "status"]);
+            let now = now_unix();
+            for category in CATEGORIES {
+                let entries = match cache_entries(category) {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        println!("  {} {} - unavailable ({})", BULLET_YELLOW, category, e);
+                        continue;
+                    }
+                };
+                let total: u64 = entries.iter().map(|e| e.size).sum();
+                println!(
+                    "  {} {} - {} in {} entr{}",
+                    BULLET_BLUE,
+                    category,
+                    format_size(total),
+                    entries.len(),
+                    if entries.len() == 1 { "y" } else { "ies" }
+                );
+                for entry in entries {
+                    rows.row(vec![
+                        category.to_string(),
+                        entry.name,
+                        format_size(entry.size),
+                        format_age(now.saturating_sub(entry.created)),
+                        if entry.in_use { "in use" } else { "" }.to_string(),
+                    ]);
+                }
+            }
+            let rendered = rows.render(&table).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            println!();
+            print!("{}", rendered);
+        }
+        CacheCommands::Prune {
+            deps,
+            git,
+            images,
+            workspaces,
+            older_than,
+            dry_run,
+            yes,
+        } => {
+            let min_age = older_than
+                .as_deref()
+                .map(parse_age)
+                .transpose()
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+                .unwrap_or(0);
+            let all = !deps && !git && !images && !workspaces;
+            let selected: Vec<&str> = CATEGORIES
+                .iter()
+                .zip([deps, git, images, workspaces])
+                // Workspaces may hold unpushed work, so only an explicit --workspaces prunes them.
+                .filter(|(c, picked)| *picked || (all && **c != "workspaces"))
+                .map(|(c, _)| *c)
+                .collect();
+            prune(&selected, min_age, dry_run, yes).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+        }
+    }
+}
+
+fn cache_entries(category: &str) -> Result<Vec<docker::CacheEntry>, String> {
+    match category {
+        "deps" => docker::dep_caches().map_err(|e| e.to_string()),
+        "images" => docker::smith_images().map_err(|e| e.to_string()),
+        "workspaces" => docker::workspace_volumes().map_err(|e| e.to_string()),
+        _ => git_mirrors(),
+    }
+}
+
+/// Project mirrors under the git mirror cache; age is the last time a mirror was updated.
+fn git_mirrors() -> Result<Vec<docker::CacheEntry>, String> {
+    let dir = git_mirror_dir().map_err(|e| e.to_string())?;
+    let Ok(read) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut entries: Vec<docker::CacheEntry> = read
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let modified = e
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs());
+            docker::CacheEntry {
+                name: e.file_name().to_string_lossy().to_string(),
+                size: dir_size(&e.path()),
+                created: modified,
+                in_use: false,
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(read) = fs::read_dir(path) else {
+        return 0;
+    };
+    read.flatten()
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            Ok(t) if t.is_file() => e.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+fn prune(categories: &[&str], min_age: u64, dry_run: bool, yes: bool) -> Result<(), String> {
+    let now = now_unix();
+    let mut targets = Vec::new();
+    for category in categories {
+        let entries = match cache_entries(category) {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("  {} {} - skipped ({})", BULLET_YELLOW, category, e);
+                continue;
+            }
+        };
+        for entry in entries {
+            if now.saturating_sub(entry.created) < min_age {
+                continue;
+            }
+            if entry.in_use {
+                println!(
+                    "  {} {} {} - in use, skipped",
+                    BULLET_YELLOW, category, entry.name
+                );
+                continue;
+            }
+            targets.push((*category, entry));
+        }
+    }
+    if targets.is_empty() {
+        println!("  {} Nothing to prune", BULLET_GREEN);
+        return Ok(());
+    }
+    let total: u64 = targets.iter().map(|(_, e)| e.size).sum();
+    for (category, entry) in &targets {
+        println!(
+            "  {} {} ({}, {})",
+            category,
+            entry.name,
+            format_size(entry.size),
+            format_age(now.saturating_sub(entry.created))
+        );
+    }
+    if dry_run {
+        println!("  Dry run: would free {}.", format_size(total));
+        return Ok(());
+    }
+    if !yes
+        && !prompt_yn(
+            &format!("Remove {} entries ({})?", targets.len(), format_size(total)),
+            false,
+        )
+    {
+        return Err("Prune cancelled.".to_string());
+    }
+    let mut freed = 0;
+    for (category, entry) in targets {
+        let result = match category {
+            "deps" | "workspaces" => docker::remove_volume(&entry.name).map_err(|e| e.to_string()),
+            "images" => docker::remove_image(&entry.name)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            _ => git_mirror_dir().map_err(|e| e.to_string()).and_then(|dir| {
+                fs::remove_dir_all(dir.join(&entry.name)).map_err(|e| e.to_string())
+            }),
+        };
+        match result {
+            Ok(()) => freed += entry.size,
+            Err(e) => eprintln!("  {} {} {} - {}", BULLET_RED, category, entry.name, e),
+        }
+    }
+    println!("  {} Freed {}", BULLET_GREEN, format_size(freed));
+    Ok(())
+}
+
+/// Parse an age like "30m", "12h", "7d" or "2w" into seconds.
+pub(crate) fn parse_age(raw: &str) -> Result<u64, String> {
+    let raw = raw.trim();
+    let split = raw.len() - raw.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (number, unit) = raw.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid age '{}' (expected e.g. 12h, 7d, 2w)", raw))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return Err(format!("Invalid age '{}' (expected e.g. 12h, 7d, 2w)", raw)),
+    };
+    Ok(number * unit_secs)
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+pub(crate) fn format_age(secs: u64) -> String {
+    match secs {
+        0..=3_599 => format!("{}m", secs / 60),
+        3_600..=86_399 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
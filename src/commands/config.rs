@@ -0,0 +1,316 @@
+use crate::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Placeholder written by `config export --redact-secrets`; never imported over a real value.
+const REDACTED: &str = "***";
+
+pub async fn handle(cmd: ConfigCommands) {
+    match cmd {
+        ConfigCommands::Export {
+            redact_secrets,
+            output,
+        } => {
+            let text = export_config(redact_secrets).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            match output {
+                Some(path) => {
+                    fs::write(&path, text).unwrap_or_else(|e| {
+                        eprintln!("Error: Failed to write {}: {}", path.display(), e);
+                        std::process::exit(1);
+                    });
+                    println!("  {} Exported config to {}", BULLET_GREEN, path.display());
+                }
+                None => print!("{}", text),
+            }
+        }
+        ConfigCommands::Import {
+            file,
+            replace,
+            allow_hooks,
+            yes,
+        } => {
+            import_config(&file, replace, allow_hooks, yes).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+        }
+    }
+}
+
+fn export_config(redact_secrets: bool) -> Result<String, String> {
+    let cfg = load_config().map_err(|e| e.to_string())?;
+    let mut value = toml::Value::try_from(&cfg).map_err(|e| e.to_string())?;
+    if redact_secrets {
+        logging::redact_toml(&mut value);
+    }
+    toml::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+fn import_config(file: &Path, replace: bool, allow_hooks: bool, yes: bool) -> Result<(), String> {
+    let raw = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+    let mut incoming: toml::Value = toml::from_str(&raw)
+        .map_err(|e| format!("Invalid config file {}: {}", file.display(), e))?;
+    let dropped = drop_redacted(&mut incoming, "");
+
+    let current = load_config().map_err(|e| e.to_string())?;
+    let before = toml::Value::try_from(&current).map_err(|e| e.to_string())?;
+    let mut merged = before.clone();
+    if replace {
+        merged = incoming;
+    } else {
+        merge_toml(&mut merged, incoming);
+    }
+    let cfg: SmithConfig = merged
+        .clone()
+        .try_into()
+        .map_err(|e| format!("Imported config is invalid: {}", e))?;
+    // Round-trip so defaults filled in by deserializing do not show up as changes.
+    let after = toml::Value::try_from(&cfg).map_err(|e| e.to_string())?;
+
+    let changes = config_changes(&before, &after);
+    if changes.is_empty() {
+        println!(
+            "  {} Config already matches {}",
+            BULLET_GREEN,
+            file.display()
+        );
+        return Ok(());
+    }
+    print_config_changes(&changes);
+    println!();
+    let host_access = host_access_changes(&changes);
+    let listed = host_access
+        .iter()
+        .map(|(key, what)| format!("{} {}", key, what))
+        .collect::<Vec<_>>();
+    if !host_access.is_empty() && !allow_hooks {
+        return Err(format!(
+            "{} sets keys that act on this machine ({}); review them and pass --allow-hooks to import them",
+            file.display(),
+            listed.join(", ")
+        ));
+    }
+    if !host_access.is_empty() {
+        println!("  {} These keys act on this machine:", BULLET_YELLOW);
+        for line in &listed {
+            println!("     - {}", line);
+        }
+        println!();
+    }
+    let question = if replace {
+        "Replace the entire config with the imported file?"
+    } else {
+        "Import these changes?"
+    };
+    if !yes && !prompt_yn(question, true) {
+        return Err("Import cancelled.".to_string());
+    }
+    save_config(&cfg).map_err(|e| e.to_string())?;
+
+    println!(
+        "  {} Imported {} ({} project(s), {} agent(s))",
+        BULLET_GREEN,
+        file.display(),
+        cfg.projects.len(),
+        cfg.agents.as_ref().map_or(0, Vec::len)
+    );
+    if !dropped.is_empty() {
+        println!(
+            "  {} Redacted values were not imported; set them again if needed:",
+            BULLET_YELLOW
+        );
+        for key in dropped {
+            println!("     - {}", key);
+        }
+    }
+    Ok(())
+}
+
+/// Remove redacted placeholders so they never overwrite real secrets; returns their key paths.
+pub(crate) fn drop_redacted(value: &mut toml::Value, path: &str) -> Vec<String> {
+    let mut dropped = Vec::new();
+    match value {
+        toml::Value::Table(table) => {
+            let redacted: Vec<String> = table
+                .iter()
+                .filter(|(_, v)| v.as_str() == Some(REDACTED))
+                .map(|(k, _)| k.clone())
+                .collect();
+            let prefix = table
+                .get("name")
+                .and_then(toml::Value::as_str)
+                .map(|name| format!("{}{}.", path, name))
+                .unwrap_or_else(|| path.to_string());
+            for key in redacted {
+                table.remove(&key);
+                dropped.push(format!("{}{}", prefix, key));
+            }
+            for (key, v) in table.iter_mut() {
+                let nested = if v.is_table() {
+                    format!("{}{}.", prefix, key)
+                } else {
+                    prefix.clone()
+                };
+                dropped.extend(drop_redacted(v, &nested));
+            }
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                dropped.extend(drop_redacted(item, path));
+            }
+        }
+        _ => {}
+    }
+    dropped
+}
+
+/// Merge `incoming` into `base`: tables merge key by key, arrays of named tables
+/// (projects, agents, ...) merge entry by entry on `name`, anything else is overwritten.
+pub(crate) fn merge_toml(base: &mut toml::Value, incoming: toml::Value) {
+    match (base, incoming) {
+        (toml::Value::Table(base), toml::Value::Table(incoming)) => {
+            for (key, value) in incoming {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (toml::Value::Array(base), toml::Value::Array(incoming))
+            if incoming.iter().all(|v| entry_name(v).is_some()) =>
+        {
+            for item in incoming {
+                match base
+                    .iter_mut()
+                    .find(|b| entry_name(b).is_some() && entry_name(b) == entry_name(&item))
+                {
+                    Some(existing) => merge_toml(existing, item),
+                    None => base.push(item),
+                }
+            }
+        }
+        (base, incoming) => *base = incoming,
+    }
+}
+
+/// One changed config key: its path (`projects.web.repo`), the old and the new value.
+pub(crate) type ConfigChange = (String, Option<toml::Value>, Option<toml::Value>);
+
+/// Keys that differ between two configs, in path order.
+pub(crate) fn config_changes(before: &toml::Value, after: &toml::Value) -> Vec<ConfigChange> {
+    let mut old = BTreeMap::new();
+    let mut new = BTreeMap::new();
+    flatten_toml(before, "", &mut old);
+    flatten_toml(after, "", &mut new);
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|k| old.get(*k) != new.get(*k))
+        .map(|k| (k.clone(), old.get(k).cloned(), new.get(k).cloned()))
+        .collect()
+}
+
+/// Keys a change sets that reach beyond smith's own settings, with what they do: hooks run
+/// commands, event subscribers deliver to a webhook or file, agent `volumes` mount host paths,
+/// agent `env`/`env_passthrough` forward host environment, and nodes add SSH endpoints.
+pub(crate) fn host_access_changes(changes: &[ConfigChange]) -> Vec<(String, &'static str)> {
+    changes
+        .iter()
+        .filter_map(|(key, _, new)| Some((key.clone(), host_access(key, new.as_ref()?)?)))
+        .collect()
+}
+
+fn host_access(key: &str, value: &toml::Value) -> Option<&'static str> {
+    let mut parts = key.rsplit('.');
+    let field = parts.next().unwrap_or(key);
+    let parent = parts.next();
+    if field.ends_with("_hook") {
+        return Some("runs a command");
+    }
+    if key == "event_subscribers" {
+        let targets = value
+            .as_array()
+            .is_some_and(|subs| subs.iter().any(|s| s.get("target").is_some()));
+        return targets.then_some("delivers events to a webhook or file");
+    }
+    if key.starts_with("agents.") {
+        return match (parent, field) {
+            (_, "volumes") => Some("mounts host paths"),
+            (_, "env_passthrough") | (Some("env"), _) => Some("forwards host environment"),
+            _ => None,
+        };
+    }
+    (key.starts_with("nodes.") && field == "endpoint").then_some("adds an SSH endpoint")
+}
+
+/// Leaf values of a config keyed by path; entries of named arrays are keyed by their name.
+fn flatten_toml(value: &toml::Value, path: &str, out: &mut BTreeMap<String, toml::Value>) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match value {
+        toml::Value::Table(table) => {
+            for (key, v) in table {
+                flatten_toml(v, &join(key), out);
+            }
+        }
+        toml::Value::Array(items) if items.iter().all(|v| entry_name(v).is_some()) => {
+            for item in items {
+                let Some(table) = item.as_table() else {
+                    continue;
+                };
+                let prefix = join(entry_name(item).unwrap_or_default());
+                for (key, v) in table.iter().filter(|(k, _)| *k != "name") {
+                    flatten_toml(v, &format!("{}.{}", prefix, key), out);
+                }
+            }
+        }
+        _ => {
+            out.insert(path.to_string(), value.clone());
+        }
+    }
+}
+
+fn show_config_value(key: &str, value: &toml::Value) -> String {
+    let field = key.rsplit('.').next().unwrap_or(key);
+    if logging::is_secret_name(field) {
+        "***".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_config_changes(changes: &[ConfigChange]) {
+    for (key, old, new) in changes {
+        match (old, new) {
+            (None, Some(new)) => println!(
+                "  {}+ {}: {}{}",
+                ANSI_GREEN,
+                key,
+                show_config_value(key, new),
+                ANSI_RESET
+            ),
+            (Some(old), Some(new)) => println!(
+                "  {}~ {}: {} -> {}{}",
+                ANSI_YELLOW,
+                key,
+                show_config_value(key, old),
+                show_config_value(key, new),
+                ANSI_RESET
+            ),
+            _ => println!("  {}- {}{}", ANSI_RED, key, ANSI_RESET),
+        }
+    }
+}
+
+fn entry_name(value: &toml::Value) -> Option<&str> {
+    value.get("name").and_then(toml::Value::as_str)
+}
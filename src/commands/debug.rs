@@ -0,0 +1,228 @@
+use crate::*;
+
+/// Lines kept from each container log and from the events log.
+const BUNDLE_LOG_TAIL: usize = 500;
+
+pub async fn handle(cmd: DebugCommands) {
+    match cmd {
+        DebugCommands::Bundle { output, runs } => {
+            let stamp = now_unix();
+            let output =
+                output.unwrap_or_else(|| PathBuf::from(format!("smith-debug-{}.tar.gz", stamp)));
+            let bundle_name = format!("smith-debug-{}", stamp);
+            let staging =
+                std::env::temp_dir().join(format!("{}-{}", bundle_name, std::process::id()));
+            let root = staging.join(&bundle_name);
+
+            let result = collect_bundle(&root, runs)
+                .and_then(|_| write_tarball(&staging, &bundle_name, &output));
+            let _ = fs::remove_dir_all(&staging);
+            match result {
+                Ok(()) => {
+                    println!(
+                        "  {} Debug bundle written to {}",
+                        BULLET_GREEN,
+                        output.display()
+                    );
+                    println!(
+                        "     Secrets are redacted, but review the archive before sharing it."
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn collect_bundle(root: &Path, runs: usize) -> Result<(), String> {
+    fs::create_dir_all(root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+    let write = |name: &str, body: &str| -> Result<(), String> {
+        let path = root.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, body).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    write("environment.txt", &environment_report())?;
+    write("config.toml", &redacted_config())?;
+
+    if let Ok(Ok(raw)) = config_dir().map(|d| fs::read_to_string(d.join("events.jsonl"))) {
+        write(
+            "events.jsonl",
+            &logging::redact(&tail_lines(&raw, BUNDLE_LOG_TAIL)),
+        )?;
+    }
+
+    let mut containers = String::new();
+    let agents = docker::list_running_agent_containers().unwrap_or_default();
+    for agent in &agents {
+        let name = docker::agent_container_name(agent);
+        containers.push_str(&format!("agent {} ({})\n", agent, name));
+        write(&format!("logs/{}.log", name), &container_logs(&name))?;
+    }
+    let spawned = docker::list_spawned_containers().unwrap_or_default();
+    for c in &spawned {
+        containers.push_str(&format!(
+            "spawned {}::{} ({}, {}, port {}, image {})\n",
+            c.project, c.branch, c.container_name, c.status, c.port, c.image
+        ));
+        write(
+            &format!("logs/{}.log", c.container_name),
+            &container_logs(&c.container_name),
+        )?;
+        if !c.status.to_lowercase().contains("up") {
+            continue;
+        }
+        for (run_id, manifest) in recent_run_manifests(&c.project, &c.branch, runs) {
+            write(
+                &format!("runs/{}/{}.json", c.container_name, run_id),
+                &logging::redact(&manifest),
+            )?;
+        }
+    }
+    if containers.is_empty() {
+        containers.push_str("(no smith containers)\n");
+    }
+    write("containers.txt", &containers)?;
+    Ok(())
+}
+
+/// smith/OS versions, tool versions and relevant environment variables.
+fn environment_report() -> String {
+    let mut report = format!(
+        "smith {}\nos: {} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    for (label, program, args) in [
+        ("uname", "uname", &["-a"][..]),
+        ("docker", "docker", &["version"][..]),
+        ("dagger", "dagger", &["version"][..]),
+        ("git", "git", &["--version"][..]),
+    ] {
+        let text = match Command::new(program).args(args).logged().output() {
+            Ok(out) => {
+                let mut text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+                if !stderr.is_empty() {
+                    text.push_str(&format!("\n(stderr) {}", stderr));
+                }
+                text
+            }
+            Err(_) => "not installed".to_string(),
+        };
+        report.push_str(&format!("\n[{}]\n{}\n", label, text));
+    }
+    report.push_str("\n[environment]\n");
+    let mut vars: Vec<(String, String)> = std::env::vars()
+        .filter(|(k, _)| {
+            k.starts_with("SMITH_")
+                || k.starts_with("DOCKER_")
+                || ["NO_COLOR", "TERM", "SHELL", "LANG"].contains(&k.as_str())
+        })
+        .collect();
+    vars.sort();
+    for (key, value) in vars {
+        let value = if logging::is_secret_name(&key) {
+            "***".to_string()
+        } else {
+            value
+        };
+        report.push_str(&format!("{}={}\n", key, value));
+    }
+    report
+}
+
+/// config.toml with every secret-looking value replaced by "***".
+fn redacted_config() -> String {
+    let Ok(path) = config_file_path() else {
+        return "(config path unavailable)\n".to_string();
+    };
+    let Ok(raw) = fs::read_to_string(&path) else {
+        return format!("(no config at {})\n", path.display());
+    };
+    match raw.parse::<toml::Value>() {
+        Ok(mut value) => {
+            logging::redact_toml(&mut value);
+            toml::to_string_pretty(&value).unwrap_or_default()
+        }
+        Err(e) => format!("(config at {} does not parse: {})\n", path.display(), e),
+    }
+}
+
+/// Last lines of a container's logs (stdout and stderr), redacted.
+fn container_logs(name: &str) -> String {
+    let tail = BUNDLE_LOG_TAIL.to_string();
+    match Command::new("docker")
+        .args(["logs", "--tail", &tail, name])
+        .logged()
+        .output()
+    {
+        Ok(out) => logging::redact(&format!(
+            "{}{}",
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        )),
+        Err(e) => format!("(failed to read logs: {})\n", e),
+    }
+}
+
+/// (run id, manifest.json) for the most recent runs under /state in a running spawned container.
+fn recent_run_manifests(project: &str, branch: &str, limit: usize) -> Vec<(String, String)> {
+    let Ok(listing) = docker::run_spawn_shell(project, branch, "ls -1t /state 2>/dev/null; true")
+    else {
+        return Vec::new();
+    };
+    listing
+        .lines()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .filter_map(|id| {
+            docker::read_spawn_file(project, branch, &format!("/state/{}/manifest.json", id))
+                .ok()
+                .map(|manifest| (id.to_string(), manifest))
+        })
+        .take(limit)
+        .collect()
+}
+
+fn tail_lines(text: &str, n: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(n);
+    let mut out = lines[start..].join("\n");
+    out.push('\n');
+    out
+}
+
+fn write_tarball(staging: &Path, bundle_name: &str, output: &Path) -> Result<(), String> {
+    let output = if output.is_absolute() {
+        output.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map_err(|e| format!("Failed to resolve current directory: {}", e))?
+            .join(output)
+    };
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&output)
+        .arg("-C")
+        .arg(staging)
+        .arg(bundle_name)
+        .logged()
+        .status()
+        .map_err(|e| format!("Failed to run tar: {}", e))?;
+    if !status.success() {
+        return Err(format!(
+            "tar exited with {} writing {}",
+            status,
+            output.display()
+        ));
+    }
+    Ok(())
+}
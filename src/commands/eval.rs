@@ -0,0 +1,365 @@
+use crate::*;
+use commands::pipeline::matrix::matrix_branch_slug;
+
+/// An evaluation suite: cases run against each agent, scored by programmatic checks.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EvalSuite {
+    /// Project whose image, credentials, roles and model are used.
+    pub(crate) project: String,
+    /// Model profiles to evaluate (default: the project's `model`).
+    #[serde(default)]
+    pub(crate) agents: Vec<String>,
+    pub(crate) cases: Vec<EvalCase>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EvalCase {
+    pub(crate) name: String,
+    pub(crate) task: String,
+    #[serde(default)]
+    pub(crate) fixture: EvalFixture,
+    pub(crate) checks: Vec<EvalCheck>,
+}
+
+/// Starting state of the workspace: a ref of the project repo (or of `repo`), then `setup`.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EvalFixture {
+    pub(crate) repo: Option<String>,
+    #[serde(rename = "ref")]
+    pub(crate) git_ref: Option<String>,
+    pub(crate) setup: Option<String>,
+}
+
+/// Either `command` (passes on `exit_code`, default 0) or `file` (passes when it exists and,
+/// with `contains`, includes that text).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct EvalCheck {
+    pub(crate) command: Option<String>,
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) file: Option<String>,
+    pub(crate) contains: Option<String>,
+}
+
+impl EvalCheck {
+    pub(crate) fn label(&self) -> String {
+        match (&self.command, &self.file, &self.contains) {
+            (Some(cmd), _, _) => format!("`{}` exits {}", cmd, self.exit_code.unwrap_or(0)),
+            (_, Some(file), Some(text)) => format!("{} contains {:?}", file, text),
+            (_, Some(file), None) => format!("{} exists", file),
+            _ => "invalid check".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CaseResult {
+    case: String,
+    agent: String,
+    passed: bool,
+    /// Labels of the checks that failed (or the error that stopped the case).
+    failures: Vec<String>,
+    duration_secs: u64,
+    tokens: u64,
+    cost: f64,
+}
+
+const EXIT_MARKER: &str = "SMITH_EVAL_EXIT=";
+
+pub async fn handle(cmd: EvalCommands) {
+    match cmd {
+        EvalCommands::Run {
+            suite,
+            agents,
+            case,
+            keep,
+            json,
+        } => {
+            let suite = load_suite(&suite)
+                .unwrap_or_else(|e| exit_with_error(SmithError::Validation(e), json));
+            run_suite(suite, agents, case.as_deref(), keep, json).await;
+        }
+    }
+}
+
+/// Parse a suite file (YAML, or JSON by extension) and validate its cases.
+pub(crate) fn load_suite(path: &Path) -> Result<EvalSuite, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let suite: EvalSuite = if path.extension().is_some_and(|e| e == "json") {
+        serde_json::from_str(&raw)
+            .map_err(|e| format!("Invalid suite {}: {}", path.display(), e))?
+    } else {
+        serde_yaml::from_str(&raw)
+            .map_err(|e| format!("Invalid suite {}: {}", path.display(), e))?
+    };
+    validate_suite(&suite)?;
+    Ok(suite)
+}
+
+pub(crate) fn validate_suite(suite: &EvalSuite) -> Result<(), String> {
+    if suite.cases.is_empty() {
+        return Err("Suite has no cases".to_string());
+    }
+    for (i, case) in suite.cases.iter().enumerate() {
+        if case.name.trim().is_empty() {
+            return Err(format!("Case {} has no name", i + 1));
+        }
+        if suite.cases[..i].iter().any(|c| c.name == case.name) {
+            return Err(format!("Duplicate case name '{}'", case.name));
+        }
+        if case.task.trim().is_empty() {
+            return Err(format!("Case '{}' has an empty task", case.name));
+        }
+        if case.checks.is_empty() {
+            return Err(format!("Case '{}' has no checks", case.name));
+        }
+        for check in &case.checks {
+            match (&check.command, &check.file) {
+                (Some(_), None) if check.contains.is_none() => {}
+                (None, Some(_)) if check.exit_code.is_none() => {}
+                _ => {
+                    return Err(format!(
+                        "Case '{}': each check needs either `command` (with optional `exit_code`) or `file` (with optional `contains`)",
+                        case.name
+                    ))
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_suite(
+    suite: EvalSuite,
+    agents: Vec<String>,
+    case_filter: Option<&str>,
+    keep: bool,
+    json: bool,
+) {
+    let project_config = resolve_project_config(Some(suite.project.clone()))
+        .unwrap_or_else(|e| exit_with_error(SmithError::Config(e), json))
+        .unwrap_or_else(|| {
+            exit_with_error(
+                SmithError::Config(format!("Project '{}' not found", suite.project)),
+                json,
+            )
+        });
+    let agents = if !agents.is_empty() {
+        agents
+    } else if !suite.agents.is_empty() {
+        suite.agents.clone()
+    } else {
+        match &project_config.model {
+            Some(model) => vec![model.clone()],
+            None => exit_with_error(
+                SmithError::Validation(format!(
+                    "Project '{}' is missing `model`; list agents in the suite or pass --agents",
+                    suite.project
+                )),
+                json,
+            ),
+        }
+    };
+    let cases: Vec<&EvalCase> = suite
+        .cases
+        .iter()
+        .filter(|c| case_filter.is_none_or(|f| c.name == f))
+        .collect();
+    if cases.is_empty() {
+        exit_with_error(
+            SmithError::Validation(format!(
+                "No case named '{}'",
+                case_filter.unwrap_or_default()
+            )),
+            json,
+        );
+    }
+
+    let mut results = Vec::new();
+    for agent in &agents {
+        for case in &cases {
+            if !json {
+                println!("  {} {} with {}", BULLET_BLUE, case.name, agent);
+            }
+            let started = now_unix();
+            docker::take_prompt_usage();
+            let outcome = run_case(&suite.project, agent, case, keep).await;
+            let usage = docker::take_prompt_usage();
+            let failures = match outcome {
+                Ok(failures) => failures,
+                Err(e) => vec![format!("error: {}", e)],
+            };
+            if !json {
+                if failures.is_empty() {
+                    println!("    {} passed", BULLET_GREEN);
+                }
+                for failure in &failures {
+                    println!("    {} {}", BULLET_RED, failure);
+                }
+            }
+            results.push(CaseResult {
+                case: case.name.clone(),
+                agent: agent.clone(),
+                passed: failures.is_empty(),
+                failures,
+                duration_secs: now_unix().saturating_sub(started),
+                tokens: usage.tokens,
+                cost: usage.cost,
+            });
+        }
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&results).unwrap_or_default()
+        );
+    } else {
+        println!();
+        print_scoreboard(&agents, &results);
+    }
+    if results.iter().any(|r| !r.passed) {
+        std::process::exit(1);
+    }
+}
+
+/// Run one case with one agent on a scratch branch; returns the failed checks.
+async fn run_case(
+    project: &str,
+    agent: &str,
+    case: &EvalCase,
+    keep: bool,
+) -> Result<Vec<String>, String> {
+    let branch = format!(
+        "eval-{}-{}",
+        matrix_branch_slug(&case.name),
+        matrix_branch_slug(agent)
+    );
+    set_agent_override(Some(agent.to_string()));
+    let started = ensure_spawned_container_for_pipeline(project, &branch, true, false).await;
+    let project_config = resolve_project_config(Some(project.to_string()));
+    set_agent_override(None);
+    started?;
+    let project_config = project_config?;
+
+    let result = prepare_fixture(project, &branch, &case.fixture).and_then(|()| {
+        let roles = resolve_pipeline_roles(project_config.as_ref(), "dev");
+        let profile = resolve_project_model_profile(project_config.as_ref())?;
+        let role = roles.execute_run.as_ref();
+        docker::run_prompt_in_spawned_container_with_options(
+            project,
+            &branch,
+            &case.task,
+            logging::verbose(),
+            role.and_then(|r| r.model.as_deref())
+                .or(profile.model.as_deref()),
+            role.and_then(|r| r.prompt.as_deref()),
+        )?;
+        Ok(case
+            .checks
+            .iter()
+            .filter(|check| !check_passes(project, &branch, check))
+            .map(EvalCheck::label)
+            .collect())
+    });
+
+    if !keep {
+        if let Err(e) = docker::stop_spawned_container(project, &branch) {
+            eprintln!("  {} failed to stop {}: {}", BULLET_YELLOW, branch, e);
+        }
+    }
+    result
+}
+
+/// Reset the workspace to the fixture ref and run its setup script.
+fn prepare_fixture(project: &str, branch: &str, fixture: &EvalFixture) -> Result<(), String> {
+    let mut script = String::from("set -e; cd /workspace");
+    if fixture.repo.is_some() || fixture.git_ref.is_some() {
+        script.push_str(&format!(
+            "; git fetch -q {} {}; git reset -q --hard FETCH_HEAD; git clean -qfdx",
+            quote(fixture.repo.as_deref().unwrap_or("origin")),
+            quote(fixture.git_ref.as_deref().unwrap_or("HEAD"))
+        ));
+    }
+    if let Some(setup) = &fixture.setup {
+        script.push_str(&format!("; {}", setup));
+    }
+    docker::run_spawn_shell(project, branch, &script)
+        .map(|_| ())
+        .map_err(|e| format!("fixture setup failed: {}", e))
+}
+
+fn check_passes(project: &str, branch: &str, check: &EvalCheck) -> bool {
+    if let Some(command) = &check.command {
+        let script = format!(
+            "cd /workspace; ( {} ) >/dev/null 2>&1; echo \"{}$?\"",
+            command, EXIT_MARKER
+        );
+        return docker::run_spawn_shell(project, branch, &script)
+            .ok()
+            .and_then(|out| parse_exit_marker(&out))
+            == Some(check.exit_code.unwrap_or(0));
+    }
+    let Some(file) = &check.file else {
+        return false;
+    };
+    let path = if file.starts_with('/') {
+        file.clone()
+    } else {
+        format!("/workspace/{}", file)
+    };
+    match docker::read_spawn_file(project, branch, &path) {
+        Ok(content) => check
+            .contains
+            .as_ref()
+            .is_none_or(|text| content.contains(text.as_str())),
+        Err(_) => false,
+    }
+}
+
+/// Exit code printed by a check command's wrapper script.
+pub(crate) fn parse_exit_marker(output: &str) -> Option<i32> {
+    output
+        .lines()
+        .rev()
+        .find_map(|l| l.trim().strip_prefix(EXIT_MARKER))
+        .and_then(|code| code.parse().ok())
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\"'\"'"))
+}
+
+/// One row per agent: pass count, cost and the cases it failed.
+fn print_scoreboard(agents: &[String], results: &[CaseResult]) {
+    let mut table = Table::new(&["agent", "score", "passed", "duration", "cost", "failed"]);
+    for agent in agents {
+        let runs: Vec<&CaseResult> = results.iter().filter(|r| &r.agent == agent).collect();
+        let passed = runs.iter().filter(|r| r.passed).count();
+        let failed: Vec<&str> = runs
+            .iter()
+            .filter(|r| !r.passed)
+            .map(|r| r.case.as_str())
+            .collect();
+        table.row(vec![
+            agent.clone(),
+            format!("{:.0}%", passed as f64 * 100.0 / runs.len().max(1) as f64),
+            format!("{}/{}", passed, runs.len()),
+            format!("{}s", runs.iter().map(|r| r.duration_secs).sum::<u64>()),
+            format!("${:.2}", runs.iter().map(|r| r.cost).sum::<f64>()),
+            failed.join(", "),
+        ]);
+    }
+    let Ok(rendered) = table.render(&TableArgs::default()) else {
+        return;
+    };
+    println!("  {} Scoreboard", BULLET_BLUE);
+    for line in rendered.lines() {
+        println!("  {}", line);
+    }
+    println!();
+}
@@ -0,0 +1,576 @@
+use crate::*;
+use smith_core::config::{env_overrides, EnvOverride};
+
+pub async fn handle(cmd: ExplainCommands) {
+    match cmd {
+        ExplainCommands::Run {
+            pipeline,
+            project,
+            branch,
+            base,
+            agent,
+            model,
+            execute_model,
+            validate_model,
+            preset,
+            ssh_key,
+            table,
+        } => {
+            let args = ExplainArgs {
+                pipeline: if pipeline == "dev" {
+                    "develop".to_string()
+                } else {
+                    pipeline
+                },
+                project,
+                branch,
+                base,
+                agent,
+                model,
+                execute_model,
+                validate_model,
+                preset,
+                ssh_key,
+            };
+            let rows = explain_run(&args).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let mut out = Table::new(&["setting", "value", "rule"]);
+            for (setting, value, rule) in rows {
+                out.row(vec![setting, value, rule]);
+            }
+            print!(
+                "{}",
+                out.render(&table).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+            );
+        }
+    }
+}
+
+/// The command line `smith explain run` explains.
+pub(crate) struct ExplainArgs {
+    pub pipeline: String,
+    pub project: Option<String>,
+    pub branch: Option<String>,
+    pub base: Option<String>,
+    pub agent: Option<String>,
+    pub model: Option<String>,
+    pub execute_model: Option<String>,
+    pub validate_model: Option<String>,
+    pub preset: Option<String>,
+    pub ssh_key: Option<PathBuf>,
+}
+
+/// Setting, value and the rule that chose it, for each setting `smith run <pipeline>` resolves,
+/// in the order the pipeline resolves them.
+pub(crate) fn explain_run(args: &ExplainArgs) -> Result<Vec<(String, String, String)>, String> {
+    let mut rows: Vec<(String, String, String)> = Vec::new();
+    let mut add = |setting: &str, value: String, rule: String| {
+        rows.push((setting.to_string(), value, rule));
+    };
+    let overrides = env_overrides(std::env::vars());
+
+    let (project_name, rule) = match &args.project {
+        Some(p) => (p.clone(), "--project".to_string()),
+        None => match detect_project_from_cwd()? {
+            Some(p) => (
+                p,
+                "matched the current git repository (origin URL or path)".to_string(),
+            ),
+            None => (
+                detect_or_pick_project()?
+                    .ok_or("No project given or detected; pass --project <name>")?,
+                "picked (no --project, none matched the current directory)".to_string(),
+            ),
+        },
+    };
+    add("project", project_name.clone(), rule);
+    let cfg = load_config()?;
+    let project = cfg
+        .projects
+        .iter()
+        .find(|p| p.name == project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+    let from_env = |section: &str, name: &str, key: &str, rule: String| match env_override_for(
+        &overrides, section, name, key,
+    ) {
+        Some(o) => format!("{} (set by ${})", rule, o.var),
+        None => rule,
+    };
+
+    // Model profile: --agent replaces the project's `model` for the whole run.
+    let (profile_name, rule) = match (&args.agent, &project.model) {
+        (Some(a), _) => (Some(a.clone()), "--agent".to_string()),
+        (None, Some(m)) => (
+            Some(m.clone()),
+            from_env(
+                "projects",
+                &project.name,
+                "model",
+                "project `model`".to_string(),
+            ),
+        ),
+        (None, None) => (
+            None,
+            "project sets no `model`; pipelines refuse to run".to_string(),
+        ),
+    };
+    let profile = profile_name
+        .as_deref()
+        .and_then(|name| cfg.agents.iter().flatten().find(|a| a.name == name));
+    let profile_value = match (&profile_name, profile) {
+        (Some(name), Some(p)) if p.enabled == Some(false) => format!("{} (disabled)", name),
+        (Some(name), Some(_)) => name.clone(),
+        (Some(name), None) => format!("{} (not configured)", name),
+        (None, _) => String::new(),
+    };
+    add("model profile", profile_value, rule);
+
+    let default_model = profile.and_then(|p| p.model.clone());
+    add(
+        "default model",
+        default_model.clone().unwrap_or_default(),
+        match (&default_model, profile) {
+            (Some(_), Some(p)) => {
+                from_env("agents", &p.name, "model", "profile `model`".to_string())
+            }
+            _ => "profile sets no `model`; the agent's own default".to_string(),
+        },
+    );
+
+    let steps: &[(&str, &str)] = match args.pipeline.as_str() {
+        "develop" | "review" => &[
+            ("develop", "dev_execute_run"),
+            ("self-check", "dev_execute_check"),
+            ("validate", "dev_validate_run"),
+        ],
+        _ => &[],
+    };
+    if steps.is_empty() {
+        let (value, rule) = match &args.model {
+            Some(m) => (
+                m.clone(),
+                format!(
+                    "--model (overrides the profile for every run {} step)",
+                    args.pipeline
+                ),
+            ),
+            None => (
+                default_model.clone().unwrap_or_default(),
+                format!(
+                    "run {} uses the profile's model for every step",
+                    args.pipeline
+                ),
+            ),
+        };
+        add("steps", value, rule);
+    }
+    let mut skipped: Vec<&str> = Vec::new();
+    if args.pipeline == "develop" {
+        let (preset, rule) = match (&args.preset, &project.preset) {
+            (Some(p), _) => (p.as_str(), "--preset".to_string()),
+            (None, Some(p)) => (
+                p.as_str(),
+                from_env(
+                    "projects",
+                    &project.name,
+                    "preset",
+                    "project `preset`".to_string(),
+                ),
+            ),
+            (None, None) => (
+                preset::DEFAULT_PRESET,
+                "default (no --preset or project `preset`)".to_string(),
+            ),
+        };
+        let preset = preset::find(preset)?;
+        add(
+            "preset",
+            preset.name.to_string(),
+            format!("{}; {}", rule, preset.summary),
+        );
+        if !preset.self_check {
+            skipped.push("self-check");
+        }
+        if !preset.validate {
+            skipped.push("validate");
+        }
+    }
+    if args.pipeline == "develop" && project.dev_handoff_run.is_some() {
+        let (value, rule) = explain_step(
+            &cfg,
+            project,
+            profile_name.as_deref(),
+            default_model.as_deref(),
+            None,
+            "dev_handoff_run",
+        );
+        add("step handoff", value, rule);
+    }
+    for (label, step) in steps {
+        if skipped.contains(label) {
+            add(
+                &format!("step {}", label),
+                "skipped".to_string(),
+                "the preset leaves this step out".to_string(),
+            );
+            continue;
+        }
+        let step_flag = match *step {
+            "dev_validate_run" => args
+                .validate_model
+                .as_deref()
+                .map(|m| (m, "--validate-model")),
+            _ => args
+                .execute_model
+                .as_deref()
+                .map(|m| (m, "--execute-model")),
+        };
+        let (value, rule) = explain_step(
+            &cfg,
+            project,
+            profile_name.as_deref(),
+            default_model.as_deref(),
+            step_flag.or(args.model.as_deref().map(|m| (m, "--model"))),
+            step,
+        );
+        add(&format!("step {}", label), value, rule);
+    }
+
+    let (image, rule) = explain_image(project);
+    add("image", image, rule);
+
+    let (branch, rule) = match &args.branch {
+        Some(b) => (b.clone(), "--branch".to_string()),
+        None => match current_git_branch() {
+            Some(b) => (b, "current git branch (no --branch)".to_string()),
+            None => (
+                String::new(),
+                "no --branch and not in a git checkout".to_string(),
+            ),
+        },
+    };
+    add("branch", branch.clone(), rule);
+    if !branch.is_empty() {
+        let git_branch = resolve_git_branch(&branch, Some(project));
+        if git_branch != branch {
+            add(
+                "remote branch",
+                git_branch,
+                format!(
+                    "project covers `{}` of a monorepo: branches are prefixed with the project name",
+                    project.scope().unwrap_or_default()
+                ),
+            );
+        }
+    }
+
+    let base = resolve_base_branch(args.base.as_deref(), Some(project));
+    let rule = if args.base.is_some() {
+        "--base".to_string()
+    } else if project.base_branch.is_some() {
+        from_env(
+            "projects",
+            &project.name,
+            "base_branch",
+            "project `base_branch`".to_string(),
+        )
+    } else {
+        "default (no --base, project sets no `base_branch`)".to_string()
+    };
+    add("base branch", base, rule);
+
+    let ssh_key = resolve_ssh_key(args.ssh_key.as_ref(), Some(project));
+    let rule = if args.ssh_key.is_some() {
+        "--ssh-key".to_string()
+    } else if project.ssh_key.is_some() {
+        from_env(
+            "projects",
+            &project.name,
+            "ssh_key",
+            "project `ssh_key`".to_string(),
+        )
+    } else if ssh_key.is_some() {
+        "$SSH_KEY_PATH (project sets no `ssh_key`)".to_string()
+    } else {
+        "none: no --ssh-key, project `ssh_key` or $SSH_KEY_PATH".to_string()
+    };
+    let ssh_value = ssh_key
+        .map(|k| {
+            let missing = if k.exists() { "" } else { " (missing)" };
+            format!("{}{}", k.display(), missing)
+        })
+        .unwrap_or_default();
+    add("ssh key", ssh_value, rule);
+
+    let (forge, rule) = match resolve_forge(Some(project)) {
+        Ok(Forge::GitHub { api_url }) => (
+            format!("github ({})", api_url),
+            if project.forge.is_some() {
+                "project `forge`".to_string()
+            } else {
+                "default (project sets no `forge`)".to_string()
+            },
+        ),
+        Ok(Forge::Gitea { base_url }) => (
+            format!("gitea ({})", base_url),
+            "project `forge`".to_string(),
+        ),
+        Err(e) => (String::new(), e.to_string()),
+    };
+    add("forge", forge, rule);
+    let (token, rule) = if project
+        .github_token
+        .as_deref()
+        .is_some_and(|t| !t.is_empty())
+    {
+        (
+            "set".to_string(),
+            from_env(
+                "projects",
+                &project.name,
+                "github_token",
+                "project `github_token`".to_string(),
+            ),
+        )
+    } else if let (Some(id), Some(_)) = (&project.github_app_id, &project.github_app_key) {
+        (
+            format!("GitHub App {}", id),
+            "installation token minted when needed (no `github_token`)".to_string(),
+        )
+    } else {
+        (
+            String::new(),
+            "no `github_token` or GitHub App; PRs cannot be opened".to_string(),
+        )
+    };
+    add("forge token", token, rule);
+
+    let author = commit_author(&cfg, Some(project));
+    let mut rule = if author.name_from == author.email_from {
+        format!("{} name and email", author.name_from)
+    } else {
+        format!("{} name, {} email", author.name_from, author.email_from)
+    };
+    if let Some(bot_from) = author.bot_from {
+        rule.push_str(&format!("; [bot] from {} `bot`", bot_from));
+    }
+    add(
+        "commit author",
+        format!(
+            "{} <{}>",
+            author.name.as_deref().unwrap_or(DEFAULT_COMMIT_NAME),
+            author.email.as_deref().unwrap_or(DEFAULT_COMMIT_EMAIL)
+        ),
+        format!("{} (project > model profile > global [commit])", rule),
+    );
+
+    if let Some(sched) = project
+        .schedule
+        .as_ref()
+        .filter(|s| schedule::applies(s, &args.pipeline))
+    {
+        let (weekday, minute) = schedule::local_time(now_unix());
+        let mut value = if sched.windows.is_empty() {
+            "any time".to_string()
+        } else {
+            sched.windows.join(", ")
+        };
+        if !sched.days.is_empty() {
+            value.push_str(&format!(" on {}", sched.days.join(", ")));
+        }
+        if sched.metered.as_deref() == Some("deny") {
+            value.push_str("; not on metered connections");
+        }
+        let state = match schedule::open_at(sched, weekday, minute) {
+            Ok(true) => "open now".to_string(),
+            Ok(false) => match schedule::minutes_until_open(sched, weekday, minute) {
+                Some(wait) => format!("closed now, opens in {}h{:02}m", wait / 60, wait % 60),
+                None => "never opens".to_string(),
+            },
+            Err(e) => e,
+        };
+        add(
+            "run window",
+            value,
+            format!(
+                "project [schedule], local time; {} (--ignore-schedule overrides)",
+                state
+            ),
+        );
+    }
+
+    // Overrides of other keys still change the run; list them so none go unnoticed.
+    for o in &overrides {
+        add(
+            "env override",
+            format!("{} = {}", o.path.join("."), o.value),
+            format!("${}", o.var),
+        );
+    }
+    Ok(rows)
+}
+
+/// Model and rule of one develop step: the override (model and the flag that gave it: the
+/// step's `--execute-model`/`--validate-model`, else `--model`), else the step's role (project
+/// step mapping, else the profile's `default_role`, with the `*` role as fallback), else the
+/// profile model.
+pub(crate) fn explain_step(
+    cfg: &SmithConfig,
+    project: &ProjectConfig,
+    profile: Option<&str>,
+    default_model: Option<&str>,
+    model_override: Option<(&str, &str)>,
+    step: &str,
+) -> (String, String) {
+    if let Some((m, flag)) = model_override {
+        return (
+            m.to_string(),
+            format!("{} (overrides roles and profile)", flag),
+        );
+    }
+    let fallback = |why: String| {
+        (
+            default_model.unwrap_or_default().to_string(),
+            format!("{}; profile `model`", why),
+        )
+    };
+    let Some(profile) = profile else {
+        return fallback("no model profile".to_string());
+    };
+    let (agent, role, source) = match project_step_mapping(project, step) {
+        Some(mapping) => match mapping.split_once(':') {
+            Some((agent, role)) => (
+                agent.to_string(),
+                role.to_string(),
+                format!("project `{}` = \"{}\"", step, mapping),
+            ),
+            None => (
+                profile.to_string(),
+                mapping.clone(),
+                format!("project `{}` = \"{}\"", step, mapping),
+            ),
+        },
+        None => {
+            let default_role = cfg
+                .agents
+                .iter()
+                .flatten()
+                .find(|a| a.name == profile)
+                .and_then(|a| a.default_role.clone());
+            match default_role {
+                Some(role) => (
+                    profile.to_string(),
+                    role,
+                    format!("profile `default_role` (no project `{}`)", step),
+                ),
+                None => {
+                    return fallback(format!("no project `{}` or profile `default_role`", step))
+                }
+            }
+        }
+    };
+    let roles = cfg
+        .agents
+        .iter()
+        .flatten()
+        .find(|a| a.name == agent)
+        .and_then(|a| a.roles.as_ref());
+    let (found, used) = match roles {
+        Some(r) if r.contains_key(&role) => (r.get(&role), role.clone()),
+        Some(r) => (r.get("*"), "*".to_string()),
+        None => (None, role.clone()),
+    };
+    let Some(found) = found else {
+        return fallback(format!(
+            "{}: role '{}' not found on '{}'",
+            source, role, agent
+        ));
+    };
+    let via = if used == role {
+        format!("{}: role '{}' of '{}'", source, role, agent)
+    } else {
+        format!(
+            "{}: role '{}' missing on '{}', using '*'",
+            source, role, agent
+        )
+    };
+    match &found.model {
+        Some(m) => (m.clone(), format!("{}, role `model`", via)),
+        None => fallback(format!("{}, role sets no model", via)),
+    }
+}
+
+/// Image and rule as `resolve_project_image` picks it, without building anything.
+fn explain_image(project: &ProjectConfig) -> (String, String) {
+    let (image, configured) = match &project.image {
+        Some(i) => (i.clone(), "project `image`"),
+        None => (DEFAULT_AGENT_IMAGE.to_string(), "default agent image"),
+    };
+    let script = project.script.as_deref().unwrap_or_default().trim();
+    if project.backend.as_deref() == Some("kubernetes") {
+        return (
+            image,
+            format!(
+                "{} (kubernetes backend: no baked or setup layers)",
+                configured
+            ),
+        );
+    }
+    if docker::check_docker_available().is_err() {
+        return (
+            image,
+            format!(
+                "{}; Docker unavailable, baked/setup layers not checked",
+                configured
+            ),
+        );
+    }
+    let baked = docker::baked_image_tag(&project.name, &image, script);
+    if docker::image_exists(&baked).unwrap_or(false) {
+        return (
+            baked,
+            format!("`smith project bake` image of the {}", configured),
+        );
+    }
+    if script.is_empty() {
+        return (image, format!("{} (no bake, no setup script)", configured));
+    }
+    let tag = docker::setup_image_tag(&image, script);
+    let rule = if docker::image_exists(&tag).unwrap_or(false) {
+        format!("cached setup layer: {} + setup `script`", configured)
+    } else {
+        format!(
+            "setup layer built on first run: {} + setup `script`",
+            configured
+        )
+    };
+    (tag, rule)
+}
+
+/// The override setting `section.<name>.key`, if any (names match as override paths do).
+fn env_override_for<'a>(
+    overrides: &'a [EnvOverride],
+    section: &str,
+    name: &str,
+    key: &str,
+) -> Option<&'a EnvOverride> {
+    let name = name.to_lowercase().replace('-', "_");
+    overrides.iter().find(|o| {
+        o.path.len() == 3 && o.path[0] == section && o.path[1] == name && o.path[2] == key
+    })
+}
+
+fn current_git_branch() -> Option<String> {
+    Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|b| !b.is_empty() && b != "HEAD")
+}
@@ -0,0 +1,428 @@
+use crate::*;
+
+use crate::commands::system::project_git_command;
+use smith_core::index::{CodeIndex, IndexSettings, IndexedFile};
+use std::collections::BTreeMap;
+
+pub async fn handle(cmd: IndexCommands) {
+    match cmd {
+        IndexCommands::Build { project, git_ref } => {
+            let result = match indexed_project(&project) {
+                Ok((proj, settings)) => {
+                    let git_ref = git_ref.unwrap_or_else(|| resolve_base_branch(None, Some(&proj)));
+                    reindex(&proj, &settings, &git_ref, None).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        IndexCommands::Update { project } => {
+            if let Err(e) = update(project.as_deref()).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        IndexCommands::Status {
+            project,
+            fetch,
+            table,
+        } => {
+            if let Err(e) = status(project.as_deref(), fetch, &table) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Clone or fetch the project's bare mirror under the git mirror cache and return its path.
+pub(crate) fn update_mirror(proj: &ProjectConfig) -> Result<PathBuf, String> {
+    let dir = git_mirror_dir().map_err(|e| e.to_string())?;
+    let mirror = dir.join(&proj.name);
+    let output = if mirror.join("HEAD").is_file() {
+        project_git_command(proj)
+            .arg("-C")
+            .arg(&mirror)
+            .args(["fetch", "--prune", "--quiet", "origin"])
+            .output()
+    } else {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        project_git_command(proj)
+            .args(["clone", "--mirror", "--quiet", &proj.repo])
+            .arg(&mirror)
+            .output()
+    }
+    .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to update the mirror of {}: {}",
+            proj.repo,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(mirror)
+}
+
+/// Run git in a bare mirror and return its stdout.
+pub(crate) fn mirror_git(mirror: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(mirror)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// A file of the tree at a commit: path (relative to the project's scope), blob and size.
+pub(crate) struct TreeFile {
+    pub path: String,
+    pub blob: String,
+    pub size: u64,
+}
+
+/// Files of `commit` in the mirror, limited to the project's `subdir` when it is scoped.
+pub(crate) fn tree_files(
+    mirror: &Path,
+    commit: &str,
+    proj: &ProjectConfig,
+) -> Result<Vec<TreeFile>, String> {
+    let mut args = vec!["ls-tree", "-r", "-l", "-z", commit];
+    if let Some(scope) = proj.scope() {
+        args.extend(["--", scope]);
+    }
+    let listing = mirror_git(mirror, &args)?;
+    let prefix = proj.scope().map(|s| format!("{}/", s)).unwrap_or_default();
+    Ok(listing
+        .split(|b| *b == 0)
+        .filter_map(|entry| {
+            // "<mode> blob <sha> <size>\t<path>"
+            let entry = String::from_utf8_lossy(entry);
+            let (meta, path) = entry.split_once('\t')?;
+            let mut fields = meta.split_whitespace();
+            let (_, kind, blob, size) = (
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+                fields.next()?,
+            );
+            if kind != "blob" {
+                return None;
+            }
+            Some(TreeFile {
+                path: path.strip_prefix(&prefix).unwrap_or(path).to_string(),
+                blob: blob.to_string(),
+                size: size.trim().parse().ok()?,
+            })
+        })
+        .collect())
+}
+
+/// Project `name` and the `[index]` settings.
+fn indexed_project(name: &str) -> Result<(ProjectConfig, IndexSettings), String> {
+    let cfg = load_config()?;
+    let proj = cfg
+        .projects
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| format!("Project '{}' not found", name))?;
+    Ok((proj, IndexSettings::from_config(cfg.index.as_ref())))
+}
+
+/// Commit `git_ref` points at in the mirror.
+fn resolve_commit(mirror: &Path, proj: &ProjectConfig, git_ref: &str) -> Result<String, String> {
+    let commit = mirror_git(
+        mirror,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", git_ref)],
+    )
+    .map_err(|_| format!("Ref '{}' not found in {}", git_ref, proj.repo))?;
+    Ok(String::from_utf8_lossy(&commit).trim().to_string())
+}
+
+/// Fetch the project's mirror and index its files at `git_ref`. Files whose blob is unchanged
+/// since `previous` keep their chunks; only the others are read and embedded.
+async fn reindex(
+    proj: &ProjectConfig,
+    settings: &IndexSettings,
+    git_ref: &str,
+    previous: Option<&CodeIndex>,
+) -> Result<(), String> {
+    let previous = previous.filter(|p| p.matches(settings));
+    println!("  {} Fetching mirror of {}", BULLET_BLUE, proj.repo);
+    let mirror = update_mirror(proj)?;
+    let commit = resolve_commit(&mirror, proj, git_ref)?;
+
+    let mut files = BTreeMap::new();
+    let mut changed = Vec::new();
+    let (mut kept, mut skipped) = (0, 0);
+    for file in tree_files(&mirror, &commit, proj)? {
+        if !index::is_indexable(&file.path, file.size, settings.max_file_kb) {
+            skipped += 1;
+            continue;
+        }
+        if let Some(unchanged) = previous
+            .and_then(|p| p.files.get(&file.path))
+            .filter(|f| f.blob == file.blob)
+        {
+            files.insert(file.path, unchanged.clone());
+            kept += 1;
+            continue;
+        }
+        let content = mirror_git(&mirror, &["cat-file", "blob", &file.blob])?;
+        let Some(text) = index::as_text(&content) else {
+            skipped += 1;
+            continue;
+        };
+        let chunks = index::chunk_file(text, settings.chunk_lines);
+        changed.push(file.path.clone());
+        files.insert(
+            file.path,
+            IndexedFile {
+                blob: file.blob,
+                chunks,
+            },
+        );
+    }
+    let removed = previous.map_or(0, |p| {
+        p.files
+            .keys()
+            .filter(|path| !files.contains_key(*path))
+            .count()
+    });
+
+    let texts: Vec<String> = changed
+        .iter()
+        .flat_map(|path| {
+            files[path]
+                .chunks
+                .iter()
+                .map(move |c| format!("{}\n{}", path, c.text))
+        })
+        .collect();
+    if !texts.is_empty() {
+        println!(
+            "  {} Embedding {} chunks of {} files with {}",
+            BULLET_BLUE,
+            texts.len(),
+            changed.len(),
+            settings.embedding_model
+        );
+    }
+    let mut vectors = index::embed(settings, &texts).await?.into_iter();
+    for path in &changed {
+        if let Some(file) = files.get_mut(path) {
+            for chunk in &mut file.chunks {
+                chunk.vector = vectors.next().unwrap_or_default();
+            }
+        }
+    }
+
+    let code_index = CodeIndex {
+        project: proj.name.clone(),
+        git_ref: git_ref.to_string(),
+        commit: commit.clone(),
+        model: settings.embedding_model.clone(),
+        chunk_lines: settings.chunk_lines,
+        built_at: now_unix(),
+        files,
+    };
+    let path = code_index.save().map_err(|e| e.to_string())?;
+    println!(
+        "  {} Indexed {} files ({} chunks) of {} at {}: {} embedded, {} unchanged, {} removed, {} skipped -> {}",
+        BULLET_GREEN,
+        code_index.files.len(),
+        code_index.chunk_count(),
+        git_ref,
+        short_sha(&commit),
+        changed.len(),
+        kept,
+        removed,
+        skipped,
+        path.display()
+    );
+    Ok(())
+}
+
+fn short_sha(commit: &str) -> &str {
+    &commit[..commit.len().min(12)]
+}
+
+/// `smith index update`: fetch and reindex the changed files of one project, or of every
+/// indexed project.
+async fn update(project: Option<&str>) -> Result<(), String> {
+    let names: Vec<String> = match project {
+        Some(name) => vec![name.to_string()],
+        None => load_config()?
+            .projects
+            .iter()
+            .filter(|p| index::index_path(&p.name).is_ok_and(|path| path.is_file()))
+            .map(|p| p.name.clone())
+            .collect(),
+    };
+    if names.is_empty() {
+        println!("  {} No indexed projects", BULLET_BLUE);
+        return Ok(());
+    }
+    let mut failed = false;
+    for name in &names {
+        let result = async {
+            let (proj, settings) = indexed_project(name)?;
+            let previous = index::load(name)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| {
+                    format!(
+                        "'{}' has no index; build it with `smith index build {}`",
+                        name, name
+                    )
+                })?;
+            if !previous.matches(&settings) {
+                println!(
+                    "  {} {}: [index] model or chunk size changed; re-embedding every file",
+                    BULLET_YELLOW, name
+                );
+            }
+            reindex(&proj, &settings, &previous.git_ref, Some(&previous)).await
+        }
+        .await;
+        if let Err(e) = result {
+            eprintln!("  {} {}: {}", BULLET_RED, name, e);
+            failed = true;
+        }
+    }
+    if failed {
+        return Err("some indexes were not updated".to_string());
+    }
+    Ok(())
+}
+
+/// How far an index is behind its ref in the (last fetched) mirror.
+enum Staleness {
+    Current,
+    Behind {
+        commits: usize,
+        files: usize,
+    },
+    /// `[index]` changed model or chunk size; the next update re-embeds everything
+    Settings,
+    /// The ref is gone from the mirror
+    MissingRef,
+}
+
+fn staleness(
+    proj: &ProjectConfig,
+    settings: &IndexSettings,
+    code_index: &CodeIndex,
+) -> Result<Staleness, String> {
+    if !code_index.matches(settings) {
+        return Ok(Staleness::Settings);
+    }
+    let mirror = git_mirror_dir()
+        .map_err(|e| e.to_string())?
+        .join(&proj.name);
+    let Ok(head) = resolve_commit(&mirror, proj, &code_index.git_ref) else {
+        return Ok(Staleness::MissingRef);
+    };
+    if head == code_index.commit {
+        return Ok(Staleness::Current);
+    }
+    let range = format!("{}..{}", code_index.commit, head);
+    let commits = String::from_utf8_lossy(&mirror_git(&mirror, &["rev-list", "--count", &range])?)
+        .trim()
+        .parse()
+        .unwrap_or(0);
+    let mut args = vec!["diff", "--name-only", "-z", &code_index.commit, &head];
+    if let Some(scope) = proj.scope() {
+        args.extend(["--", scope]);
+    }
+    let files = mirror_git(&mirror, &args)?
+        .split(|b| *b == 0)
+        .filter(|path| !path.is_empty())
+        .count();
+    Ok(Staleness::Behind { commits, files })
+}
+
+/// `smith index status`: each index's ref, commit and age, and how far it is behind the mirror.
+fn status(project: Option<&str>, fetch: bool, table: &TableArgs) -> Result<(), String> {
+    let cfg = load_config()?;
+    let settings = IndexSettings::from_config(cfg.index.as_ref());
+    let mut rows = Table::new(&["project", "ref", "commit", "files", "age", "status"]);
+    let mut found = false;
+    for proj in cfg
+        .projects
+        .iter()
+        .filter(|p| project.is_none_or(|name| p.name == name))
+    {
+        let Some(code_index) = index::load(&proj.name).map_err(|e| e.to_string())? else {
+            if project.is_some() {
+                return Err(format!(
+                    "'{}' has no index; build it with `smith index build {}`",
+                    proj.name, proj.name
+                ));
+            }
+            continue;
+        };
+        found = true;
+        if fetch {
+            if let Err(e) = update_mirror(proj) {
+                eprintln!("  Warning: {}: {}", proj.name, e);
+            }
+        }
+        let state = match staleness(proj, &settings, &code_index)? {
+            Staleness::Current => "current".to_string(),
+            Staleness::Behind { commits, files } => format!(
+                "stale: {} commit{}, {} file{} changed",
+                commits,
+                if commits == 1 { "" } else { "s" },
+                files,
+                if files == 1 { "" } else { "s" }
+            ),
+            Staleness::Settings => "stale: [index] settings changed".to_string(),
+            Staleness::MissingRef => "stale: ref not in mirror".to_string(),
+        };
+        rows.row(vec![
+            proj.name.clone(),
+            code_index.git_ref.clone(),
+            short_sha(&code_index.commit).to_string(),
+            code_index.files.len().to_string(),
+            commands::cache::format_age(now_unix().saturating_sub(code_index.built_at)),
+            state,
+        ]);
+    }
+    if let Some(name) = project.filter(|_| !found) {
+        return Err(format!("Project '{}' not found", name));
+    }
+    if !found {
+        println!("  {} No indexed projects", BULLET_BLUE);
+        return Ok(());
+    }
+    print!("{}", rows.render(table)?);
+    Ok(())
+}
+
+/// `prompt` with retrieved snippets from the project's index, when one has been built; the
+/// prompt unchanged (with a warning) when retrieval fails.
+pub(crate) async fn augment_with_index(project: &str, prompt: &str) -> String {
+    let Ok(Some(code_index)) = index::load(project) else {
+        return prompt.to_string();
+    };
+    let settings = IndexSettings::from_config(load_config().ok().and_then(|c| c.index).as_ref());
+    match index::augment_prompt(&code_index, &settings, prompt).await {
+        Ok(augmented) => augmented,
+        Err(e) => {
+            eprintln!("  Warning: index not used: {}", e);
+            prompt.to_string()
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use crate::*;
+use smith_core::history::RunRecord;
+
+/// One line that matched `smith logs search`.
+#[derive(Serialize, Debug, PartialEq)]
+pub(crate) struct LogMatch {
+    /// "run" (an artifact under /state) or "container" (the container's own log)
+    pub source: &'static str,
+    /// `project:branch` of a spawned agent, or the model profile name
+    pub target: String,
+    /// Run the line belongs to, when known
+    pub run_id: Option<String>,
+    /// Unix time of the log line (container logs only)
+    pub at: Option<u64>,
+    /// `<file>:<line>` under the run directory, or the container name
+    pub location: String,
+    pub line: String,
+}
+
+/// What to look for and where.
+struct Search {
+    pattern: String,
+    ignore_case: bool,
+    /// Seconds ago the window starts / ends
+    since: Option<u64>,
+    until: Option<u64>,
+}
+
+impl Search {
+    fn matches(&self, text: &str) -> bool {
+        if self.ignore_case {
+            text.to_lowercase().contains(&self.pattern.to_lowercase())
+        } else {
+            text.contains(&self.pattern)
+        }
+    }
+}
+
+pub async fn handle(cmd: LogsCommands) {
+    match cmd {
+        LogsCommands::Search {
+            pattern,
+            project,
+            agent,
+            since,
+            until,
+            ignore_case,
+            limit,
+            json,
+        } => {
+            let age = |raw: Option<String>| {
+                raw.as_deref()
+                    .map(commands::cache::parse_age)
+                    .transpose()
+                    .unwrap_or_else(|e| exit_with_error(SmithError::Validation(e), json))
+            };
+            let search = Search {
+                pattern,
+                ignore_case,
+                since: age(since),
+                until: age(until),
+            };
+            let matches = search_all(&search, project.as_deref(), agent.as_deref())
+                .unwrap_or_else(|e| exit_with_error(SmithError::Docker(e), json));
+            print_matches(&matches, limit, json);
+        }
+    }
+}
+
+fn search_all(
+    search: &Search,
+    project: Option<&str>,
+    agent: Option<&str>,
+) -> Result<Vec<LogMatch>, String> {
+    let cfg = load_config().map_err(|e| e.to_string())?;
+    let history = history::load().unwrap_or_default();
+    let mut matches = Vec::new();
+
+    // Spawned agents of the selected projects; --agent keeps the projects using that profile.
+    let spawned: Vec<docker::SpawnInfo> = docker::list_spawned_containers()?
+        .into_iter()
+        .filter(|c| project.is_none_or(|p| c.project == p))
+        .filter(|c| {
+            agent.is_none_or(|a| {
+                cfg.projects
+                    .iter()
+                    .any(|p| p.name == c.project && p.model.as_deref() == Some(a))
+            })
+        })
+        .collect();
+    for container in &spawned {
+        let target = format!("{}:{}", container.project, container.branch);
+        let running = container.status.starts_with("Up") || container.status.starts_with("Running");
+        if running {
+            match docker::run_spawn_shell(
+                &container.project,
+                &container.branch,
+                &state_grep_script(search),
+            ) {
+                Ok(out) => matches.extend(out.lines().filter_map(parse_state_match).map(
+                    |(run_id, location, line)| LogMatch {
+                        source: "run",
+                        target: target.clone(),
+                        run_id,
+                        at: None,
+                        location,
+                        line,
+                    },
+                )),
+                Err(e) => eprintln!("  Warning: {}: {}", target, e),
+            }
+        }
+        let runs: Vec<&RunRecord> = history
+            .iter()
+            .filter(|r| r.project == container.project && r.branch == container.branch)
+            .collect();
+        let logs = docker::spawn_logs_command(
+            &container.project,
+            &container.branch,
+            &container_log_options(search),
+        );
+        match search_container_log(logs, search) {
+            Ok(found) => matches.extend(found.into_iter().map(|(at, line)| LogMatch {
+                source: "container",
+                target: target.clone(),
+                run_id: run_at(&runs, at),
+                at,
+                location: container.container_name.clone(),
+                line,
+            })),
+            Err(e) => eprintln!("  Warning: {}: {}", target, e),
+        }
+    }
+
+    // Model profile containers, unless the search is narrowed to a project.
+    if project.is_none() {
+        let profiles = docker::list_running_agent_containers().map_err(|e| e.to_string())?;
+        for name in profiles.iter().filter(|n| agent.is_none_or(|a| a == *n)) {
+            let container = docker::agent_container_name(name);
+            let mut logs = Command::new("docker");
+            logs.arg("logs")
+                .args(container_log_options(search).args(false))
+                .arg(&container);
+            match search_container_log(logs, search) {
+                Ok(found) => matches.extend(found.into_iter().map(|(at, line)| LogMatch {
+                    source: "container",
+                    target: name.clone(),
+                    run_id: None,
+                    at,
+                    location: container.clone(),
+                    line,
+                })),
+                Err(e) => eprintln!("  Warning: {}: {}", name, e),
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Shell script listing `<path>:<line>:<text>` for matching lines in files under /state,
+/// limited by modification time to the search window.
+fn state_grep_script(search: &Search) -> String {
+    let mut find = String::from("find . -type f");
+    if let Some(since) = search.since {
+        find.push_str(&format!(" -mmin -{}", since.div_ceil(60).max(1)));
+    }
+    if let Some(until) = search.until {
+        find.push_str(&format!(" ! -mmin -{}", until / 60));
+    }
+    format!(
+        "cd /state 2>/dev/null || exit 0; {} -exec grep -HnF{} -e '{}' -- {{}} + 2>/dev/null; true",
+        find,
+        if search.ignore_case { "i" } else { "" },
+        search.pattern.replace('\'', "'\"'\"'")
+    )
+}
+
+/// Split a grep line from /state ("./plan-ab12/develop-1.json:12:text") into run id,
+/// `<file>:<line>` and text. Files directly under /state belong to no run.
+pub(crate) fn parse_state_match(raw: &str) -> Option<(Option<String>, String, String)> {
+    let raw = raw.strip_prefix("./").unwrap_or(raw);
+    let (path, rest) = raw.split_once(':')?;
+    let (line_no, text) = rest.split_once(':')?;
+    line_no.parse::<u32>().ok()?;
+    let (run_id, file) = match path.split_once('/') {
+        Some((run, file)) => (Some(run.to_string()), file),
+        None => (None, path),
+    };
+    Some((run_id, format!("{}:{}", file, line_no), text.to_string()))
+}
+
+fn container_log_options(search: &Search) -> docker::LogOptions {
+    docker::LogOptions {
+        since: search.since.map(|s| format!("{}s", s)),
+        until: search.until.map(|s| format!("{}s", s)),
+        timestamps: true,
+        ..Default::default()
+    }
+}
+
+/// Matching lines of a `docker logs --timestamps` (or `kubectl logs`) command, with their time.
+fn search_container_log(
+    mut logs: Command,
+    search: &Search,
+) -> Result<Vec<(Option<u64>, String)>, String> {
+    let output = logs
+        .logged()
+        .output()
+        .map_err(|e| format!("Failed to read container logs: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    // The agent server writes to both streams; search them alike.
+    let text = [output.stdout, output.stderr].concat();
+    Ok(String::from_utf8_lossy(&text)
+        .lines()
+        .map(split_timestamp)
+        .filter(|(_, line)| search.matches(line))
+        .map(|(at, line)| (at, line.to_string()))
+        .collect())
+}
+
+/// Split the RFC 3339 UTC timestamp `--timestamps` puts before each line.
+pub(crate) fn split_timestamp(line: &str) -> (Option<u64>, &str) {
+    match line.split_once(' ') {
+        Some((stamp, rest)) => match parse_utc_timestamp(stamp) {
+            Some(at) => (Some(at), rest),
+            None => (None, line),
+        },
+        None => (None, line),
+    }
+}
+
+/// "2026-10-17T12:34:56.123456789Z" to unix seconds (fraction dropped).
+fn parse_utc_timestamp(stamp: &str) -> Option<u64> {
+    if !stamp.ends_with('Z') || stamp.len() < 20 {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| stamp.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if stamp.as_bytes()[10] != b'T' || !(1..=12).contains(&month) {
+        return None;
+    }
+    // Days since 1970-01-01 in the proleptic Gregorian calendar.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    u64::try_from(days * 86_400 + hour * 3_600 + minute * 60 + second).ok()
+}
+
+/// The recorded run that was in progress at `at`, if any.
+pub(crate) fn run_at(runs: &[&RunRecord], at: Option<u64>) -> Option<String> {
+    let at = at?;
+    runs.iter()
+        .filter(|r| r.started_at <= at && at <= r.finished_at)
+        .max_by_key(|r| r.started_at)
+        .map(|r| r.run_id.clone())
+}
+
+fn print_matches(matches: &[LogMatch], limit: usize, json: bool) {
+    if json {
+        for m in matches.iter().take(limit) {
+            println!("{}", serde_json::to_string(m).unwrap_or_default());
+        }
+        return;
+    }
+    if matches.is_empty() {
+        println!("  {} No matching lines", BULLET_YELLOW);
+        return;
+    }
+    let mut table = Table::new(&["time", "target", "run", "source", "location", "line"]);
+    for m in matches.iter().take(limit) {
+        table.row(vec![
+            m.at.map(audit::format_utc).unwrap_or_default(),
+            m.target.clone(),
+            m.run_id.clone().unwrap_or_default(),
+            m.source.to_string(),
+            m.location.clone(),
+            m.line.trim().to_string(),
+        ]);
+    }
+    print!(
+        "{}",
+        table.render(&TableArgs::default()).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        })
+    );
+    if matches.len() > limit {
+        println!(
+            "{} more match(es) not shown; narrow the search or raise --limit",
+            matches.len() - limit
+        );
+    }
+}
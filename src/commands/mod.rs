@@ -1,7 +1,21 @@
 pub mod agent;
+pub mod apply;
+pub mod audit;
+pub mod cache;
+pub mod config;
+pub mod debug;
+pub mod eval;
+pub mod explain;
+pub mod index;
+pub mod logs;
 pub mod model;
+pub mod node;
 pub mod pipeline;
+pub mod plugin;
+pub mod pool;
 pub mod project;
 pub mod role;
 pub mod run;
+pub mod selftest;
+pub mod stats;
 pub mod system;
@@ -1,4 +1,5 @@
 use crate::*;
+use std::io::IsTerminal;
 
 pub async fn handle(cmd: ModelCommands) {
     match cmd {
@@ -13,6 +14,12 @@ pub async fn handle(cmd: ModelCommands) {
             port,
             enabled,
             env,
+            env_passthrough,
+            volumes,
+            bind_address,
+            health_path,
+            health_match,
+            health_command,
         } => {
             let mut cfg = load_config().unwrap_or_else(|e| {
                 eprintln!("Error: {}", e);
@@ -29,6 +36,30 @@ pub async fn handle(cmd: ModelCommands) {
                 },
                 None => None,
             };
+            let volumes = volumes
+                .map(|v| {
+                    v.into_iter()
+                        .filter(|v| !v.trim().is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .filter(|v| !v.is_empty());
+            if let Err(e) = docker::agent_volume_args(volumes.as_deref().unwrap_or_default()) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            let bind_address = bind_address.filter(|b| !b.trim().is_empty());
+            if let Some(Err(e)) = bind_address.as_deref().map(docker::parse_bind_address) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            let passthrough = match env_passthrough.as_deref().map(parse_env_passthrough) {
+                Some(Ok(names)) if !names.is_empty() => Some(names),
+                Some(Err(e)) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                _ => None,
+            };
             if let Err(e) = add_agent_to_config(
                 &mut cfg,
                 name.clone(),
@@ -47,17 +78,26 @@ pub async fn handle(cmd: ModelCommands) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
+            if let Some(entry) = cfg
+                .agents
+                .as_mut()
+                .and_then(|agents| agents.iter_mut().find(|a| a.name == name))
+            {
+                entry.health_path = health_path.filter(|p| !p.trim().is_empty());
+                entry.health_match = health_match;
+                entry.health_command = health_command.filter(|c| !c.trim().is_empty());
+                entry.env_passthrough = passthrough;
+                entry.volumes = volumes;
+                entry.bind_address = bind_address;
+            }
             save_config(&cfg).unwrap_or_else(|e| {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             });
             println!("Agent '{}' added successfully", name);
         }
-        ModelCommands::Status => {
-            let cfg = load_config().unwrap_or_else(|e| {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            });
+        ModelCommands::Status { json, table } => {
+            let cfg = load_config().unwrap_or_else(|e| exit_with_error(e, json));
             let running = docker::list_running_agent_containers().unwrap_or_default();
             let _current = cfg.current_agent.as_deref().unwrap_or(DEFAULT_AGENT_NAME);
             let base = docker::OPENCODE_SERVER_PORT;
@@ -109,6 +149,24 @@ pub async fn handle(cmd: ModelCommands) {
                     })
                     .collect()
             };
+            let mut json_agents: Vec<Value> = Vec::new();
+            let mut rows = Table::new(&[
+                "status",
+                "name",
+                "type",
+                "active",
+                "port",
+                "url",
+                "image",
+                "model",
+                "small_model",
+                "provider",
+                "roles",
+                "env",
+            ])
+            .defaults(&[
+                "status", "name", "type", "active", "port", "url", "model", "provider", "roles",
+            ]);
             for (
                 name,
                 image,
@@ -116,7 +174,7 @@ pub async fn handle(cmd: ModelCommands) {
                 model,
                 small_model,
                 provider,
-                _base_url,
+                base_url,
                 enabled,
                 roles,
                 env_map,
@@ -124,78 +182,148 @@ pub async fn handle(cmd: ModelCommands) {
             ) in &agents
             {
                 let active = running.contains(name);
-                let reachable = if active {
-                    Some(docker::check_agent_reachable(*port))
+                let profile = list.iter().find(|e| &e.name == name);
+                let health = if active {
+                    Some(
+                        docker::HealthCheck::for_agent(profile)
+                            .probe(
+                                &docker::agent_container_name(name),
+                                *port,
+                                std::time::Duration::from_secs(2),
+                            )
+                            .await,
+                    )
                 } else {
                     None
                 };
+                let reachable = health.as_ref().map(|h| h.is_ok());
                 let built =
                     docker::image_exists(&docker::agent_built_image_tag(name)).unwrap_or(false);
                 let is_cloud = agent_type.as_deref() != Some("local");
-                let circle = status_circle(active, reachable, built, is_cloud);
-                println!("\n  {} {}", circle, name);
-                if active {
+                if json {
+                    let built_tag = docker::agent_built_image_tag(name);
+                    let digest_ref = if built { &built_tag } else { image };
+                    json_agents.push(serde_json::json!({
+                        "name": name,
+                        "type": if is_cloud { "cloud" } else { "local" },
+                        "enabled": enabled.unwrap_or(true),
+                        "running": active,
+                        "reachable": reachable,
+                        "health_error": health.as_ref().and_then(|h| h.as_ref().err()),
+                        "port": port,
+                        "url": format!("http://localhost:{}", port),
+                        "image": image,
+                        "built_image": if built { Some(&built_tag) } else { None },
+                        "image_digest": docker::image_digest(digest_ref).ok().flatten(),
+                        "model": model,
+                        "small_model": small_model,
+                        "provider": provider,
+                        "base_url": base_url,
+                        "roles": roles.clone().unwrap_or_default(),
+                        "env": env_map.clone().unwrap_or_default(),
+                        "env_passthrough": profile
+                            .and_then(|e| e.env_passthrough.clone())
+                            .unwrap_or_default(),
+                        "volumes": profile
+                            .and_then(|e| e.volumes.clone())
+                            .unwrap_or_default(),
+                        "bind_address": docker::agent_bind_address(&cfg, profile).to_string(),
+                        "commit": profile.and_then(|e| e.commit.clone()),
+                    }));
+                    continue;
+                }
+                let disabled = !enabled.unwrap_or(true);
+                let active_str = if active {
                     if reachable == Some(false) {
-                        println!("      Active:   active (running)");
-                        println!(
-                            "      Port:     {} {} (warning: unreachable)",
-                            port,
-                            clickable_agent_url(*port)
-                        );
+                        "running (unreachable)"
+                    } else if disabled {
+                        "running (disabled)"
                     } else {
-                        println!("      Active:   active (running)");
-                        println!("      Port:     {} {}", port, clickable_agent_url(*port));
+                        "running"
                     }
+                } else if disabled {
+                    "disabled"
                 } else if is_cloud {
-                    println!("      Active:   cloud (config only)");
+                    "cloud"
                 } else {
-                    println!(
-                        "      Active:   {}",
-                        if enabled.unwrap_or(true) {
-                            "inactive"
-                        } else {
-                            "disabled"
-                        }
-                    );
-                }
-                if !is_cloud {
-                    let built_tag = docker::agent_built_image_tag(name);
-                    let image_line = if built {
-                        built_tag
-                    } else {
-                        format!("not built (uses {})", image)
-                    };
-                    println!("      Image:    {}", image_line);
-                }
-                let model_str = model.as_deref().unwrap_or("default");
-                let small_model_str = small_model.as_deref().unwrap_or("-");
-                let is_local = agent_type.as_deref() == Some("local");
-                let provider_str = provider.as_deref().unwrap_or("-");
-                let mode_str = if is_local { "local" } else { "cloud" };
-                println!(
-                    "      Model:    {}  Small: {}  Provider: {}  Type: {}",
-                    model_str, small_model_str, provider_str, mode_str
-                );
-                let roles_str = match roles.as_ref() {
-                    Some(r) if !r.is_empty() => r.keys().cloned().collect::<Vec<_>>().join(", "),
-                    _ => "-".to_string(),
+                    "inactive"
                 };
-                println!("      Roles:    {}", roles_str);
-                let env_str = match env_map.as_ref() {
-                    Some(m) if !m.is_empty() => {
-                        let mut parts: Vec<String> =
-                            m.iter().map(|(k, v)| format!("{}<-{}", k, v)).collect();
-                        parts.sort();
-                        parts.join(", ")
-                    }
-                    _ => "-".to_string(),
+                let image_str = if is_cloud {
+                    String::new()
+                } else if built {
+                    docker::agent_built_image_tag(name)
+                } else {
+                    format!("not built (uses {})", image)
                 };
-                println!("      Env:      {}", env_str);
+                let mut role_names: Vec<String> = roles
+                    .as_ref()
+                    .map(|r| r.keys().cloned().collect())
+                    .unwrap_or_default();
+                role_names.sort();
+                let mut env_parts: Vec<String> = env_map
+                    .as_ref()
+                    .map(|m| m.iter().map(|(k, v)| format!("{}<-{}", k, v)).collect())
+                    .unwrap_or_default();
+                env_parts.sort();
+                rows.row(vec![
+                    status_circle(active, reachable, built, is_cloud),
+                    name.clone(),
+                    if is_cloud { "cloud" } else { "local" }.to_string(),
+                    active_str.to_string(),
+                    port.to_string(),
+                    if active {
+                        clickable_agent_url(*port)
+                    } else {
+                        String::new()
+                    },
+                    image_str,
+                    model.clone().unwrap_or_else(|| "default".to_string()),
+                    small_model.clone().unwrap_or_default(),
+                    provider.clone().unwrap_or_default(),
+                    role_names.join(","),
+                    env_parts.join(", "),
+                ]);
             }
-            if agents.is_empty() {
-                println!("\n  (no cloud agents configured)");
+            // Provider usage over the last week, with --verbose.
+            let usage_stats = logging::verbose().then(|| {
+                let since = now_unix().saturating_sub(USAGE_WINDOW_SECS);
+                usage::summarize(&usage::load_since(since).unwrap_or_default())
+            });
+            if json {
+                let mut out = serde_json::json!({ "agents": json_agents });
+                if let Some(stats) = &usage_stats {
+                    out["usage"] = stats
+                        .iter()
+                        .map(|s| {
+                            serde_json::json!({
+                                "agent": s.agent,
+                                "provider": s.provider,
+                                "requests": s.requests,
+                                "rate_limited": s.rate_limited,
+                                "server_errors": s.server_errors,
+                                "errors": s.errors,
+                                "p50_ms": s.p50_ms,
+                                "p95_ms": s.p95_ms,
+                            })
+                        })
+                        .collect();
+                }
+                println!("{}", serde_json::to_string_pretty(&out).unwrap_or_default());
             } else {
-                println!();
+                if agents.is_empty() {
+                    println!("\n  (no cloud agents configured)");
+                } else {
+                    print!(
+                        "{}",
+                        rows.render(&table).unwrap_or_else(|e| {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        })
+                    );
+                }
+                if let Some(stats) = &usage_stats {
+                    print_usage(stats, &table);
+                }
             }
         }
         ModelCommands::Update {
@@ -209,6 +337,15 @@ pub async fn handle(cmd: ModelCommands) {
             port,
             enabled,
             env,
+            env_passthrough,
+            volumes,
+            bind_address,
+            health_path,
+            health_match,
+            health_command,
+            commit_name,
+            commit_email,
+            commit_bot,
         } => {
             let mut cfg = load_config().unwrap_or_else(|e| {
                 eprintln!("Error: {}", e);
@@ -225,7 +362,16 @@ pub async fn handle(cmd: ModelCommands) {
                         && base_url.is_none()
                         && port.is_none()
                         && enabled.is_none()
-                        && env.is_none();
+                        && env.is_none()
+                        && env_passthrough.is_none()
+                        && volumes.is_none()
+                        && bind_address.is_none()
+                        && health_path.is_none()
+                        && health_match.is_none()
+                        && health_command.is_none()
+                        && commit_name.is_none()
+                        && commit_email.is_none()
+                        && commit_bot.is_none();
                     if is_wizard {
                         println!("  Updating agent '{}'", entry.name);
                         let image_in = prompt_line(&format!("  Image [{}]: ", entry.image));
@@ -363,6 +509,77 @@ pub async fn handle(cmd: ModelCommands) {
                         if let Some(e) = enabled {
                             entry.enabled = Some(e);
                         }
+                        if let Some(ref specs) = volumes {
+                            let specs: Vec<String> = specs
+                                .iter()
+                                .filter(|v| !v.trim().is_empty())
+                                .cloned()
+                                .collect();
+                            if let Err(e) = docker::agent_volume_args(&specs) {
+                                eprintln!("Error: {}", e);
+                                std::process::exit(1);
+                            }
+                            entry.volumes = (!specs.is_empty()).then_some(specs);
+                        }
+                        if let Some(ref s) = bind_address {
+                            if s.trim().is_empty() {
+                                entry.bind_address = None;
+                            } else if let Err(e) = docker::parse_bind_address(s) {
+                                eprintln!("Error: {}", e);
+                                std::process::exit(1);
+                            } else {
+                                entry.bind_address = Some(s.trim().to_string());
+                            }
+                        }
+                        if let Some(ref names) = env_passthrough {
+                            match parse_env_passthrough(names) {
+                                Ok(names) if !names.is_empty() => {
+                                    entry.env_passthrough = Some(names)
+                                }
+                                Ok(_) => entry.env_passthrough = None,
+                                Err(e) => {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }
+                            }
+                        }
+                        if let Some(ref s) = health_path {
+                            entry.health_path = if s.trim().is_empty() {
+                                None
+                            } else {
+                                Some(s.clone())
+                            };
+                        }
+                        if let Some(ref s) = health_match {
+                            entry.health_match = if s == "default" {
+                                None
+                            } else {
+                                Some(s.clone())
+                            };
+                        }
+                        if let Some(ref s) = health_command {
+                            entry.health_command = if s.trim().is_empty() {
+                                None
+                            } else {
+                                Some(s.clone())
+                            };
+                        }
+                        if commit_name.is_some() || commit_email.is_some() || commit_bot.is_some() {
+                            let commit = entry.commit.get_or_insert_with(Default::default);
+                            let value = |s: &String| (!s.trim().is_empty()).then(|| s.clone());
+                            if let Some(ref s) = commit_name {
+                                commit.name = value(s);
+                            }
+                            if let Some(ref s) = commit_email {
+                                commit.email = value(s);
+                            }
+                            if let Some(bot) = commit_bot {
+                                commit.bot = Some(bot);
+                            }
+                            if *commit == CommitIdentity::default() {
+                                entry.commit = None;
+                            }
+                        }
                         if let Some(entries) = env.as_ref() {
                             if entries.len() == 1 && entries[0].trim().is_empty() {
                                 entry.env = None;
@@ -416,6 +633,74 @@ pub async fn handle(cmd: ModelCommands) {
             });
             println!("Agent '{}' removed successfully", name);
         }
+        ModelCommands::Enable { name } => {
+            let mut cfg = load_config().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            match set_agent_enabled(&mut cfg, &name, true) {
+                Ok(false) => println!("Agent '{}' is already enabled", name),
+                Ok(true) => {
+                    save_config(&cfg).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    });
+                    println!("Agent '{}' enabled", name);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        ModelCommands::Disable { name, stop } => {
+            let mut cfg = load_config().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            match set_agent_enabled(&mut cfg, &name, false) {
+                Ok(false) => println!("Agent '{}' is already disabled", name),
+                Ok(true) => {
+                    save_config(&cfg).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    });
+                    println!("Agent '{}' disabled", name);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            let users: Vec<&str> = cfg
+                .projects
+                .iter()
+                .filter(|p| p.model.as_deref() == Some(name.as_str()))
+                .map(|p| p.name.as_str())
+                .collect();
+            if !users.is_empty() {
+                println!(
+                    "  {} Projects using it cannot run pipelines until it is enabled: {}",
+                    BULLET_YELLOW,
+                    users.join(", ")
+                );
+            }
+            let running = docker::list_running_agent_containers().unwrap_or_default();
+            if running.contains(&name) {
+                if stop {
+                    docker::stop_agent_container(&name).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    });
+                    println!("  {}: stopped", name);
+                } else {
+                    println!(
+                        "  {} Its container is still running; stop it with `smith model disable {} --stop`",
+                        BULLET_BLUE, name
+                    );
+                }
+            }
+        }
         ModelCommands::Sync => {
             use serde_json::{json, Map, Value};
 
@@ -525,12 +810,8 @@ pub async fn handle(cmd: ModelCommands) {
             );
             println!("  Edit ~/.config/opencode/opencode.json to select model with \"model\": \"agent-name/model\"");
         }
-        ModelCommands::Build {
-            name,
-            all,
-            force,
-            verbose,
-        } => {
+        ModelCommands::Build { name, all, force } => {
+            let verbose = logging::verbose();
             if let Err(e) = docker::check_docker_available() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
@@ -546,7 +827,7 @@ pub async fn handle(cmd: ModelCommands) {
                 Option<String>,
                 Option<String>,
                 Option<String>,
-                Option<String>,
+                Option<OpencodeSettings>,
                 u16,
             )> = if all || name.is_none() {
                 match cfg.agents.as_deref() {
@@ -560,7 +841,7 @@ pub async fn handle(cmd: ModelCommands) {
                                 e.agent_type.clone(),
                                 e.model.clone(),
                                 e.small_model.clone(),
-                                e.provider.clone(),
+                                e.opencode.clone(),
                                 agent_port(e, i),
                             )
                         })
@@ -577,7 +858,7 @@ pub async fn handle(cmd: ModelCommands) {
                 }
             } else {
                 let n = name.as_deref().unwrap_or(DEFAULT_AGENT_NAME);
-                let (base_image, agent_type, model, small_model, provider, port) = cfg
+                let (base_image, agent_type, model, small_model, opencode, port) = cfg
                     .agents
                     .as_deref()
                     .and_then(|a| {
@@ -588,7 +869,7 @@ pub async fn handle(cmd: ModelCommands) {
                                 e.agent_type.clone(),
                                 e.model.clone(),
                                 e.small_model.clone(),
-                                e.provider.clone(),
+                                e.opencode.clone(),
                                 agent_port(e, idx),
                             )
                         })
@@ -614,7 +895,7 @@ pub async fn handle(cmd: ModelCommands) {
                     agent_type,
                     model,
                     small_model,
-                    provider,
+                    opencode,
                     port,
                 )]
             };
@@ -624,7 +905,7 @@ pub async fn handle(cmd: ModelCommands) {
             });
             let mut ok = 0usize;
             let mut failed = Vec::new();
-            for (agent_name, base_image, agent_type, model, small_model, provider, port) in &agents
+            for (agent_name, base_image, agent_type, model, small_model, opencode, port) in &agents
             {
                 let is_cloud = agent_type.as_deref() != Some("local");
                 if is_cloud {
@@ -651,7 +932,7 @@ pub async fn handle(cmd: ModelCommands) {
                     *port,
                     model.as_deref(),
                     small_model.as_deref(),
-                    provider.as_deref(),
+                    opencode.as_ref(),
                     force,
                 ) {
                     Ok(()) => {
@@ -680,7 +961,8 @@ pub async fn handle(cmd: ModelCommands) {
                 std::process::exit(1);
             }
         }
-        ModelCommands::Start { verbose } => {
+        ModelCommands::Start { reassign_ports } => {
+            let verbose = logging::verbose();
             if let Err(e) = docker::check_docker_available() {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
@@ -694,7 +976,7 @@ pub async fn handle(cmd: ModelCommands) {
             // Start Ollama for each local agent (each gets its own container with its model)
             let local_agents: Vec<_> = agents
                 .iter()
-                .filter(|e| e.agent_type.as_deref() == Some("local"))
+                .filter(|e| e.agent_type.as_deref() == Some("local") && e.enabled.unwrap_or(true))
                 .collect();
             for local in &local_agents {
                 let local_model = local
@@ -704,7 +986,7 @@ pub async fn handle(cmd: ModelCommands) {
                 if docker::is_ollama_running() {
                     println!("  Ollama already running");
                 } else {
-                    match docker::start_ollama_container(&local_model, true) {
+                    match docker::start_ollama_container(&local_model, true).await {
                         Ok(_) => {}
                         Err(e) => {
                             eprintln!("Error starting Ollama: {}", e);
@@ -719,14 +1001,15 @@ pub async fn handle(cmd: ModelCommands) {
                 // Default agent is cloud, so no containers to start
                 vec![]
             } else {
+                // Index in the full list, so disabling one agent does not move the others' ports.
                 agents
                     .iter()
-                    .filter(|e| {
+                    .enumerate()
+                    .filter(|(_, e)| {
                         let is_enabled = e.enabled.unwrap_or(true);
                         let is_local = e.agent_type.as_deref() == Some("local");
                         is_enabled && is_local
                     })
-                    .enumerate()
                     .map(|(i, e)| {
                         let image = if docker::image_exists(&docker::agent_built_image_tag(&e.name))
                             .unwrap_or(false)
@@ -742,10 +1025,7 @@ pub async fn handle(cmd: ModelCommands) {
                             e.provider.clone()
                         };
                         let base_url = if is_local {
-                            Some(format!(
-                                "http://host.docker.internal:{}",
-                                docker::OLLAMA_PORT
-                            ))
+                            Some(docker::ollama_base_url())
                         } else {
                             e.base_url.clone()
                         };
@@ -760,12 +1040,14 @@ pub async fn handle(cmd: ModelCommands) {
                     })
                     .collect()
             };
-            // Print skipping messages for cloud agents
+            // Print skipping messages for cloud and disabled agents
             if !agents.is_empty() {
                 for agent in agents.iter() {
                     let is_local = agent.agent_type.as_deref() == Some("local");
                     if !is_local {
                         println!("  {} Skipping cloud agent '{}'", BULLET_BLUE, agent.name);
+                    } else if !agent.enabled.unwrap_or(true) {
+                        println!("  {} Skipping disabled agent '{}'", BULLET_BLUE, agent.name);
                     }
                 }
             }
@@ -795,6 +1077,7 @@ pub async fn handle(cmd: ModelCommands) {
                     println!("Already running: {}", running.join(", "));
                 }
             }
+            let reserved: Vec<u16> = enabled_agents.iter().map(|a| a.5).collect();
             let mut ok = 0usize;
             let mut failed = Vec::new();
             for (name, image, provider, base_url, _enabled, port) in &enabled_agents {
@@ -808,6 +1091,14 @@ pub async fn handle(cmd: ModelCommands) {
                     ok += 1;
                     continue;
                 }
+                let port = match resolve_port_conflict(name, *port, &reserved, reassign_ports) {
+                    Ok(port) => port,
+                    Err(e) => {
+                        eprintln!("  {}: {}", name, e);
+                        failed.push((name.clone(), e));
+                        continue;
+                    }
+                };
                 if verbose {
                     let container_name = docker::agent_container_name(name);
                     let env_vars = if let Some(ref p) = provider {
@@ -820,32 +1111,71 @@ pub async fn handle(cmd: ModelCommands) {
                         String::new()
                     };
                     println!(
-                                "  {}: docker run -d --name {} -p {}:{}{} --entrypoint opencode {} serve --hostname 0.0.0.0 --port {}",
-                                name, container_name, port, port, env_vars, image, port
+                                "  {}: docker run -d --name {} -p {}{} --entrypoint opencode {} serve --hostname 0.0.0.0 --port {}",
+                                name,
+                                container_name,
+                                docker::publish_arg(
+                                    docker::agent_bind_address(
+                                        &cfg,
+                                        agents.iter().find(|a| &a.name == name)
+                                    ),
+                                    port
+                                ),
+                                env_vars,
+                                image,
+                                port
                             );
                 }
+                let profile = agents.iter().find(|a| &a.name == name);
+                let mut run_args = docker::hardening_args(&cfg, false);
+                match docker::agent_volume_args(
+                    profile
+                        .and_then(|a| a.volumes.as_deref())
+                        .unwrap_or_default(),
+                ) {
+                    Ok(volumes) => run_args.extend(volumes),
+                    Err(e) => {
+                        eprintln!("  {}: {}", name, e);
+                        failed.push((name.clone(), e));
+                        continue;
+                    }
+                }
                 match docker::start_agent_container(
                     name,
                     image,
-                    *port,
+                    port,
                     provider.as_deref(),
                     base_url.as_deref(),
-                ) {
+                    profile
+                        .and_then(|a| a.env_passthrough.as_deref())
+                        .unwrap_or_default(),
+                    &run_args,
+                )
+                .await
+                {
                     Ok(()) => {
                         println!(
                             "  {}: started (port {} {})",
                             name,
                             port,
-                            clickable_agent_url(*port)
+                            clickable_agent_url(port)
                         );
                         if verbose {
                             println!("  {}: waiting 3s before health check...", name);
                         }
-                        std::thread::sleep(std::time::Duration::from_secs(3));
+                        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
                         if verbose {
-                            println!("  {}: GET {}", name, clickable_agent_url(*port));
+                            println!("  {}: GET {}", name, clickable_agent_url(port));
                         }
-                        match docker::test_agent_server(*port) {
+                        let health =
+                            docker::HealthCheck::for_agent(agents.iter().find(|a| &a.name == name));
+                        match docker::test_agent_server(
+                            &docker::agent_container_name(name),
+                            port,
+                            &health,
+                        )
+                        .await
+                        {
                             Ok(()) => {
                                 println!("  {}: health check OK", name);
                                 ok += 1;
@@ -930,6 +1260,17 @@ pub async fn handle(cmd: ModelCommands) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
+            let name = name
+                .or_else(|| {
+                    let running = docker::list_running_agent_containers().ok()?;
+                    picker::interactive()
+                        .then(|| picker::pick("running agent", &running))
+                        .flatten()
+                })
+                .unwrap_or_else(|| {
+                    eprintln!("Error: agent name required (see 'smith model status')");
+                    std::process::exit(1);
+                });
             let container_name = docker::agent_container_name(&name);
             if !docker::container_exists(&container_name).unwrap_or(false) {
                 eprintln!(
@@ -940,6 +1281,7 @@ pub async fn handle(cmd: ModelCommands) {
             }
             let status = Command::new("docker")
                 .args(["logs", "-f", &container_name])
+                .logged()
                 .status()
                 .unwrap_or_else(|e| {
                     eprintln!("Error: Failed to run docker logs: {}", e);
@@ -950,5 +1292,245 @@ pub async fn handle(cmd: ModelCommands) {
             }
             std::process::exit(1);
         }
+        ModelCommands::Config { cmd } => match cmd {
+            ModelConfigCommands::Show { name } => {
+                let cfg = load_config().unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
+                let entry = find_agent(&cfg, &name).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
+                match smith_core::opencode::render(
+                    &entry.name,
+                    entry.model.as_deref(),
+                    entry.small_model.as_deref(),
+                    entry.opencode.as_ref(),
+                ) {
+                    Some(text) => print!("{}", text),
+                    None => println!("  {} '{}' sets no OpenCode settings", BULLET_BLUE, name),
+                }
+                let problems = entry
+                    .opencode
+                    .as_ref()
+                    .map(smith_core::opencode::validate)
+                    .unwrap_or_default();
+                if !problems.is_empty() {
+                    for problem in &problems {
+                        eprintln!("  {} {}", BULLET_RED, problem);
+                    }
+                    std::process::exit(1);
+                }
+            }
+            ModelConfigCommands::Edit { name } => {
+                edit_opencode_settings(&name).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
+            }
+        },
+    }
+}
+
+/// Window of `smith model status --verbose` provider usage.
+const USAGE_WINDOW_SECS: u64 = 7 * 86_400;
+
+/// Provider usage per model profile and provider: requests, 429 and 5xx responses, other
+/// failures and latency.
+fn print_usage(stats: &[usage::UsageStats], table: &TableArgs) {
+    println!("\nProvider usage (last 7 days):");
+    if stats.is_empty() {
+        println!("  (no prompts recorded)");
+        return;
+    }
+    let mut rows = Table::new(&[
+        "agent", "provider", "requests", "429", "5xx", "other", "errors", "p50", "p95",
+    ]);
+    for s in stats {
+        rows.row(vec![
+            s.agent.clone().unwrap_or_else(|| "-".to_string()),
+            s.provider.clone(),
+            s.requests.to_string(),
+            s.rate_limited.to_string(),
+            s.server_errors.to_string(),
+            s.errors.to_string(),
+            format!("{:.0}%", s.error_rate()),
+            commands::pipeline::summary::format_duration_ms(s.p50_ms),
+            commands::pipeline::summary::format_duration_ms(s.p95_ms),
+        ]);
+    }
+    print!(
+        "{}",
+        rows.render(table).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        })
+    );
+}
+
+fn find_agent<'a>(cfg: &'a SmithConfig, name: &str) -> Result<&'a AgentEntry, String> {
+    cfg.agents
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .find(|a| a.name == name)
+        .ok_or_else(|| format!("Agent '{}' not found", name))
+}
+
+/// Commented example shown when an agent has no OpenCode settings yet.
+const OPENCODE_SETTINGS_TEMPLATE: &str = r#"# [provider.ollama.options]
+# baseURL = "http://host.docker.internal:11434/v1"
+#
+# [mcp.github]
+# command = ["npx", "-y", "@modelcontextprotocol/server-github"]
+# environment = { GITHUB_PERSONAL_ACCESS_TOKEN = "..." }
+#
+# [mcp.docs]
+# url = "https://mcp.example.com/mcp"
+# headers = { Authorization = "Bearer ..." }
+#
+# [permission]
+# edit = "allow"
+# webfetch = "ask"
+# bash = { "git push*" = "deny", "*" = "allow" }
+"#;
+
+/// Open the agent's `opencode` settings as TOML in the user's editor and save them once they
+/// parse and validate; invalid edits can be reopened with the errors shown.
+fn edit_opencode_settings(name: &str) -> Result<(), String> {
+    let mut cfg = load_config().map_err(|e| e.to_string())?;
+    let current = find_agent(&cfg, name)?.opencode.clone().unwrap_or_default();
+    let mut text = format!(
+        "# OpenCode settings of agent '{}', rendered to its opencode.jsonc. Save and close to apply.\n",
+        name
+    );
+    if current == OpencodeSettings::default() {
+        text.push_str(OPENCODE_SETTINGS_TEMPLATE);
+    } else {
+        text.push_str(&toml::to_string_pretty(&current).map_err(|e| e.to_string())?);
+    }
+    let path = std::env::temp_dir().join(format!("smith-opencode-{}.toml", name));
+    fs::write(&path, &text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let settings = loop {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh")
+            .arg(&path)
+            .status()
+            .map_err(|e| format!("Failed to run {}: {}", editor, e))?;
+        if !status.success() {
+            return Err(format!("{} exited with {}; nothing saved", editor, status));
+        }
+        let edited = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let problems = match toml::from_str::<OpencodeSettings>(&edited) {
+            Ok(settings) => {
+                let problems = smith_core::opencode::validate(&settings);
+                if problems.is_empty() {
+                    break settings;
+                }
+                problems
+            }
+            Err(e) => vec![e.to_string()],
+        };
+        for problem in &problems {
+            eprintln!("  {} {}", BULLET_RED, problem);
+        }
+        if !io::stdin().is_terminal() || !prompt_yn("Edit again?", false) {
+            let _ = fs::remove_file(&path);
+            return Err("invalid OpenCode settings; nothing saved".to_string());
+        }
+    };
+    let _ = fs::remove_file(&path);
+    if settings == current {
+        println!("  {} No changes", BULLET_BLUE);
+        return Ok(());
+    }
+    let entry = cfg
+        .agents
+        .as_mut()
+        .and_then(|agents| agents.iter_mut().find(|a| a.name == name))
+        .ok_or_else(|| format!("Agent '{}' not found", name))?;
+    let local = entry.agent_type.as_deref() == Some("local");
+    entry.opencode = (settings != OpencodeSettings::default()).then_some(settings);
+    save_config(&cfg).map_err(|e| e.to_string())?;
+    println!("  {} Saved OpenCode settings of '{}'", BULLET_GREEN, name);
+    if local {
+        println!(
+            "  Rebuild its image to apply: smith model build {} --force",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Check that `port` is free before starting `name`. When it is taken, report the owner and
+/// move the agent to the next free port (saved to the config) if `reassign` is set or the user
+/// agrees; otherwise fail with guidance instead of starting a container that cannot bind.
+fn resolve_port_conflict(
+    name: &str,
+    port: u16,
+    reserved: &[u16],
+    reassign: bool,
+) -> Result<u16, String> {
+    if docker::host_port_free(port) {
+        return Ok(port);
+    }
+    let owner = docker::port_owner(port);
+    eprintln!("  {}: port {} is already in use by {}", name, port, owner);
+    let guidance = format!(
+        "port {} is in use by {}; stop it, pick another port with `smith model update {} --port <port>`, or rerun with --reassign-ports",
+        port, owner, name
+    );
+    let Some(free) = docker::next_free_port(port, reserved) else {
+        return Err(guidance);
+    };
+    let accepted = reassign
+        || (io::stdin().is_terminal()
+            && prompt_yn(
+                &format!(
+                    "  Move '{}' to port {} and save it to the config?",
+                    name, free
+                ),
+                true,
+            ));
+    if !accepted {
+        return Err(guidance);
+    }
+    let mut cfg = load_config().map_err(|e| e.to_string())?;
+    let entry = cfg
+        .agents
+        .as_mut()
+        .and_then(|agents| agents.iter_mut().find(|a| a.name == name))
+        .ok_or_else(|| format!("Agent '{}' not found", name))?;
+    entry.port = Some(free);
+    save_config(&cfg).map_err(|e| e.to_string())?;
+    println!("  {}: port {} saved to config", name, free);
+    Ok(free)
+}
+
+/// Set an agent's `enabled` flag. Returns whether it changed.
+pub(crate) fn set_agent_enabled(
+    cfg: &mut SmithConfig,
+    name: &str,
+    enabled: bool,
+) -> Result<bool, String> {
+    let entry = cfg
+        .agents
+        .as_mut()
+        .and_then(|agents| agents.iter_mut().find(|a| a.name == name))
+        .ok_or_else(|| format!("Agent '{}' not found", name))?;
+    if entry.enabled.unwrap_or(true) == enabled {
+        return Ok(false);
     }
+    // Enabled is the default; keep the config free of `enabled = true`.
+    entry.enabled = (!enabled).then_some(false);
+    Ok(true)
 }
@@ -0,0 +1,225 @@
+use crate::*;
+use smith_core::nodes::{self, NodeStatus};
+use std::io::IsTerminal;
+
+pub async fn handle(cmd: NodeCommands) {
+    match cmd {
+        NodeCommands::Add {
+            endpoint,
+            name,
+            labels,
+            capacity,
+        } => {
+            let mut cfg = load_config().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let name = match name {
+                Some(n) => Ok(n),
+                None => nodes::default_node_name(&endpoint),
+            }
+            .and_then(|name| {
+                nodes::parse_endpoint(&endpoint)?;
+                if cfg.nodes.iter().any(|n| n.name == name) {
+                    return Err(format!("Node '{}' already exists", name));
+                }
+                if let Some(bad) = labels.iter().find(|l| !l.contains('=')) {
+                    return Err(format!("Invalid label '{}': expected KEY=VALUE", bad));
+                }
+                Ok(name)
+            })
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let node = NodeConfig {
+                name: name.clone(),
+                endpoint: endpoint.trim().to_string(),
+                labels,
+                capacity,
+            };
+            match nodes::probe(&node).version {
+                Ok(version) => println!("  {} {}: smith {}", BULLET_GREEN, name, version),
+                Err(e) => println!(
+                    "  {} {}: not reachable yet ({}); added anyway",
+                    BULLET_YELLOW, name, e
+                ),
+            }
+            cfg.nodes.push(node);
+            save_config(&cfg).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            println!("Node '{}' added successfully", name);
+        }
+        NodeCommands::List { json, table } => {
+            let cfg = load_config().unwrap_or_else(|e| exit_with_error(e, json));
+            let statuses = probe_all(&cfg.nodes);
+            if json {
+                let entries: Vec<_> = cfg
+                    .nodes
+                    .iter()
+                    .zip(&statuses)
+                    .map(|(node, status)| {
+                        serde_json::json!({
+                            "name": node.name,
+                            "endpoint": node.endpoint,
+                            "labels": node.labels,
+                            "capacity": node.capacity,
+                            "reachable": status.reachable(),
+                            "version": status.version.as_ref().ok(),
+                            "error": status.version.as_ref().err(),
+                            "agents": status.agents,
+                        })
+                    })
+                    .collect();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({ "nodes": entries }))
+                        .unwrap_or_default()
+                );
+                return;
+            }
+            if cfg.nodes.is_empty() {
+                println!("No nodes. Add one with `smith node add <user@host>`.");
+                return;
+            }
+            let mut rows =
+                Table::new(&["status", "name", "endpoint", "labels", "agents", "version"]);
+            for (node, status) in cfg.nodes.iter().zip(&statuses) {
+                rows.row(vec![
+                    if status.reachable() {
+                        BULLET_GREEN
+                    } else {
+                        BULLET_RED
+                    }
+                    .to_string(),
+                    node.name.clone(),
+                    node.endpoint.clone(),
+                    node.labels.join(","),
+                    load_summary(node, status),
+                    match &status.version {
+                        Ok(v) => v.clone(),
+                        Err(e) => e.clone(),
+                    },
+                ]);
+            }
+            print!(
+                "{}",
+                rows.render(&table).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+            );
+            let reachable = statuses.iter().filter(|s| s.reachable()).count();
+            let agents: usize = statuses.iter().map(|s| s.agents).sum();
+            println!(
+                "{} agent(s) running across {}/{} reachable node(s)",
+                agents,
+                reachable,
+                statuses.len()
+            );
+        }
+        NodeCommands::Remove { name } => {
+            let mut cfg = load_config().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let before = cfg.nodes.len();
+            cfg.nodes.retain(|n| n.name != name);
+            if cfg.nodes.len() == before {
+                eprintln!("Error: Node '{}' not found", name);
+                std::process::exit(1);
+            }
+            save_config(&cfg).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            println!("Node '{}' removed", name);
+        }
+    }
+}
+
+/// Probe every node in parallel (each is one SSH round trip).
+pub(crate) fn probe_all(nodes: &[NodeConfig]) -> Vec<NodeStatus> {
+    thread::scope(|scope| {
+        let probes: Vec<_> = nodes
+            .iter()
+            .map(|node| scope.spawn(move || nodes::probe(node)))
+            .collect();
+        probes
+            .into_iter()
+            .map(|p| {
+                p.join().unwrap_or_else(|_| NodeStatus {
+                    version: Err("probe panicked".to_string()),
+                    agents: 0,
+                })
+            })
+            .collect()
+    })
+}
+
+/// "2/4" with a capacity, else the running agent count.
+pub(crate) fn load_summary(node: &NodeConfig, status: &NodeStatus) -> String {
+    match node.capacity {
+        Some(cap) => format!("{}/{}", status.agents, cap),
+        None => status.agents.to_string(),
+    }
+}
+
+/// Re-run this smith invocation (`args`, without the node flags) on a node: the one named by
+/// `node`, or with `auto`/`labels` the least loaded matching node. Returns the exit code.
+pub fn run_on_node(node: Option<&str>, labels: &[String], args: &[String]) -> i32 {
+    let cfg = load_config().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    if cfg.nodes.is_empty() {
+        eprintln!("Error: No nodes registered. Add one with `smith node add <user@host>`.");
+        return 1;
+    }
+    let target = match node {
+        Some(name) if name != "auto" => {
+            let Some(found) = cfg.nodes.iter().find(|n| n.name == name) else {
+                eprintln!("Error: Node '{}' not found", name);
+                return 1;
+            };
+            if !nodes::has_labels(found, labels) {
+                eprintln!(
+                    "Error: Node '{}' does not have labels {}",
+                    name,
+                    labels.join(", ")
+                );
+                return 1;
+            }
+            found
+        }
+        _ => {
+            let statuses = probe_all(&cfg.nodes);
+            match nodes::select_node(&cfg.nodes, &statuses, labels) {
+                Ok(node) => node,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return 1;
+                }
+            }
+        }
+    };
+    println!(
+        "  {} Scheduling on node {} ({})",
+        BULLET_BLUE, target.name, target.endpoint
+    );
+    let tty = io::stdin().is_terminal() && io::stdout().is_terminal();
+    let status = nodes::remote_smith(target, args, tty).and_then(|mut cmd| {
+        cmd.logged()
+            .status()
+            .map_err(|e| format!("Failed to run ssh: {}", e))
+    });
+    match status {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::*;
+
+/// Host directory failed runs are exported under, set by `--export-on-failure`. Taken by the
+/// first export so a run that records its failure more than once is exported once.
+static FAILURE_EXPORT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Enable the workspace export for this run: `dir`, or the cache's `failures` directory when
+/// the flag was given without a value.
+pub(crate) fn set_failure_export(dir: Option<String>) {
+    let dir = dir.and_then(|d| match d.trim() {
+        "" => cache_dir().ok().map(|c| c.join("failures")),
+        d => Some(PathBuf::from(d)),
+    });
+    if let Ok(mut current) = FAILURE_EXPORT.lock() {
+        *current = dir;
+    }
+}
+
+/// `--export-on-failure=<dir>` for a child `smith run develop`, when the export is enabled.
+pub(crate) fn failure_export_arg() -> Option<String> {
+    FAILURE_EXPORT
+        .lock()
+        .ok()?
+        .as_ref()
+        .map(|dir| format!("--export-on-failure={}", dir.display()))
+}
+
+/// Export the workspace of a failed run if `--export-on-failure` was given, printing where it
+/// went. Export problems are warnings: the run has already failed for its own reason.
+pub(crate) fn export_on_failure(project: &str, branch: &str, run_id: &str) {
+    let Some(base) = FAILURE_EXPORT.lock().ok().and_then(|mut d| d.take()) else {
+        return;
+    };
+    let dir = base.join(export_dir_name(project, branch, run_id));
+    match export_workspace(project, branch, run_id, &dir) {
+        Ok(missing) => {
+            eprintln!("  {} Workspace exported to {}", BULLET_BLUE, dir.display());
+            for (file, e) in missing {
+                eprintln!("  Warning: {} not exported: {}", file, e);
+            }
+        }
+        Err(e) => eprintln!("  Warning: failed to export workspace: {}", e),
+    }
+}
+
+/// `<project>-<branch>-<run id>`, with path separators in the branch replaced.
+pub(crate) fn export_dir_name(project: &str, branch: &str, run_id: &str) -> String {
+    let clean = |s: &str| {
+        s.chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' => '-',
+                c => c,
+            })
+            .collect::<String>()
+    };
+    format!("{}-{}-{}", clean(project), clean(branch), clean(run_id))
+}
+
+/// Files of an export and the workspace command producing each. The diff includes untracked
+/// files so new files the agent wrote show up too.
+pub(crate) const EXPORT_SCRIPTS: &[(&str, &str)] = &[
+    (
+        "diff.patch",
+        "cd /workspace && git diff HEAD; git ls-files --others --exclude-standard | while IFS= read -r f; do git diff --no-index -- /dev/null \"$f\"; done; true",
+    ),
+    (
+        "git-status.txt",
+        "cd /workspace && git status --branch --short && echo && git rev-parse HEAD",
+    ),
+    (
+        "git-log.txt",
+        "cd /workspace && git log --oneline --decorate -n 50",
+    ),
+];
+
+/// Write the workspace diff, git state, container log and the run's /state artifacts to `dir`.
+/// Returns the files that could not be produced, with why.
+fn export_workspace(
+    project: &str,
+    branch: &str,
+    run_id: &str,
+    dir: &Path,
+) -> Result<Vec<(String, String)>, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    let write = |name: &str, body: &str| {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, body).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    };
+    let mut missing = Vec::new();
+
+    for (file, script) in EXPORT_SCRIPTS {
+        if let Err(e) =
+            docker::run_spawn_shell(project, branch, script).and_then(|out| write(file, &out))
+        {
+            missing.push((file.to_string(), e));
+        }
+    }
+
+    let logs = docker::spawn_logs_command(project, branch, &docker::LogOptions::default())
+        .logged()
+        .output()
+        .map_err(|e| format!("Failed to read container logs: {}", e))
+        .and_then(|out| {
+            // The agent server writes to both streams; keep them together.
+            let text = [out.stdout, out.stderr].concat();
+            write("container.log", &String::from_utf8_lossy(&text))
+        });
+    if let Err(e) = logs {
+        missing.push(("container.log".to_string(), e));
+    }
+
+    let run_dir = format!("/state/{}", run_id);
+    let listing = format!(
+        "cd '{}' 2>/dev/null && find . -type f; true",
+        run_dir.replace('\'', "'\"'\"'")
+    );
+    match docker::run_spawn_shell(project, branch, &listing) {
+        Ok(out) => {
+            for file in out.lines().map(|l| l.trim_start_matches("./")) {
+                if file.is_empty() || file.split('/').any(|part| part == "..") {
+                    continue;
+                }
+                let name = format!("state/{}", file);
+                if let Err(e) =
+                    docker::read_spawn_file(project, branch, &format!("{}/{}", run_dir, file))
+                        .and_then(|body| write(&name, &body))
+                {
+                    missing.push((name, e));
+                }
+            }
+        }
+        Err(e) => missing.push((run_dir, e)),
+    }
+    Ok(missing)
+}
@@ -0,0 +1,128 @@
+use crate::*;
+
+/// Title of the issue filed for a finding.
+pub(crate) fn finding_issue_title(finding: &DevAssuranceIssue) -> String {
+    format!(
+        "[{}] {}",
+        finding.severity.trim().to_lowercase(),
+        finding.title.trim()
+    )
+}
+
+/// Body of the issue filed for a finding: the detail, and where it was found, linked to the
+/// reviewed branch.
+pub(crate) fn finding_issue_body(
+    finding: &DevAssuranceIssue,
+    branch_url: &str,
+    git_branch: &str,
+    source: &str,
+) -> String {
+    let mut body = format!(
+        "{}\n\nFound by smith review ({}) on branch [`{}`]({}), finding `{}`.",
+        finding.detail.trim(),
+        source,
+        git_branch,
+        branch_url,
+        finding.id
+    );
+    if !finding.related_ids.is_empty() {
+        body.push_str(&format!(
+            "\nRelated plan items: {}",
+            finding.related_ids.join(", ")
+        ));
+    }
+    body
+}
+
+/// File one GitHub issue per finding (`run review --create-issues`), labelled `smith` and with
+/// the severity; a finding whose title hash matches an open `smith` issue is skipped.
+pub(crate) async fn create_finding_issues(
+    project_config: Option<&ProjectConfig>,
+    branch: &str,
+    source: &str,
+    findings: &[DevAssuranceIssue],
+) -> Result<(), String> {
+    let Some(project) = project_config else {
+        return Err("--create-issues needs a configured project".to_string());
+    };
+    ensure_not_sandboxed("run review --create-issues", Some(&project.name))?;
+    let api_url = match resolve_forge(project_config).map_err(|e| e.to_string())? {
+        Forge::GitHub { api_url } => api_url,
+        Forge::Gitea { .. } => {
+            return Err("--create-issues files GitHub issues; the project uses Gitea".to_string())
+        }
+    };
+    let repo = github::extract_repo_info(&project.repo)?;
+    let token = resolve_forge_token(
+        project_config,
+        &Forge::GitHub {
+            api_url: api_url.clone(),
+        },
+        &repo.owner,
+        &repo.name,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or("--create-issues needs the project's github_token or a GitHub App")?;
+
+    let git_branch = project.git_branch(branch);
+    let branch_url = format!(
+        "{}/{}/{}/tree/{}",
+        github::web_url(&api_url),
+        repo.owner,
+        repo.name,
+        git_branch
+    );
+    let mut known: Vec<String> = github::list_open_issues(
+        &api_url,
+        &token,
+        &repo.owner,
+        &repo.name,
+        github::SMITH_ISSUE_LABEL,
+    )
+    .await?
+    .iter()
+    .map(|issue| github::title_hash(&issue.title))
+    .collect();
+
+    let (mut created, mut skipped) = (0, 0);
+    for finding in findings {
+        let title = finding_issue_title(finding);
+        let hash = github::title_hash(&title);
+        if known.contains(&hash) {
+            println!("  {} {} already has an open issue", BULLET_BLUE, finding.id);
+            skipped += 1;
+            continue;
+        }
+        let labels = vec![
+            github::SMITH_ISSUE_LABEL.to_string(),
+            format!("severity:{}", finding.severity.trim().to_lowercase()),
+        ];
+        let issue = github::create_issue(
+            &api_url,
+            &token,
+            &repo.owner,
+            &repo.name,
+            &title,
+            &finding_issue_body(finding, &branch_url, &git_branch, source),
+            &labels,
+        )
+        .await?;
+        audit::record(
+            "issue_create",
+            &issue.html_url,
+            Some(format!("{}/{} {}", project.name, branch, finding.id)),
+        );
+        println!(
+            "  {} {} -> issue #{} {}",
+            BULLET_GREEN, finding.id, issue.number, issue.html_url
+        );
+        known.push(hash);
+        created += 1;
+    }
+    println!(
+        "  {} Issues: {} created, {} already open",
+        BULLET_BLUE, created, skipped
+    );
+    Ok(())
+}
@@ -0,0 +1,507 @@
+use crate::*;
+
+/// One matrix variant: a model profile (`--matrix`) or a model (`--models`) on its own branch.
+pub(crate) struct Variant {
+    pub(crate) label: String,
+    pub(crate) branch: String,
+    pub(crate) args: [String; 2],
+}
+
+struct VariantResult {
+    variant: Variant,
+    outcome: String,
+    verdict: String,
+    attempts: u32,
+    diff_lines: Option<u64>,
+    duration_secs: u64,
+    cost: f64,
+}
+
+pub async fn handle(cmd: RunCommands) {
+    let RunCommands::Develop {
+        project,
+        branch,
+        base,
+        plan,
+        max_validate_passes,
+        allow_secrets,
+        matrix,
+        models,
+        handoff,
+        preset,
+        co_author,
+        provenance,
+        commit_strategy,
+        sync_base,
+        no_sync_base,
+        export_on_failure,
+        task,
+        ..
+    } = cmd
+    else {
+        return;
+    };
+    super::export::set_failure_export(export_on_failure);
+
+    let project = match project {
+        Some(p) => p,
+        None => match detect_or_pick_project() {
+            Ok(Some(name)) => name,
+            _ => {
+                eprintln!("Error: --project required");
+                std::process::exit(1);
+            }
+        },
+    };
+    let branch = match branch {
+        Some(b) => b,
+        None => {
+            let output = Command::new("git")
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .output();
+            match output {
+                Ok(out) if out.status.success() => {
+                    String::from_utf8_lossy(&out.stdout).trim().to_string()
+                }
+                _ => {
+                    eprintln!("Error: --branch required");
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    let variants = matrix_variants(&branch, &matrix, &models).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    if !matrix.is_empty() {
+        let cfg = load_config().unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        let profiles = cfg.agents.unwrap_or_default();
+        for v in &variants {
+            if !profiles.iter().any(|a| a.name == v.label) {
+                eprintln!("Error: model profile '{}' not found", v.label);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    ensure_spawned_container_for_pipeline(&project, &branch, false, false)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+    let plan_dirs = docker::list_spawn_plan_dirs(&project, &branch).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let selected_plan = resolve_plan_id_filter(&plan, &plan_dirs).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let plan_dir = format!("/state/{}", selected_plan);
+    let plan_files = docker::run_spawn_shell(
+        &project,
+        &branch,
+        &format!("cd '{}' && find . -type f", plan_dir),
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("Error: failed listing plan '{}': {}", selected_plan, e);
+        std::process::exit(1);
+    });
+    let plan_files = plan_files
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("./"))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let source_pushed = docker::run_spawn_shell(
+        &project,
+        &branch,
+        &format!(
+            "cd /workspace && git show-ref --verify --quiet 'refs/remotes/origin/{}' && echo yes || true",
+            git_branch(&project, &branch)
+        ),
+    )
+    .map(|out| out.trim() == "yes")
+    .unwrap_or(false);
+
+    println!(
+        "  {} Matrix: {} variant(s) of plan {} for {}:{}",
+        BULLET_BLUE,
+        variants.len(),
+        selected_plan,
+        project,
+        branch
+    );
+
+    let mut results = Vec::new();
+    for variant in variants {
+        println!();
+        println!(
+            "  {} Variant {} on branch {}",
+            BULLET_BLUE, variant.label, variant.branch
+        );
+        let result = run_variant(
+            &project,
+            &branch,
+            base.as_deref(),
+            &selected_plan,
+            &plan_files,
+            source_pushed,
+            max_validate_passes,
+            allow_secrets,
+            handoff.as_deref(),
+            preset.as_deref(),
+            &co_author,
+            provenance,
+            commit_strategy.as_deref(),
+            sync_base.as_deref(),
+            no_sync_base,
+            &task,
+            variant,
+        )
+        .await;
+        match result {
+            Ok(r) => results.push(r),
+            Err((variant, e)) => {
+                eprintln!("  {} {}: {}", BULLET_RED, variant.label, e);
+                results.push(VariantResult {
+                    variant,
+                    outcome: "error".to_string(),
+                    verdict: "-".to_string(),
+                    attempts: 0,
+                    diff_lines: None,
+                    duration_secs: 0,
+                    cost: 0.0,
+                });
+            }
+        }
+    }
+
+    println!();
+    print_matrix_table(&results);
+    println!(
+        "  {} Branches left for comparison: {}",
+        BULLET_BLUE,
+        results
+            .iter()
+            .map(|r| r.variant.branch.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if results.iter().all(|r| r.outcome != "completed") {
+        std::process::exit(1);
+    }
+}
+
+/// Build one variant per profile or model, each on `<branch>-<slug>`.
+pub(crate) fn matrix_variants(
+    branch: &str,
+    matrix: &[String],
+    models: &[String],
+) -> Result<Vec<Variant>, String> {
+    let (flag, values) = if matrix.is_empty() {
+        ("--model", models)
+    } else {
+        ("--agent", matrix)
+    };
+    let mut variants: Vec<Variant> = Vec::new();
+    for value in values.iter().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        let variant_branch = format!("{}-{}", branch, matrix_branch_slug(value));
+        if variants.iter().any(|v| v.branch == variant_branch) {
+            return Err(format!(
+                "'{}' maps to branch '{}' more than once",
+                value, variant_branch
+            ));
+        }
+        variants.push(Variant {
+            label: value.to_string(),
+            branch: variant_branch,
+            args: [flag.to_string(), value.to_string()],
+        });
+    }
+    if variants.len() < 2 {
+        return Err("a matrix run needs at least two agents or models".to_string());
+    }
+    Ok(variants)
+}
+
+/// Branch-safe form of a profile or model name (`openai/gpt-4.1` -> `openai-gpt-4.1`).
+pub(crate) fn matrix_branch_slug(value: &str) -> String {
+    let slug = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    slug.split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Seed the variant's agent with the plan (and the source branch's commits when pushed), then
+/// run `smith run develop` for it as a child process so each variant gets its own run id,
+/// history record and container.
+#[allow(clippy::too_many_arguments)]
+async fn run_variant(
+    project: &str,
+    source_branch: &str,
+    base: Option<&str>,
+    plan_id: &str,
+    plan_files: &[String],
+    source_pushed: bool,
+    max_validate_passes: u32,
+    allow_secrets: bool,
+    handoff: Option<&str>,
+    preset: Option<&str>,
+    co_authors: &[String],
+    provenance: bool,
+    commit_strategy: Option<&str>,
+    sync_base: Option<&str>,
+    no_sync_base: bool,
+    task: &str,
+    variant: Variant,
+) -> Result<VariantResult, (Variant, String)> {
+    let branch = variant.branch.clone();
+    if variant.args[0] == "--agent" {
+        set_agent_override(Some(variant.label.clone()));
+    }
+    let started = ensure_spawned_container_for_pipeline(project, &branch, true, false).await;
+    set_agent_override(None);
+    if let Err(e) = started {
+        return Err((variant, e));
+    }
+
+    if source_pushed {
+        let script = format!(
+            "cd /workspace && if ! git show-ref --verify --quiet 'refs/remotes/origin/{branch}'; then git reset --hard 'origin/{source}' >/dev/null; fi",
+            branch = git_branch(project, &branch),
+            source = git_branch(project, source_branch)
+        );
+        if let Err(e) = docker::run_spawn_shell(project, &branch, &script) {
+            return Err((variant, e));
+        }
+    }
+
+    for file in plan_files {
+        let src = format!("/state/{}/{}", plan_id, file);
+        let mut content = match docker::read_spawn_file(project, source_branch, &src) {
+            Ok(c) => c,
+            Err(e) => return Err((variant, e)),
+        };
+        if file == "manifest.json" {
+            let mut manifest = match serde_json::from_str::<serde_json::Value>(&content) {
+                Ok(v) => v,
+                Err(e) => return Err((variant, format!("invalid plan manifest: {}", e))),
+            };
+            manifest["branch"] = serde_json::Value::String(branch.clone());
+            content = serde_json::to_string_pretty(&manifest).unwrap_or(content);
+        }
+        if let Err(e) = docker::write_spawn_file(project, &branch, &src, &content) {
+            return Err((variant, e));
+        }
+    }
+
+    let start_commit =
+        docker::run_spawn_shell(project, &branch, "cd /workspace && git rev-parse HEAD")
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+
+    let exe = match std::env::current_exe() {
+        Ok(p) => p,
+        Err(e) => return Err((variant, format!("cannot locate smith binary: {}", e))),
+    };
+    let backend = match replay::begin_child() {
+        Ok(b) => b,
+        Err(e) => return Err((variant, e)),
+    };
+    let mut cmd = Command::new(exe);
+    cmd.args(child_global_args(
+        logging::verbosity(),
+        docker::prompt_timeout(),
+        OVERRIDE_BUDGET.load(Ordering::Relaxed),
+        IGNORE_SCHEDULE.load(Ordering::Relaxed),
+        SIMULATE_FIXTURES.get().map(String::as_str),
+    ))
+    .args(backend.args());
+    if matches!(backend, replay::ChildBackend::Record(_)) {
+        cmd.env(replay::RECORD_APPEND_ENV, "1");
+    }
+    cmd.args(["run", "develop", "--project", project, "--branch", &branch])
+        .args(["--plan", plan_id])
+        .args(["--max-validate-passes", &max_validate_passes.to_string()])
+        .args(&variant.args);
+    if let Some(b) = base {
+        cmd.args(["--base", b]);
+    }
+    if allow_secrets {
+        cmd.arg("--allow-secrets");
+    }
+    if let Some(h) = handoff {
+        cmd.args(["--handoff", h]);
+    }
+    if let Some(p) = preset {
+        cmd.args(["--preset", p]);
+    }
+    for author in co_authors {
+        cmd.args(["--co-author", author]);
+    }
+    if provenance {
+        cmd.arg("--provenance");
+    }
+    if let Some(s) = commit_strategy {
+        cmd.args(["--commit-strategy", s]);
+    }
+    if let Some(mode) = sync_base {
+        cmd.arg(format!("--sync-base={}", mode));
+    }
+    if no_sync_base {
+        cmd.arg("--no-sync-base");
+    }
+    cmd.args(super::export::failure_export_arg());
+    cmd.arg(task);
+
+    let since = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let status = cmd.status();
+    replay::end_child(backend);
+    let status = match status {
+        Ok(s) => s,
+        Err(e) => return Err((variant, format!("failed to run develop: {}", e))),
+    };
+
+    let record = history::load()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| {
+            r.stage == "develop"
+                && r.project == project
+                && r.branch == branch
+                && r.finished_at >= since
+        })
+        .max_by_key(|r| r.finished_at);
+    let (verdict, attempts) = record
+        .as_ref()
+        .and_then(|r| {
+            docker::read_spawn_file(
+                project,
+                &branch,
+                &format!("/state/{}/manifest.json", r.run_id),
+            )
+            .ok()
+        })
+        .and_then(|raw| serde_json::from_str::<DevRunManifest>(&raw).ok())
+        .map(|m| {
+            (
+                m.final_verdict.unwrap_or_else(|| "-".to_string()),
+                m.attempts.len() as u32,
+            )
+        })
+        .unwrap_or_else(|| ("-".to_string(), 0));
+    let diff_lines = if start_commit.is_empty() {
+        None
+    } else {
+        docker::run_spawn_shell(
+            project,
+            &branch,
+            &format!("cd /workspace && git diff --numstat {} HEAD", start_commit),
+        )
+        .ok()
+        .map(|numstat| docker::diff_line_count(&numstat))
+    };
+
+    let outcome = match &record {
+        Some(r) => r.state.clone(),
+        None if status.success() => "completed".to_string(),
+        None => "failed".to_string(),
+    };
+    Ok(VariantResult {
+        duration_secs: record.as_ref().map(|r| r.duration_secs()).unwrap_or(0),
+        cost: record.as_ref().map(|r| r.cost).unwrap_or(0.0),
+        variant,
+        outcome,
+        verdict,
+        attempts,
+        diff_lines,
+    })
+}
+
+/// Global flags a variant's child `smith run develop` takes over from this run: its `-v` count,
+/// prompt limit (`--timeout 0` for none, so the child does not fall back to the project's
+/// default) and, when simulating, the `--simulate` fixtures path. Recording and replay are
+/// added separately (see [`replay::begin_child`]).
+pub(crate) fn child_global_args(
+    verbosity: u8,
+    timeout: Option<std::time::Duration>,
+    override_budget: bool,
+    ignore_schedule: bool,
+    simulate: Option<&str>,
+) -> Vec<String> {
+    let mut args = Vec::new();
+    if verbosity > 0 {
+        args.push(format!("-{}", "v".repeat(verbosity as usize)));
+    }
+    args.push("--timeout".to_string());
+    args.push(timeout.map(|t| t.as_secs()).unwrap_or(0).to_string());
+    if override_budget {
+        args.push("--override-budget".to_string());
+    }
+    if ignore_schedule {
+        args.push("--ignore-schedule".to_string());
+    }
+    if let Some(fixtures) = simulate {
+        args.push(format!("--simulate={}", fixtures));
+    }
+    args
+}
+
+fn print_matrix_table(results: &[VariantResult]) {
+    let mut table = Table::new(&[
+        "variant", "branch", "outcome", "verdict", "passes", "diff", "duration", "cost",
+    ]);
+    for r in results {
+        table.row(vec![
+            r.variant.label.clone(),
+            r.variant.branch.clone(),
+            r.outcome.clone(),
+            r.verdict.clone(),
+            r.attempts.to_string(),
+            r.diff_lines
+                .map(|n| format!("{} lines", n))
+                .unwrap_or_else(|| "-".to_string()),
+            format!("{}s", r.duration_secs),
+            if r.cost > 0.0 {
+                format!("${:.2}", r.cost)
+            } else {
+                "-".to_string()
+            },
+        ]);
+    }
+    let Ok(rendered) = table.render(&TableArgs::default()) else {
+        return;
+    };
+    println!("  {} Matrix results", BULLET_BLUE);
+    for line in rendered.lines() {
+        println!("  {}", line);
+    }
+    println!();
+}
+
+/// Remote branch of a pipeline branch of `project`.
+fn git_branch(project: &str, branch: &str) -> String {
+    let project_config = resolve_project_config(Some(project.to_string()))
+        .ok()
+        .flatten();
+    resolve_git_branch(branch, project_config.as_ref())
+}
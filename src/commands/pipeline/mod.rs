@@ -1,12 +1,23 @@
 use crate::*;
 
+pub mod export;
+pub mod issues;
+pub mod matrix;
+pub mod share;
 pub mod stages;
+pub mod summary;
 
 pub async fn handle(cmd: RunCommands) {
     match cmd {
         cmd @ RunCommands::Plan { .. } => stages::plan::handle(cmd).await,
+        RunCommands::Develop {
+            ref matrix,
+            ref models,
+            ..
+        } if !matrix.is_empty() || !models.is_empty() => matrix::handle(cmd).await,
         cmd @ RunCommands::Develop { .. } => stages::develop::handle(cmd).await,
         cmd @ RunCommands::Release { .. } => stages::release::handle(cmd).await,
         cmd @ RunCommands::Review { .. } => stages::review::handle(cmd).await,
+        cmd @ RunCommands::Resolve { .. } => stages::resolve::handle(cmd).await,
     }
 }
@@ -0,0 +1,32 @@
+use crate::*;
+
+/// Upload a run's output to a secret gist with the project's GitHub token and print its URL
+/// (`--share`); `redact` masks credentials first (`--redact`).
+pub(crate) async fn share_output(
+    project_config: Option<&ProjectConfig>,
+    description: &str,
+    filename: &str,
+    text: &str,
+    redact: bool,
+) -> Result<String, String> {
+    let api_url = match resolve_forge(project_config).map_err(|e| e.to_string())? {
+        Forge::GitHub { api_url } => api_url,
+        Forge::Gitea { .. } => {
+            return Err("--share uploads to a GitHub gist; the project uses Gitea".to_string())
+        }
+    };
+    let token = project_config
+        .and_then(|p| p.github_token.as_deref())
+        .filter(|t| !t.is_empty())
+        .ok_or(
+            "--share needs the project's github_token (with the gist scope); GitHub App tokens cannot create gists",
+        )?;
+    let content = if redact {
+        secrets::redact_text(text)
+    } else {
+        text.to_string()
+    };
+    let url = github::create_gist(&api_url, token, description, filename, &content).await?;
+    audit::record("share", filename, Some(url.clone()));
+    Ok(url)
+}
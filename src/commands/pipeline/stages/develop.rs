@@ -1,4 +1,5 @@
 use crate::*;
+use commands::pipeline::{export, summary};
 
 const MAX_SELF_CHECK_PASSES: u32 = 2;
 
@@ -10,13 +11,30 @@ pub async fn handle(cmd: RunCommands) {
             base,
             plan,
             max_validate_passes,
-            verbose,
+            reuse_workspace,
+            force_with_lease,
+            allow_secrets,
+            agent,
+            model,
+            execute_model,
+            validate_model,
+            handoff,
+            preset,
+            co_author,
+            provenance,
+            commit_strategy,
+            sync_base,
+            no_sync_base,
+            export_on_failure,
             task,
             ..
         } => {
+            set_agent_override(agent);
+            export::set_failure_export(export_on_failure);
+            let verbose = logging::verbose();
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     _ => {
                         eprintln!("Error: --project required");
@@ -42,7 +60,14 @@ pub async fn handle(cmd: RunCommands) {
                 }
             };
 
-            ensure_spawned_container_for_pipeline(&project, &branch, false).unwrap_or_else(|e| {
+            ensure_spawned_container_for_pipeline(
+                &project,
+                &branch,
+                reuse_workspace,
+                reuse_workspace,
+            )
+            .await
+            .unwrap_or_else(|e| {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             });
@@ -58,14 +83,83 @@ pub async fn handle(cmd: RunCommands) {
                     std::process::exit(1);
                 });
             let resolved_base = resolve_base_branch(base.as_deref(), project_config.as_ref());
+            let git_branch = resolve_git_branch(&branch, project_config.as_ref());
             let (commit_name, commit_email) = resolve_commit_author(project_config.as_ref());
-            let pipeline_roles = resolve_pipeline_roles(project_config.as_ref(), "dev");
+            let preset = preset::resolve(
+                preset.as_deref(),
+                project_config.as_ref().and_then(|p| p.preset.as_deref()),
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let commit_strategy = match commit_strategy
+                .or_else(|| project_config.as_ref()?.commit_strategy.clone())
+            {
+                Some(strategy) => validate_commit_strategy(&strategy).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }),
+                None => "squash".to_string(),
+            };
+            let sync_base = sync_base
+                .or_else(|| project_config.as_ref()?.sync_base.clone())
+                .filter(|_| !no_sync_base)
+                .map(|mode| {
+                    validate_sync_base(&mode).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    })
+                });
+            let validate_commands = project_config
+                .as_ref()
+                .and_then(|p| p.validate_commands.clone())
+                .unwrap_or_default();
+            if preset.run_validate_commands && validate_commands.is_empty() {
+                eprintln!(
+                    "Error: preset '{}' runs the project's validate_commands, but project '{}' has none",
+                    preset.name, project
+                );
+                std::process::exit(1);
+            }
+            if preset.review_before_push && !picker::interactive() {
+                eprintln!(
+                    "Error: preset '{}' asks before pushing; run on a terminal or pick another --preset",
+                    preset.name
+                );
+                std::process::exit(1);
+            }
+            let mut pipeline_roles = resolve_pipeline_roles(project_config.as_ref(), "dev");
+            apply_preset_prompts(&mut pipeline_roles, preset);
             let model_profile = resolve_project_model_profile(project_config.as_ref())
                 .unwrap_or_else(|e| {
                     eprintln!("Error: {}", e);
                     std::process::exit(1);
                 });
+            let handoff_agent = resolve_handoff_agent(project_config.as_ref(), handoff.as_deref())
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
             let default_model = model_profile.model.as_deref();
+            let model_override = model.as_deref();
+            let execute_override = execute_model.as_deref().or(model_override);
+            let validate_override = validate_model.as_deref().or(model_override);
+            let develop_model = stage_model(
+                execute_override,
+                pipeline_roles.execute_run.as_ref(),
+                default_model,
+            );
+            let self_check_model = stage_model(
+                execute_override,
+                pipeline_roles.execute_check.as_ref(),
+                default_model,
+            );
+            let validate_model = stage_model(
+                validate_override,
+                pipeline_roles.validate_run.as_ref(),
+                default_model,
+            );
 
             if let Err(e) = docker::ensure_spawn_state_dir(&project, &branch) {
                 eprintln!("Error: {}", e);
@@ -179,6 +273,18 @@ pub async fn handle(cmd: RunCommands) {
             }
 
             let dev_run_id = format!("dev-{}-{}", now_unix(), generate_short_plan_id(0));
+            let commit_trailers = resolve_commit_trailers(
+                project_config.as_ref(),
+                &co_author,
+                provenance,
+                Some(&model_profile.name),
+                develop_model,
+                &dev_run_id,
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
             let dev_run_dir = format!("/state/{}", dev_run_id);
             if let Err(e) = docker::ensure_spawn_dir(&project, &branch, &dev_run_dir) {
                 eprintln!("Error: {}", e);
@@ -236,11 +342,61 @@ pub async fn handle(cmd: RunCommands) {
                 eprintln!("Error: failed writing develop manifest: {}", e);
                 std::process::exit(1);
             }
+            emit_run_started("develop", &project, &branch, &dev_run_id);
 
-            let branch_escaped = branch.replace('\'', "'\"'\"'");
+            let branch_escaped = git_branch.replace('\'', "'\"'\"'");
             let base_escaped = resolved_base.replace('\'', "'\"'\"'");
+            let mut lease = None;
+            if force_with_lease {
+                let probe = build_branch_tip_probe_script(&git_branch);
+                let checked = docker::run_spawn_shell(&project, &branch, &probe).and_then(|out| {
+                    let Some((tip, tip_log)) = out.trim().split_once('\n') else {
+                        return Ok(None);
+                    };
+                    let tip = tip.trim().to_string();
+                    check_smith_branch_tip(tip_log, commit_email.as_deref())
+                        .map(|_| Some(tip.clone()))
+                        .map_err(|e| {
+                            format!(
+                                "refusing --force-with-lease: origin/{} ({}) was not made by a smith run: {}",
+                                git_branch,
+                                &tip[..tip.len().min(12)],
+                                e
+                            )
+                        })
+                });
+                match checked {
+                    Ok(tip) => lease = tip,
+                    Err(e) => {
+                        dev_manifest.errors.push(e.clone());
+                        dev_manifest.set_state("failed", "setup");
+                        let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                if let Some(tip) = &lease {
+                    println!(
+                        "  {} Replacing origin/{} ({}) from {}",
+                        BULLET_BLUE,
+                        git_branch,
+                        &tip[..tip.len().min(12)],
+                        resolved_base
+                    );
+                }
+                dev_manifest.replaced_commit = lease.clone();
+            }
+            let resume_remote_branch = if force_with_lease {
+                "false".to_string()
+            } else {
+                format!(
+                    "git show-ref --verify --quiet 'refs/remotes/origin/{}'",
+                    branch_escaped
+                )
+            };
             let setup_script = format!(
-                    "cd /workspace && git rev-parse --is-inside-work-tree >/dev/null 2>&1 || {{ echo 'Not a git repo at /workspace'; exit 1; }} && git fetch origin 2>&1 && if git show-ref --verify --quiet 'refs/remotes/origin/{branch}'; then git checkout -B '{branch}' 'refs/remotes/origin/{branch}' 2>&1; else git show-ref --verify --quiet 'refs/remotes/origin/{base}' || {{ echo 'Missing remote base branch origin/{base}'; exit 1; }}; git checkout -B '{branch}' 'refs/remotes/origin/{base}' 2>&1; fi && git reset --hard HEAD 2>&1 && git clean -fd 2>&1 && test -z \"$(git status --porcelain)\" || {{ echo 'Workspace is not clean after setup'; exit 1; }}",
+                    "cd /workspace && git rev-parse --is-inside-work-tree >/dev/null 2>&1 || {{ echo 'Not a git repo at /workspace'; exit 1; }} && git fetch origin 2>&1 && if {resume}; then git checkout -B '{branch}' 'refs/remotes/origin/{branch}' 2>&1; else git show-ref --verify --quiet 'refs/remotes/origin/{base}' || {{ echo 'Missing remote base branch origin/{base}'; exit 1; }}; git checkout -B '{branch}' 'refs/remotes/origin/{base}' 2>&1; fi && git reset --hard HEAD 2>&1 && git clean -fd 2>&1 && test -z \"$(git status --porcelain)\" || {{ echo 'Workspace is not clean after setup'; exit 1; }}",
+                    resume = resume_remote_branch,
                     branch = branch_escaped,
                     base = base_escaped
                 );
@@ -254,6 +410,52 @@ pub async fn handle(cmd: RunCommands) {
                 std::process::exit(1);
             }
 
+            let network_mode = project_config.as_ref().and_then(|p| p.network.clone());
+            if let Some(mode) = network_mode.as_deref() {
+                let providers: Vec<&str> = [
+                    default_model,
+                    model_profile.provider.as_deref(),
+                    execute_override,
+                    validate_override,
+                    pipeline_roles
+                        .execute_run
+                        .as_ref()
+                        .and_then(|r| r.model.as_deref()),
+                    pipeline_roles
+                        .execute_check
+                        .as_ref()
+                        .and_then(|r| r.model.as_deref()),
+                    pipeline_roles
+                        .validate_run
+                        .as_ref()
+                        .and_then(|r| r.model.as_deref()),
+                    handoff_agent.as_ref().and_then(|h| h.model.as_deref()),
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+                let base_urls: Vec<&str> = model_profile.base_url.as_deref().into_iter().collect();
+                let repo = project_config.as_ref().map_or("", |p| p.repo.as_str());
+                if let Err(e) = docker::egress_allowlist(mode, &providers, &base_urls, repo)
+                    .and_then(|hosts| {
+                        docker::isolate_spawned_network(&project, &branch, &hosts)
+                            .map_err(String::from)
+                    })
+                {
+                    dev_manifest.errors.push(e.clone());
+                    dev_manifest.set_state("failed", "network");
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                if verbose {
+                    println!(
+                        "  {} Network isolated ({}) for develop and validate",
+                        BULLET_BLUE, mode
+                    );
+                }
+            }
+
             if verbose {
                 println!(
                     "  {} Running spawn develop for {}:{} using plan {} (id: {})",
@@ -261,6 +463,54 @@ pub async fn handle(cmd: RunCommands) {
                 );
             }
 
+            let handoff_path = format!("{}/handoff.json", dev_run_dir);
+            if let Some(agent) = &handoff_agent {
+                println!("  {} Handoff from {}", BULLET_BLUE, agent.label());
+                dev_manifest.set_prompt_phase("handoff", agent.model.as_deref());
+                dev_manifest.handoff_agent = Some(agent.label());
+                let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                let prompt = build_spawn_handoff_prompt(
+                    &task,
+                    &plan_dir,
+                    &execution_brief_path,
+                    &handoff_path,
+                );
+                let handoff =
+                    apply_pre_prompt_hook(project_config.as_ref(), "handoff", &branch, prompt)
+                        .and_then(|prompt| {
+                            docker::run_prompt_in_spawned_container_with_options(
+                                &project,
+                                &branch,
+                                &prompt,
+                                verbose,
+                                agent.model.as_deref(),
+                                agent.prompt.as_deref(),
+                            )
+                        })
+                        .and_then(|_| docker::read_spawn_file(&project, &branch, &handoff_path))
+                        .and_then(|raw| parse_handoff(&raw))
+                        .map_err(|e| format!("handoff from {} failed: {}", agent.label(), e));
+                match handoff {
+                    Ok(handoff) => {
+                        dev_manifest.handoff_artifact = Some(handoff_path.clone());
+                        if verbose {
+                            println!("  {} {}", BULLET_GREEN, handoff.summary);
+                        }
+                    }
+                    Err(e) => {
+                        dev_manifest.errors.push(e.clone());
+                        dev_manifest.set_state("failed", "handoff");
+                        let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            let handoff_label = handoff_agent.as_ref().map(HandoffAgent::label);
+            let handoff_context = handoff_label
+                .as_deref()
+                .map(|label| (label, handoff_path.as_str()));
+
             let mut latest_report: Option<DevAssuranceReport> = None;
             for attempt in 1..=max_validate_passes {
                 let develop_artifact_path = format!("{}/develop-{}.json", dev_run_dir, attempt);
@@ -270,30 +520,39 @@ pub async fn handle(cmd: RunCommands) {
 
                 let mut self_check_passed = false;
                 for self_pass in 1..=MAX_SELF_CHECK_PASSES {
-                    dev_manifest.set_phase(&format!("develop-{}-{}", attempt, self_pass));
+                    dev_manifest.set_prompt_phase(
+                        &format!("develop-{}-{}", attempt, self_pass),
+                        develop_model,
+                    );
                     let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
                     let develop_prompt = build_spawn_develop_prompt(
                         &task,
                         &plan_dir,
                         &execution_brief_path,
+                        handoff_context,
                         &develop_artifact_path,
                         attempt,
+                        &validate_commands,
                     );
-                    if let Err(e) = docker::run_prompt_in_spawned_container_with_options(
-                        &project,
+                    if let Err(e) = apply_pre_prompt_hook(
+                        project_config.as_ref(),
+                        "develop",
                         &branch,
-                        &develop_prompt,
-                        verbose,
-                        pipeline_roles
-                            .execute_run
-                            .as_ref()
-                            .and_then(|r| r.model.as_deref())
-                            .or(default_model),
-                        pipeline_roles
-                            .execute_run
-                            .as_ref()
-                            .and_then(|r| r.prompt.as_deref()),
-                    ) {
+                        develop_prompt,
+                    )
+                    .and_then(|develop_prompt| {
+                        docker::run_prompt_in_spawned_container_with_options(
+                            &project,
+                            &branch,
+                            &develop_prompt,
+                            verbose,
+                            develop_model,
+                            pipeline_roles
+                                .execute_run
+                                .as_ref()
+                                .and_then(|r| r.prompt.as_deref()),
+                        )
+                    }) {
                         dev_manifest.errors.push(e.clone());
                         dev_manifest.set_state("failed", &format!("develop-{}", attempt));
                         let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
@@ -315,7 +574,38 @@ pub async fn handle(cmd: RunCommands) {
                         std::process::exit(1);
                     }
 
-                    dev_manifest.set_phase(&format!("self-check-{}-{}", attempt, self_pass));
+                    match docker::enforce_spawned_paths(&project, &branch) {
+                        Ok(reverted) if reverted.is_empty() => {}
+                        Ok(reverted) => {
+                            println!(
+                                "  {} Reverted changes to protected paths: {}",
+                                BULLET_YELLOW,
+                                reverted.join(", ")
+                            );
+                            dev_manifest.reverted_paths.extend(reverted);
+                            dev_manifest.reverted_paths.sort();
+                            dev_manifest.reverted_paths.dedup();
+                        }
+                        Err(e) => {
+                            dev_manifest.errors.push(e.clone());
+                            dev_manifest.set_state("failed", &format!("develop-{}", attempt));
+                            let _ =
+                                write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                            eprintln!("Error: {}", e);
+                            println!("  State Dir: {}", dev_run_dir);
+                            std::process::exit(1);
+                        }
+                    }
+
+                    if !preset.self_check {
+                        self_check_passed = true;
+                        break;
+                    }
+
+                    dev_manifest.set_prompt_phase(
+                        &format!("self-check-{}-{}", attempt, self_pass),
+                        self_check_model,
+                    );
                     let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
                     let self_check_prompt = build_spawn_self_check_prompt(
                         &task,
@@ -325,21 +615,25 @@ pub async fn handle(cmd: RunCommands) {
                         &self_check_artifact_path,
                         attempt,
                     );
-                    if let Err(e) = docker::run_prompt_in_spawned_container_with_options(
-                        &project,
+                    if let Err(e) = apply_pre_prompt_hook(
+                        project_config.as_ref(),
+                        "self-check",
                         &branch,
-                        &self_check_prompt,
-                        verbose,
-                        pipeline_roles
-                            .execute_check
-                            .as_ref()
-                            .and_then(|r| r.model.as_deref())
-                            .or(default_model),
-                        pipeline_roles
-                            .execute_check
-                            .as_ref()
-                            .and_then(|r| r.prompt.as_deref()),
-                    ) {
+                        self_check_prompt,
+                    )
+                    .and_then(|self_check_prompt| {
+                        docker::run_prompt_in_spawned_container_with_options(
+                            &project,
+                            &branch,
+                            &self_check_prompt,
+                            verbose,
+                            self_check_model,
+                            pipeline_roles
+                                .execute_check
+                                .as_ref()
+                                .and_then(|r| r.prompt.as_deref()),
+                        )
+                    }) {
                         dev_manifest.errors.push(e.clone());
                         dev_manifest.set_state("failed", &format!("self-check-{}", attempt));
                         let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
@@ -412,7 +706,21 @@ pub async fn handle(cmd: RunCommands) {
                     std::process::exit(1);
                 }
 
-                dev_manifest.set_phase(&format!("validate-{}", attempt));
+                if !preset.validate {
+                    dev_manifest.attempts.push(DevAttemptRecord {
+                        attempt,
+                        develop_artifact: develop_artifact_path,
+                        self_check_artifact: self_check_artifact_path,
+                        assurance_artifact: String::new(),
+                        verdict: "skipped".to_string(),
+                        blocking_issues: 0,
+                        non_blocking_issues: 0,
+                    });
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    break;
+                }
+
+                dev_manifest.set_prompt_phase(&format!("validate-{}", attempt), validate_model);
                 let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
                 let assurance_prompt = build_spawn_assurance_prompt(
                     &task,
@@ -423,21 +731,25 @@ pub async fn handle(cmd: RunCommands) {
                     &assurance_artifact_path,
                     attempt,
                 );
-                if let Err(e) = docker::run_prompt_in_spawned_container_with_options(
-                    &project,
+                if let Err(e) = apply_pre_prompt_hook(
+                    project_config.as_ref(),
+                    "validate",
                     &branch,
-                    &assurance_prompt,
-                    verbose,
-                    pipeline_roles
-                        .validate_run
-                        .as_ref()
-                        .and_then(|r| r.model.as_deref())
-                        .or(default_model),
-                    pipeline_roles
-                        .validate_run
-                        .as_ref()
-                        .and_then(|r| r.prompt.as_deref()),
-                ) {
+                    assurance_prompt,
+                )
+                .and_then(|assurance_prompt| {
+                    docker::run_prompt_in_spawned_container_with_options(
+                        &project,
+                        &branch,
+                        &assurance_prompt,
+                        verbose,
+                        validate_model,
+                        pipeline_roles
+                            .validate_run
+                            .as_ref()
+                            .and_then(|r| r.prompt.as_deref()),
+                    )
+                }) {
                     dev_manifest.errors.push(e.clone());
                     dev_manifest.set_state("failed", &format!("validate-{}", attempt));
                     let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
@@ -461,7 +773,7 @@ pub async fn handle(cmd: RunCommands) {
                         }
                     };
 
-                let report = match parse_dev_assurance_report(&assurance_raw) {
+                let mut report = match parse_dev_assurance_report(&assurance_raw) {
                     Ok(r) => r,
                     Err(e) => {
                         dev_manifest.errors.push(e.clone());
@@ -472,6 +784,26 @@ pub async fn handle(cmd: RunCommands) {
                     }
                 };
 
+                if preset.run_validate_commands {
+                    dev_manifest.set_phase(&format!("validate-commands-{}", attempt));
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    let failed =
+                        run_validate_commands(&project, &branch, &validate_commands, verbose);
+                    if !failed.is_empty() {
+                        report.verdict = "fail".to_string();
+                        report.blocking_issues.extend(failed);
+                        // The agent's next pass reads the findings from the artifact.
+                        if let Ok(raw) = serde_json::to_string_pretty(&report) {
+                            let _ = docker::write_spawn_file(
+                                &project,
+                                &branch,
+                                &assurance_artifact_path,
+                                &raw,
+                            );
+                        }
+                    }
+                }
+
                 if verbose {
                     println!(
                         "  {} validate pass {} verdict={} blocking={} non_blocking={}",
@@ -516,39 +848,344 @@ pub async fn handle(cmd: RunCommands) {
                 }
             }
 
+            if network_mode.is_some() {
+                if let Err(e) = docker::restore_spawned_network(&project, &branch) {
+                    dev_manifest.errors.push(e.to_string());
+                    dev_manifest.set_state("failed", "network");
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+
             dev_manifest.set_phase("commit");
             let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
 
-            let commit_msg = format!(
-                "{} [plan:{}]",
-                task.replace('\n', " "),
-                dev_manifest.short_plan_id
-            )
-            .replace('\'', "'\"'\"'");
-            let git_name_cmd = match commit_name.as_deref() {
-                Some(name) if !name.trim().is_empty() => format!(
-                    "git config user.name '{}' && ",
-                    name.replace('\'', "'\"'\"'")
-                ),
-                _ => "git config user.name 'Smith' && ".to_string(),
-            };
-            let git_email_cmd = match commit_email.as_deref() {
-                Some(email) if !email.trim().is_empty() => format!(
-                    "git config user.email '{}' && ",
-                    email.replace('\'', "'\"'\"'")
-                ),
-                _ => "git config user.email 'smith@localhost' && ".to_string(),
-            };
-            let commit_script = format!(
-                    "cd /workspace && test -n \"$(git status --porcelain)\" || {{ echo 'SMITH_NO_CHANGES'; exit 3; }} && {git_name}{git_email}git add -A && git commit -m '{msg}' 2>&1 && git fetch origin 2>&1 && if git show-ref --verify --quiet 'refs/remotes/origin/{branch}'; then git rebase 'refs/remotes/origin/{branch}' 2>&1 || {{ echo 'Rebase failed'; exit 1; }}; fi && git push origin 'HEAD:refs/heads/{branch}' 2>&1 && git rev-parse HEAD",
-                    git_name = git_name_cmd,
-                    git_email = git_email_cmd,
-                    msg = commit_msg,
-                    branch = branch_escaped
+            match docker::check_spawned_policy(&project, &branch) {
+                Ok(violations) if violations.is_empty() => {}
+                Ok(violations) => {
+                    eprintln!(
+                        "Error: run violates the policy of project '{}'; nothing was committed:",
+                        project
+                    );
+                    for violation in &violations {
+                        eprintln!("  {} {}", BULLET_RED, violation);
+                        dev_manifest
+                            .errors
+                            .push(format!("policy violation: {}", violation));
+                    }
+                    dev_manifest.set_state("failed", "policy");
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    println!("  State Dir: {}", dev_run_dir);
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    dev_manifest.errors.push(e.clone());
+                    dev_manifest.set_state("failed", "policy");
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    eprintln!("Error: policy check failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+
+            let secret_scan = project_config
+                .as_ref()
+                .and_then(|p| p.secret_scan.as_deref())
+                .unwrap_or("builtin");
+            if secret_scan != "off" {
+                match docker::scan_spawned_secrets(&project, &branch, secret_scan == "gitleaks") {
+                    Ok(findings) if findings.is_empty() => {}
+                    Ok(findings) => {
+                        let bullet = if allow_secrets {
+                            BULLET_YELLOW
+                        } else {
+                            BULLET_RED
+                        };
+                        if allow_secrets {
+                            println!(
+                                "  {} Secret scan found {} suspected credential(s); committing anyway (--allow-secrets):",
+                                BULLET_YELLOW,
+                                findings.len()
+                            );
+                        } else {
+                            eprintln!(
+                                "Error: secret scan found {} suspected credential(s); nothing was committed or pushed:",
+                                findings.len()
+                            );
+                        }
+                        for f in &findings {
+                            eprintln!(
+                                "  {} {}:{} [{}] {}",
+                                bullet, f.file, f.line, f.rule, f.excerpt
+                            );
+                        }
+                        if allow_secrets {
+                            dev_manifest.allowed_secrets = findings;
+                        } else {
+                            dev_manifest.errors.extend(findings.iter().map(|f| {
+                                format!("secret detected: {}:{} [{}]", f.file, f.line, f.rule)
+                            }));
+                            dev_manifest.set_state("failed", "secret-scan");
+                            let _ =
+                                write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                            eprintln!(
+                                "  Remove the credentials, or rerun with --allow-secrets if they are false positives."
+                            );
+                            println!("  State Dir: {}", dev_run_dir);
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(e) => {
+                        dev_manifest.errors.push(e.clone());
+                        dev_manifest.set_state("failed", "secret-scan");
+                        let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                        eprintln!("Error: secret scan failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            let mut commit_msg = format!("{} [plan:{}]", task, dev_manifest.short_plan_id);
+            if let Some(lint) = project_config.as_ref().and_then(|p| p.commit_lint.as_ref()) {
+                let max_rewrites = lint
+                    .max_rewrites
+                    .unwrap_or(commit_lint::DEFAULT_MAX_REWRITES);
+                let commit_role = pipeline_roles.commit_run.as_ref();
+                let commit_model = stage_model(model_override, commit_role, develop_model);
+                let message_path = format!("{}/commit-message.txt", dev_run_dir);
+                let mut rewrite = 0;
+                loop {
+                    let violations = commit_lint::lint(lint, &commit_msg);
+                    if violations.is_empty() {
+                        break;
+                    }
+                    if rewrite == max_rewrites {
+                        eprintln!(
+                            "Error: commit message still fails commit_lint after {} rewrite(s):",
+                            max_rewrites
+                        );
+                        for violation in &violations {
+                            eprintln!("  {} {}", BULLET_RED, violation);
+                            dev_manifest
+                                .errors
+                                .push(format!("commit lint: {}", violation));
+                        }
+                        dev_manifest.commit_message = Some(commit_msg);
+                        dev_manifest.set_state("failed", "commit-lint");
+                        let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                        println!("  State Dir: {}", dev_run_dir);
+                        std::process::exit(1);
+                    }
+                    rewrite += 1;
+                    if verbose {
+                        println!(
+                            "  {} commit message fails lint ({}); asking the agent to rewrite it",
+                            BULLET_YELLOW,
+                            violations.join("; ")
+                        );
+                    }
+                    dev_manifest
+                        .set_prompt_phase(&format!("commit-lint-{}", rewrite), commit_model);
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    let prompt = build_spawn_commit_message_prompt(
+                        &task,
+                        &commit_msg,
+                        &violations,
+                        &commit_lint::describe_rules(lint),
+                        &message_path,
+                    );
+                    let rewritten = docker::run_prompt_in_spawned_container_with_options(
+                        &project,
+                        &branch,
+                        &prompt,
+                        verbose,
+                        commit_model,
+                        commit_role.and_then(|r| r.prompt.as_deref()),
+                    )
+                    .and_then(|_| docker::read_spawn_file(&project, &branch, &message_path));
+                    match rewritten {
+                        Ok(message) if !message.trim().is_empty() => {
+                            commit_msg = message.trim().to_string();
+                            if !commit_msg.contains(&dev_manifest.short_plan_id) {
+                                commit_msg
+                                    .push_str(&format!("\n\nPlan: {}", dev_manifest.short_plan_id));
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            dev_manifest.errors.push(e.clone());
+                            dev_manifest.set_state("failed", "commit-lint");
+                            let _ =
+                                write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
+            commit_msg = commit_lint::append_trailers(&commit_msg, &commit_trailers);
+            if commit_strategy != "squash" {
+                let grouped =
+                    docker::spawned_changed_paths(&project, &branch).and_then(|changed| {
+                        if commit_strategy == "per-file" {
+                            return Ok(per_file_commit_groups(&commit_msg, &changed));
+                        }
+                        let lint = project_config.as_ref().and_then(|p| p.commit_lint.as_ref());
+                        let groups_path = format!("{}/commit-groups.json", dev_run_dir);
+                        let prompt = build_spawn_commit_groups_prompt(
+                            &task,
+                            &planner_path,
+                            &changed,
+                            &lint.map(commit_lint::describe_rules).unwrap_or_default(),
+                            &groups_path,
+                        );
+                        let commit_role = pipeline_roles.commit_run.as_ref();
+                        let raw = docker::run_prompt_in_spawned_container_with_options(
+                            &project,
+                            &branch,
+                            &prompt,
+                            verbose,
+                            stage_model(model_override, commit_role, develop_model),
+                            commit_role.and_then(|r| r.prompt.as_deref()),
+                        )
+                        .and_then(|_| docker::read_spawn_file(&project, &branch, &groups_path))?;
+                        let mut groups = parse_commit_groups(&raw, &changed)?;
+                        for group in &mut groups {
+                            if let Some(violation) =
+                                lint.and_then(|l| commit_lint::lint(l, &group.message).pop())
+                            {
+                                return Err(format!("'{}': {}", group.message, violation));
+                            }
+                            if !group.message.contains(&dev_manifest.short_plan_id) {
+                                group
+                                    .message
+                                    .push_str(&format!("\n\nPlan: {}", dev_manifest.short_plan_id));
+                            }
+                            group.message =
+                                commit_lint::append_trailers(&group.message, &commit_trailers);
+                        }
+                        Ok(groups)
+                    });
+                match grouped {
+                    Ok(groups) => dev_manifest.commit_groups = groups,
+                    Err(e) => println!(
+                        "  {} Could not split the commit {} ({}); committing the changes at once",
+                        BULLET_YELLOW, commit_strategy, e
+                    ),
+                }
+            }
+            dev_manifest.commit_message = Some(commit_msg.clone());
+
+            if preset.review_before_push
+                && !review_before_push(&project, &branch, &git_branch, &commit_msg)
+            {
+                dev_manifest
+                    .errors
+                    .push("Push declined at the diff review".to_string());
+                dev_manifest.set_state("failed", "review");
+                let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                println!(
+                    "  {} Not pushed; the changes stay in the agent's workspace",
+                    BULLET_YELLOW
                 );
+                println!("  State Dir: {}", dev_run_dir);
+                std::process::exit(1);
+            }
+
+            if let Some(hook) = project_config
+                .as_ref()
+                .and_then(|p| p.pre_push_hook.as_deref())
+            {
+                let diff = docker::run_spawn_shell(
+                    &project,
+                    &branch,
+                    "cd /workspace && git add -A -N . && git diff HEAD",
+                )
+                .unwrap_or_default();
+                let context = serde_json::json!({
+                    "hook": "pre_push",
+                    "stage": "develop",
+                    "project": project,
+                    "branch": branch,
+                    "base": resolved_base,
+                    "task": task,
+                    "commit_message": commit_msg,
+                    "diff": diff,
+                    "findings": latest_report
+                        .as_ref()
+                        .map(|r| r.non_blocking_issues.clone())
+                        .unwrap_or_default(),
+                });
+                if let Err(e) = run_project_hook("pre_push", hook, &context) {
+                    dev_manifest.errors.push(e.clone());
+                    dev_manifest.set_state("failed", "pre-push-hook");
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    eprintln!("Error: {}", e);
+                    println!("  State Dir: {}", dev_run_dir);
+                    std::process::exit(1);
+                }
+            }
+
+            let commit_script = build_spawn_split_commit_script(
+                &dev_manifest.commit_groups,
+                &commit_msg,
+                &git_branch,
+                commit_name.as_deref(),
+                commit_email.as_deref(),
+                lease.as_deref(),
+                sync_base
+                    .as_deref()
+                    .map(|mode| (mode, resolved_base.as_str())),
+            );
 
             let commit_output = match docker::run_spawn_shell(&project, &branch, &commit_script) {
                 Ok(v) => v,
+                Err(e) if e.contains("SMITH_BASE_CONFLICT") => {
+                    println!(
+                        "  {} Conflicts with origin/{}; asking the agent to resolve them",
+                        BULLET_YELLOW, resolved_base
+                    );
+                    dev_manifest.set_prompt_phase("sync-base", develop_model);
+                    let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                    let resolved = resolve_base_conflicts(
+                        &project,
+                        &branch,
+                        &resolved_base,
+                        &task,
+                        verbose,
+                        develop_model,
+                        pipeline_roles
+                            .execute_run
+                            .as_ref()
+                            .and_then(|r| r.prompt.as_deref()),
+                    )
+                    .and_then(|_| {
+                        docker::run_spawn_shell(
+                            &project,
+                            &branch,
+                            &format!(
+                                "cd /workspace && {}",
+                                build_spawn_push_step(&git_branch, lease.as_deref())
+                            ),
+                        )
+                    });
+                    match resolved {
+                        Ok(v) => v,
+                        Err(e) => {
+                            dev_manifest.errors.push(e.clone());
+                            dev_manifest.set_state("failed", "sync-base");
+                            let _ =
+                                write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
+                            eprintln!("Error: {}", e);
+                            println!(
+                                "  {} Not pushed; the commits stay in the agent's workspace",
+                                BULLET_YELLOW
+                            );
+                            println!("  State Dir: {}", dev_run_dir);
+                            std::process::exit(1);
+                        }
+                    }
+                }
                 Err(e) => {
                     if e.contains("SMITH_NO_CHANGES") {
                         dev_manifest.set_state("failed", "commit");
@@ -568,13 +1205,7 @@ pub async fn handle(cmd: RunCommands) {
                 }
             };
 
-            let commit_hash = commit_output
-                .lines()
-                .rev()
-                .map(str::trim)
-                .find(|line| line.len() >= 7 && line.chars().all(|c| c.is_ascii_hexdigit()))
-                .unwrap_or("")
-                .to_string();
+            let commit_hash = parse_commit_hash(&commit_output);
 
             if commit_hash.is_empty() {
                 dev_manifest
@@ -586,7 +1217,20 @@ pub async fn handle(cmd: RunCommands) {
                 std::process::exit(1);
             }
 
+            events::emit(&SmithEvent::PushPerformed {
+                project: project.clone(),
+                branch: branch.clone(),
+                commit: commit_hash.clone(),
+            });
+            if let Some(tip) = &lease {
+                audit::record(
+                    "force_push",
+                    &format!("{}/{}", project, git_branch),
+                    Some(format!("{} -> {}", tip, commit_hash)),
+                );
+            }
             dev_manifest.final_commit = Some(commit_hash.clone());
+            dev_manifest.bytes_pushed = summary::parse_push_bytes(&commit_output);
             dev_manifest.set_state("completed", "done");
             let _ = write_dev_manifest(&project, &branch, &dev_run_dir, &dev_manifest);
 
@@ -602,12 +1246,164 @@ pub async fn handle(cmd: RunCommands) {
 
             println!("  {} Spawn develop completed", BULLET_GREEN);
             println!("  Commit: {}", commit_hash);
+            if !dev_manifest.commit_groups.is_empty() {
+                println!(
+                    "  Commits: {} ({})",
+                    dev_manifest.commit_groups.len(),
+                    commit_strategy
+                );
+            }
             println!(
                 "  Plan: {} (id: {})",
                 selected_plan, dev_manifest.short_plan_id
             );
             println!("  State Dir: {}", dev_run_dir);
+            dev_manifest.stages.print_summary(dev_manifest.bytes_pushed);
         }
         _ => unreachable!("non-develop command routed to develop handler"),
     }
 }
+
+/// Have the agent resolve the conflicts of a `--sync-base` rebase or merge until it completes;
+/// after `MAX_SYNC_BASE_ROUNDS` prompts the rebase or merge is aborted.
+fn resolve_base_conflicts(
+    project: &str,
+    branch: &str,
+    base: &str,
+    task: &str,
+    verbose: bool,
+    model: Option<&str>,
+    role_prompt: Option<&str>,
+) -> Result<(), String> {
+    for round in 1..=MAX_SYNC_BASE_ROUNDS {
+        let files: Vec<String> = docker::run_spawn_shell(
+            project,
+            branch,
+            "cd /workspace && git diff --name-only --diff-filter=U",
+        )?
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+        if files.is_empty() {
+            let _ = docker::run_spawn_shell(project, branch, SYNC_BASE_ABORT_SCRIPT);
+            return Err(format!(
+                "syncing with origin/{} failed without conflicted files",
+                base
+            ));
+        }
+        println!(
+            "  {} Resolving {} conflicted file(s) (round {}/{})",
+            BULLET_BLUE,
+            files.len(),
+            round,
+            MAX_SYNC_BASE_ROUNDS
+        );
+        let prompt = build_spawn_base_conflict_prompt(task, base, &files);
+        let continued = docker::run_prompt_in_spawned_container_with_options(
+            project,
+            branch,
+            &prompt,
+            verbose,
+            model,
+            role_prompt,
+        )
+        .and_then(|_| {
+            docker::run_spawn_shell(project, branch, &build_sync_base_continue_script(&files))
+        });
+        match continued {
+            Ok(_) => return Ok(()),
+            Err(e) if e.contains("SMITH_BASE_CONFLICT") => {}
+            Err(e) => {
+                let _ = docker::run_spawn_shell(project, branch, SYNC_BASE_ABORT_SCRIPT);
+                return Err(e);
+            }
+        }
+    }
+    let _ = docker::run_spawn_shell(project, branch, SYNC_BASE_ABORT_SCRIPT);
+    Err(format!(
+        "conflicts with origin/{} remain after {} resolution rounds; the rebase or merge was aborted",
+        base, MAX_SYNC_BASE_ROUNDS
+    ))
+}
+
+/// The step's override (`--execute-model`/`--validate-model`, else `--model`) wins over the
+/// role's model, which wins over the profile default.
+pub(crate) fn stage_model<'a>(
+    model_override: Option<&'a str>,
+    role: Option<&'a RoleInfo>,
+    default_model: Option<&'a str>,
+) -> Option<&'a str> {
+    model_override
+        .or_else(|| role.and_then(|r| r.model.as_deref()))
+        .or(default_model)
+}
+
+/// Append the preset's instructions to the develop, self-check and validate role prompts.
+fn apply_preset_prompts(roles: &mut PipelineRoles, preset: &preset::Preset) {
+    for (role, extra) in [
+        (&mut roles.execute_run, preset.execute_prompt),
+        (&mut roles.execute_check, preset.check_prompt),
+        (&mut roles.validate_run, preset.validate_prompt),
+    ] {
+        if extra.is_none() {
+            continue;
+        }
+        let role = role.get_or_insert_with(|| RoleInfo::new(None, None));
+        role.prompt = preset::with_prompt(role.prompt.as_deref(), extra);
+    }
+}
+
+/// Run the project's validation commands in the agent's workspace; each failing one becomes a
+/// blocking finding.
+pub(crate) fn run_validate_commands(
+    project: &str,
+    branch: &str,
+    commands: &[String],
+    verbose: bool,
+) -> Vec<DevAssuranceIssue> {
+    let mut failed = Vec::new();
+    for (i, cmd) in commands.iter().enumerate() {
+        if verbose {
+            println!("  {} Running `{}`", BULLET_BLUE, cmd);
+        }
+        if let Err(e) =
+            docker::run_spawn_shell(project, branch, &format!("cd /workspace && {}", cmd))
+        {
+            let tail: Vec<&str> = e.lines().rev().take(40).collect();
+            failed.push(DevAssuranceIssue {
+                id: format!("VC-{}", i + 1),
+                severity: "high".to_string(),
+                title: format!("`{}` fails", cmd),
+                detail: tail.into_iter().rev().collect::<Vec<_>>().join("\n"),
+                related_ids: Vec::new(),
+            });
+        }
+    }
+    failed
+}
+
+/// Show the change about to be committed and pushed, and ask for a go-ahead.
+fn review_before_push(project: &str, branch: &str, git_branch: &str, commit_msg: &str) -> bool {
+    let stat = docker::run_spawn_shell(
+        project,
+        branch,
+        "cd /workspace && git add -A -N . && git diff HEAD --stat",
+    )
+    .unwrap_or_default();
+    println!();
+    println!("  {} Review before push to {}", BULLET_BLUE, git_branch);
+    println!(
+        "  Commit: {}",
+        commit_msg.lines().next().unwrap_or_default()
+    );
+    for line in stat.lines() {
+        println!("    {}", line);
+    }
+    println!(
+        "  Full diff: docker exec {} git -C /workspace diff HEAD",
+        docker::spawn_container_name(project, branch)
+    );
+    prompt_yn("Commit and push?", true)
+}
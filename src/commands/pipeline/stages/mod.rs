@@ -1,4 +1,5 @@
 pub mod develop;
 pub mod plan;
 pub mod release;
+pub mod resolve;
 pub mod review;
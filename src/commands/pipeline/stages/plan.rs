@@ -7,13 +7,14 @@ pub async fn handle(cmd: RunCommands) {
         RunCommands::Plan {
             project,
             branch,
-            verbose,
+            model,
             prompt,
         } => {
+            let verbose = logging::verbose();
             // Auto-detect project and branch if not provided
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     _ => {
                         eprintln!("Error: --project required");
@@ -39,11 +40,13 @@ pub async fn handle(cmd: RunCommands) {
                 }
             };
 
-            let auto_started = ensure_spawned_container_for_pipeline(&project, &branch, true)
-                .unwrap_or_else(|e| {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                });
+            let auto_started =
+                ensure_spawned_container_for_pipeline(&project, &branch, true, false)
+                    .await
+                    .unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    });
             if auto_started {
                 println!(
                     "  {} Started spawned agent for {}:{}",
@@ -61,7 +64,8 @@ pub async fn handle(cmd: RunCommands) {
                     eprintln!("Error: {}", e);
                     std::process::exit(1);
                 });
-            let default_model = model_profile.model.as_deref();
+            // --model replaces the profile model for every planning step.
+            let default_model = model.as_deref().or(model_profile.model.as_deref());
 
             if let Err(e) = docker::ensure_spawn_state_dir(&project, &branch) {
                 eprintln!("Error: {}", e);
@@ -106,8 +110,10 @@ pub async fn handle(cmd: RunCommands) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             }
+            emit_run_started("plan", &project, &branch, &run_id);
 
             manifest.set_state("in_progress", "planner");
+            manifest.stages.begin("planner", default_model);
             if let Err(e) = write_plan_manifest(&project, &branch, &run_dir, &manifest) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
@@ -128,14 +134,17 @@ pub async fn handle(cmd: RunCommands) {
             }
 
             let plan_prompt = build_spawn_plan_prompt(&prompt, &run_dir);
-            match docker::run_prompt_in_spawned_container_with_options(
-                &project,
-                &branch,
-                &plan_prompt,
-                verbose,
-                default_model,
-                None,
-            ) {
+            match apply_pre_prompt_hook(project_config.as_ref(), "plan", &branch, plan_prompt)
+                .and_then(|plan_prompt| {
+                    docker::run_prompt_in_spawned_container_with_options(
+                        &project,
+                        &branch,
+                        &plan_prompt,
+                        verbose,
+                        default_model,
+                        None,
+                    )
+                }) {
                 Ok(()) => {
                     if let Some(stop) = tracker_stop.take() {
                         stop.store(true, Ordering::SeqCst);
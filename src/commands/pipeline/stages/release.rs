@@ -7,13 +7,14 @@ pub async fn handle(cmd: RunCommands) {
             branch,
             base,
             plan,
-            verbose,
             keep_agent,
+            model,
             ..
         } => {
+            let verbose = logging::verbose();
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     _ => {
                         eprintln!("Error: --project required");
@@ -39,10 +40,12 @@ pub async fn handle(cmd: RunCommands) {
                 }
             };
 
-            ensure_spawned_container_for_pipeline(&project, &branch, false).unwrap_or_else(|e| {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            });
+            ensure_spawned_container_for_pipeline(&project, &branch, false, false)
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
 
             let project_config =
                 resolve_project_config(Some(project.clone())).unwrap_or_else(|e| {
@@ -55,7 +58,8 @@ pub async fn handle(cmd: RunCommands) {
                     eprintln!("Error: {}", e);
                     std::process::exit(1);
                 });
-            let default_model = model_profile.model.as_deref();
+            // --model replaces the profile model for every release step.
+            let default_model = model.as_deref().or(model_profile.model.as_deref());
 
             if let Err(e) = docker::ensure_spawn_state_dir(&project, &branch) {
                 eprintln!("Error: {}", e);
@@ -202,6 +206,7 @@ pub async fn handle(cmd: RunCommands) {
                 eprintln!("Error: failed writing release manifest: {}", e);
                 std::process::exit(1);
             }
+            emit_run_started("release", &project, &branch, &release_run_id);
 
             release_manifest.set_phase("review");
             let _ = write_release_manifest(&project, &branch, &release_run_dir, &release_manifest);
@@ -225,14 +230,22 @@ pub async fn handle(cmd: RunCommands) {
                     &assurance_artifact_path,
                     artifact_path,
                 );
-                if let Err(e) = docker::run_prompt_in_spawned_container_with_options(
-                    &project,
+                if let Err(e) = apply_pre_prompt_hook(
+                    project_config.as_ref(),
+                    "release-review",
                     &branch,
-                    &review_prompt,
-                    verbose,
-                    default_model,
-                    Some(&format!("@{}", role)),
-                ) {
+                    review_prompt,
+                )
+                .and_then(|review_prompt| {
+                    docker::run_prompt_in_spawned_container_with_options(
+                        &project,
+                        &branch,
+                        &review_prompt,
+                        verbose,
+                        default_model,
+                        Some(&format!("@{}", role)),
+                    )
+                }) {
                     release_manifest.errors.push(e.clone());
                     release_manifest.set_state("failed", "review");
                     let _ = write_release_manifest(
@@ -382,11 +395,12 @@ pub async fn handle(cmd: RunCommands) {
                 let _ =
                     write_release_manifest(&project, &branch, &release_run_dir, &release_manifest);
 
-                let branch_escaped = branch.replace('\'', "'\"'\"'");
+                let git_branch = resolve_git_branch(&branch, project_config.as_ref());
+                let branch_escaped = git_branch.replace('\'', "'\"'\"'");
                 let base_escaped = resolved_base.replace('\'', "'\"'\"'");
                 let merge_msg = format!(
                     "Merge '{}' into '{}' [plan:{}]",
-                    branch, resolved_base, release_manifest.short_plan_id
+                    git_branch, resolved_base, release_manifest.short_plan_id
                 )
                 .replace('\'', "'\"'\"'");
                 let integrate_script = format!(
@@ -434,6 +448,13 @@ pub async fn handle(cmd: RunCommands) {
                     .trim()
                     == "true";
 
+                if pushed {
+                    events::emit(&SmithEvent::PushPerformed {
+                        project: project.clone(),
+                        branch: resolved_base.clone(),
+                        commit: merge_commit.clone(),
+                    });
+                }
                 release_manifest.integration_status = Some(status.clone());
                 if !strategy.is_empty() {
                     release_manifest.merge_strategy = Some(strategy.clone());
@@ -490,14 +511,22 @@ pub async fn handle(cmd: RunCommands) {
                 &integrate_artifact_path,
                 &sync_artifact_path,
             );
-            if let Err(e) = docker::run_prompt_in_spawned_container_with_options(
-                &project,
+            if let Err(e) = apply_pre_prompt_hook(
+                project_config.as_ref(),
+                "release-sync",
                 &branch,
-                &sync_prompt,
-                verbose,
-                default_model,
-                Some("@planner"),
-            ) {
+                sync_prompt,
+            )
+            .and_then(|sync_prompt| {
+                docker::run_prompt_in_spawned_container_with_options(
+                    &project,
+                    &branch,
+                    &sync_prompt,
+                    verbose,
+                    default_model,
+                    Some("@planner"),
+                )
+            }) {
                 release_manifest.errors.push(e.clone());
                 release_manifest.set_state("failed", "sync");
                 let _ =
@@ -539,6 +568,7 @@ pub async fn handle(cmd: RunCommands) {
                 release_manifest.set_state("completed", "done");
             }
             let _ = write_release_manifest(&project, &branch, &release_run_dir, &release_manifest);
+            release_manifest.stages.print_summary(None);
 
             if final_blocked {
                 eprintln!(
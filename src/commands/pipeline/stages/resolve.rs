@@ -0,0 +1,240 @@
+use crate::*;
+use commands::pipeline::stages::develop::{run_validate_commands, stage_model};
+
+pub async fn handle(cmd: RunCommands) {
+    let RunCommands::Resolve {
+        project,
+        branch,
+        base,
+        check,
+        model,
+    } = cmd
+    else {
+        unreachable!("non-resolve command routed to resolve handler");
+    };
+    let verbose = logging::verbose();
+    let project = match project {
+        Some(p) => p,
+        None => match detect_or_pick_project() {
+            Ok(Some(name)) => name,
+            _ => {
+                eprintln!("Error: --project required");
+                std::process::exit(1);
+            }
+        },
+    };
+    let project_config = resolve_project_config(Some(project.clone())).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let resolved_base = resolve_base_branch(base.as_deref(), project_config.as_ref());
+    let git_branch = resolve_git_branch(&branch, project_config.as_ref());
+    let (commit_name, commit_email) = resolve_commit_author(project_config.as_ref());
+    let pipeline_roles = resolve_pipeline_roles(project_config.as_ref(), "dev");
+    let model_profile =
+        resolve_project_model_profile(project_config.as_ref()).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+    let resolve_model = stage_model(
+        model.as_deref(),
+        pipeline_roles.execute_run.as_ref(),
+        model_profile.model.as_deref(),
+    );
+    let role_prompt = pipeline_roles
+        .execute_run
+        .as_ref()
+        .and_then(|r| r.prompt.as_deref());
+
+    ensure_spawned_container_for_pipeline(&project, &branch, true, false)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+
+    let conflicts = docker::run_spawn_shell(
+        &project,
+        &branch,
+        &build_spawn_resolve_merge_script(
+            &git_branch,
+            &resolved_base,
+            commit_name.as_deref(),
+            commit_email.as_deref(),
+        ),
+    )
+    .map(|out| {
+        out.lines()
+            .filter_map(|l| l.trim().strip_prefix("SMITH_CONFLICT "))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    if conflicts.is_empty() {
+        let _ = docker::run_spawn_shell(&project, &branch, SYNC_BASE_ABORT_SCRIPT);
+        println!(
+            "  {} {} merges cleanly with origin/{}; nothing to resolve",
+            BULLET_GREEN, git_branch, resolved_base
+        );
+        return;
+    }
+    println!(
+        "  {} {} conflicts with origin/{} in {} file(s):",
+        if check { BULLET_RED } else { BULLET_YELLOW },
+        git_branch,
+        resolved_base,
+        conflicts.len()
+    );
+    for file in &conflicts {
+        println!("    {}", file);
+    }
+    if check {
+        let _ = docker::run_spawn_shell(&project, &branch, SYNC_BASE_ABORT_SCRIPT);
+        std::process::exit(1);
+    }
+
+    let fail = |e: String| -> ! {
+        let _ = docker::run_spawn_shell(&project, &branch, SYNC_BASE_ABORT_SCRIPT);
+        eprintln!("Error: {}", e);
+        println!("  {} Merge aborted; nothing was pushed", BULLET_YELLOW);
+        std::process::exit(1);
+    };
+
+    for (i, file) in conflicts.iter().enumerate() {
+        println!(
+            "  {} Resolving {} ({}/{})",
+            BULLET_BLUE,
+            file,
+            i + 1,
+            conflicts.len()
+        );
+        if let Err(e) = resolve_file(
+            &project,
+            &branch,
+            &git_branch,
+            &resolved_base,
+            file,
+            &conflicts,
+            verbose,
+            resolve_model,
+            role_prompt,
+        ) {
+            fail(e);
+        }
+    }
+
+    let validate_commands = project_config
+        .as_ref()
+        .and_then(|p| p.validate_commands.clone())
+        .unwrap_or_default();
+    let mut round = 0;
+    loop {
+        let failures = run_validate_commands(&project, &branch, &validate_commands, verbose);
+        if failures.is_empty() {
+            if !validate_commands.is_empty() {
+                println!("  {} validate_commands pass", BULLET_GREEN);
+            }
+            break;
+        }
+        round += 1;
+        for failure in &failures {
+            println!("  {} {}", BULLET_RED, failure.title);
+        }
+        if round > MAX_SYNC_BASE_ROUNDS {
+            fail(format!(
+                "validate_commands still fail after {} fix rounds",
+                MAX_SYNC_BASE_ROUNDS
+            ));
+        }
+        let prompt = build_spawn_resolve_validate_prompt(&git_branch, &resolved_base, &failures);
+        if let Err(e) = docker::run_prompt_in_spawned_container_with_options(
+            &project,
+            &branch,
+            &prompt,
+            verbose,
+            resolve_model,
+            role_prompt,
+        ) {
+            fail(e);
+        }
+    }
+
+    let run_id = format!("resolve-{}-{}", now_unix(), generate_short_plan_id(0));
+    let trailers = resolve_commit_trailers(
+        project_config.as_ref(),
+        &[],
+        false,
+        Some(&model_profile.name),
+        resolve_model,
+        &run_id,
+    )
+    .unwrap_or_else(|e| fail(e));
+    let message = commit_lint::append_trailers(
+        &format!("Merge {} into {}", resolved_base, git_branch),
+        &trailers,
+    );
+    let script = format!(
+        "cd /workspace && git add -A && git commit -m '{}' 2>&1 && {}",
+        message.replace('\'', "'\"'\"'"),
+        build_spawn_push_step(&git_branch, None)
+    );
+    let output = docker::run_spawn_shell(&project, &branch, &script).unwrap_or_else(|e| fail(e));
+    let commit_hash = parse_commit_hash(&output);
+    events::emit(&SmithEvent::PushPerformed {
+        project: project.clone(),
+        branch: branch.clone(),
+        commit: commit_hash.clone(),
+    });
+    println!(
+        "  {} Resolved {} conflict(s) with origin/{}",
+        BULLET_GREEN,
+        conflicts.len(),
+        resolved_base
+    );
+    println!("  Commit: {}", commit_hash);
+}
+
+/// Have the agent resolve one conflicted file, then stage it; fails when conflict markers
+/// remain after `MAX_SYNC_BASE_ROUNDS` prompts.
+#[allow(clippy::too_many_arguments)]
+fn resolve_file(
+    project: &str,
+    branch: &str,
+    git_branch: &str,
+    base: &str,
+    file: &str,
+    conflicts: &[String],
+    verbose: bool,
+    model: Option<&str>,
+    role_prompt: Option<&str>,
+) -> Result<(), String> {
+    let escaped = file.replace('\'', "'\"'\"'");
+    let stage = format!(
+        "cd /workspace && if test -e '{path}' && grep -qE '^(<<<<<<<|>>>>>>>)( |$)' -- '{path}'; then echo 'SMITH_BASE_CONFLICT'; exit 4; fi && git add -A -- ':(literal){path}'",
+        path = escaped
+    );
+    for _ in 0..MAX_SYNC_BASE_ROUNDS {
+        let prompt = build_spawn_resolve_file_prompt(git_branch, base, file, conflicts);
+        docker::run_prompt_in_spawned_container_with_options(
+            project,
+            branch,
+            &prompt,
+            verbose,
+            model,
+            role_prompt,
+        )?;
+        match docker::run_spawn_shell(project, branch, &stage) {
+            Ok(_) => return Ok(()),
+            Err(e) if e.contains("SMITH_BASE_CONFLICT") => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Err(format!(
+        "{} still has conflict markers after {} rounds",
+        file, MAX_SYNC_BASE_ROUNDS
+    ))
+}
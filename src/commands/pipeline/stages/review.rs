@@ -1,4 +1,6 @@
 use crate::*;
+use commands::pipeline::stages::develop::stage_model;
+use commands::pipeline::{export, issues, share};
 
 pub async fn handle(cmd: RunCommands) {
     match cmd {
@@ -9,10 +11,39 @@ pub async fn handle(cmd: RunCommands) {
             state,
             plan,
             reply,
+            fix,
+            severity,
+            local,
+            staged,
+            model,
+            execute_model,
+            validate_model,
+            share,
+            redact,
+            create_issues,
+            export_on_failure,
         } => {
+            export::set_failure_export(export_on_failure);
+            let verbose = logging::verbose();
+            if let Some(path) = local {
+                let model_override = validate_model.or(model);
+                let share = share.then_some(redact);
+                run_local_review(
+                    project,
+                    branch,
+                    &path,
+                    staged,
+                    model_override,
+                    share,
+                    create_issues.then_some(severity.as_slice()),
+                    verbose,
+                )
+                .await;
+                return;
+            }
             let project = match project {
                 Some(p) => p,
-                None => match detect_project_from_cwd() {
+                None => match detect_or_pick_project() {
                     Ok(Some(name)) => name,
                     _ => {
                         eprintln!("Error: --project required");
@@ -38,10 +69,12 @@ pub async fn handle(cmd: RunCommands) {
                 }
             };
 
-            ensure_spawned_container_for_pipeline(&project, &branch, false).unwrap_or_else(|e| {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            });
+            ensure_spawned_container_for_pipeline(&project, &branch, false, false)
+                .await
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
 
             if let Err(e) = docker::ensure_spawn_state_dir(&project, &branch) {
                 eprintln!("Error: {}", e);
@@ -65,6 +98,10 @@ pub async fn handle(cmd: RunCommands) {
                 eprintln!("Error: --reply requires --plan <id>");
                 std::process::exit(1);
             }
+            if fix && plan.is_none() {
+                eprintln!("Error: --fix requires --plan <id>");
+                std::process::exit(1);
+            }
 
             let resolved_plan = if let Some(plan_filter) = plan.as_ref() {
                 match resolve_plan_id_filter(plan_filter, &plan_dirs) {
@@ -205,6 +242,7 @@ pub async fn handle(cmd: RunCommands) {
 
             let desired_state = state.as_ref().map(|s| s.to_lowercase());
             let mut shown = 0usize;
+            let mut shared: Vec<String> = Vec::new();
             for (dir_name, manifest, _) in manifests {
                 if let Some(ref desired) = desired_state {
                     if manifest.state.to_lowercase() != *desired {
@@ -245,7 +283,9 @@ pub async fn handle(cmd: RunCommands) {
                     .ok()
                     .and_then(|raw| extract_assurance_preview(&raw));
 
-                print_plan_block(&dir_name, &manifest, assurance_preview.as_ref());
+                let block = format_plan_block(&dir_name, &manifest, assurance_preview.as_ref());
+                print!("{}", block);
+                shared.push(block);
 
                 shown += 1;
             }
@@ -257,7 +297,439 @@ pub async fn handle(cmd: RunCommands) {
                     println!("No plans found");
                 }
             }
+
+            if share && !shared.is_empty() {
+                let project_config = resolve_project_config(Some(project.clone())).ok().flatten();
+                match share::share_output(
+                    project_config.as_ref(),
+                    &format!("smith review of {}:{}", project, branch),
+                    &format!("smith-review-{}-{}.txt", project, branch.replace('/', "-")),
+                    &shared
+                        .join("\n------------------------------------------------------------\n\n"),
+                    redact,
+                )
+                .await
+                {
+                    Ok(url) => println!("\n  {} Shared: {}", BULLET_GREEN, url),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            if create_issues {
+                let Some(selected_plan) = resolved_plan.as_ref() else {
+                    eprintln!("Error: --create-issues requires --plan <id> or --local <path>");
+                    std::process::exit(1);
+                };
+                let (dev_run_id, _, _, findings) =
+                    latest_run_findings(&project, &branch, selected_plan, &severity)
+                        .unwrap_or_else(|e| {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        });
+                let project_config = resolve_project_config(Some(project.clone())).ok().flatten();
+                if let Err(e) = issues::create_finding_issues(
+                    project_config.as_ref(),
+                    &branch,
+                    &format!("develop run {}", dev_run_id),
+                    &findings,
+                )
+                .await
+                {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+
+            if fix {
+                let selected_plan = resolved_plan.clone().expect("resolved plan must exist");
+                run_review_fix(
+                    &project,
+                    &branch,
+                    &selected_plan,
+                    &severity,
+                    execute_model.or(model).as_deref(),
+                    verbose,
+                );
+            }
         }
         _ => unreachable!("non-review command routed to review handler"),
     }
 }
+
+/// Diff of a working tree's uncommitted changes against HEAD: staged and unstaged, and
+/// untracked files as additions; only the index with `staged`.
+pub(crate) fn local_changes_diff(root: &Path, staged: bool) -> Result<String, String> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(root)
+            .args(args)
+            .logged()
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))
+    };
+    let out = git(&[
+        "diff",
+        "--no-color",
+        if staged { "--cached" } else { "HEAD" },
+    ])?;
+    if !out.status.success() {
+        return Err(format!(
+            "git diff failed in {}: {}",
+            root.display(),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    let mut diff = String::from_utf8_lossy(&out.stdout).into_owned();
+    if staged {
+        return Ok(diff);
+    }
+    let untracked = git(&["ls-files", "--others", "--exclude-standard", "-z"])?;
+    for file in String::from_utf8_lossy(&untracked.stdout)
+        .split('\0')
+        .filter(|f| !f.is_empty())
+    {
+        // Exits 1 when the files differ, which they always do here.
+        let out = git(&["diff", "--no-color", "--no-index", "--", "/dev/null", file])?;
+        diff.push_str(&String::from_utf8_lossy(&out.stdout));
+    }
+    Ok(diff)
+}
+
+/// `run review --local`: send the working tree's uncommitted diff to the project's agent for a
+/// review, without pushing anything. Exits non-zero when the verdict is fail.
+#[allow(clippy::too_many_arguments)]
+async fn run_local_review(
+    project: Option<String>,
+    branch: Option<String>,
+    path: &Path,
+    staged: bool,
+    model_override: Option<String>,
+    share: Option<bool>,
+    create_issues: Option<&[String]>,
+    verbose: bool,
+) {
+    let fail = |e: String| -> ! {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    };
+    let root = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| PathBuf::from(String::from_utf8_lossy(&o.stdout).trim()))
+        .unwrap_or_else(|| fail(format!("{} is not a git working tree", path.display())));
+    let diff = local_changes_diff(&root, staged).unwrap_or_else(|e| fail(e));
+    if diff.trim().is_empty() {
+        println!(
+            "  {} No {}changes to review in {}",
+            BULLET_BLUE,
+            if staged { "staged " } else { "" },
+            root.display()
+        );
+        return;
+    }
+    let git_out = |args: &[&str]| {
+        Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    let base = git_out(&["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| "HEAD".to_string());
+    // Project detection and the branch default look at the reviewed tree, not the cwd.
+    let project = match project {
+        Some(p) => p,
+        None => {
+            let _ = std::env::set_current_dir(&root);
+            detect_or_pick_project()
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| fail("--project required".to_string()))
+        }
+    };
+    let branch = branch
+        .or_else(|| git_out(&["rev-parse", "--abbrev-ref", "HEAD"]))
+        .unwrap_or_else(|| fail("--branch required".to_string()));
+
+    let project_config = resolve_project_config(Some(project.clone())).unwrap_or_else(|e| fail(e));
+    let pipeline_roles = resolve_pipeline_roles(project_config.as_ref(), "dev");
+    let model_profile =
+        resolve_project_model_profile(project_config.as_ref()).unwrap_or_else(|e| fail(e));
+    let review_model = stage_model(
+        model_override.as_deref(),
+        pipeline_roles.validate_run.as_ref(),
+        model_profile.model.as_deref(),
+    );
+
+    ensure_spawned_container_for_pipeline(&project, &branch, true, false)
+        .await
+        .unwrap_or_else(|e| fail(e));
+    docker::ensure_spawn_state_dir(&project, &branch).unwrap_or_else(|e| fail(e));
+
+    let run_dir = format!("/state/local-review-{}", now_unix());
+    let patch_path = format!("{}/changes.patch", run_dir);
+    let report_path = format!("{}/review.json", run_dir);
+    docker::write_spawn_file(&project, &branch, &patch_path, &diff).unwrap_or_else(|e| fail(e));
+    println!(
+        "  {} Reviewing {} changed line(s) of {} (base {}) with {}:{}",
+        BULLET_BLUE,
+        diff.lines()
+            .filter(|l| (l.starts_with('+') && !l.starts_with("+++"))
+                || (l.starts_with('-') && !l.starts_with("---")))
+            .count(),
+        root.display(),
+        base,
+        project,
+        branch
+    );
+    let prompt = build_spawn_local_review_prompt(&patch_path, &base, &report_path);
+    apply_pre_prompt_hook(project_config.as_ref(), "validate", &branch, prompt)
+        .and_then(|prompt| {
+            docker::run_prompt_in_spawned_container_with_options(
+                &project,
+                &branch,
+                &prompt,
+                verbose,
+                review_model,
+                pipeline_roles
+                    .validate_run
+                    .as_ref()
+                    .and_then(|r| r.prompt.as_deref()),
+            )
+        })
+        .unwrap_or_else(|e| fail(e));
+    let report = docker::read_spawn_file(&project, &branch, &report_path)
+        .and_then(|raw| parse_dev_assurance_report(&raw))
+        .unwrap_or_else(|e| fail(format!("review report {}: {}", report_path, e)));
+
+    let bullet = match report.verdict.as_str() {
+        "pass" => BULLET_GREEN,
+        "pass_with_risk" => BULLET_YELLOW,
+        _ => BULLET_RED,
+    };
+    println!("  {} Verdict: {}", bullet, report.verdict);
+    let findings = local_review_findings(&report);
+    print!("{}", findings);
+    println!("  Report: {} in {}:{}", report_path, project, branch);
+    if let Some(redact) = share {
+        let text = format!(
+            "Review of the uncommitted changes of {} (base {})\nVerdict: {}\n{}",
+            root.display(),
+            base,
+            report.verdict,
+            findings
+        );
+        match share::share_output(
+            project_config.as_ref(),
+            &format!("smith review of local changes ({})", project),
+            &format!("smith-review-{}-local.txt", project),
+            &text,
+            redact,
+        )
+        .await
+        {
+            Ok(url) => println!("  {} Shared: {}", BULLET_GREEN, url),
+            Err(e) => fail(e),
+        }
+    }
+    if let Some(severities) = create_issues {
+        let mut all_issues = report.blocking_issues.clone();
+        all_issues.extend(report.non_blocking_issues.clone());
+        let findings = filter_issues_by_severity(&all_issues, severities);
+        issues::create_finding_issues(project_config.as_ref(), &branch, "local changes", &findings)
+            .await
+            .unwrap_or_else(|e| fail(e));
+    }
+    if report.verdict == "fail" || !report.blocking_issues.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Findings of the given severities in the latest completed develop run of a plan, with the
+/// run id, its manifest and the assurance artifact they were read from.
+fn latest_run_findings(
+    project: &str,
+    branch: &str,
+    selected_plan: &str,
+    severities: &[String],
+) -> Result<(String, DevRunManifest, String, Vec<DevAssuranceIssue>), String> {
+    let (dev_run_id, dev_manifest) =
+        find_latest_completed_dev_run_for_plan(project, branch, selected_plan)?;
+    let Some(latest_attempt) = dev_manifest.attempts.last() else {
+        return Err(format!(
+            "develop run '{}' has no recorded attempts",
+            dev_manifest.dev_run_id
+        ));
+    };
+    if latest_attempt.assurance_artifact.is_empty() {
+        return Err(format!(
+            "develop run '{}' skipped validation (preset fast); it has no findings",
+            dev_manifest.dev_run_id
+        ));
+    }
+    let assurance_path = latest_attempt.assurance_artifact.clone();
+    let assurance_raw = docker::read_spawn_file(project, branch, &assurance_path).map_err(|e| {
+        format!(
+            "failed reading assurance artifact '{}': {}",
+            assurance_path, e
+        )
+    })?;
+    let report = parse_dev_assurance_report(&assurance_raw)
+        .map_err(|e| format!("assurance artifact is invalid: {}", e))?;
+    let mut all_issues = report.blocking_issues.clone();
+    all_issues.extend(report.non_blocking_issues.clone());
+    let findings = filter_issues_by_severity(&all_issues, severities);
+    Ok((dev_run_id, dev_manifest, assurance_path, findings))
+}
+
+fn run_review_fix(
+    project: &str,
+    branch: &str,
+    selected_plan: &str,
+    severities: &[String],
+    model_override: Option<&str>,
+    verbose: bool,
+) {
+    let project_config = resolve_project_config(Some(project.to_string())).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let (commit_name, commit_email) = resolve_commit_author(project_config.as_ref());
+    let pipeline_roles = resolve_pipeline_roles(project_config.as_ref(), "dev");
+    let model_profile =
+        resolve_project_model_profile(project_config.as_ref()).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+    let default_model = model_profile.model.as_deref();
+
+    let (dev_run_id, dev_manifest, assurance_path, findings) =
+        latest_run_findings(project, branch, selected_plan, severities).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+    if findings.is_empty() {
+        println!(
+            "\n  {} No findings matching severity '{}' in {}; nothing to fix",
+            BULLET_BLUE,
+            severities.join(","),
+            dev_run_id
+        );
+        return;
+    }
+
+    println!(
+        "\n  {} Fixing {} finding(s) from {}",
+        BULLET_BLUE,
+        findings.len(),
+        dev_run_id
+    );
+
+    let fix_model = stage_model(
+        model_override,
+        pipeline_roles.execute_run.as_ref(),
+        default_model,
+    );
+    let plan_dir = format!("/state/{}", selected_plan);
+    let fix_artifact_path = format!("/state/{}/review-fix-{}.json", dev_run_id, now_unix());
+    let fix_prompt =
+        build_spawn_review_fix_prompt(&findings, &plan_dir, &assurance_path, &fix_artifact_path);
+    if let Err(e) = apply_pre_prompt_hook(project_config.as_ref(), "review-fix", branch, fix_prompt)
+        .and_then(|fix_prompt| {
+            docker::run_prompt_in_spawned_container_with_options(
+                project,
+                branch,
+                &fix_prompt,
+                verbose,
+                fix_model,
+                pipeline_roles
+                    .execute_run
+                    .as_ref()
+                    .and_then(|r| r.prompt.as_deref()),
+            )
+        })
+    {
+        eprintln!("Error: {}", e);
+        export::export_on_failure(project, branch, &dev_run_id);
+        std::process::exit(1);
+    }
+
+    let ids = findings
+        .iter()
+        .map(|f| f.id.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let commit_trailers = resolve_commit_trailers(
+        project_config.as_ref(),
+        &[],
+        false,
+        Some(&model_profile.name),
+        fix_model,
+        &dev_run_id,
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let commit_msg = commit_lint::append_trailers(
+        &format!(
+            "Address review findings {} [plan:{}]",
+            ids, dev_manifest.short_plan_id
+        ),
+        &commit_trailers,
+    );
+    let commit_script = build_spawn_commit_script(
+        &commit_msg,
+        &resolve_git_branch(branch, project_config.as_ref()),
+        commit_name.as_deref(),
+        commit_email.as_deref(),
+    );
+    let commit_output = match docker::run_spawn_shell(project, branch, &commit_script) {
+        Ok(v) => v,
+        Err(e) => {
+            if e.contains("SMITH_NO_CHANGES") {
+                println!("\n⚠ No changes were made by the fix pass");
+                std::process::exit(1);
+            }
+            eprintln!("Error: {}", e);
+            export::export_on_failure(project, branch, &dev_run_id);
+            std::process::exit(1);
+        }
+    };
+
+    let commit_hash = parse_commit_hash(&commit_output);
+    println!("  {} Review fix completed", BULLET_GREEN);
+    if !commit_hash.is_empty() {
+        println!("  Commit: {}", commit_hash);
+    }
+    println!("  Fix Artifact: {}", fix_artifact_path);
+}
+
+/// Summary and findings of a `--local` review, as printed below the verdict.
+fn local_review_findings(report: &DevAssuranceReport) -> String {
+    let mut out = String::new();
+    for line in &report.summary {
+        out.push_str(&format!("     {}\n", line));
+    }
+    for issue in report
+        .blocking_issues
+        .iter()
+        .chain(report.non_blocking_issues.iter())
+    {
+        out.push_str(&format!(
+            "  - [{}] {} {}: {}\n",
+            issue.severity, issue.id, issue.title, issue.detail
+        ));
+    }
+    out
+}
@@ -0,0 +1,167 @@
+use crate::*;
+
+/// Time, model and tokens of one pipeline phase ("develop-1-2", "validate-1", "commit").
+#[derive(Serialize, Deserialize, Clone)]
+struct StageRecord {
+    phase: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    duration_ms: u64,
+    #[serde(default)]
+    tokens: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    cost: f64,
+}
+
+fn is_zero(value: &f64) -> bool {
+    *value == 0.0
+}
+
+/// Per-phase timings of a run, stored in its manifest as `stages` so pipeline speed can be
+/// compared across runs. The running phase is only kept in memory until it finishes.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(transparent)]
+pub(crate) struct RunStages {
+    records: Vec<StageRecord>,
+    #[serde(skip)]
+    current: Option<(String, Option<String>, Instant)>,
+}
+
+impl RunStages {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Finish the running phase and start timing `phase`.
+    pub(crate) fn begin(&mut self, phase: &str, model: Option<&str>) {
+        self.finish();
+        if self.records.is_empty() {
+            // Tokens spent before the first timed phase belong to another run.
+            docker::take_prompt_usage();
+        }
+        self.current = Some((phase.to_string(), model.map(str::to_string), Instant::now()));
+    }
+
+    /// Record the running phase, if any, with the tokens its prompts used.
+    pub(crate) fn finish(&mut self) {
+        if let Some((phase, model, started)) = self.current.take() {
+            let usage = docker::take_prompt_usage();
+            self.records.push(StageRecord {
+                phase,
+                model,
+                duration_ms: started.elapsed().as_millis() as u64,
+                tokens: usage.tokens,
+                cost: usage.cost,
+            });
+        }
+    }
+
+    /// Tokens and cost over all recorded phases.
+    pub(crate) fn usage(&self) -> docker::PromptUsage {
+        docker::PromptUsage {
+            tokens: self.records.iter().map(|r| r.tokens).sum(),
+            cost: self.records.iter().map(|r| r.cost).sum(),
+        }
+    }
+
+    /// Distinct models the run's prompts used, in first-use order.
+    pub(crate) fn models(&self) -> Vec<String> {
+        let mut models: Vec<String> = Vec::new();
+        for model in self.records.iter().filter_map(|r| r.model.as_ref()) {
+            if !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+        models
+    }
+
+    /// Print per-stage durations, retries (extra passes of a stage), models and tokens.
+    pub(crate) fn print_summary(&self, bytes_pushed: Option<u64>) {
+        if self.records.is_empty() {
+            return;
+        }
+        let mut stages: Vec<(String, u32, u64, Vec<String>, u64)> = Vec::new();
+        for record in &self.records {
+            let name = stage_name(&record.phase);
+            let index = match stages.iter().position(|s| s.0 == name) {
+                Some(i) => i,
+                None => {
+                    stages.push((name.to_string(), 0, 0, Vec::new(), 0));
+                    stages.len() - 1
+                }
+            };
+            let stage = &mut stages[index];
+            stage.1 += 1;
+            stage.2 += record.duration_ms;
+            if let Some(model) = &record.model {
+                if !stage.3.contains(model) {
+                    stage.3.push(model.clone());
+                }
+            }
+            stage.4 += record.tokens;
+        }
+        let mut table = Table::new(&["stage", "runs", "retries", "duration", "model", "tokens"]);
+        for (name, runs, duration_ms, models, tokens) in &stages {
+            table.row(vec![
+                name.clone(),
+                runs.to_string(),
+                runs.saturating_sub(1).to_string(),
+                format_duration_ms(*duration_ms),
+                models.join(", "),
+                if *tokens > 0 {
+                    tokens.to_string()
+                } else {
+                    String::new()
+                },
+            ]);
+        }
+        let Ok(rendered) = table.render(&TableArgs::default()) else {
+            return;
+        };
+        println!();
+        println!("  Run summary");
+        for line in rendered.lines() {
+            println!("  {}", line);
+        }
+        let total_ms: u64 = self.records.iter().map(|r| r.duration_ms).sum();
+        let usage = self.usage();
+        let mut totals = format!("  Total: {}", format_duration_ms(total_ms));
+        if usage.tokens > 0 {
+            totals.push_str(&format!(", {} tokens", usage.tokens));
+        }
+        if usage.cost > 0.0 {
+            totals.push_str(&format!(", ${:.2}", usage.cost));
+        }
+        if let Some(bytes) = bytes_pushed {
+            totals.push_str(&format!(", {} pushed", commands::cache::format_size(bytes)));
+        }
+        println!("{}", totals);
+    }
+}
+
+/// Stage a phase belongs to: the phase without its pass numbers ("self-check-2-1" -> "self-check").
+pub(crate) fn stage_name(phase: &str) -> &str {
+    let mut name = phase;
+    while let Some((head, tail)) = name.rsplit_once('-') {
+        if tail.is_empty() || !tail.chars().all(|c| c.is_ascii_digit()) {
+            break;
+        }
+        name = head;
+    }
+    name
+}
+
+pub(crate) fn format_duration_ms(ms: u64) -> String {
+    match ms {
+        0..=59_999 => format!("{:.1}s", ms as f64 / 1000.0),
+        _ => format!("{}m{:02}s", ms / 60_000, (ms % 60_000) / 1000),
+    }
+}
+
+/// Size of the pack `git push` sends, printed by the commit script as `SMITH_PUSH_BYTES=<n>`.
+pub(crate) fn parse_push_bytes(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("SMITH_PUSH_BYTES="))
+        .and_then(|n| n.trim().parse().ok())
+}
@@ -0,0 +1,142 @@
+use crate::*;
+
+/// Executables named `smith-<name>` on PATH become `smith <name>` subcommands.
+const PLUGIN_PREFIX: &str = "smith-";
+
+/// Run an external plugin: `smith <name> [args...]` -> `smith-<name> [args...]`.
+/// The plugin receives a JSON context on stdin (smith version, config paths, selected project)
+/// and smith exits with the plugin's exit code.
+pub fn handle(args: Vec<String>) {
+    let Some((name, rest)) = args.split_first() else {
+        print_smith_help();
+        std::process::exit(0);
+    };
+    let Some(path) = find_plugin(name) else {
+        eprintln!(
+            "Error: unrecognized command '{}' (no '{}{}' plugin found on PATH)",
+            name, PLUGIN_PREFIX, name
+        );
+        eprintln!("Run `smith help` to see available commands.");
+        std::process::exit(1);
+    };
+
+    let context = plugin_context(rest).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let mut child = Command::new(&path)
+        .args(rest)
+        .env("SMITH_PLUGIN", "1")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| {
+            eprintln!("Error: Failed to run plugin {}: {}", path.display(), e);
+            std::process::exit(1);
+        });
+    if let Some(mut stdin) = child.stdin.take() {
+        // Plugins that ignore stdin may exit before reading it; a broken pipe is not an error.
+        let _ = stdin.write_all(context.to_string().as_bytes());
+    }
+    let status = child.wait().unwrap_or_else(|e| {
+        eprintln!("Error: Failed to wait for plugin {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// JSON handed to plugins on stdin.
+fn plugin_context(args: &[String]) -> Result<Value, String> {
+    let cfg = load_config()?;
+    let project_name = plugin_project_arg(args).or_else(|| {
+        std::env::var("SMITH_PROJECT")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+    });
+    let project = match project_name {
+        Some(name) => {
+            let proj = cfg
+                .projects
+                .iter()
+                .find(|p| p.name == name)
+                .ok_or_else(|| format!("Project '{}' not found", name))?;
+            serde_json::json!({
+                "name": proj.name,
+                "repo": proj.repo,
+                "base_branch": proj.base_branch,
+                "model": proj.model,
+            })
+        }
+        None => Value::Null,
+    };
+    Ok(serde_json::json!({
+        "smith_version": env!("CARGO_PKG_VERSION"),
+        "config_dir": config_dir()?,
+        "config_file": config_file_path()?,
+        "project": project,
+        "projects": cfg.projects.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+        "args": args,
+    }))
+}
+
+/// Value of `--project <name>` / `--project=<name>` in plugin args, if present.
+pub(crate) fn plugin_project_arg(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--project" {
+            return iter.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix("--project=") {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Resolve `smith-<name>` on PATH.
+fn find_plugin(name: &str) -> Option<PathBuf> {
+    discover_plugins()
+        .into_iter()
+        .find(|(plugin, _)| plugin == name)
+        .map(|(_, path)| path)
+}
+
+/// All `smith-*` executables on PATH as (name, path), sorted; earlier PATH entries win.
+pub fn discover_plugins() -> Vec<(String, PathBuf)> {
+    let Some(paths) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut found: Vec<(String, PathBuf)> = Vec::new();
+    for dir in std::env::split_paths(&paths) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(name) = file_name.strip_prefix(PLUGIN_PREFIX) else {
+                continue;
+            };
+            if name.is_empty() || found.iter().any(|(n, _)| n == name) {
+                continue;
+            }
+            let path = entry.path();
+            if is_executable(&path) {
+                found.push((name.to_string(), path));
+            }
+        }
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
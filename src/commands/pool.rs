@@ -0,0 +1,210 @@
+use crate::*;
+
+pub async fn handle(cmd: PoolCommands) {
+    match cmd {
+        PoolCommands::Start { project, size } => {
+            ensure_not_sandboxed("pool start", project.as_deref()).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            if let Err(e) = docker::check_docker_available() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            let targets = set_pool_sizes(project.as_deref(), size).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let mut failed = false;
+            for proj in &targets {
+                if let Err(e) = fill_pool(proj).await {
+                    eprintln!("  {} {}: {}", BULLET_RED, proj.name, e);
+                    failed = true;
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        PoolCommands::Stop { project } => {
+            ensure_not_sandboxed("pool stop", project.as_deref()).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let warm = docker::list_pool_containers().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let mut removed = 0;
+            for c in warm
+                .iter()
+                .filter(|c| project.as_ref().is_none_or(|p| c.project == *p))
+            {
+                match docker::remove_pool_container(&c.container_name) {
+                    Ok(()) => removed += 1,
+                    Err(e) => eprintln!("  Warning: {}: {}", c.container_name, e),
+                }
+            }
+            // Without a size, claims are no longer replaced.
+            if let Ok(mut cfg) = load_config() {
+                let mut changed = false;
+                for proj in cfg
+                    .projects
+                    .iter_mut()
+                    .filter(|p| project.as_ref().is_none_or(|name| p.name == *name))
+                {
+                    changed |= proj.pool_size.take().is_some();
+                }
+                if changed {
+                    if let Err(e) = save_config(&cfg) {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            println!("  {} Removed {} warm pool agent(s)", BULLET_GREEN, removed);
+        }
+        PoolCommands::Status { project, table } => {
+            let cfg = load_config().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let warm = docker::list_pool_containers().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let selected = |name: &str| project.as_ref().is_none_or(|p| p == name);
+            for proj in cfg.projects.iter().filter(|p| selected(&p.name)) {
+                let ready = warm
+                    .iter()
+                    .filter(|c| c.project == proj.name && c.status.starts_with("Up"))
+                    .count();
+                match proj.pool_size {
+                    Some(size) => println!(
+                        "  {} {} - {}/{} warm",
+                        if ready as u32 >= size {
+                            BULLET_GREEN
+                        } else {
+                            BULLET_YELLOW
+                        },
+                        proj.name,
+                        ready,
+                        size
+                    ),
+                    None if ready > 0 => {
+                        println!(
+                            "  {} {} - {} warm (no pool size)",
+                            BULLET_BLUE, proj.name, ready
+                        )
+                    }
+                    None => {}
+                }
+            }
+            let mut rows = Table::new(&["project", "container", "port", "status"]);
+            for c in warm.iter().filter(|c| selected(&c.project)) {
+                rows.row(vec![
+                    c.project.clone(),
+                    c.container_name.clone(),
+                    c.port.to_string(),
+                    c.status.clone(),
+                ]);
+            }
+            if warm.iter().any(|c| selected(&c.project)) {
+                println!();
+                print!(
+                    "{}",
+                    rows.render(&table).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    })
+                );
+            } else {
+                println!("  {} No warm pool agents", BULLET_BLUE);
+            }
+        }
+    }
+}
+
+/// Projects whose pool `pool start` fills: the one named (its `pool_size` set to `size`, or 1
+/// when it has none), else every project with a `pool_size`.
+fn set_pool_sizes(project: Option<&str>, size: Option<u32>) -> Result<Vec<ProjectConfig>, String> {
+    let mut cfg = load_config().map_err(|e| e.to_string())?;
+    let Some(name) = project else {
+        if size.is_some() {
+            return Err("--size requires --project".to_string());
+        }
+        let pooled: Vec<ProjectConfig> = cfg
+            .projects
+            .into_iter()
+            .filter(|p| p.pool_size.is_some_and(|s| s > 0))
+            .collect();
+        if pooled.is_empty() {
+            return Err(
+                "No project has a warm pool; run `smith pool start --project <name> [--size <n>]`"
+                    .to_string(),
+            );
+        }
+        return Ok(pooled);
+    };
+    let proj = cfg
+        .projects
+        .iter_mut()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Project '{}' not found", name))?;
+    if proj.backend.as_deref() == Some("kubernetes") {
+        return Err(format!(
+            "Project '{}' runs on Kubernetes; warm pools are Docker only",
+            name
+        ));
+    }
+    let wanted = size.or(proj.pool_size).unwrap_or(1);
+    let updated = proj.pool_size != Some(wanted);
+    proj.pool_size = Some(wanted);
+    let proj = proj.clone();
+    if updated {
+        save_config(&cfg).map_err(|e| e.to_string())?;
+    }
+    Ok(vec![proj])
+}
+
+/// Start warm containers until `pool_size` of the project's are running, on the lowest free
+/// placeholder slots (stopped ones are started again).
+async fn fill_pool(project: &ProjectConfig) -> Result<(), String> {
+    let size = project.pool_size.unwrap_or(1) as usize;
+    let warm: Vec<docker::SpawnInfo> = docker::list_pool_containers()?
+        .into_iter()
+        .filter(|c| c.project == project.name)
+        .collect();
+    let running: Vec<&str> = warm
+        .iter()
+        .filter(|c| c.status.starts_with("Up"))
+        .map(|c| c.branch.as_str())
+        .collect();
+    let missing = size.saturating_sub(running.len());
+    if missing == 0 {
+        println!(
+            "  {} {} - {}/{} warm",
+            BULLET_GREEN,
+            project.name,
+            running.len(),
+            size
+        );
+        return Ok(());
+    }
+    let slots = (1..)
+        .map(|n| format!("{}{}", docker::POOL_BRANCH_PREFIX, n))
+        .filter(|slot| !running.contains(&slot.as_str()))
+        .take(missing);
+    for slot in slots {
+        println!(
+            "  {} {} - starting warm agent {} ...",
+            BULLET_BLUE, project.name, slot
+        );
+        let port = start_project_container(project, &slot, false).await?;
+        println!(
+            "  {} {} - warm agent {} ready (port {})",
+            BULLET_GREEN, project.name, slot, port
+        );
+    }
+    Ok(())
+}
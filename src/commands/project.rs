@@ -1,44 +1,129 @@
 use crate::*;
+use std::collections::BTreeMap;
 
 pub async fn handle(cmd: ProjectCommands) {
     match cmd {
         ProjectCommands::Add {
             name,
             repo,
+            subdir,
             image,
             ssh_key,
             base_branch,
             remote,
             github_token,
+            forge,
+            forge_url,
+            github_api_url,
+            github_app_id,
+            github_app_key,
             script,
             commit_name,
             commit_email,
             model,
+            pre_prompt_hook,
+            pre_push_hook,
+            sandboxed: sandbox,
+            network,
+            backend,
+            dep_cache,
+            persist_workspace,
+            clone_filter,
+            secret_scan,
+            default_timeout,
+            default_verbose,
+            no_detect,
         } => {
             let mut cfg = load_config().unwrap_or_else(|e| {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
             });
+            let subdir = subdir.filter(|s| !s.is_empty());
             let ssh_key = ssh_key.filter(|s| !s.is_empty());
             let base_branch = base_branch.filter(|s| !s.is_empty());
             let remote = remote.filter(|s| !s.is_empty());
             let github_token = github_token.filter(|s| !s.is_empty());
+            let forge = forge.filter(|s| !s.is_empty()).map(|f| {
+                validate_forge_name(&f).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+            });
+            let forge_url = forge_url.filter(|s| !s.is_empty());
+            let github_api_url = github_api_url.filter(|s| !s.is_empty());
+            let github_app_id = github_app_id.filter(|s| !s.is_empty());
+            let github_app_key = github_app_key.filter(|s| !s.is_empty());
             let script = script.filter(|s| !s.is_empty());
             let commit_name = commit_name.filter(|s| !s.is_empty());
             let commit_email = commit_email.filter(|s| !s.is_empty());
             let model = model.filter(|s| !s.is_empty());
-            let project = ProjectConfig {
+            let pre_prompt_hook = pre_prompt_hook.filter(|s| !s.is_empty());
+            let pre_push_hook = pre_push_hook.filter(|s| !s.is_empty());
+            let network = network.filter(|s| !s.is_empty()).map(|n| {
+                validate_network_mode(&n).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+            });
+            let backend = backend.filter(|s| !s.is_empty()).map(|b| {
+                validate_backend(&b).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+            });
+            let secret_scan = secret_scan.filter(|s| !s.is_empty()).map(|s| {
+                validate_secret_scan(&s).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+            });
+            let clone_filter = clone_filter.filter(|s| !s.is_empty()).map(|f| {
+                validate_clone_filter(&f).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                })
+            });
+            let mut project = ProjectConfig {
                 name: name.clone(),
                 repo,
+                subdir,
                 image,
                 ssh_key,
                 base_branch,
                 remote,
                 github_token,
+                forge,
+                forge_url,
+                github_api_url,
+                github_app_id,
+                github_app_key,
                 script,
                 commit_name,
                 commit_email,
                 model,
+                pre_prompt_hook,
+                pre_push_hook,
+                sandbox,
+                network,
+                backend,
+                dep_cache,
+                persist_workspace,
+                clone_filter,
+                policy: None,
+                schedule: None,
+                commit_lint: None,
+                secret_scan,
+                preset: None,
+                co_authors: Vec::new(),
+                provenance: None,
+                commit_strategy: None,
+                sync_base: None,
+                toolchain: None,
+                validate_commands: None,
+                default_timeout: default_timeout.filter(|t| *t > 0),
+                default_verbose,
+                pool_size: None,
+                mcp: None,
                 ask_setup_run: None,
                 ask_setup_check: None,
                 ask_execute_run: None,
@@ -47,6 +132,7 @@ pub async fn handle(cmd: ProjectCommands) {
                 ask_validate_check: None,
                 dev_setup_run: None,
                 dev_setup_check: None,
+                dev_handoff_run: None,
                 dev_execute_run: None,
                 dev_execute_check: None,
                 dev_validate_run: None,
@@ -60,6 +146,19 @@ pub async fn handle(cmd: ProjectCommands) {
                 review_validate_run: None,
                 review_validate_check: None,
             };
+            // Local checkouts are detected now; remote repositories on their first run.
+            let root = Path::new(&project.repo).join(project.scope().unwrap_or_default());
+            let local_files: Option<Vec<String>> = fs::read_dir(root).ok().map(|dir| {
+                dir.filter_map(|e| e.ok())
+                    .map(|e| e.file_name().to_string_lossy().into_owned())
+                    .collect()
+            });
+            let detected = if no_detect {
+                project.toolchain = Some("none".to_string());
+                None
+            } else {
+                local_files.and_then(|files| apply_detected_toolchain(&mut project, &files))
+            };
             if let Err(e) = add_project_to_config(&mut cfg, project) {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
@@ -69,48 +168,98 @@ pub async fn handle(cmd: ProjectCommands) {
                 std::process::exit(1);
             });
             println!("Project added successfully");
+            if let Some(t) = detected {
+                println!(
+                    "  {} Detected {} toolchain; validation: {}",
+                    BULLET_BLUE,
+                    t.name,
+                    t.validate.join(", ")
+                );
+            }
         }
-        ProjectCommands::List => {
-            let cfg = load_config().unwrap_or_else(|e| {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            });
-            if cfg.projects.is_empty() {
+        ProjectCommands::List { json, table } => {
+            let cfg = load_config().unwrap_or_else(|e| exit_with_error(e, json));
+            if json {
+                let projects: Vec<Value> = cfg.projects.iter().map(project_json).collect();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&serde_json::json!({ "projects": projects }))
+                        .unwrap_or_default()
+                );
+            } else if cfg.projects.is_empty() {
                 println!("No projects registered");
             } else {
+                let mut rows = Table::new(&[
+                    "name",
+                    "repo",
+                    "subdir",
+                    "image",
+                    "ssh_key",
+                    "base_branch",
+                    "remote",
+                    "github_token",
+                    "forge",
+                    "forge_url",
+                    "github_api_url",
+                    "github_app",
+                    "model",
+                    "pre_prompt_hook",
+                    "pre_push_hook",
+                    "network",
+                    "backend",
+                    "script",
+                ])
+                .defaults(&[
+                    "name",
+                    "repo",
+                    "base_branch",
+                    "forge",
+                    "model",
+                    "image",
+                ]);
                 for proj in &cfg.projects {
-                    let mut parts = format!("  {} -> {}", proj.name, proj.repo);
-                    if let Some(ref image) = proj.image {
-                        parts.push_str(&format!(" (image: {})", image));
-                    }
-                    if let Some(ref sk) = proj.ssh_key {
-                        parts.push_str(&format!(" (ssh_key: {})", sk));
-                    }
-                    if let Some(ref bb) = proj.base_branch {
-                        parts.push_str(&format!(" (base_branch: {})", bb));
-                    }
-                    if let Some(ref r) = proj.remote {
-                        parts.push_str(&format!(" (remote: {})", r));
-                    }
-                    if proj.github_token.is_some() {
-                        parts.push_str(" (github-token: set)");
-                    }
-                    if let Some(ref model) = proj.model {
-                        parts.push_str(&format!(" (model: {})", model));
-                    }
-                    if let Some(ref script) = proj.script {
-                        let truncated = if script.len() > 40 {
-                            format!("{}...", &script[..40])
+                    let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+                    let script = match proj.script.as_deref() {
+                        Some(script) if script.len() > 40 => format!("{}...", &script[..40]),
+                        Some(script) => script.to_string(),
+                        None => String::new(),
+                    };
+                    rows.row(vec![
+                        proj.name.clone(),
+                        proj.repo.clone(),
+                        opt(&proj.subdir),
+                        opt(&proj.image),
+                        opt(&proj.ssh_key),
+                        opt(&proj.base_branch),
+                        opt(&proj.remote),
+                        if proj.github_token.is_some() {
+                            "set".to_string()
                         } else {
-                            script.clone()
-                        };
-                        parts.push_str(&format!(" (script: {})", truncated));
-                    }
-                    println!("{}", parts);
+                            String::new()
+                        },
+                        opt(&proj.forge),
+                        opt(&proj.forge_url),
+                        opt(&proj.github_api_url),
+                        opt(&proj.github_app_id),
+                        opt(&proj.model),
+                        opt(&proj.pre_prompt_hook),
+                        opt(&proj.pre_push_hook),
+                        opt(&proj.network),
+                        opt(&proj.backend),
+                        script,
+                    ]);
                 }
+                print!(
+                    "{}",
+                    rows.render(&table).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    })
+                );
             }
         }
-        ProjectCommands::Status { project, verbose } => {
+        ProjectCommands::Status { project, fix } => {
+            let verbose = logging::verbose();
             let cfg = load_config().unwrap_or_else(|e| {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
@@ -131,30 +280,75 @@ pub async fn handle(cmd: ProjectCommands) {
                     cfg.projects.iter().collect()
                 }
             };
+            let mut any_failed = false;
             for proj in projects {
                 let resolved_repo = &proj.repo;
-                if resolved_repo.starts_with("https://") {
-                    eprintln!("Error: HTTPS URLs are not supported. Use SSH URLs (git@github.com:user/repo.git).");
-                    std::process::exit(1);
-                }
                 let base = resolve_base_branch(None, Some(proj));
                 let ssh_key_path = resolve_ssh_key(None, Some(proj));
                 if verbose {
                     println!("Project: {} -> {}", proj.name, resolved_repo);
                     println!("  Branch: {}", base);
                 }
+
+                let mut problems: Vec<String> = Vec::new();
+                if resolved_repo.starts_with("https://") {
+                    problems.push(
+                        "HTTPS URLs are not supported. Use SSH URLs (git@github.com:user/repo.git)."
+                            .to_string(),
+                    );
+                    if fix {
+                        if let Some(ssh_url) = https_repo_to_ssh(resolved_repo) {
+                            println!(
+                                "    suggested: smith project update {} --repo {}",
+                                proj.name, ssh_url
+                            );
+                        }
+                    }
+                }
                 if let Some(path) = ssh_key_path.as_ref() {
                     if !path.exists() {
+                        problems.push(format!("ssh key not found at {}", path.display()));
+                        if fix {
+                            println!(
+                                "    suggested: smith project update {} --ssh-key <path> (or --ssh-key \"\" to use the default key)",
+                                proj.name
+                            );
+                        }
+                    }
+                }
+                if let Some(host) = repo_ssh_host(resolved_repo) {
+                    if !ssh_host_is_known(&host) {
+                        if fix {
+                            match ssh_keyscan_host(&host) {
+                                Ok(()) => println!(
+                                    "    {} fixed: added {} to ~/.ssh/known_hosts",
+                                    BULLET_BLUE, host
+                                ),
+                                Err(e) => problems.push(e),
+                            }
+                        } else {
+                            problems.push(format!("host key for {} not in known_hosts", host));
+                        }
+                    }
+                }
+                if let Some(model) = proj.model.as_deref() {
+                    problems.extend(check_model_profile(&cfg, model, fix).await);
+                }
+
+                if problems.is_empty() {
+                    println!("\n  {} {} - ready", BULLET_GREEN, proj.name);
+                } else {
+                    any_failed = true;
+                    for problem in &problems {
+                        eprintln!("  {} {} - failed: {}", BULLET_RED, proj.name, problem);
+                    }
+                    if !fix {
                         eprintln!(
-                            "  {} {} - failed: ssh key not found at {}",
-                            BULLET_RED,
-                            proj.name,
-                            path.display()
+                            "    run `smith project status --project {} --fix` to attempt repairs",
+                            proj.name
                         );
-                        std::process::exit(1);
                     }
                 }
-                println!("\n  {} {} - ready", BULLET_GREEN, proj.name);
                 if verbose {
                     println!("  ---");
                     println!("    repo: {}", resolved_repo);
@@ -166,23 +360,49 @@ pub async fn handle(cmd: ProjectCommands) {
                     }
                 }
             }
+            if any_failed {
+                std::process::exit(1);
+            }
         }
         ProjectCommands::Update {
             name,
             repo,
+            subdir,
             image,
             ssh_key,
             base_branch,
             remote,
             github_token,
+            forge,
+            forge_url,
+            github_api_url,
+            github_app_id,
+            github_app_key,
             script,
             commit_name,
             commit_email,
             model,
+            pre_prompt_hook,
+            pre_push_hook,
+            sandboxed: sandbox,
+            network,
+            backend,
+            dep_cache,
+            persist_workspace,
+            clone_filter,
+            secret_scan,
+            preset,
+            co_author,
+            provenance,
+            commit_strategy,
+            sync_base,
+            default_timeout,
+            default_verbose,
             ask_setup,
             ask_execute,
             ask_validate,
             dev_setup,
+            dev_handoff,
             dev_execute,
             dev_validate,
             dev_commit,
@@ -198,19 +418,42 @@ pub async fn handle(cmd: ProjectCommands) {
             match project {
                 Some(proj) => {
                     let is_wizard = repo.is_none()
+                        && subdir.is_none()
                         && image.is_none()
                         && ssh_key.is_none()
                         && base_branch.is_none()
                         && remote.is_none()
                         && github_token.is_none()
+                        && forge.is_none()
+                        && forge_url.is_none()
+                        && github_api_url.is_none()
+                        && github_app_id.is_none()
+                        && github_app_key.is_none()
                         && script.is_none()
                         && commit_name.is_none()
                         && commit_email.is_none()
                         && model.is_none()
+                        && pre_prompt_hook.is_none()
+                        && pre_push_hook.is_none()
+                        && sandbox.is_none()
+                        && network.is_none()
+                        && backend.is_none()
+                        && dep_cache.is_none()
+                        && persist_workspace.is_none()
+                        && clone_filter.is_none()
+                        && secret_scan.is_none()
+                        && preset.is_none()
+                        && co_author.is_none()
+                        && provenance.is_none()
+                        && commit_strategy.is_none()
+                        && sync_base.is_none()
+                        && default_timeout.is_none()
+                        && default_verbose.is_none()
                         && ask_setup.is_none()
                         && ask_execute.is_none()
                         && ask_validate.is_none()
                         && dev_setup.is_none()
+                        && dev_handoff.is_none()
                         && dev_execute.is_none()
                         && dev_validate.is_none()
                         && dev_commit.is_none()
@@ -342,6 +585,13 @@ pub async fn handle(cmd: ProjectCommands) {
                             proj.dev_setup_run = parts.first().map(|s| s.to_string());
                             proj.dev_setup_check = parts.get(1).map(|s| s.to_string());
                         }
+                        let dev_handoff_in = prompt_line(&format!(
+                            "    dev.handoff [{}]: ",
+                            proj.dev_handoff_run.as_deref().unwrap_or("-")
+                        ));
+                        if !dev_handoff_in.is_empty() {
+                            proj.dev_handoff_run = Some(dev_handoff_in);
+                        }
                         let dev_execute_in = prompt_line(&format!(
                             "    dev.execute [{} {}]: ",
                             proj.dev_execute_run.as_deref().unwrap_or("-"),
@@ -411,6 +661,13 @@ pub async fn handle(cmd: ProjectCommands) {
                         if let Some(new_repo) = repo {
                             proj.repo = new_repo;
                         }
+                        if let Some(new_subdir) = subdir {
+                            proj.subdir = if new_subdir.is_empty() {
+                                None
+                            } else {
+                                Some(new_subdir)
+                            };
+                        }
                         if let Some(new_image) = image {
                             proj.image = Some(new_image);
                         } else if image.is_some() {
@@ -444,6 +701,44 @@ pub async fn handle(cmd: ProjectCommands) {
                                 Some(new_gt)
                             };
                         }
+                        if let Some(new_forge) = forge {
+                            proj.forge = if new_forge.is_empty() {
+                                None
+                            } else {
+                                Some(validate_forge_name(&new_forge).unwrap_or_else(|e| {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }))
+                            };
+                        }
+                        if let Some(new_forge_url) = forge_url {
+                            proj.forge_url = if new_forge_url.is_empty() {
+                                None
+                            } else {
+                                Some(new_forge_url)
+                            };
+                        }
+                        if let Some(new_api_url) = github_api_url {
+                            proj.github_api_url = if new_api_url.is_empty() {
+                                None
+                            } else {
+                                Some(new_api_url)
+                            };
+                        }
+                        if let Some(new_app_id) = github_app_id {
+                            proj.github_app_id = if new_app_id.is_empty() {
+                                None
+                            } else {
+                                Some(new_app_id)
+                            };
+                        }
+                        if let Some(new_app_key) = github_app_key {
+                            proj.github_app_key = if new_app_key.is_empty() {
+                                None
+                            } else {
+                                Some(new_app_key)
+                            };
+                        }
                         if let Some(new_script) = script {
                             proj.script = if new_script.is_empty() {
                                 None
@@ -472,6 +767,127 @@ pub async fn handle(cmd: ProjectCommands) {
                                 Some(new_model)
                             };
                         }
+                        if let Some(new_pre_prompt_hook) = pre_prompt_hook {
+                            proj.pre_prompt_hook = if new_pre_prompt_hook.is_empty() {
+                                None
+                            } else {
+                                Some(new_pre_prompt_hook)
+                            };
+                        }
+                        if let Some(new_pre_push_hook) = pre_push_hook {
+                            proj.pre_push_hook = if new_pre_push_hook.is_empty() {
+                                None
+                            } else {
+                                Some(new_pre_push_hook)
+                            };
+                        }
+                        if let Some(new_sandbox) = sandbox {
+                            proj.sandbox = Some(new_sandbox);
+                        }
+                        if let Some(new_network) = network {
+                            proj.network = if new_network.is_empty() {
+                                None
+                            } else {
+                                Some(validate_network_mode(&new_network).unwrap_or_else(|e| {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }))
+                            };
+                        }
+                        if let Some(new_backend) = backend {
+                            proj.backend = if new_backend.is_empty() {
+                                None
+                            } else {
+                                Some(validate_backend(&new_backend).unwrap_or_else(|e| {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }))
+                            };
+                        }
+                        if let Some(new_dep_cache) = dep_cache {
+                            proj.dep_cache = Some(new_dep_cache);
+                        }
+                        if let Some(new_persist_workspace) = persist_workspace {
+                            proj.persist_workspace = Some(new_persist_workspace);
+                        }
+                        if let Some(filter) = clone_filter {
+                            proj.clone_filter = if filter.is_empty() {
+                                None
+                            } else {
+                                Some(validate_clone_filter(&filter).unwrap_or_else(|e| {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }))
+                            };
+                        }
+                        if let Some(new_secret_scan) = secret_scan {
+                            proj.secret_scan = if new_secret_scan.is_empty() {
+                                None
+                            } else {
+                                Some(validate_secret_scan(&new_secret_scan).unwrap_or_else(|e| {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }))
+                            };
+                        }
+                        if let Some(new_preset) = preset {
+                            proj.preset = if new_preset.is_empty() {
+                                None
+                            } else {
+                                Some(
+                                    preset::find(&new_preset)
+                                        .unwrap_or_else(|e| {
+                                            eprintln!("Error: {}", e);
+                                            std::process::exit(1);
+                                        })
+                                        .name
+                                        .to_string(),
+                                )
+                            };
+                        }
+                        if let Some(authors) = co_author {
+                            let authors: Vec<String> = authors
+                                .into_iter()
+                                .map(|a| a.trim().to_string())
+                                .filter(|a| !a.is_empty())
+                                .collect();
+                            for author in &authors {
+                                if let Err(e) = commit_lint::co_author_trailer(author) {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }
+                            }
+                            proj.co_authors = authors;
+                        }
+                        if let Some(provenance) = provenance {
+                            proj.provenance = Some(provenance);
+                        }
+                        if let Some(mode) = sync_base {
+                            proj.sync_base = if mode.is_empty() {
+                                None
+                            } else {
+                                Some(validate_sync_base(&mode).unwrap_or_else(|e| {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }))
+                            };
+                        }
+                        if let Some(strategy) = commit_strategy {
+                            proj.commit_strategy = if strategy.is_empty() {
+                                None
+                            } else {
+                                Some(validate_commit_strategy(&strategy).unwrap_or_else(|e| {
+                                    eprintln!("Error: {}", e);
+                                    std::process::exit(1);
+                                }))
+                            };
+                        }
+                        if let Some(secs) = default_timeout {
+                            proj.default_timeout = (secs > 0).then_some(secs);
+                        }
+                        if let Some(verbose) = default_verbose {
+                            proj.default_verbose = Some(verbose);
+                        }
                         // Parse role pairs: first is run, second is check (if provided)
                         if let Some(ref roles) = ask_setup {
                             proj.ask_setup_run = roles.first().cloned().filter(|s| !s.is_empty());
@@ -492,6 +908,9 @@ pub async fn handle(cmd: ProjectCommands) {
                             proj.dev_setup_run = roles.first().cloned().filter(|s| !s.is_empty());
                             proj.dev_setup_check = roles.get(1).cloned().filter(|s| !s.is_empty());
                         }
+                        if let Some(mapping) = dev_handoff {
+                            proj.dev_handoff_run = Some(mapping).filter(|s| !s.is_empty());
+                        }
                         if let Some(ref roles) = dev_execute {
                             proj.dev_execute_run = roles.first().cloned().filter(|s| !s.is_empty());
                             proj.dev_execute_check =
@@ -603,5 +1022,711 @@ pub async fn handle(cmd: ProjectCommands) {
             });
             println!("Project removed successfully");
         }
+        ProjectCommands::Rename {
+            name,
+            new_name,
+            stop,
+        } => {
+            let new_name = new_name.trim().to_string();
+            let mut cfg = load_config().unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            rename_project_in_config(&mut cfg, &name, &new_name).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let agents: Vec<docker::SpawnInfo> = docker::list_spawned_containers()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|c| c.project == name)
+                .collect();
+            // Warm pool containers are labelled with the project and could not be claimed
+            // under the new name.
+            let warm: Vec<docker::SpawnInfo> = docker::list_pool_containers()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|c| c.project == name)
+                .collect();
+            if !agents.is_empty() || !warm.is_empty() {
+                let targets: Vec<String> = agents
+                    .iter()
+                    .chain(&warm)
+                    .map(|c| format!("{}:{}", c.project, c.branch))
+                    .collect();
+                if !stop {
+                    eprintln!(
+                        "Error: Project '{}' has spawned agents ({}); their containers are named after the project. Stop them first, or pass --stop",
+                        name,
+                        targets.join(", ")
+                    );
+                    std::process::exit(1);
+                }
+                if let Err(e) = ensure_not_sandboxed("agent stop", Some(&name)) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                for agent in &agents {
+                    docker::stop_spawned_container(&agent.project, &agent.branch).unwrap_or_else(
+                        |e| {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        },
+                    );
+                    println!(
+                        "  {} Stopped {}:{}",
+                        BULLET_GREEN, agent.project, agent.branch
+                    );
+                }
+                for container in &warm {
+                    docker::remove_pool_container(&container.container_name).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    });
+                    println!(
+                        "  {} Removed warm pool container {}",
+                        BULLET_GREEN, container.container_name
+                    );
+                }
+            }
+            save_config(&cfg).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            audit::record("project_rename", &name, Some(new_name.clone()));
+            match history::rename_project(&name, &new_name) {
+                Ok(0) => {}
+                Ok(n) => println!("  {} Moved {} run(s) in the history", BULLET_GREEN, n),
+                Err(e) => eprintln!("  {} Run history not updated: {}", BULLET_YELLOW, e),
+            }
+            match usage::rename_project(&name, &new_name) {
+                Ok(0) => {}
+                Ok(n) => println!("  {} Moved {} usage record(s)", BULLET_GREEN, n),
+                Err(e) => eprintln!("  {} Usage records not updated: {}", BULLET_YELLOW, e),
+            }
+            match docker::rename_pool_claims(&name, &new_name) {
+                Ok(0) => {}
+                Ok(n) => println!("  {} Moved {} pool claim(s)", BULLET_GREEN, n),
+                Err(e) => eprintln!("  {} Pool claims not updated: {}", BULLET_YELLOW, e),
+            }
+            match index::rename_project(&name, &new_name) {
+                Ok(false) => {}
+                Ok(true) => println!("  {} Moved the code index", BULLET_GREEN),
+                Err(e) => eprintln!("  {} Code index not moved: {}", BULLET_YELLOW, e),
+            }
+            if let Ok(dir) = git_mirror_dir() {
+                let mirror = dir.join(&name);
+                if mirror.is_dir() {
+                    let target = dir.join(&new_name);
+                    let moved = fs::remove_dir_all(&target)
+                        .or_else(|e| match e.kind() {
+                            std::io::ErrorKind::NotFound => Ok(()),
+                            _ => Err(e),
+                        })
+                        .and_then(|_| fs::rename(&mirror, &target));
+                    match moved {
+                        Ok(()) => println!("  {} Moved the git mirror", BULLET_GREEN),
+                        Err(e) => eprintln!("  {} Git mirror not moved: {}", BULLET_YELLOW, e),
+                    }
+                }
+            }
+            println!("Project '{}' renamed to '{}'", name, new_name);
+            let volumes: Vec<String> = docker::dep_caches()
+                .into_iter()
+                .chain(docker::workspace_volumes())
+                .flatten()
+                .map(|v| v.name)
+                .filter(|v| {
+                    *v == docker::dep_cache_volume(&name)
+                        || v.contains(&docker::spawn_container_name(&name, ""))
+                })
+                .collect();
+            if !volumes.is_empty() {
+                println!(
+                    "  {} Volumes of the old name are not carried over ({}); `smith cache prune` removes them",
+                    BULLET_BLUE,
+                    volumes.join(", ")
+                );
+            }
+            if cfg
+                .projects
+                .iter()
+                .any(|p| p.name == new_name && p.scope().is_some())
+            {
+                println!(
+                    "  {} New branches are pushed as '{}/<branch>'",
+                    BULLET_BLUE, new_name
+                );
+            }
+        }
+        ProjectCommands::Bake { name, force } => {
+            let project = resolve_project_config(Some(name.clone()))
+                .and_then(|p| p.ok_or_else(|| format!("Project '{}' not found", name)))
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
+            bake_project_image(&project, force).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+        }
+        ProjectCommands::Cleanup {
+            name,
+            prefix,
+            force,
+        } => {
+            let project_config = resolve_project_config(Some(name.clone())).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let forge = resolve_forge(project_config.as_ref()).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let repo = project_config
+                .as_ref()
+                .map(|p| p.repo.clone())
+                .unwrap_or_default();
+            let repo_info = github::extract_repo_info(&repo).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+            let token = match resolve_forge_token(
+                project_config.as_ref(),
+                &forge,
+                &repo_info.owner,
+                &repo_info.name,
+            )
+            .await
+            {
+                Ok(Some(t)) => t,
+                Ok(None) => {
+                    eprintln!(
+                        "Error: project '{}' has no github_token or GitHub App configured",
+                        name
+                    );
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let merged = match &forge {
+                Forge::GitHub { api_url } => {
+                    github::list_merged_pr_branches(
+                        api_url,
+                        &token,
+                        &repo_info.owner,
+                        &repo_info.name,
+                    )
+                    .await
+                }
+                Forge::Gitea { base_url } => {
+                    gitea::list_merged_pr_branches(
+                        base_url,
+                        &token,
+                        &repo_info.owner,
+                        &repo_info.name,
+                    )
+                    .await
+                }
+            }
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+
+            let base = resolve_base_branch(None, project_config.as_ref());
+            let mut candidates: Vec<github::MergedBranch> = Vec::new();
+            for m in merged {
+                let matches = match prefix.as_deref() {
+                    Some(p) => m.branch.starts_with(p),
+                    None => m.smith_created,
+                };
+                if matches && m.branch != base && !candidates.iter().any(|c| c.branch == m.branch) {
+                    candidates.push(m);
+                }
+            }
+            if candidates.is_empty() {
+                println!("No merged smith branches found for '{}'", name);
+                return;
+            }
+
+            println!("Merged branches for '{}':", name);
+            for c in &candidates {
+                println!("  {} (PR #{})", c.branch, c.pr_number);
+            }
+            if !confirm_reset(
+                &format!(
+                    "Delete these {} branch(es) remotely and locally? [y/N]: ",
+                    candidates.len()
+                ),
+                force,
+            ) {
+                println!("Aborted.");
+                return;
+            }
+
+            let in_project_repo =
+                detect_project_from_cwd().ok().flatten().as_deref() == Some(name.as_str());
+            let current_branch = Command::new("git")
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+
+            for c in &candidates {
+                let remote = match &forge {
+                    Forge::GitHub { api_url } => {
+                        github::delete_branch(
+                            api_url,
+                            &token,
+                            &repo_info.owner,
+                            &repo_info.name,
+                            &c.branch,
+                        )
+                        .await
+                    }
+                    Forge::Gitea { base_url } => {
+                        gitea::delete_branch(
+                            base_url,
+                            &token,
+                            &repo_info.owner,
+                            &repo_info.name,
+                            &c.branch,
+                        )
+                        .await
+                    }
+                };
+                match remote {
+                    Ok(true) => println!("  {} Deleted remote branch {}", BULLET_GREEN, c.branch),
+                    Ok(false) => println!(
+                        "  {} Remote branch {} already deleted",
+                        BULLET_BLUE, c.branch
+                    ),
+                    Err(e) => eprintln!("  {} {}", BULLET_YELLOW, e),
+                }
+
+                if in_project_repo && current_branch.as_deref() != Some(c.branch.as_str()) {
+                    let local_exists = Command::new("git")
+                        .args([
+                            "show-ref",
+                            "--verify",
+                            "--quiet",
+                            &format!("refs/heads/{}", c.branch),
+                        ])
+                        .status()
+                        .map(|s| s.success())
+                        .unwrap_or(false);
+                    if local_exists {
+                        let deleted = Command::new("git")
+                            .args(["branch", "-D", &c.branch])
+                            .output()
+                            .map(|o| o.status.success())
+                            .unwrap_or(false);
+                        if deleted {
+                            println!("  {} Deleted local branch {}", BULLET_GREEN, c.branch);
+                        } else {
+                            eprintln!(
+                                "  {} Failed to delete local branch {}",
+                                BULLET_YELLOW, c.branch
+                            );
+                        }
+                    }
+                }
+
+                let container = docker::spawn_container_name(&name, &c.branch);
+                if docker::container_exists(&container).unwrap_or(false) {
+                    match docker::stop_spawned_container(&name, &c.branch) {
+                        Ok(()) => println!(
+                            "  {} Stopped spawned agent {}:{}",
+                            BULLET_GREEN, name, c.branch
+                        ),
+                        Err(e) => eprintln!("  {} {}", BULLET_YELLOW, e),
+                    }
+                }
+            }
+        }
+        ProjectCommands::Mcp { cmd } => {
+            handle_mcp(cmd).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+        }
+    }
+}
+
+fn handle_mcp(cmd: ProjectMcpCommands) -> Result<(), String> {
+    let mut cfg = load_config().map_err(|e| e.to_string())?;
+    match cmd {
+        ProjectMcpCommands::Add {
+            project,
+            name,
+            url,
+            env,
+            headers,
+            disabled,
+            command,
+        } => {
+            let pairs = |values: Vec<String>, what: &str| {
+                values
+                    .into_iter()
+                    .map(|kv| match kv.split_once('=') {
+                        Some((k, v)) if !k.trim().is_empty() => {
+                            Ok((k.trim().to_string(), v.to_string()))
+                        }
+                        _ => Err(format!("Invalid {} '{}': expected KEY=VALUE", what, kv)),
+                    })
+                    .collect::<Result<BTreeMap<String, String>, String>>()
+            };
+            let server = McpServer {
+                command,
+                url,
+                environment: pairs(env, "--env")?,
+                headers: pairs(headers, "--header")?,
+                enabled: disabled.then_some(false),
+            };
+            let problems = smith_core::opencode::validate_mcp(&BTreeMap::from([(
+                name.clone(),
+                server.clone(),
+            )]));
+            if !problems.is_empty() {
+                return Err(problems.join("; "));
+            }
+            let proj = cfg
+                .projects
+                .iter_mut()
+                .find(|p| p.name == project)
+                .ok_or_else(|| format!("Project '{}' not found", project))?;
+            let replaced = proj
+                .mcp
+                .get_or_insert_with(BTreeMap::new)
+                .insert(name.clone(), server)
+                .is_some();
+            save_config(&cfg).map_err(|e| e.to_string())?;
+            println!(
+                "  {} {} MCP server '{}' for '{}'; agents started from now on get it",
+                BULLET_GREEN,
+                if replaced { "Replaced" } else { "Added" },
+                name,
+                project
+            );
+        }
+        ProjectMcpCommands::Remove { project, name } => {
+            let proj = cfg
+                .projects
+                .iter_mut()
+                .find(|p| p.name == project)
+                .ok_or_else(|| format!("Project '{}' not found", project))?;
+            let servers = proj.mcp.get_or_insert_with(BTreeMap::new);
+            if servers.remove(&name).is_none() {
+                return Err(format!(
+                    "Project '{}' has no MCP server '{}'",
+                    project, name
+                ));
+            }
+            if servers.is_empty() {
+                proj.mcp = None;
+            }
+            save_config(&cfg).map_err(|e| e.to_string())?;
+            println!(
+                "  {} Removed MCP server '{}' from '{}'",
+                BULLET_GREEN, name, project
+            );
+        }
+        ProjectMcpCommands::List { project } => {
+            let proj = cfg
+                .projects
+                .iter()
+                .find(|p| p.name == project)
+                .ok_or_else(|| format!("Project '{}' not found", project))?;
+            let profile = proj
+                .model
+                .as_ref()
+                .and_then(|_| resolve_project_model_profile(Some(proj)).ok());
+            let from_profile = profile
+                .as_ref()
+                .and_then(|p| p.opencode.as_ref())
+                .map(|o| o.mcp.clone())
+                .unwrap_or_default();
+            let servers = smith_core::opencode::merged_mcp_servers(
+                profile.as_ref().and_then(|p| p.opencode.as_ref()),
+                proj.mcp.as_ref(),
+            );
+            if servers.is_empty() {
+                println!("  {} No MCP servers for '{}'", BULLET_BLUE, project);
+                return Ok(());
+            }
+            let mut table = Table::new(&["name", "source", "server", "enabled"]);
+            for (name, server) in &servers {
+                let source = if proj.mcp.as_ref().is_some_and(|m| m.contains_key(name)) {
+                    "project".to_string()
+                } else if from_profile.contains_key(name) {
+                    format!("model {}", proj.model.as_deref().unwrap_or_default())
+                } else {
+                    String::new()
+                };
+                table.row(vec![
+                    name.clone(),
+                    source,
+                    server
+                        .url
+                        .clone()
+                        .unwrap_or_else(|| server.command.join(" ")),
+                    server.enabled.unwrap_or(true).to_string(),
+                ]);
+            }
+            print!("{}", table.render(&TableArgs::default())?);
+        }
+    }
+    Ok(())
+}
+
+/// True when `host` already has an entry in ~/.ssh/known_hosts.
+/// Build `smith/project-<name>:<hash>`: the project image plus git/openssh (what the spawn
+/// startup script would otherwise install) and the setup script.
+fn bake_project_image(project: &ProjectConfig, force: bool) -> Result<(), String> {
+    if project.backend.as_deref() == Some("kubernetes") {
+        return Err(format!(
+            "Project '{}' uses the kubernetes backend; baked images are local to this Docker host",
+            project.name
+        ));
+    }
+    let image = project
+        .image
+        .clone()
+        .unwrap_or_else(|| DEFAULT_AGENT_IMAGE.to_string());
+    let script = project.script.as_deref().unwrap_or_default().trim();
+    let tag = docker::baked_image_tag(&project.name, &image, script);
+    if !force && docker::image_exists(&tag).unwrap_or(false) {
+        println!("  {} {} is up to date", BULLET_GREEN, tag);
+        return Ok(());
+    }
+    let deps = "(command -v git && command -v ssh) >/dev/null 2>&1 \
+        || apk add --no-cache git openssh-client \
+        || (apt-get update && apt-get install -y --no-install-recommends git openssh-client ca-certificates && rm -rf /var/lib/apt/lists/*)";
+    let mut dockerfile = format!(
+        "FROM {}\nLABEL smith.project={}\nRUN [\"/bin/sh\", \"-c\", {}]\n",
+        image,
+        serde_json::to_string(&project.name).unwrap_or_default(),
+        serde_json::to_string(deps).unwrap_or_default()
+    );
+    if !script.is_empty() {
+        dockerfile.push_str(&format!(
+            "RUN [\"/bin/sh\", \"-c\", {}]\n",
+            serde_json::to_string(script).unwrap_or_default()
+        ));
+    }
+    println!("  {} Baking {} from {} ...", BULLET_BLUE, tag, image);
+    build_image_from_dockerfile(&tag, &dockerfile, force)?;
+    println!(
+        "  {} Baked {}; new agents for '{}' start from it",
+        BULLET_GREEN, tag, project.name
+    );
+    println!(
+        "     Running agents keep their image until restarted (`smith agent stop` / `start`)."
+    );
+    Ok(())
+}
+
+fn ssh_host_is_known(host: &str) -> bool {
+    Command::new("ssh-keygen")
+        .args(["-F", host])
+        .output()
+        .map(|o| o.status.success() && !o.stdout.is_empty())
+        .unwrap_or(false)
+}
+
+/// Append the host's SSH keys to ~/.ssh/known_hosts via ssh-keyscan.
+fn ssh_keyscan_host(host: &str) -> Result<(), String> {
+    let output = Command::new("ssh-keyscan")
+        .args(["-T", "10", host])
+        .output()
+        .map_err(|e| format!("Failed to run ssh-keyscan: {}", e))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!(
+            "ssh-keyscan {} failed: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let ssh_dir = dirs::home_dir()
+        .ok_or_else(|| "Could not determine home directory".to_string())?
+        .join(".ssh");
+    fs::create_dir_all(&ssh_dir).map_err(|e| format!("Failed to create ~/.ssh: {}", e))?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(ssh_dir.join("known_hosts"))
+        .map_err(|e| format!("Failed to open known_hosts: {}", e))?;
+    file.write_all(&output.stdout)
+        .map_err(|e| format!("Failed to write known_hosts: {}", e))
+}
+
+/// Check the project's model profile: it must exist and, for local agents, have a built image
+/// and a running container. With `fix`, builds/starts what is missing. Returns remaining problems.
+async fn check_model_profile(cfg: &SmithConfig, model: &str, fix: bool) -> Vec<String> {
+    let agents = cfg.agents.as_deref().unwrap_or(&[]);
+    let Some(idx) = agents.iter().position(|a| a.name == model) else {
+        if model == DEFAULT_AGENT_NAME {
+            return Vec::new();
+        }
+        if fix {
+            println!(
+                "    suggested: smith model add {} (or smith project update <name> --model <profile>)",
+                model
+            );
+        }
+        return vec![format!("model profile '{}' not found", model)];
+    };
+    let entry = &agents[idx];
+    if entry.agent_type.as_deref() != Some("local") {
+        return Vec::new();
+    }
+    if let Err(e) = docker::check_docker_available() {
+        return vec![e.to_string()];
+    }
+
+    let mut problems = Vec::new();
+    let port = agent_port(entry, idx);
+    let tag = docker::agent_built_image_tag(&entry.name);
+    if !docker::image_exists(&tag).unwrap_or(false) {
+        if !fix {
+            problems.push(format!("agent image {} not built", tag));
+        } else {
+            let built = config_dir().map_err(String::from).and_then(|dir| {
+                build_agent_image(
+                    dir.as_path(),
+                    &entry.name,
+                    &entry.image,
+                    port,
+                    entry.model.as_deref(),
+                    entry.small_model.as_deref(),
+                    entry.opencode.as_ref(),
+                    false,
+                )
+            });
+            match built {
+                Ok(()) => println!("    {} fixed: built {}", BULLET_BLUE, tag),
+                Err(e) => {
+                    problems.push(format!("agent image build failed: {}", e));
+                    return problems;
+                }
+            }
+        }
+    }
+
+    let running = docker::list_running_agent_containers().unwrap_or_default();
+    if !running.contains(&entry.name) {
+        if !fix {
+            problems.push(format!(
+                "agent container for '{}' is not running",
+                entry.name
+            ));
+            return problems;
+        }
+        if !docker::is_ollama_running() {
+            let ollama_model = entry.model.as_deref().unwrap_or("qwen3:8b");
+            if let Err(e) = docker::start_ollama_container(ollama_model, true).await {
+                problems.push(format!("failed to start Ollama: {}", e));
+                return problems;
+            }
+        }
+        if !docker::host_port_free(port) {
+            problems.push(format!(
+                "port {} for agent '{}' is in use by {}",
+                port,
+                entry.name,
+                docker::port_owner(port)
+            ));
+            println!("    suggested: smith model start --reassign-ports");
+            return problems;
+        }
+        let base_url = docker::ollama_base_url();
+        let mut run_args = docker::hardening_args(cfg, false);
+        match docker::agent_volume_args(entry.volumes.as_deref().unwrap_or_default()) {
+            Ok(volumes) => run_args.extend(volumes),
+            Err(e) => {
+                problems.push(e);
+                return problems;
+            }
+        }
+        match docker::start_agent_container(
+            &entry.name,
+            &tag,
+            port,
+            Some("ollama"),
+            Some(&base_url),
+            entry.env_passthrough.as_deref().unwrap_or_default(),
+            &run_args,
+        )
+        .await
+        {
+            Ok(()) => println!(
+                "    {} fixed: started agent '{}' ({})",
+                BULLET_BLUE,
+                entry.name,
+                clickable_agent_url(port)
+            ),
+            Err(e) => problems.push(format!("failed to start agent '{}': {}", entry.name, e)),
+        }
+    } else if !docker::check_agent_reachable(
+        &docker::agent_container_name(&entry.name),
+        port,
+        &docker::HealthCheck::for_agent(Some(entry)),
+    )
+    .await
+    {
+        problems.push(format!(
+            "agent '{}' is running but not reachable on port {}",
+            entry.name, port
+        ));
+        if fix {
+            println!("    suggested: smith model stop && smith model start");
+        }
+    }
+    problems
+}
+
+/// Structured project entry for `project list --json`; the GitHub token is reported as set/unset only.
+fn project_json(proj: &ProjectConfig) -> Value {
+    let mut value = serde_json::to_value(proj).unwrap_or(Value::Null);
+    if let Value::Object(map) = &mut value {
+        map.remove("github_token");
+        map.insert(
+            "github_token_set".to_string(),
+            Value::Bool(proj.github_token.is_some()),
+        );
+    }
+    value
+}
+
+/// Rename project `old` to `new` in the config, along with the budgets that name it.
+pub(crate) fn rename_project_in_config(
+    cfg: &mut SmithConfig,
+    old: &str,
+    new: &str,
+) -> Result<(), String> {
+    if new.is_empty() {
+        return Err("New project name must not be empty".to_string());
+    }
+    if cfg.projects.iter().any(|p| p.name == new) {
+        return Err(format!("Project '{}' already exists", new));
+    }
+    let project = cfg
+        .projects
+        .iter_mut()
+        .find(|p| p.name == old)
+        .ok_or_else(|| format!("Project '{}' not found", old))?;
+    project.name = new.to_string();
+    for budget in &mut cfg.budgets {
+        if budget.project.as_deref() == Some(old) {
+            budget.project = Some(new.to_string());
+        }
     }
+    Ok(())
 }
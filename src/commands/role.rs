@@ -2,7 +2,8 @@ use crate::*;
 
 pub async fn handle(cmd: RoleCommands) {
     match cmd {
-        RoleCommands::List { verbose } => {
+        RoleCommands::List => {
+            let verbose = logging::verbose();
             let roles = list_role_files().unwrap_or_else(|e| {
                 eprintln!("Error: {}", e);
                 std::process::exit(1);
@@ -1,7 +1,22 @@
 use crate::*;
 
+/// Pull request step to run after a successful `run develop --pr`.
+struct PostDevelopPr {
+    project: Option<String>,
+    repo: String,
+    branch: String,
+    /// Branch the PR is opened from (see `resolve_git_branch`)
+    head: String,
+    base: String,
+    /// PR title: the task, prefixed with the project for path-scoped projects
+    title: String,
+    plan: String,
+    wait_ci: Option<u64>,
+    auto_merge: Option<String>,
+}
+
 pub async fn handle(cmd: RunCommands) {
-    let mut post_pr: Option<(String, String, String, String)> = None;
+    let mut post_pr: Option<PostDevelopPr> = None;
     let mut post_release_pr: Option<(Option<String>, String, String, String)> = None;
 
     match &cmd {
@@ -9,52 +24,68 @@ pub async fn handle(cmd: RunCommands) {
             project,
             branch,
             base,
+            plan,
             pr,
+            wait_ci,
+            ci_timeout,
+            auto_merge,
             task,
             ..
-        } => {
-            if *pr {
-                let detected_project = if project.is_none() {
-                    match detect_project_from_cwd() {
-                        Ok(Some(name)) => Some(name),
-                        _ => None,
-                    }
-                } else {
-                    project.clone()
-                };
+        } if *pr => {
+            let detected_project = if project.is_none() {
+                match detect_or_pick_project() {
+                    Ok(Some(name)) => Some(name),
+                    _ => None,
+                }
+            } else {
+                project.clone()
+            };
 
-                let project_config = resolve_project_config(detected_project.clone())
-                    .unwrap_or_else(|e| {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    });
+            let project_config =
+                resolve_project_config(detected_project.clone()).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
 
-                let resolved_repo =
-                    resolve_repo(None, detected_project.clone()).unwrap_or_else(|e| {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    });
+            let resolved_repo = resolve_repo(None, detected_project.clone()).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
 
-                let resolved_branch = branch.clone().unwrap_or_else(|| {
-                    let output = Command::new("git")
-                        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-                        .output();
-                    match output {
-                        Ok(out) if out.status.success() => {
-                            String::from_utf8_lossy(&out.stdout).trim().to_string()
-                        }
-                        _ => {
-                            eprintln!(
-                                "Error: --pr requested but no branch provided and auto-detection failed"
-                            );
-                            std::process::exit(1);
-                        }
+            let resolved_branch = branch.clone().unwrap_or_else(|| {
+                let output = Command::new("git")
+                    .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                    .output();
+                match output {
+                    Ok(out) if out.status.success() => {
+                        String::from_utf8_lossy(&out.stdout).trim().to_string()
                     }
-                });
+                    _ => {
+                        eprintln!(
+                            "Error: --pr requested but no branch provided and auto-detection failed"
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            });
 
-                let resolved_base = resolve_base_branch(base.as_deref(), project_config.as_ref());
-                post_pr = Some((resolved_repo, resolved_branch, resolved_base, task.clone()));
-            }
+            let resolved_base = resolve_base_branch(base.as_deref(), project_config.as_ref());
+            let head = resolve_git_branch(&resolved_branch, project_config.as_ref());
+            let title = match project_config.as_ref().filter(|p| p.scope().is_some()) {
+                Some(p) => format!("[{}] {}", p.name, task),
+                None => task.clone(),
+            };
+            post_pr = Some(PostDevelopPr {
+                project: detected_project,
+                repo: resolved_repo,
+                branch: resolved_branch,
+                head,
+                base: resolved_base,
+                title,
+                plan: plan.clone(),
+                wait_ci: wait_ci.then_some(*ci_timeout),
+                auto_merge: auto_merge.clone(),
+            });
         }
         RunCommands::Release {
             project,
@@ -62,101 +93,153 @@ pub async fn handle(cmd: RunCommands) {
             base,
             pr,
             ..
-        } => {
-            if *pr {
-                let detected_project = if project.is_none() {
-                    match detect_project_from_cwd() {
-                        Ok(Some(name)) => Some(name),
-                        _ => None,
-                    }
-                } else {
-                    project.clone()
-                };
+        } if *pr => {
+            let detected_project = if project.is_none() {
+                match detect_or_pick_project() {
+                    Ok(Some(name)) => Some(name),
+                    _ => None,
+                }
+            } else {
+                project.clone()
+            };
 
-                let project_config = resolve_project_config(detected_project.clone())
-                    .unwrap_or_else(|e| {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    });
+            let project_config =
+                resolve_project_config(detected_project.clone()).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
 
-                let resolved_repo =
-                    resolve_repo(None, detected_project.clone()).unwrap_or_else(|e| {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    });
+            let resolved_repo = resolve_repo(None, detected_project.clone()).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
 
-                let resolved_branch = branch.clone().unwrap_or_else(|| {
-                    let output = Command::new("git")
-                        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-                        .output();
-                    match output {
-                        Ok(out) if out.status.success() => {
-                            String::from_utf8_lossy(&out.stdout).trim().to_string()
-                        }
-                        _ => {
-                            eprintln!(
-                                "Error: --pr requested but no branch provided and auto-detection failed"
-                            );
-                            std::process::exit(1);
-                        }
+            let resolved_branch = branch.clone().unwrap_or_else(|| {
+                let output = Command::new("git")
+                    .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                    .output();
+                match output {
+                    Ok(out) if out.status.success() => {
+                        String::from_utf8_lossy(&out.stdout).trim().to_string()
                     }
-                });
+                    _ => {
+                        eprintln!(
+                            "Error: --pr requested but no branch provided and auto-detection failed"
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            });
 
-                let resolved_base = resolve_base_branch(base.as_deref(), project_config.as_ref());
-                post_release_pr = Some((
-                    detected_project,
-                    resolved_repo,
-                    resolved_branch,
-                    resolved_base,
-                ));
-            }
+            let resolved_base = resolve_base_branch(base.as_deref(), project_config.as_ref());
+            post_release_pr = Some((
+                detected_project,
+                resolved_repo,
+                resolve_git_branch(&resolved_branch, project_config.as_ref()),
+                resolved_base,
+            ));
         }
         _ => {}
     }
 
     commands::pipeline::handle(cmd).await;
 
-    if let Some((resolved_repo, branch_out, base_branch, task_pr)) = post_pr {
-        let project = detect_project_from_cwd().ok().flatten();
-        let project_config = resolve_project_config(project).unwrap_or_else(|e| {
+    if let Some(post) = post_pr {
+        let project_config = resolve_project_config(post.project.clone()).unwrap_or_else(|e| {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         });
-        let token = project_config
-            .as_ref()
-            .and_then(|p| p.github_token.as_deref());
-
-        if let Some(token) = token {
-            if let Ok(repo_info) = github::extract_repo_info(&resolved_repo) {
-                match github::create_or_update_pr(
-                    token,
-                    &repo_info.owner,
-                    &repo_info.name,
-                    &branch_out,
-                    &base_branch,
-                    &task_pr,
-                )
-                .await
-                {
-                    Ok(pr_url) => println!("  {} Pull request: {}", BULLET_GREEN, pr_url),
-                    Err(e) => {
-                        eprintln!("  {} Failed to create/update PR: {}", BULLET_YELLOW, e);
-                        if e.contains("403") || e.contains("Resource not accessible") {
-                            eprintln!("     Your token may be missing required permissions.");
+
+        if let Some((forge, repo_info, token)) =
+            resolve_pr_target(project_config.as_ref(), &post.repo, "PR creation").await
+        {
+            let result = match &forge {
+                Forge::GitHub { api_url } => {
+                    if let Err(e) = github::preflight_pr_access(
+                        api_url,
+                        &token,
+                        &repo_info.owner,
+                        &repo_info.name,
+                    )
+                    .await
+                    {
+                        eprintln!("  {} Skipping PR creation: {}", BULLET_YELLOW, e);
+                        if post.wait_ci.is_some() {
+                            std::process::exit(1);
                         }
+                        return;
+                    }
+                    github::create_or_update_pr(
+                        api_url,
+                        &token,
+                        &repo_info.owner,
+                        &repo_info.name,
+                        &post.head,
+                        &post.base,
+                        &post.title,
+                    )
+                    .await
+                }
+                Forge::Gitea { base_url } => {
+                    gitea::create_or_update_pr(
+                        base_url,
+                        &token,
+                        &repo_info.owner,
+                        &repo_info.name,
+                        &post.head,
+                        &post.base,
+                        &post.title,
+                    )
+                    .await
+                }
+            };
+            match result {
+                Ok(pr_url) => {
+                    println!("  {} Pull request: {}", BULLET_GREEN, pr_url);
+                    events::emit(&SmithEvent::PrOpened {
+                        project: post.project.clone(),
+                        branch: post.branch.clone(),
+                        url: pr_url.clone(),
+                    });
+                    if let (Forge::GitHub { api_url }, Some(project)) = (&forge, &post.project) {
+                        publish_develop_check_run(
+                            api_url,
+                            &token,
+                            &repo_info,
+                            project,
+                            &post.branch,
+                            &post.plan,
+                        )
+                        .await;
+                    }
+                    if let Some(timeout_secs) = post.wait_ci {
+                        wait_for_pr_ci(&forge, &token, &repo_info, &post.head, timeout_secs).await;
+                    }
+                    if let Some(method) = post.auto_merge.as_deref() {
+                        auto_merge_pr(
+                            &forge,
+                            &token,
+                            &repo_info,
+                            &post.head,
+                            method,
+                            post.wait_ci.is_some(),
+                        )
+                        .await;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("  {} Failed to create/update PR: {}", BULLET_YELLOW, e);
+                    let message = e.to_string();
+                    if message.contains("403") || message.contains("Resource not accessible") {
+                        eprintln!("     Your token may be missing required permissions.");
+                    }
+                    if post.wait_ci.is_some() {
+                        std::process::exit(1);
                     }
                 }
-            } else {
-                eprintln!(
-                    "  {} Could not extract repository info from URL: {}",
-                    BULLET_YELLOW, resolved_repo
-                );
             }
-        } else {
-            eprintln!(
-                "  {} GitHub token not configured for this project; skipping PR creation",
-                BULLET_YELLOW
-            );
+        } else if post.wait_ci.is_some() {
+            std::process::exit(1);
         }
     }
 
@@ -165,46 +248,320 @@ pub async fn handle(cmd: RunCommands) {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         });
-        let token = project_config
-            .as_ref()
-            .and_then(|p| p.github_token.as_deref());
-
-        if let Some(token) = token {
-            if let Ok(repo_info) = github::extract_repo_info(&resolved_repo) {
-                match github::close_pr_for_branch(
-                    token,
-                    &repo_info.owner,
-                    &repo_info.name,
-                    &branch_out,
-                    &base_branch,
-                    "Integrated via smith run release",
-                )
-                .await
-                {
-                    Ok(Some(pr_url)) => {
-                        println!("  {} Closed pull request: {}", BULLET_GREEN, pr_url)
-                    }
-                    Ok(None) => {
-                        println!(
-                            "  {} No open pull request found for branch '{}'",
-                            BULLET_BLUE, branch_out
-                        );
-                    }
-                    Err(e) => {
-                        eprintln!("  {} Failed to close pull request: {}", BULLET_YELLOW, e);
-                    }
+
+        if let Some((forge, repo_info, token)) =
+            resolve_pr_target(project_config.as_ref(), &resolved_repo, "PR close").await
+        {
+            let result = match &forge {
+                Forge::GitHub { api_url } => {
+                    github::close_pr_for_branch(
+                        api_url,
+                        &token,
+                        &repo_info.owner,
+                        &repo_info.name,
+                        &branch_out,
+                        &base_branch,
+                        "Integrated via smith run release",
+                    )
+                    .await
                 }
-            } else {
-                eprintln!(
-                    "  {} Could not extract repository info from URL: {}",
-                    BULLET_YELLOW, resolved_repo
-                );
+                Forge::Gitea { base_url } => {
+                    gitea::close_pr_for_branch(
+                        base_url,
+                        &token,
+                        &repo_info.owner,
+                        &repo_info.name,
+                        &branch_out,
+                        &base_branch,
+                        "Integrated via smith run release",
+                    )
+                    .await
+                }
+            };
+            match result {
+                Ok(Some(pr_url)) => {
+                    println!("  {} Closed pull request: {}", BULLET_GREEN, pr_url)
+                }
+                Ok(None) => {
+                    println!(
+                        "  {} No open pull request found for branch '{}'",
+                        BULLET_BLUE, branch_out
+                    );
+                }
+                Err(e) => {
+                    eprintln!("  {} Failed to close pull request: {}", BULLET_YELLOW, e);
+                }
+            }
+        }
+    }
+}
+
+/// Resolve forge, repository owner/name and API token for a post-run PR step.
+/// Prints a warning and returns None when the step has to be skipped.
+async fn resolve_pr_target(
+    project_config: Option<&ProjectConfig>,
+    resolved_repo: &str,
+    action: &str,
+) -> Option<(Forge, github::RepoInfo, String)> {
+    let project = project_config.map(|p| p.name.as_str());
+    if let Err(e) = ensure_not_sandboxed(action, project) {
+        eprintln!("  {} {}; skipping", BULLET_YELLOW, e);
+        return None;
+    }
+    let forge = resolve_forge(project_config).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let Ok(repo_info) = github::extract_repo_info(resolved_repo) else {
+        eprintln!(
+            "  {} Could not extract repository info from URL: {}",
+            BULLET_YELLOW, resolved_repo
+        );
+        return None;
+    };
+    match resolve_forge_token(project_config, &forge, &repo_info.owner, &repo_info.name).await {
+        Ok(Some(token)) => Some((forge, repo_info, token)),
+        Ok(None) => {
+            eprintln!(
+                "  {} No github_token or GitHub App configured for this project; skipping {}",
+                BULLET_YELLOW, action
+            );
+            None
+        }
+        Err(e) => {
+            eprintln!(
+                "  {} Failed to resolve forge credentials; skipping {}: {}",
+                BULLET_YELLOW, action, e
+            );
+            None
+        }
+    }
+}
+
+/// Publish the latest completed develop run for the plan as a GitHub check run on its commit.
+/// Failures only warn: the PR itself has already been created.
+async fn publish_develop_check_run(
+    api_url: &str,
+    token: &str,
+    repo_info: &github::RepoInfo,
+    project: &str,
+    branch: &str,
+    plan: &str,
+) {
+    let report = match build_develop_check_run(project, branch, plan) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("  {} Skipping check run: {}", BULLET_YELLOW, e);
+            return;
+        }
+    };
+    match github::create_check_run(api_url, token, &repo_info.owner, &repo_info.name, &report).await
+    {
+        Ok(url) => println!("  {} Check run: {}", BULLET_GREEN, url),
+        Err(e) => {
+            eprintln!("  {} Failed to publish check run: {}", BULLET_YELLOW, e);
+            if e.to_string().contains("403") {
+                eprintln!("     Check runs can only be created with GitHub App credentials.");
+            }
+        }
+    }
+}
+
+fn build_develop_check_run(
+    project: &str,
+    branch: &str,
+    plan: &str,
+) -> Result<github::CheckRunReport, String> {
+    let plan_dirs = docker::list_spawn_plan_dirs(project, branch)?;
+    let selected_plan = resolve_plan_id_filter(plan, &plan_dirs)?;
+    let (dev_run_id, manifest) =
+        find_latest_completed_dev_run_for_plan(project, branch, &selected_plan)?;
+    let head_sha = manifest
+        .final_commit
+        .clone()
+        .ok_or_else(|| format!("develop run '{}' has no final commit", dev_run_id))?;
+
+    let verdict = manifest.final_verdict.as_deref().unwrap_or("unknown");
+    let conclusion = if verdict == "pass" {
+        "success"
+    } else {
+        "neutral"
+    };
+
+    let mut summary = vec![
+        format!(
+            "**Plan:** `{}` (id: {})",
+            manifest.plan_id, manifest.short_plan_id
+        ),
+        format!("**Task:** {}", manifest.task),
+        format!("**Final verdict:** {}", verdict),
+        String::new(),
+        "| Attempt | Verdict | Blocking | Non-blocking |".to_string(),
+        "|---|---|---|---|".to_string(),
+    ];
+    for attempt in &manifest.attempts {
+        summary.push(format!(
+            "| {} | {} | {} | {} |",
+            attempt.attempt, attempt.verdict, attempt.blocking_issues, attempt.non_blocking_issues
+        ));
+    }
+
+    let mut text: Vec<String> = Vec::new();
+    if let Some(latest) = manifest.attempts.last() {
+        if let Ok(raw) = docker::read_spawn_file(project, branch, &latest.develop_artifact) {
+            let validation = serde_json::from_str::<Value>(&raw)
+                .ok()
+                .and_then(|v| v.get("validation").and_then(Value::as_array).cloned())
+                .unwrap_or_default();
+            if !validation.is_empty() {
+                text.push("### Validation".to_string());
+                for entry in validation {
+                    let field = |key: &str| {
+                        entry
+                            .get(key)
+                            .and_then(Value::as_str)
+                            .unwrap_or("")
+                            .to_string()
+                    };
+                    text.push(format!(
+                        "- `{}`: {} {}",
+                        field("command"),
+                        field("result"),
+                        field("notes")
+                    ));
+                }
+            }
+        }
+    }
+    if !manifest.non_blocking_issues.is_empty() {
+        text.push("### Non-blocking issues".to_string());
+        for issue in &manifest.non_blocking_issues {
+            text.push(format!(
+                "- {} [{}] {}: {}",
+                issue.id, issue.severity, issue.title, issue.detail
+            ));
+        }
+    }
+
+    Ok(github::CheckRunReport {
+        name: "smith/develop".to_string(),
+        head_sha,
+        conclusion: conclusion.to_string(),
+        title: format!("Develop {} ({})", dev_run_id, verdict),
+        summary: summary.join("\n"),
+        text: text.join("\n"),
+    })
+}
+
+/// Block until CI on the PR branch finishes; exits non-zero unless it passes.
+async fn wait_for_pr_ci(
+    forge: &Forge,
+    token: &str,
+    repo_info: &github::RepoInfo,
+    branch: &str,
+    timeout_secs: u64,
+) {
+    let Forge::GitHub { api_url } = forge else {
+        eprintln!("Error: --wait-ci is only supported for GitHub projects");
+        std::process::exit(1);
+    };
+    println!("  {} Waiting for CI on '{}'...", BULLET_BLUE, branch);
+    match github::wait_for_ci(
+        api_url,
+        token,
+        &repo_info.owner,
+        &repo_info.name,
+        branch,
+        Duration::from_secs(timeout_secs),
+    )
+    .await
+    {
+        Ok(outcome) if outcome.no_checks => {
+            println!("  {} No CI checks reported for '{}'", BULLET_YELLOW, branch);
+        }
+        Ok(outcome) if outcome.passed => {
+            println!("  {} CI passed", BULLET_GREEN);
+        }
+        Ok(outcome) => {
+            eprintln!("  {} CI failed:", BULLET_RED);
+            for check in &outcome.failed_checks {
+                eprintln!("     - {}", check);
             }
-        } else {
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Merge the PR now when CI has already passed (--wait-ci), otherwise enable GitHub auto-merge.
+async fn auto_merge_pr(
+    forge: &Forge,
+    token: &str,
+    repo_info: &github::RepoInfo,
+    branch: &str,
+    method: &str,
+    ci_passed: bool,
+) {
+    let Forge::GitHub { api_url } = forge else {
+        eprintln!(
+            "  {} --auto-merge is only supported for GitHub projects",
+            BULLET_YELLOW
+        );
+        return;
+    };
+    let pr = match github::find_open_pr(api_url, token, &repo_info.owner, &repo_info.name, branch)
+        .await
+    {
+        Ok(Some(pr)) => pr,
+        Ok(None) => {
             eprintln!(
-                "  {} GitHub token not configured for this project; skipping PR close",
-                BULLET_YELLOW
+                "  {} No open pull request found for '{}'; skipping auto-merge",
+                BULLET_YELLOW, branch
             );
+            return;
+        }
+        Err(e) => {
+            eprintln!("  {} Failed to look up pull request: {}", BULLET_YELLOW, e);
+            return;
+        }
+    };
+
+    if ci_passed {
+        match github::merge_pr(
+            api_url,
+            token,
+            &repo_info.owner,
+            &repo_info.name,
+            pr.number,
+            method,
+        )
+        .await
+        {
+            Ok(()) => println!(
+                "  {} Merged pull request #{} ({})",
+                BULLET_GREEN, pr.number, method
+            ),
+            Err(e) => {
+                eprintln!("  {} Failed to merge pull request: {}", BULLET_RED, e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        match github::enable_auto_merge(api_url, token, &pr.node_id, method).await {
+            Ok(()) => println!(
+                "  {} Auto-merge ({}) enabled for {}",
+                BULLET_GREEN, method, pr.html_url
+            ),
+            Err(e) => {
+                eprintln!("  {} Failed to enable auto-merge: {}", BULLET_YELLOW, e);
+                let message = e.to_string();
+                if message.contains("not allowed") || message.contains("auto merge") {
+                    eprintln!("     Enable 'Allow auto-merge' in the repository settings.");
+                }
+            }
         }
     }
 }
@@ -0,0 +1,220 @@
+use crate::*;
+
+/// Stub agent: alpine with git and an `opencode` that appends a line to the workspace.
+const SELFTEST_DOCKERFILE: &str = r#"FROM alpine:3.20
+RUN apk add --no-cache git \
+ && git config --global --add safe.directory '*' \
+ && printf '#!/bin/sh\necho "smith selftest" >> /workspace/SELFTEST.md\necho "{\\"type\\":\\"text\\",\\"part\\":{\\"text\\":\\"done\\"}}"\n' > /usr/local/bin/opencode \
+ && chmod +x /usr/local/bin/opencode
+"#;
+const SELFTEST_BRANCH: &str = "smith-selftest";
+
+pub fn handle(keep: bool) {
+    let scratch = std::env::temp_dir().join(format!("smith-selftest-{}", std::process::id()));
+    let remote = scratch.join("remote.git");
+    let container = format!("smith_selftest_{}", std::process::id());
+    let image = format!("smith/selftest:{}", env!("CARGO_PKG_VERSION"));
+
+    println!("  {} Self-test", BULLET_BLUE);
+    let result = run_steps(&scratch, &remote, &container, &image);
+
+    // The container pushed as root; hand the repo back so it can be inspected or removed.
+    let owner = format!("{}:{}", unsafe { libc::getuid() }, unsafe {
+        libc::getgid()
+    });
+    let _ = docker_exec(&container, &format!("chown -R {} /remote.git", owner));
+    if !keep {
+        let _ = Command::new("docker")
+            .args(["rm", "-f", &container])
+            .output();
+        let _ = fs::remove_dir_all(&scratch);
+    } else if scratch.exists() {
+        println!(
+            "  {} Kept container {} and repo {}",
+            BULLET_BLUE,
+            container,
+            remote.display()
+        );
+    }
+    match result {
+        Ok(()) => println!("  {} smith works end to end", BULLET_GREEN),
+        Err(subsystem) => {
+            eprintln!("Error: self-test failed at '{}'", subsystem);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run each step in order, printing its outcome; returns the subsystem of the first failure.
+fn run_steps(
+    scratch: &Path,
+    remote: &Path,
+    container: &str,
+    image: &str,
+) -> Result<(), &'static str> {
+    report(
+        "config",
+        load_config()
+            .map(|cfg| {
+                format!(
+                    "{} agent(s), {} project(s)",
+                    cfg.agents.map(|a| a.len()).unwrap_or(0),
+                    cfg.projects.len()
+                )
+            })
+            .map_err(|e| e.to_string()),
+    )?;
+    report(
+        "git",
+        seed_selftest_remote(scratch, remote).map(|()| "throwaway repo created".to_string()),
+    )?;
+    report(
+        "docker",
+        docker::check_docker_available()
+            .map(|()| "daemon reachable".to_string())
+            .map_err(|e| e.to_string()),
+    )?;
+    report(
+        "image",
+        if docker::image_exists(image).unwrap_or(false) {
+            Ok(format!("{} (cached)", image))
+        } else {
+            build_image_from_dockerfile(image, SELFTEST_DOCKERFILE, false)
+                .map(|()| format!("{} built", image))
+        },
+    )?;
+    let mount = format!("{}:/remote.git", remote.display());
+    report(
+        "container",
+        docker_output(&[
+            "run", "-d", "--name", container, "-v", &mount, image, "sleep", "600",
+        ])
+        .map(|_| format!("{} started", container)),
+    )?;
+    report(
+        "clone",
+        docker_exec(container, "git clone -q /remote.git /workspace")
+            .map(|_| "cloned into /workspace".to_string()),
+    )?;
+    report(
+        "agent",
+        docker_exec(container, "cd /workspace && opencode run selftest")
+            .and_then(|_| docker_exec(container, "cat /workspace/SELFTEST.md"))
+            .map(|_| "stub agent edited SELFTEST.md".to_string()),
+    )?;
+    let commit_script = format!(
+        "cd /workspace && git checkout -q -b {branch} && {commit}",
+        branch = SELFTEST_BRANCH,
+        commit = build_spawn_commit_script("test: smith selftest", SELFTEST_BRANCH, None, None)
+    );
+    report(
+        "commit",
+        docker_exec(container, &commit_script).map(|_| format!("pushed {}", SELFTEST_BRANCH)),
+    )?;
+    report(
+        "verify",
+        verify_selftest_remote(remote).map(|()| "commit found on the remote".to_string()),
+    )
+}
+
+fn report(subsystem: &'static str, result: Result<String, String>) -> Result<(), &'static str> {
+    match result {
+        Ok(detail) => {
+            println!("    {} {}: {}", BULLET_GREEN, subsystem, detail);
+            Ok(())
+        }
+        Err(e) => {
+            println!("    {} {}: {}", BULLET_RED, subsystem, e);
+            Err(subsystem)
+        }
+    }
+}
+
+/// Create a bare repo at `remote` with one commit on `main`.
+pub(crate) fn seed_selftest_remote(scratch: &Path, remote: &Path) -> Result<(), String> {
+    let seed = scratch.join("seed");
+    fs::create_dir_all(&seed).map_err(|e| format!("Failed to create {}: {}", seed.display(), e))?;
+    host_git(None, &["init", "-q", "--bare", &remote.to_string_lossy()])?;
+    host_git(Some(&seed), &["init", "-q", "-b", "main"])?;
+    fs::write(seed.join("README.md"), "# smith selftest\n")
+        .map_err(|e| format!("Failed to write README.md: {}", e))?;
+    host_git(Some(&seed), &["add", "README.md"])?;
+    host_git(
+        Some(&seed),
+        &[
+            "-c",
+            "user.name=Smith",
+            "-c",
+            "user.email=smith@localhost",
+            "commit",
+            "-q",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    host_git(
+        Some(&seed),
+        &["push", "-q", &remote.to_string_lossy(), "main"],
+    )?;
+    Ok(())
+}
+
+/// The self-test branch on `remote` holds the agent's edit.
+pub(crate) fn verify_selftest_remote(remote: &Path) -> Result<(), String> {
+    let content = host_git(
+        Some(remote),
+        &["show", &format!("{}:SELFTEST.md", SELFTEST_BRANCH)],
+    )?;
+    if content.contains("smith selftest") {
+        Ok(())
+    } else {
+        Err("SELFTEST.md on the remote does not contain the agent's edit".to_string())
+    }
+}
+
+fn host_git(dir: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let output = cmd
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn docker_exec(container: &str, script: &str) -> Result<String, String> {
+    docker_output(&["exec", container, "sh", "-c", script])
+}
+
+fn docker_output(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("docker")
+        .args(args)
+        .logged()
+        .output()
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Err(format!(
+            "docker {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            if stderr.trim().is_empty() {
+                stdout.trim()
+            } else {
+                stderr.trim()
+            }
+        ))
+    }
+}
@@ -0,0 +1,235 @@
+use crate::*;
+use smith_core::history::RunRecord;
+
+/// Time windows reported by `smith stats` (label, seconds; None = all history).
+const WINDOWS: &[(&str, Option<u64>)] = &[
+    ("24h", Some(86_400)),
+    ("7d", Some(604_800)),
+    ("30d", Some(2_592_000)),
+    ("all", None),
+];
+
+/// Totals for one group of runs.
+#[derive(Serialize)]
+pub(crate) struct RunStats {
+    name: String,
+    runs: usize,
+    succeeded: usize,
+    success_rate: f64,
+    median_duration_secs: u64,
+    tokens: u64,
+    cost: f64,
+}
+
+pub async fn handle(project: Option<String>, since: Option<String>, json: bool) {
+    let min_finished = since
+        .as_deref()
+        .map(commands::cache::parse_age)
+        .transpose()
+        .unwrap_or_else(|e| exit_with_error(SmithError::Validation(e), json))
+        .map(|age| now_unix().saturating_sub(age));
+    let runs: Vec<RunRecord> = history::load()
+        .unwrap_or_else(|e| exit_with_error(e, json))
+        .into_iter()
+        .filter(|r| project.as_ref().is_none_or(|p| r.project == *p))
+        .filter(|r| min_finished.is_none_or(|t| r.finished_at >= t))
+        .collect();
+
+    let now = now_unix();
+    let windows: Vec<RunStats> = WINDOWS
+        .iter()
+        .map(|(label, secs)| {
+            let in_window: Vec<&RunRecord> = runs
+                .iter()
+                .filter(|r| secs.is_none_or(|s| r.finished_at >= now.saturating_sub(s)))
+                .collect();
+            run_stats(label, &in_window)
+        })
+        .collect();
+    let projects = grouped(&runs, |r| Some(r.project.clone()));
+    let agents = grouped(&runs, |r| r.agent.clone());
+    let stages = grouped(&runs, |r| Some(r.stage.clone()));
+    let budgets = budget_rows(project.as_deref());
+
+    if json {
+        let value = serde_json::json!({
+            "windows": windows,
+            "projects": projects,
+            "agents": agents,
+            "stages": stages,
+            "budgets": budgets,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).unwrap_or_default()
+        );
+        return;
+    }
+
+    if runs.is_empty() {
+        println!(
+            "  {} No runs recorded yet; plan, develop and release runs are added when they finish.",
+            BULLET_YELLOW
+        );
+        return;
+    }
+    print_table("Windows", "window", &windows);
+    print_table("By project", "project", &projects);
+    print_table("By agent", "agent", &agents);
+    print_table("By stage", "stage", &stages);
+    print_budgets(&budgets);
+}
+
+/// Spend of one configured budget in its current period.
+#[derive(Serialize)]
+struct BudgetRow {
+    budget: String,
+    spent: f64,
+    limit: f64,
+    percent: f64,
+    hard_cap: bool,
+}
+
+/// Current-period spend of every configured budget (those covering `project` when given).
+fn budget_rows(project: Option<&str>) -> Vec<BudgetRow> {
+    let Ok(cfg) = load_config() else {
+        return Vec::new();
+    };
+    let runs = history::load().unwrap_or_default();
+    let now = now_unix();
+    cfg.budgets
+        .iter()
+        .filter(|b| project.is_none_or(|p| b.project.as_deref().is_none_or(|bp| bp == p)))
+        .map(|b| {
+            let status = budget::status(b, &runs, now);
+            BudgetRow {
+                budget: status.label(),
+                spent: status.spent,
+                limit: b.limit,
+                percent: status.percent(),
+                hard_cap: b.hard_cap == Some(true),
+            }
+        })
+        .collect()
+}
+
+fn print_budgets(rows: &[BudgetRow]) {
+    if rows.is_empty() {
+        return;
+    }
+    let mut table = Table::new(&["budget", "spent", "limit", "used", "cap"]);
+    for row in rows {
+        table.row(vec![
+            row.budget.clone(),
+            format!("${:.2}", row.spent),
+            format!("${:.2}", row.limit),
+            format!("{:.0}%", row.percent),
+            if row.hard_cap { "hard" } else { "warn" }.to_string(),
+        ]);
+    }
+    let Ok(rendered) = table.render(&TableArgs::default()) else {
+        return;
+    };
+    println!("  {} Budgets", BULLET_BLUE);
+    for line in rendered.lines() {
+        println!("  {}", line);
+    }
+    println!();
+}
+
+/// Stats per distinct `key`, busiest first; runs without a key are left out.
+fn grouped(runs: &[RunRecord], key: impl Fn(&RunRecord) -> Option<String>) -> Vec<RunStats> {
+    let mut groups: Vec<(String, Vec<&RunRecord>)> = Vec::new();
+    for run in runs {
+        let Some(name) = key(run) else {
+            continue;
+        };
+        match groups.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, members)) => members.push(run),
+            None => groups.push((name, vec![run])),
+        }
+    }
+    let mut stats: Vec<RunStats> = groups
+        .iter()
+        .map(|(name, members)| run_stats(name, members))
+        .collect();
+    stats.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.name.cmp(&b.name)));
+    stats
+}
+
+pub(crate) fn run_stats(name: &str, runs: &[&RunRecord]) -> RunStats {
+    let succeeded = runs.iter().filter(|r| r.succeeded()).count();
+    let mut durations: Vec<u64> = runs.iter().map(|r| r.duration_secs()).collect();
+    RunStats {
+        name: name.to_string(),
+        runs: runs.len(),
+        succeeded,
+        success_rate: if runs.is_empty() {
+            0.0
+        } else {
+            succeeded as f64 / runs.len() as f64
+        },
+        median_duration_secs: median(&mut durations),
+        tokens: runs.iter().map(|r| r.tokens).sum(),
+        cost: runs.iter().map(|r| r.cost).sum(),
+    }
+}
+
+/// Median of `values` (mean of the middle two for an even count); 0 when empty.
+fn median(values: &mut [u64]) -> u64 {
+    values.sort_unstable();
+    match values.len() {
+        0 => 0,
+        n if n % 2 == 1 => values[n / 2],
+        n => (values[n / 2 - 1] + values[n / 2]) / 2,
+    }
+}
+
+fn print_table(title: &str, key: &'static str, stats: &[RunStats]) {
+    if stats.is_empty() {
+        return;
+    }
+    let mut table = Table::new(&[key, "runs", "success", "median", "tokens", "cost"]);
+    for s in stats {
+        table.row(vec![
+            s.name.clone(),
+            s.runs.to_string(),
+            if s.runs == 0 {
+                String::new()
+            } else {
+                format!("{:.0}%", s.success_rate * 100.0)
+            },
+            if s.runs == 0 {
+                String::new()
+            } else {
+                format_secs(s.median_duration_secs)
+            },
+            if s.tokens == 0 {
+                String::new()
+            } else {
+                s.tokens.to_string()
+            },
+            if s.cost == 0.0 {
+                String::new()
+            } else {
+                format!("${:.2}", s.cost)
+            },
+        ]);
+    }
+    let Ok(rendered) = table.render(&TableArgs::default()) else {
+        return;
+    };
+    println!("  {} {}", BULLET_BLUE, title);
+    for line in rendered.lines() {
+        println!("  {}", line);
+    }
+    println!();
+}
+
+fn format_secs(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3_599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3_600, (secs % 3_600) / 60),
+    }
+}
@@ -1,6 +1,94 @@
 use crate::*;
 
-pub async fn handle_status(verbose: bool) {
+/// Readiness cache file (project name -> last successful check, unix seconds).
+const READINESS_CACHE_FILE: &str = "readiness.json";
+/// A successful readiness check is reused for this long before probing the remote again.
+const READINESS_CACHE_TTL_SECS: u64 = 600;
+
+fn load_readiness_cache() -> HashMap<String, u64> {
+    config_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(READINESS_CACHE_FILE)).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_readiness_cache(cache: &HashMap<String, u64>) {
+    if let (Ok(dir), Ok(body)) = (config_dir(), serde_json::to_string_pretty(cache)) {
+        let _ = fs::create_dir_all(&dir);
+        let _ = fs::write(dir.join(READINESS_CACHE_FILE), body);
+    }
+}
+
+/// Run git against the project remote non-interactively, using the project's SSH key if set.
+pub(crate) fn project_git_command(proj: &ProjectConfig) -> Command {
+    let mut ssh = "ssh -o BatchMode=yes -o ConnectTimeout=5".to_string();
+    if let Some(key) = resolve_ssh_key(None, Some(proj)) {
+        ssh.push_str(&format!(" -i '{}'", key.display()));
+    }
+    let mut cmd = Command::new("git");
+    cmd.env("GIT_SSH_COMMAND", ssh)
+        .env("GIT_TERMINAL_PROMPT", "0");
+    cmd
+}
+
+/// Shallow readiness: `git ls-remote` for the base branch.
+fn check_project_remote(proj: &ProjectConfig) -> Result<(), String> {
+    let base = resolve_base_branch(None, Some(proj));
+    let output = project_git_command(proj)
+        .args(["ls-remote", "--exit-code", "--heads", &proj.repo, &base])
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    if output.status.code() == Some(2) {
+        return Err(format!("base branch '{}' not found on remote", base));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(stderr
+        .lines()
+        .last()
+        .unwrap_or("remote unreachable")
+        .trim()
+        .to_string())
+}
+
+/// Deep readiness: shallow clone of the base branch into a temp dir.
+fn check_project_clone(proj: &ProjectConfig) -> Result<(), String> {
+    let base = resolve_base_branch(None, Some(proj));
+    let dir =
+        std::env::temp_dir().join(format!("smith-status-{}-{}", proj.name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let output = project_git_command(proj)
+        .args([
+            "clone",
+            "--quiet",
+            "--depth",
+            "1",
+            "--single-branch",
+            "--branch",
+        ])
+        .arg(&base)
+        .arg(&proj.repo)
+        .arg(&dir)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e));
+    let _ = fs::remove_dir_all(&dir);
+    let output = output?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .last()
+            .unwrap_or("clone failed")
+            .trim()
+            .to_string())
+    }
+}
+
+pub async fn handle_status(verbose: bool, deep: bool) {
     let docker_ok = docker::check_docker_available().is_ok();
     let installed = is_installed();
 
@@ -70,11 +158,20 @@ pub async fn handle_status(verbose: bool) {
                 .map(|t| t != "local")
                 .unwrap_or(true);
             let port = agent_port(agent_entry, i);
-            let reachable = if active {
-                Some(docker::check_agent_reachable(port))
+            let health = if active {
+                Some(
+                    docker::HealthCheck::for_agent(Some(agent_entry))
+                        .probe(
+                            &docker::agent_container_name(name),
+                            port,
+                            std::time::Duration::from_secs(2),
+                        )
+                        .await,
+                )
             } else {
                 None
             };
+            let reachable = health.as_ref().map(|h| h.is_ok());
             let bullet = if is_cloud {
                 BULLET_GREEN
             } else if active && reachable == Some(false) {
@@ -86,16 +183,12 @@ pub async fn handle_status(verbose: bool) {
             } else {
                 BULLET_RED
             };
-            let state = if is_cloud {
-                "cloud"
-            } else if active && reachable == Some(false) {
-                "running (port unreachable)"
-            } else if active {
-                "running"
-            } else if built {
-                "built"
-            } else {
-                "not built"
+            let state = match &health {
+                _ if is_cloud => "cloud".to_string(),
+                Some(Err(e)) => format!("running (unhealthy: {})", e),
+                Some(Ok(())) => "running".to_string(),
+                None if built => "built".to_string(),
+                None => "not built".to_string(),
             };
             if is_cloud {
                 println!("       {} {} - {}", bullet, name, state);
@@ -117,21 +210,50 @@ pub async fn handle_status(verbose: bool) {
     }
 
     // projects:
+    let mut readiness = load_readiness_cache();
+    let now = now_unix();
     let project_results: Vec<(String, bool, String)> = cfg
         .projects
         .iter()
         .map(|proj| {
             if proj.repo.starts_with("https://") {
-                (
+                return (
                     proj.name.clone(),
                     false,
                     "unsupported repo URL (use SSH)".to_string(),
-                )
+                );
+            }
+            if !deep {
+                if let Some(last) = readiness.get(&proj.name) {
+                    let age = now.saturating_sub(*last);
+                    if age < READINESS_CACHE_TTL_SECS {
+                        return (
+                            proj.name.clone(),
+                            true,
+                            format!("ready (checked {}m ago)", age / 60),
+                        );
+                    }
+                }
+            }
+            let result = if deep {
+                check_project_clone(proj)
             } else {
-                (proj.name.clone(), true, "configured".to_string())
+                check_project_remote(proj)
+            };
+            match result {
+                Ok(()) => {
+                    readiness.insert(proj.name.clone(), now);
+                    let msg = if deep { "ready (cloned)" } else { "ready" };
+                    (proj.name.clone(), true, msg.to_string())
+                }
+                Err(e) => {
+                    readiness.remove(&proj.name);
+                    (proj.name.clone(), false, e)
+                }
             }
         })
         .collect();
+    save_readiness_cache(&readiness);
     let project_ok_count = project_results.iter().filter(|(_, ok, _)| *ok).count();
     let project_total = project_results.len();
     let projects_bullet = if project_total == 0 {
@@ -155,6 +277,31 @@ pub async fn handle_status(verbose: bool) {
         }
     }
 
+    if !cfg.nodes.is_empty() {
+        let statuses = super::node::probe_all(&cfg.nodes);
+        let reachable = statuses.iter().filter(|s| s.reachable()).count();
+        let nodes_bullet = if reachable == statuses.len() {
+            BULLET_GREEN
+        } else if reachable > 0 {
+            BULLET_YELLOW
+        } else {
+            BULLET_RED
+        };
+        println!("  {} nodes", nodes_bullet);
+        for (node, status) in cfg.nodes.iter().zip(&statuses) {
+            match &status.version {
+                Ok(version) => println!(
+                    "       {} {} - smith {}, {} agent(s)",
+                    BULLET_GREEN,
+                    node.name,
+                    version,
+                    super::node::load_summary(node, status)
+                ),
+                Err(e) => println!("       {} {} - {}", BULLET_RED, node.name, e),
+            }
+        }
+    }
+
     if verbose {
         println!();
         println!("  --- verbose ---");
@@ -175,14 +322,14 @@ pub async fn handle_status(verbose: bool) {
             println!("  installed_version: (not installed)");
         }
         if docker_ok {
-            if let Ok(o) = Command::new("docker").arg("--version").output() {
+            if let Ok(o) = Command::new("docker").arg("--version").logged().output() {
                 let out = String::from_utf8_lossy(&o.stdout);
                 let v = out.trim();
                 if !v.is_empty() {
                     println!("  docker: {}", v);
                 }
             }
-            if let Ok(o) = Command::new("docker").arg("info").output() {
+            if let Ok(o) = Command::new("docker").arg("info").logged().output() {
                 let out = String::from_utf8_lossy(&o.stdout);
                 for line in out.lines().take(15) {
                     println!("    {}", line);
@@ -215,7 +362,54 @@ pub async fn handle_status(verbose: bool) {
     }
 }
 
-pub async fn handle_install() {
+/// Answers file for `smith install --answers`: every wizard question, declaratively.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct InstallAnswers {
+    /// Enable the Docker service at boot (Linux)
+    docker_at_boot: Option<bool>,
+    /// Create the default opencode agent when no agents exist
+    default_agent: Option<bool>,
+    /// Agents to add (`[[agents]]`); existing names are skipped
+    agents: Vec<InstallAgentAnswer>,
+    /// Projects to add (`[[projects]]`, same keys as config.toml); existing names are skipped
+    projects: Vec<ProjectConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstallAgentAnswer {
+    name: String,
+    image: Option<String>,
+    agent_type: Option<String>,
+    model: Option<String>,
+    small_model: Option<String>,
+    provider: Option<String>,
+    base_url: Option<String>,
+    port: Option<u16>,
+}
+
+fn load_install_answers(path: &Path) -> Result<InstallAnswers, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read answers file {}: {}", path.display(), e))?;
+    toml::from_str(&raw).map_err(|e| format!("Invalid answers file {}: {}", path.display(), e))
+}
+
+pub async fn handle_install(yes: bool, answers_path: Option<PathBuf>) {
+    let interactive = !yes && answers_path.is_none();
+    let answers = match &answers_path {
+        Some(path) => load_install_answers(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }),
+        None => InstallAnswers::default(),
+    };
+    // Answered questions use the file; unanswered ones prompt, or take --yes when non-interactive.
+    let confirm = |answer: Option<bool>, prompt: &str| match answer {
+        Some(answer) => answer,
+        None if interactive => prompt_yn(prompt, true),
+        None => yes,
+    };
     println!("{} smith install", BULLET_GREEN);
     println!();
     // --- Dependencies ---
@@ -226,9 +420,16 @@ pub async fn handle_install() {
     #[cfg(target_os = "linux")]
     {
         if docker::check_docker_available().is_ok() {
-            println!("  Always run Docker at boot so agents stay available after restart?");
-            println!("  (Requires sudo / password to run systemctl enable docker)");
-            if prompt_yn("Enable Docker at boot?", true) {
+            if interactive {
+                println!("  Always run Docker at boot so agents stay available after restart?");
+                let engine = docker::engine_info();
+                if engine.rootless || engine.podman {
+                    println!("  (Rootless engine: enables the user service, no sudo needed)");
+                } else {
+                    println!("  (Requires sudo / password to run systemctl enable docker)");
+                }
+            }
+            if confirm(answers.docker_at_boot, "Enable Docker at boot?") {
                 ensure_docker_started_and_enabled();
                 println!("  {} Docker - enabled at boot", BULLET_GREEN);
             }
@@ -244,7 +445,7 @@ pub async fn handle_install() {
     if agents_list.is_empty() {
         println!("  Current agents: (none)");
         println!();
-        match prompt_yn("Create default opencode agent?", true) {
+        match confirm(answers.default_agent, "Create default opencode agent?") {
             false => {
                 println!("  No agent created. Use 'smith agent add' later.");
             }
@@ -262,6 +463,14 @@ pub async fn handle_install() {
                     default_role: None,
                     roles: None,
                     env: None,
+                    env_passthrough: None,
+                    volumes: None,
+                    bind_address: None,
+                    health_path: None,
+                    health_match: None,
+                    health_command: None,
+                    opencode: None,
+                    commit: None,
                 };
                 cfg.agents = Some(vec![new_agent]);
                 if let Err(e) = save_config(&cfg) {
@@ -281,89 +490,118 @@ pub async fn handle_install() {
         }
     }
     println!();
-    match prompt_yn_skip("Add more agents?") {
-        None => println!("  Skipping agents."),
-        Some(false) => {}
-        Some(true) => loop {
-            let name = prompt_line("  Agent name: ");
-            if name.is_empty() {
-                println!("  Agent name cannot be empty.");
-                continue;
-            }
-            let image_default = DEFAULT_AGENT_IMAGE.to_string();
-            let image_in = prompt_line(&format!("  Image [{}]: ", image_default));
-            let image = if image_in.is_empty() {
-                None
-            } else {
-                Some(image_in)
-            };
-            let model_in =
-                prompt_line("  Model (e.g. anthropic/claude-sonnet-4-5, Enter to skip): ");
-            let model = if model_in.is_empty() {
-                None
-            } else {
-                Some(model_in)
-            };
-            let small_model_in =
-                prompt_line("  Small model for internal ops (optional, Enter to skip): ");
-            let small_model = if small_model_in.is_empty() {
-                None
-            } else {
-                Some(small_model_in)
-            };
-            let provider_in = prompt_line(
-                "  Provider (e.g. ollama, anthropic, openai, Enter for cloud default): ",
-            );
-            let provider = if provider_in.is_empty() {
-                None
-            } else {
-                Some(provider_in)
-            };
-            let base_url_in = prompt_line("  Base URL for provider (optional, Enter to skip): ");
-            let base_url = if base_url_in.is_empty() {
-                None
-            } else {
-                Some(base_url_in)
-            };
-            let type_in = prompt_line("  Type (local or cloud, Enter for cloud): ");
-            let agent_type = if type_in.is_empty() {
-                None
-            } else {
-                Some(type_in)
-            };
-            let port_in = prompt_line("  Port for opencode serve (Enter for default 4096): ");
-            let port = if port_in.is_empty() {
-                None
-            } else {
-                port_in.parse().ok()
-            };
-            let enabled = Some(true);
+    if !interactive {
+        for agent in answers.agents {
+            let name = agent.name.clone();
             match add_agent_to_config(
                 &mut cfg,
-                name.clone(),
-                image,
-                agent_type,
-                model,
-                small_model,
-                provider,
-                base_url,
-                port,
-                enabled,
+                agent.name,
+                agent.image,
+                agent.agent_type,
+                agent.model,
+                agent.small_model,
+                agent.provider,
+                agent.base_url,
+                agent.port,
+                Some(true),
                 None,
                 None,
                 None,
             ) {
                 Ok(()) => println!("  {} Added agent '{}'", BULLET_GREEN, name),
-                Err(e) => eprintln!("  {} {}", BULLET_RED, e),
+                Err(e) => println!("  {} {} (skipped)", BULLET_YELLOW, e),
             }
-            save_config(&cfg).unwrap_or_else(|e| {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
-            });
-            if !prompt_yn("Add another agent?", false) {
-                break;
-            }
-        },
+        }
+        save_config(&cfg).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+    } else {
+        match prompt_yn_skip("Add more agents?") {
+            None => println!("  Skipping agents."),
+            Some(false) => {}
+            Some(true) => loop {
+                let name = prompt_line("  Agent name: ");
+                if name.is_empty() {
+                    println!("  Agent name cannot be empty.");
+                    continue;
+                }
+                let image_default = DEFAULT_AGENT_IMAGE.to_string();
+                let image_in = prompt_line(&format!("  Image [{}]: ", image_default));
+                let image = if image_in.is_empty() {
+                    None
+                } else {
+                    Some(image_in)
+                };
+                let model_in =
+                    prompt_line("  Model (e.g. anthropic/claude-sonnet-4-5, Enter to skip): ");
+                let model = if model_in.is_empty() {
+                    None
+                } else {
+                    Some(model_in)
+                };
+                let small_model_in =
+                    prompt_line("  Small model for internal ops (optional, Enter to skip): ");
+                let small_model = if small_model_in.is_empty() {
+                    None
+                } else {
+                    Some(small_model_in)
+                };
+                let provider_in = prompt_line(
+                    "  Provider (e.g. ollama, anthropic, openai, Enter for cloud default): ",
+                );
+                let provider = if provider_in.is_empty() {
+                    None
+                } else {
+                    Some(provider_in)
+                };
+                let base_url_in =
+                    prompt_line("  Base URL for provider (optional, Enter to skip): ");
+                let base_url = if base_url_in.is_empty() {
+                    None
+                } else {
+                    Some(base_url_in)
+                };
+                let type_in = prompt_line("  Type (local or cloud, Enter for cloud): ");
+                let agent_type = if type_in.is_empty() {
+                    None
+                } else {
+                    Some(type_in)
+                };
+                let port_in = prompt_line("  Port for opencode serve (Enter for default 4096): ");
+                let port = if port_in.is_empty() {
+                    None
+                } else {
+                    port_in.parse().ok()
+                };
+                let enabled = Some(true);
+                match add_agent_to_config(
+                    &mut cfg,
+                    name.clone(),
+                    image,
+                    agent_type,
+                    model,
+                    small_model,
+                    provider,
+                    base_url,
+                    port,
+                    enabled,
+                    None,
+                    None,
+                    None,
+                ) {
+                    Ok(()) => println!("  {} Added agent '{}'", BULLET_GREEN, name),
+                    Err(e) => eprintln!("  {} {}", BULLET_RED, e),
+                }
+                save_config(&cfg).unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                });
+                if !prompt_yn("Add another agent?", false) {
+                    break;
+                }
+            },
+        }
     }
     println!();
     // --- Projects ---
@@ -376,114 +614,165 @@ pub async fn handle_install() {
         }
     }
     println!();
-    match prompt_yn_skip("Add any projects?") {
-        None => println!("  Skipping projects."),
-        Some(false) => {}
-        Some(true) => loop {
-            let name = prompt_line("  Project name: ");
-            if name.is_empty() {
-                println!("  Project name cannot be empty.");
-                continue;
-            }
-            let repo = prompt_line("  Repository (URL or path): ");
-            if repo.is_empty() {
-                println!("  Repository is required.");
-                continue;
+    if !interactive {
+        let default_model = cfg
+            .current_agent
+            .clone()
+            .unwrap_or_else(|| DEFAULT_AGENT_NAME.to_string());
+        for mut project in answers.projects {
+            let name = project.name.clone();
+            if project.model.is_none() {
+                project.model = Some(default_model.clone());
             }
-            let image_in = prompt_line("  Image (optional, Enter to skip): ");
-            let image = if image_in.is_empty() {
-                None
-            } else {
-                Some(image_in)
-            };
-            let ssh_key_in = prompt_line("  SSH key path (optional): ");
-            let ssh_key = if ssh_key_in.is_empty() {
-                None
-            } else {
-                Some(ssh_key_in)
-            };
-            let base_in = prompt_line("  Base branch [main]: ");
-            let base_branch = if base_in.is_empty() {
-                None
-            } else {
-                Some(base_in)
-            };
-            let remote_in = prompt_line("  Remote name [origin]: ");
-            let remote = if remote_in.is_empty() {
-                None
-            } else {
-                Some(remote_in)
-            };
-            let token_in = prompt_line("  GitHub token for PRs (optional): ");
-            let github_token = if token_in.is_empty() {
-                None
-            } else {
-                Some(token_in)
-            };
-            let script_in = prompt_line("  Script to run in container (optional, e.g. curl -fsSL https://opencode.ai/install | sh): ");
-            let script = if script_in.is_empty() {
-                None
-            } else {
-                Some(script_in)
-            };
-            let model_in = prompt_line(&format!(
-                "  Model profile for this project [{}]: ",
-                cfg.current_agent.as_deref().unwrap_or(DEFAULT_AGENT_NAME)
-            ));
-            let model = if model_in.is_empty() {
-                cfg.current_agent
-                    .clone()
-                    .or_else(|| Some(DEFAULT_AGENT_NAME.to_string()))
-            } else {
-                Some(model_in)
-            };
-            let project = ProjectConfig {
-                name: name.clone(),
-                repo,
-                image,
-                ssh_key,
-                base_branch,
-                remote,
-                github_token,
-                script,
-                commit_name: None,
-                commit_email: None,
-                model,
-                ask_setup_run: None,
-                ask_setup_check: None,
-                ask_execute_run: None,
-                ask_execute_check: None,
-                ask_validate_run: None,
-                ask_validate_check: None,
-                dev_setup_run: None,
-                dev_setup_check: None,
-                dev_execute_run: None,
-                dev_execute_check: None,
-                dev_validate_run: None,
-                dev_validate_check: None,
-                dev_commit_run: None,
-                dev_commit_check: None,
-                review_setup_run: None,
-                review_setup_check: None,
-                review_execute_run: None,
-                review_execute_check: None,
-                review_validate_run: None,
-                review_validate_check: None,
-            };
             match add_project_to_config(&mut cfg, project) {
-                Ok(()) => {
-                    save_config(&cfg).unwrap_or_else(|e| {
-                        eprintln!("Error: {}", e);
-                        std::process::exit(1);
-                    });
-                    println!("  {} Added project '{}'", BULLET_GREEN, name);
-                }
-                Err(e) => eprintln!("  {} {}", BULLET_RED, e),
-            }
-            if !prompt_yn("Add another project?", false) {
-                break;
+                Ok(()) => println!("  {} Added project '{}'", BULLET_GREEN, name),
+                Err(e) => println!("  {} {} (skipped)", BULLET_YELLOW, e),
             }
-        },
+        }
+        save_config(&cfg).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+    } else {
+        match prompt_yn_skip("Add any projects?") {
+            None => println!("  Skipping projects."),
+            Some(false) => {}
+            Some(true) => loop {
+                let name = prompt_line("  Project name: ");
+                if name.is_empty() {
+                    println!("  Project name cannot be empty.");
+                    continue;
+                }
+                let repo = prompt_line("  Repository (URL or path): ");
+                if repo.is_empty() {
+                    println!("  Repository is required.");
+                    continue;
+                }
+                let image_in = prompt_line("  Image (optional, Enter to skip): ");
+                let image = if image_in.is_empty() {
+                    None
+                } else {
+                    Some(image_in)
+                };
+                let ssh_key_in = prompt_line("  SSH key path (optional): ");
+                let ssh_key = if ssh_key_in.is_empty() {
+                    None
+                } else {
+                    Some(ssh_key_in)
+                };
+                let base_in = prompt_line("  Base branch [main]: ");
+                let base_branch = if base_in.is_empty() {
+                    None
+                } else {
+                    Some(base_in)
+                };
+                let remote_in = prompt_line("  Remote name [origin]: ");
+                let remote = if remote_in.is_empty() {
+                    None
+                } else {
+                    Some(remote_in)
+                };
+                let token_in = prompt_line("  GitHub token for PRs (optional): ");
+                let github_token = if token_in.is_empty() {
+                    None
+                } else {
+                    Some(token_in)
+                };
+                let script_in = prompt_line("  Script to run in container (optional, e.g. curl -fsSL https://opencode.ai/install | sh): ");
+                let script = if script_in.is_empty() {
+                    None
+                } else {
+                    Some(script_in)
+                };
+                let model_in = prompt_line(&format!(
+                    "  Model profile for this project [{}]: ",
+                    cfg.current_agent.as_deref().unwrap_or(DEFAULT_AGENT_NAME)
+                ));
+                let model = if model_in.is_empty() {
+                    cfg.current_agent
+                        .clone()
+                        .or_else(|| Some(DEFAULT_AGENT_NAME.to_string()))
+                } else {
+                    Some(model_in)
+                };
+                let project = ProjectConfig {
+                    name: name.clone(),
+                    repo,
+                    subdir: None,
+                    image,
+                    ssh_key,
+                    base_branch,
+                    remote,
+                    github_token,
+                    forge: None,
+                    forge_url: None,
+                    github_api_url: None,
+                    github_app_id: None,
+                    github_app_key: None,
+                    script,
+                    commit_name: None,
+                    commit_email: None,
+                    model,
+                    pre_prompt_hook: None,
+                    pre_push_hook: None,
+                    sandbox: None,
+                    network: None,
+                    backend: None,
+                    dep_cache: None,
+                    persist_workspace: None,
+                    clone_filter: None,
+                    policy: None,
+                    schedule: None,
+                    commit_lint: None,
+                    secret_scan: None,
+                    preset: None,
+                    co_authors: Vec::new(),
+                    provenance: None,
+                    commit_strategy: None,
+                    sync_base: None,
+                    toolchain: None,
+                    validate_commands: None,
+                    default_timeout: None,
+                    default_verbose: None,
+                    pool_size: None,
+                    mcp: None,
+                    ask_setup_run: None,
+                    ask_setup_check: None,
+                    ask_execute_run: None,
+                    ask_execute_check: None,
+                    ask_validate_run: None,
+                    ask_validate_check: None,
+                    dev_setup_run: None,
+                    dev_setup_check: None,
+                    dev_handoff_run: None,
+                    dev_execute_run: None,
+                    dev_execute_check: None,
+                    dev_validate_run: None,
+                    dev_validate_check: None,
+                    dev_commit_run: None,
+                    dev_commit_check: None,
+                    review_setup_run: None,
+                    review_setup_check: None,
+                    review_execute_run: None,
+                    review_execute_check: None,
+                    review_validate_run: None,
+                    review_validate_check: None,
+                };
+                match add_project_to_config(&mut cfg, project) {
+                    Ok(()) => {
+                        save_config(&cfg).unwrap_or_else(|e| {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        });
+                        println!("  {} Added project '{}'", BULLET_GREEN, name);
+                    }
+                    Err(e) => eprintln!("  {} {}", BULLET_RED, e),
+                }
+                if !prompt_yn("Add another project?", false) {
+                    break;
+                }
+            },
+        }
     }
     println!();
     if let Err(e) = run_install_finish() {
@@ -529,6 +818,21 @@ pub async fn handle_uninstall(force: bool, remove_config: bool, remove_images: b
             Err(e) => eprintln!("    Warning: {}", e),
         }
 
+        println!("  Stopping spawned agent containers...");
+        match docker::stop_all_spawned_containers() {
+            Ok(stopped) => {
+                if stopped.is_empty() {
+                    println!("    (no running spawned containers)");
+                } else {
+                    for name in &stopped {
+                        println!("    {}: stopped", name);
+                    }
+                    println!("    Stopped {} container(s).", stopped.len());
+                }
+            }
+            Err(e) => eprintln!("    Warning: {}", e),
+        }
+
         if docker::is_ollama_running() {
             println!("  Stopping Ollama container...");
             if let Err(e) = docker::stop_ollama_container() {
@@ -540,21 +844,17 @@ pub async fn handle_uninstall(force: bool, remove_config: bool, remove_images: b
 
         if remove_images {
             println!("  Removing Docker images...");
-            let _ = Command::new("docker")
-                .args(["rmi", "-f", "smith/unnamed:latest"])
-                .output();
+            let _ = docker::remove_image("smith/unnamed:latest");
             let cfg = load_config().unwrap_or_default();
             if let Some(agents) = cfg.agents {
                 for agent in &agents {
                     let tag = docker::agent_built_image_tag(&agent.name);
-                    let _ = Command::new("docker").args(["rmi", "-f", &tag]).output();
+                    let _ = docker::remove_image(&tag);
                     println!("    {}: removed", tag);
                 }
             }
             if docker::image_exists("ollama/ollama").unwrap_or(false) {
-                let _ = Command::new("docker")
-                    .args(["rmi", "-f", "ollama/ollama"])
-                    .output();
+                let _ = docker::remove_image("ollama/ollama");
                 println!("    ollama/ollama: removed");
             }
         }
@@ -0,0 +1,180 @@
+//! Commit message lint: Conventional Commits subjects, subject length and issue references;
+//! and the `Co-authored-by` and provenance trailers added to commits smith makes.
+
+use crate::config::CommitLintConfig;
+
+/// Conventional commit types allowed when `types` is not configured.
+pub const DEFAULT_COMMIT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Default number of agent rewrites before the run fails.
+pub const DEFAULT_MAX_REWRITES: u32 = 2;
+
+/// Rule violations of `message` (empty when it passes).
+pub fn lint(cfg: &CommitLintConfig, message: &str) -> Vec<String> {
+    let subject = message.lines().next().unwrap_or_default().trim();
+    let mut violations = Vec::new();
+    if subject.is_empty() {
+        violations.push("subject line is empty".to_string());
+        return violations;
+    }
+    if cfg.conventional == Some(true) {
+        if let Err(e) = check_conventional(cfg, subject) {
+            violations.push(e);
+        }
+    }
+    if let Some(max) = cfg.max_subject_length {
+        let len = subject.chars().count();
+        if len > max {
+            violations.push(format!("subject is {} characters (max {})", len, max));
+        }
+    }
+    if cfg.require_issue == Some(true) && !has_issue_reference(message) {
+        violations.push("no issue reference (expected #123, ABC-123 or an issue URL)".to_string());
+    }
+    violations
+}
+
+/// The configured rules as prompt bullets.
+pub fn describe_rules(cfg: &CommitLintConfig) -> Vec<String> {
+    let mut rules = Vec::new();
+    if cfg.conventional == Some(true) {
+        rules.push(format!(
+            "Subject follows Conventional Commits: `type(optional-scope): description`, type one of: {}",
+            allowed_types(cfg).join(", ")
+        ));
+    }
+    if let Some(max) = cfg.max_subject_length {
+        rules.push(format!("Subject line is at most {} characters", max));
+    }
+    if cfg.require_issue == Some(true) {
+        rules.push("References an issue (#123, ABC-123 or an issue URL)".to_string());
+    }
+    rules
+}
+
+fn allowed_types(cfg: &CommitLintConfig) -> Vec<&str> {
+    if cfg.types.is_empty() {
+        DEFAULT_COMMIT_TYPES.to_vec()
+    } else {
+        cfg.types.iter().map(String::as_str).collect()
+    }
+}
+
+fn check_conventional(cfg: &CommitLintConfig, subject: &str) -> Result<(), String> {
+    let Some((head, description)) = subject.split_once(": ") else {
+        return Err("subject is not `type(scope): description`".to_string());
+    };
+    if description.trim().is_empty() {
+        return Err("subject has no description after the type".to_string());
+    }
+    let head = head.strip_suffix('!').unwrap_or(head);
+    let kind = match head.split_once('(') {
+        Some((kind, scope)) => {
+            let valid_scope = scope
+                .strip_suffix(')')
+                .is_some_and(|s| !s.is_empty() && !s.contains(['(', ')']));
+            if !valid_scope {
+                return Err(format!("invalid scope in '{}'", head));
+            }
+            kind
+        }
+        None => head,
+    };
+    if !allowed_types(cfg).contains(&kind) {
+        return Err(format!(
+            "type '{}' is not one of: {}",
+            kind,
+            allowed_types(cfg).join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// `#123`, `ABC-123` or `.../issues/123` anywhere in the message.
+fn has_issue_reference(message: &str) -> bool {
+    let words =
+        message.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ',' | '[' | ']'));
+    words.into_iter().any(|word| {
+        let word = word.trim_end_matches(['.', ':', ';']);
+        let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        if let Some(number) = word.strip_prefix('#') {
+            return digits(number);
+        }
+        if let Some((_, number)) = word.rsplit_once("/issues/") {
+            return digits(number);
+        }
+        word.split_once('-').is_some_and(|(key, number)| {
+            key.len() >= 2
+                && key.starts_with(|c: char| c.is_ascii_uppercase())
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                && digits(number)
+        })
+    })
+}
+
+/// `Co-authored-by` trailer for `author` ("Name <email>").
+pub fn co_author_trailer(author: &str) -> Result<String, String> {
+    let author = author.trim();
+    let valid = author.split_once('<').is_some_and(|(name, email)| {
+        !name.trim().is_empty()
+            && email
+                .strip_suffix('>')
+                .is_some_and(|e| e.contains('@') && !e.contains(char::is_whitespace))
+    });
+    if !valid {
+        return Err(format!(
+            "invalid co-author '{}' (expected \"Name <email>\")",
+            author
+        ));
+    }
+    Ok(format!("Co-authored-by: {}", author))
+}
+
+/// `Smith-Provenance` trailer naming the smith version, model profile, model and run.
+pub fn provenance_trailer(
+    version: &str,
+    agent: Option<&str>,
+    model: Option<&str>,
+    run_id: &str,
+) -> String {
+    format!(
+        "Smith-Provenance: smith/{} agent={} model={} run={}",
+        version,
+        agent.unwrap_or("default"),
+        model.unwrap_or("default"),
+        run_id
+    )
+}
+
+/// `message` with `trailers` appended as a final paragraph; trailers already present are not
+/// repeated.
+pub fn append_trailers(message: &str, trailers: &[String]) -> String {
+    let message = message.trim_end();
+    let existing: Vec<&str> = message.lines().map(str::trim).collect();
+    let new: Vec<&String> = trailers
+        .iter()
+        .filter(|t| !existing.contains(&t.as_str()))
+        .collect();
+    if new.is_empty() {
+        return message.to_string();
+    }
+    let mut out = message.to_string();
+    let last_paragraph = message.rsplit("\n\n").next().unwrap_or_default();
+    let ends_with_trailers = message.contains("\n\n")
+        && last_paragraph.lines().all(|l| {
+            l.split_once(": ")
+                .is_some_and(|(key, _)| !key.contains(' '))
+        });
+    out.push_str(if ends_with_trailers { "\n" } else { "\n\n" });
+    out.push_str(
+        &new.iter()
+            .map(|t| t.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    out
+}
@@ -0,0 +1,931 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::docker;
+use crate::error::SmithError;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SmithConfig {
+    pub projects: Vec<ProjectConfig>,
+    /// Legacy: global github token (no longer used; PRs use project.github_token)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github: Option<GitHubConfig>,
+    /// Legacy: single agent image (used when agents list is empty)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent: Option<AgentConfig>,
+    /// Named agents (e.g. opencode = OpenCode image). When set, used for resolve.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agents: Option<Vec<AgentEntry>>,
+    /// Default model profile name (used by install/model workflows)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_agent: Option<String>,
+    /// Lifecycle event subscribers (`[[event_subscribers]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_subscribers: Vec<EventSubscriber>,
+    /// Run agent and spawned containers hardened (read-only root, dropped capabilities,
+    /// no-new-privileges, tmpfs workspace); tune with `[hardening]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardened: Option<bool>,
+    /// Overrides for the hardened container defaults (only used when `hardened = true`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardening: Option<HardeningConfig>,
+    /// Cluster settings for projects with `backend = "kubernetes"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kubernetes: Option<KubernetesConfig>,
+    /// Spend limits per project or provider (`[[budgets]]`), checked against the run history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budgets: Vec<BudgetConfig>,
+    /// Host address agent ports are published on (default: 127.0.0.1; "0.0.0.0" exposes them
+    /// on every interface). A model profile's `bind_address` overrides it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<String>,
+    /// Remote machines running smith (`[[nodes]]`) that `--node` schedules work onto
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<NodeConfig>,
+    /// Embedding endpoint and retrieval settings of `smith index` (`[index]`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<IndexConfig>,
+    /// Git author of agent commits when neither the project nor its model profile sets one
+    /// (`[commit]`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<CommitIdentity>,
+}
+
+/// Author name used when no setting names one.
+pub const DEFAULT_COMMIT_NAME: &str = "Smith";
+/// Author email used when no setting names one.
+pub const DEFAULT_COMMIT_EMAIL: &str = "smith@localhost";
+
+/// Git author of agent commits: global `[commit]`, or `[agents.commit]` for one model profile.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct CommitIdentity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Append "[bot]" to the author name, so the commits read as automated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bot: Option<bool>,
+}
+
+/// Resolved commit author of a project's agents, with the setting each part came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitAuthor {
+    /// `None` leaves the default name
+    pub name: Option<String>,
+    /// `None` leaves the default email
+    pub email: Option<String>,
+    /// "project", "model profile", "global" or "default"
+    pub name_from: &'static str,
+    pub email_from: &'static str,
+    /// Where the "[bot]" suffix came from, if the name has one
+    pub bot_from: Option<&'static str>,
+}
+
+/// Commit author of a project's agents. Name and email each come from the project
+/// (`commit_name`/`commit_email`), else its model profile's `[agents.commit]`, else the global
+/// `[commit]`. The profile's `bot` (else the global one) appends "[bot]" to the name.
+pub fn commit_author(cfg: &SmithConfig, project: Option<&ProjectConfig>) -> CommitAuthor {
+    let profile = project
+        .and_then(|p| p.model.as_deref())
+        .and_then(|model| cfg.agents.as_ref()?.iter().find(|a| a.name == model))
+        .and_then(|a| a.commit.as_ref());
+    let global = cfg.commit.as_ref();
+    let set = |v: Option<&String>| v.filter(|s| !s.trim().is_empty()).cloned();
+    let pick = |from_project: Option<&String>,
+                field: fn(&CommitIdentity) -> Option<&String>|
+     -> (Option<String>, &'static str) {
+        if let Some(v) = set(from_project) {
+            (Some(v), "project")
+        } else if let Some(v) = set(profile.and_then(field)) {
+            (Some(v), "model profile")
+        } else if let Some(v) = set(global.and_then(field)) {
+            (Some(v), "global")
+        } else {
+            (None, "default")
+        }
+    };
+    let (mut name, name_from) = pick(project.and_then(|p| p.commit_name.as_ref()), |c| {
+        c.name.as_ref()
+    });
+    let (email, email_from) = pick(project.and_then(|p| p.commit_email.as_ref()), |c| {
+        c.email.as_ref()
+    });
+    let bot_from = match (profile.and_then(|c| c.bot), global.and_then(|c| c.bot)) {
+        (Some(bot), _) => bot.then_some("model profile"),
+        (None, Some(bot)) => bot.then_some("global"),
+        (None, None) => None,
+    };
+    if bot_from.is_some() {
+        let base = name.as_deref().unwrap_or(DEFAULT_COMMIT_NAME);
+        if !base.ends_with("[bot]") {
+            name = Some(format!("{}[bot]", base));
+        }
+    }
+    CommitAuthor {
+        name,
+        email,
+        name_from,
+        email_from,
+        bot_from,
+    }
+}
+
+/// Code index settings; unset fields use the defaults noted on each (a local Ollama).
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct IndexConfig {
+    /// OpenAI-compatible API root serving `/embeddings` (default: http://localhost:11434/v1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_url: Option<String>,
+    /// Embedding model (default: nomic-embed-text)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    /// Host env var holding the endpoint's API key, if it needs one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// Lines per indexed chunk (default: 60)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_lines: Option<usize>,
+    /// Snippets added to a prompt (default: 6)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+    /// Files larger than this many KiB are not indexed (default: 256)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_file_kb: Option<u64>,
+}
+
+/// Spend limit over a calendar week or month. With neither `project` nor `provider` it covers all runs.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct BudgetConfig {
+    /// Project whose runs count against the budget
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Provider whose runs count against the budget (the `provider/` prefix of the model, e.g. "anthropic")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// "weekly" (from Monday 00:00 UTC) or "monthly" (from the 1st, 00:00 UTC)
+    pub period: String,
+    /// Limit in USD
+    pub limit: f64,
+    /// Warn once spend reaches this percentage of the limit (default: 80)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warn_percent: Option<u8>,
+    /// Refuse new runs once the limit is reached, unless `--override-budget` is given (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hard_cap: Option<bool>,
+}
+
+/// Cluster used by projects with `backend = "kubernetes"`; unset fields use kubectl's defaults.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct KubernetesConfig {
+    /// Path to the kubeconfig file (default: $KUBECONFIG or ~/.kube/config)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kubeconfig: Option<String>,
+    /// kubeconfig context (default: the current context)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Namespace for agent pods (default: the context's namespace)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// CPU request and limit for agent pods, e.g. "4"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<String>,
+    /// Memory request and limit for agent pods, e.g. "8Gi"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+}
+
+/// Hardened container settings; unset fields use the defaults noted on each.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct HardeningConfig {
+    /// Read-only root filesystem (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    /// Capabilities to drop (default: ["ALL"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cap_drop: Option<Vec<String>>,
+    /// Capabilities to keep (default: CHOWN, DAC_OVERRIDE, FOWNER, SETUID, SETGID, KILL)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cap_add: Option<Vec<String>>,
+    /// `--security-opt no-new-privileges` (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub no_new_privileges: Option<bool>,
+    /// Size of the tmpfs mounted at /workspace in spawned containers (default: "4g")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_tmpfs_size: Option<String>,
+}
+
+/// Delivers lifecycle events (see `smith_core::events`) to one sink.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct EventSubscriber {
+    /// Sink kind: "log" (JSON lines file), "webhook" (HTTP POST) or "notify" (desktop notification)
+    pub sink: String,
+    /// Log file path or webhook URL (log defaults to <config dir>/events.jsonl)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Event names to deliver (e.g. ["pr_opened"]); empty delivers every event
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+}
+
+/// A remote machine running smith, reached over SSH (see `smith_core::nodes`).
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NodeConfig {
+    pub name: String,
+    /// SSH destination: "user@host", "host" or "ssh://user@host:port"
+    pub endpoint: String,
+    /// "key=value" labels `--node-label` selects on (e.g. "gpu=true")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Most spawned agents to schedule onto the node at once (unset = no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AgentConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AgentRole {
+    /// Mode for this role (e.g., "build", "plan", "ask", "review", "edit")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Model override for this role (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Prompt prefix for this role
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AgentEntry {
+    /// Unique id/name for the agent
+    pub name: String,
+    /// Docker image (e.g. ghcr.io/anomalyco/opencode); custom images allowed
+    pub image: String,
+    /// Agent type: "local" or "cloud" (default: "cloud")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<String>,
+    /// Model to use (e.g. "anthropic/claude-sonnet-4-5", "qwen3:8b")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Smaller model for internal operations (reduces API costs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub small_model: Option<String>,
+    /// Provider name: "ollama", "anthropic", "openai", "openrouter", etc.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Custom base URL (for proxies/custom endpoints)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Port for opencode serve (default: 4096 + index)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Whether this agent is enabled (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Default role name for this agent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_role: Option<String>,
+    /// Roles defined for this agent (keyed by role name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<HashMap<String, AgentRole>>,
+    /// Env passthrough mapping for spawned containers: KEY -> "$HOST_ENV"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+    /// Host env vars forwarded as-is into agent containers when set (e.g. HTTPS_PROXY)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_passthrough: Option<Vec<String>>,
+    /// Extra mounts for agent containers: "host:container[:ro]"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volumes: Option<Vec<String>>,
+    /// Host address the agent's port is published on (default: global `bind_address`, else 127.0.0.1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<String>,
+    /// Health endpoint of the agent server (default: "/global/health")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_path: Option<String>,
+    /// Text a healthy response body contains, case-insensitive (default: "healthy"; "" = any 2xx)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_match: Option<String>,
+    /// Shell command run in the agent's container instead of the HTTP probe; healthy on exit 0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_command: Option<String>,
+    /// OpenCode settings rendered into the agent's `opencode.jsonc` (`smith model config`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opencode: Option<OpencodeSettings>,
+    /// Git author of commits by projects using this profile, unless the project sets its own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<CommitIdentity>,
+}
+
+/// Structured OpenCode settings of a model profile. Tables keep OpenCode's own key names, so
+/// `provider` and `permission` entries are written to `opencode.jsonc` as they are.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct OpencodeSettings {
+    /// Provider entries keyed by provider id (e.g. `options.baseURL`, `models`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider: BTreeMap<String, toml::Value>,
+    /// MCP servers keyed by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcp: BTreeMap<String, McpServer>,
+    /// Tool permissions: "edit", "bash", "webfetch", ... -> "allow", "ask" or "deny" (bash also
+    /// takes a table of command patterns)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub permission: BTreeMap<String, toml::Value>,
+}
+
+/// An MCP server: a local command, or a remote URL.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct McpServer {
+    /// Command and arguments of a local server (e.g. ["npx", "-y", "@modelcontextprotocol/server-github"])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    /// URL of a remote server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Environment of a local server
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment: BTreeMap<String, String>,
+    /// HTTP headers sent to a remote server
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Whether OpenCode starts the server (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GitHubConfig {
+    pub token: String,
+}
+
+/// Per-project agent policy. Denied commands and hosts are enforced inside the agent container;
+/// the diff limit is checked before develop commits. Violations fail the run with a report.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PolicyConfig {
+    /// Command names the agent may not run (e.g. "curl", "docker"); shadowed by a refusing wrapper on PATH
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_commands: Vec<String>,
+    /// Hosts the agent may not reach (resolved to 0.0.0.0 inside the container)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_hosts: Vec<String>,
+    /// Largest diff (added + removed lines) a develop run may commit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_diff_lines: Option<u64>,
+    /// Globs the agent may not modify (e.g. "Cargo.lock", ".github/**", "*.pem")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_paths: Vec<String>,
+    /// When set, the only globs the agent may modify; everything else is protected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_paths: Vec<String>,
+    /// What develop does when the execute stage touched a protected path: "abort" (default) or "revert" (drop those files' changes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_action: Option<String>,
+}
+
+/// Commit message rules checked before develop commits.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct CommitLintConfig {
+    /// Require a Conventional Commits subject: `type(scope)!: description`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conventional: Option<bool>,
+    /// Allowed conventional types (default: feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+    /// Longest allowed subject line, in characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_subject_length: Option<usize>,
+    /// Require an issue reference (`#123`, `ABC-123` or an issue URL)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_issue: Option<bool>,
+    /// How many times the agent may rewrite a failing message (default: 2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rewrites: Option<u32>,
+}
+
+/// When `smith run` pipelines of a project may start, in the host's local time.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ScheduleConfig {
+    /// Windows runs may start in, as "HH:MM-HH:MM" (e.g. "22:00-06:00" wraps past midnight); empty = any time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<String>,
+    /// Days runs may start on ("mon".."sun"); a window past midnight belongs to the day it starts on; empty = every day
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<String>,
+    /// Pipelines the schedule covers ("plan", "develop", "release", "review", "resolve"); empty = all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipelines: Vec<String>,
+    /// "deny" refuses runs while NetworkManager reports a metered connection; "allow" (default) ignores it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metered: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProjectConfig {
+    pub name: String,
+    pub repo: String,
+    /// Directory of the repository this project covers (e.g. "frontend"), so several projects can
+    /// share one monorepo; its branches are pushed as `<project>/<branch>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<String>,
+    /// Base branch to clone and compare against (e.g. main). All actions fetch and use remote ref.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    /// Remote name for fetch/push (e.g. origin). All comparisons use refs on this remote.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// GitHub personal access token for PR creation (--pr). Per-repository.
+    /// Also used as the API token when `forge = "gitea"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    /// Code forge hosting the repository: "github" (default) or "gitea" (Gitea/Forgejo)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forge: Option<String>,
+    /// Base URL of the forge instance (required for gitea, e.g. https://git.example.com)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forge_url: Option<String>,
+    /// GitHub REST API root for GitHub Enterprise Server (e.g. https://ghe.example.com/api/v3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_api_url: Option<String>,
+    /// GitHub App id; with github_app_key, used instead of github_token for PR operations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_app_id: Option<String>,
+    /// Path to the GitHub App private key (PEM)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_app_key: Option<String>,
+    /// Script to run in container before pipeline (e.g., install OpenCode).
+    /// Example: "curl -fsSL https://opencode.ai/install.sh | sh"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Commit author name (overrides local git config)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_name: Option<String>,
+    /// Commit author email (overrides local git config)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_email: Option<String>,
+    /// Model profile name to use for this project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Hook command run before each agent prompt; JSON context on stdin, non-empty stdout replaces the prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_prompt_hook: Option<String>,
+    /// Hook command run before develop commits and pushes; non-zero exit vetoes the push
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_push_hook: Option<String>,
+    /// Sandbox mode for this project: pipelines may clone, plan and review, but never push, open PRs or change containers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<bool>,
+    /// Network for the develop execute/validate stages: "none" (provider endpoint only, via proxy) or "restricted" (also git host and package registries); unset = unrestricted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    /// Execution backend for this project's spawned agent: "docker" (default) or "kubernetes" (see `[kubernetes]`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    /// Mount the per-project dependency cache volume into agent containers (default true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dep_cache: Option<bool>,
+    /// Keep spawned agents' /workspace and /state in named volumes across container restarts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persist_workspace: Option<bool>,
+    /// Partial clone of the agent's workspace: "blob:none" (blobless), "tree:0" (treeless) or "blob:limit=<size>"; git fetches what it left out on demand; unset = full clone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clone_filter: Option<String>,
+    /// Restrictions on what the agent may run, reach and change (`[projects.policy]`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyConfig>,
+    /// When its `smith run` pipelines may start (`[projects.schedule]`); `--ignore-schedule` overrides
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<ScheduleConfig>,
+    /// Rules the develop commit message must satisfy (`[projects.commit_lint]`); the agent rewrites it until it does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_lint: Option<CommitLintConfig>,
+    /// Secret scan of develop diffs before commit: "builtin" (default, regex and entropy rules), "gitleaks" (also runs gitleaks in the agent container) or "off"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_scan: Option<String>,
+    /// Pipeline preset of develop runs: "standard" (default), "strict" or "fast"; `--preset` overrides
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// Co-authors ("Name <email>") added as `Co-authored-by` trailers to commits smith makes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_authors: Vec<String>,
+    /// Add a `Smith-Provenance` trailer (smith version, agent, model, run id) to commits smith makes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<bool>,
+    /// How `run develop` commits: "squash" (default, one commit), "per-file" (one commit per changed file) or "per-step" (the agent groups the changes by plan step)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_strategy: Option<String>,
+    /// Bring `run develop` commits up to date with the base branch before pushing: "rebase" or "merge" (unset: off); the agent resolves conflicts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync_base: Option<String>,
+    /// Detected language toolchain ("rust", "go", "node", "python"); set on `project add` or the first run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+    /// Commands the develop agent runs to validate its changes (suggested from the toolchain)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate_commands: Option<Vec<String>>,
+    /// Limit in seconds for each agent prompt of this project's runs, unless `--timeout` is given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_timeout: Option<u64>,
+    /// Run this project's pipelines with `-v` output unless a verbosity flag is given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_verbose: Option<bool>,
+    /// Warm pool containers `smith pool start` keeps ready for this project; claimed ones are
+    /// replaced in the background
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_size: Option<u32>,
+    /// MCP servers given to the project's spawned agents, added to the model profile's (a
+    /// server of the same name replaces the profile's); `$NAME` env and header values come from the host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mcp: Option<BTreeMap<String, McpServer>>,
+    /// Pipeline step: ask.setup.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask_setup_run: Option<String>,
+    /// Pipeline step: ask.setup.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask_setup_check: Option<String>,
+    /// Pipeline step: ask.execute.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask_execute_run: Option<String>,
+    /// Pipeline step: ask.execute.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask_execute_check: Option<String>,
+    /// Pipeline step: ask.validate.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask_validate_run: Option<String>,
+    /// Pipeline step: ask.validate.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask_validate_check: Option<String>,
+    /// Pipeline step: dev.setup.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_setup_run: Option<String>,
+    /// Pipeline step: dev.setup.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_setup_check: Option<String>,
+    /// Pipeline step: dev.handoff.run ("[profile:]role"); when set, this agent writes a structured
+    /// handoff (approach, files, steps) that the dev.execute agent implements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_handoff_run: Option<String>,
+    /// Pipeline step: dev.execute.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_execute_run: Option<String>,
+    /// Pipeline step: dev.execute.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_execute_check: Option<String>,
+    /// Pipeline step: dev.validate.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_validate_run: Option<String>,
+    /// Pipeline step: dev.validate.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_validate_check: Option<String>,
+    /// Pipeline step: dev.commit.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_commit_run: Option<String>,
+    /// Pipeline step: dev.commit.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dev_commit_check: Option<String>,
+    /// Pipeline step: review.setup.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_setup_run: Option<String>,
+    /// Pipeline step: review.setup.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_setup_check: Option<String>,
+    /// Pipeline step: review.execute.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_execute_run: Option<String>,
+    /// Pipeline step: review.execute.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_execute_check: Option<String>,
+    /// Pipeline step: review.validate.run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_validate_run: Option<String>,
+    /// Pipeline step: review.validate.check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_validate_check: Option<String>,
+}
+
+impl ProjectConfig {
+    /// Repository-relative directory the project is scoped to, without surrounding slashes.
+    pub fn scope(&self) -> Option<&str> {
+        self.subdir
+            .as_deref()
+            .map(|d| d.trim().trim_start_matches("./").trim_matches('/'))
+            .filter(|d| !d.is_empty() && *d != ".")
+    }
+
+    /// Remote branch for the pipeline branch `branch`: path-scoped projects namespace theirs as
+    /// `<project>/<branch>` so sub-projects of one repository never push to each other's branches.
+    pub fn git_branch(&self, branch: &str) -> String {
+        let prefix = format!("{}/", self.name);
+        if self.scope().is_none() || branch.starts_with(&prefix) {
+            branch.to_string()
+        } else {
+            format!("{}{}", prefix, branch)
+        }
+    }
+}
+
+/// Resolve port for an agent: port if set, else OPENCODE_SERVER_PORT + index.
+pub fn agent_port(entry: &AgentEntry, index: usize) -> u16 {
+    entry
+        .port
+        .unwrap_or_else(|| docker::OPENCODE_SERVER_PORT + index as u16)
+}
+
+/// Environment variable pointing smith at another config file or directory (set by `--config`).
+pub const CONFIG_ENV: &str = "SMITH_CONFIG";
+
+/// Config directory and file for a `SMITH_CONFIG` path: a `.toml` path or an existing file is
+/// the config file (its directory holds the run history, audit log and other state); anything
+/// else is a directory holding `config.toml`.
+pub fn config_override_paths(path: &Path) -> (PathBuf, PathBuf) {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let is_file =
+        path.is_file() || (!path.is_dir() && path.extension().is_some_and(|ext| ext == "toml"));
+    if is_file {
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        (dir, path)
+    } else {
+        let file = path.join("config.toml");
+        (path, file)
+    }
+}
+
+fn config_override() -> Option<(PathBuf, PathBuf)> {
+    std::env::var_os(CONFIG_ENV)
+        .filter(|v| !v.is_empty())
+        .map(|v| config_override_paths(Path::new(&v)))
+}
+
+/// Smith configuration directory (platform config dir for com.agent.smith, or `SMITH_CONFIG`).
+pub fn config_dir() -> Result<PathBuf, SmithError> {
+    if let Some((dir, _)) = config_override() {
+        return Ok(dir);
+    }
+    ProjectDirs::from("com", "agent", "smith")
+        .ok_or_else(|| SmithError::Config("Could not determine config directory".to_string()))
+        .map(|dirs| dirs.config_dir().to_path_buf())
+}
+
+/// Cache directory (e.g. ~/.cache/smith); safe to delete, everything in it is rebuilt on demand.
+/// With `SMITH_CONFIG` it is a subdirectory of its own (see [`config_cache_dir`]).
+pub fn cache_dir() -> Result<PathBuf, SmithError> {
+    let base = ProjectDirs::from("com", "agent", "smith")
+        .ok_or_else(|| SmithError::Config("Could not determine cache directory".to_string()))
+        .map(|dirs| dirs.cache_dir().to_path_buf())?;
+    Ok(match config_override() {
+        Some((_, file)) => config_cache_dir(&base, &file),
+        None => base,
+    })
+}
+
+/// Cache directory of an alternate config file: `<cache>/configs/<hash of its path>`, so
+/// indexes and mirrors of same-named projects in different configs never mix.
+pub fn config_cache_dir(base: &Path, config_file: &Path) -> PathBuf {
+    let hash = crate::util::fnv1a_64(config_file.to_string_lossy().as_bytes());
+    base.join("configs").join(format!("{:016x}", hash))
+}
+
+/// Bare git mirrors of project repos, one directory per project.
+pub fn git_mirror_dir() -> Result<PathBuf, SmithError> {
+    cache_dir().map(|dir| dir.join("git"))
+}
+
+/// Path to config.toml inside the config directory, or the file `SMITH_CONFIG` names.
+pub fn config_file_path() -> Result<PathBuf, SmithError> {
+    if let Some((_, file)) = config_override() {
+        return Ok(file);
+    }
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Load config.toml, with `SMITH_` environment overrides applied; the default config (plus
+/// overrides) when the file does not exist yet.
+pub fn load_config() -> Result<SmithConfig, SmithError> {
+    let file = config_file_path()?;
+    let overrides = env_overrides(std::env::vars());
+    if !file.exists() && overrides.is_empty() {
+        return Ok(SmithConfig::default());
+    }
+    let mut config = if file.exists() {
+        let content = fs::read_to_string(&file)
+            .map_err(|e| SmithError::Config(format!("Failed to read config: {}", e)))?;
+        toml::from_str(&content)
+            .map_err(|e| SmithError::Config(format!("Failed to parse config: {}", e)))?
+    } else {
+        toml::Value::try_from(SmithConfig::default())
+            .map_err(|e| SmithError::Config(e.to_string()))?
+    };
+    apply_env_overrides(&mut config, &overrides).map_err(SmithError::Config)?;
+    config
+        .try_into()
+        .map_err(|e| SmithError::Config(format!("Failed to parse config: {}", e)))
+}
+
+/// Prefix of environment variables that override config keys at load time.
+pub const ENV_OVERRIDE_PREFIX: &str = "SMITH_";
+
+/// Top-level config keys; other `SMITH_` variables (SMITH_CONFIG, SMITH_PROJECT, ...) are not
+/// overrides.
+const CONFIG_KEYS: &[&str] = &[
+    "projects",
+    "github",
+    "agent",
+    "agents",
+    "current_agent",
+    "event_subscribers",
+    "hardened",
+    "hardening",
+    "kubernetes",
+    "budgets",
+    "bind_address",
+    "nodes",
+    "index",
+    "commit",
+];
+
+/// A config key set from the environment: `SMITH_CURRENT_AGENT=x`, or with `__` between
+/// levels, `SMITH_PROJECTS__MYPROJ__BASE_BRANCH=develop` (list entries are picked by name).
+#[derive(Debug, PartialEq)]
+pub struct EnvOverride {
+    pub var: String,
+    /// Lowercased key path
+    pub path: Vec<String>,
+    pub value: String,
+}
+
+/// The config overrides among `vars`, sorted by variable name.
+pub fn env_overrides(vars: impl IntoIterator<Item = (String, String)>) -> Vec<EnvOverride> {
+    let mut overrides: Vec<EnvOverride> = vars
+        .into_iter()
+        .filter_map(|(var, value)| {
+            let path: Vec<String> = var
+                .strip_prefix(ENV_OVERRIDE_PREFIX)?
+                .split("__")
+                .map(str::to_lowercase)
+                .collect();
+            if path.iter().any(String::is_empty) || !CONFIG_KEYS.contains(&path[0].as_str()) {
+                return None;
+            }
+            Some(EnvOverride { var, path, value })
+        })
+        .collect();
+    overrides.sort_by(|a, b| a.var.cmp(&b.var));
+    overrides
+}
+
+/// Set each override's key in a parsed config.
+pub fn apply_env_overrides(
+    config: &mut toml::Value,
+    overrides: &[EnvOverride],
+) -> Result<(), String> {
+    for o in overrides {
+        let Some((key, parents)) = o.path.split_last() else {
+            continue;
+        };
+        let table = override_parent(config, parents, true)
+            .map_err(|e| format!("Invalid {}: {}", o.var, e))?;
+        let value = override_value(&o.value, table.get(key.as_str()));
+        table.insert(key.clone(), value);
+    }
+    Ok(())
+}
+
+/// Undo the overrides in `config` (about to be saved) wherever it still holds the overridden
+/// value, so saving never writes the environment into the file: keys go back to their value in
+/// `file` (the config on disk), or are removed if it had none.
+pub fn restore_env_overrides(
+    config: &mut toml::Value,
+    file: &toml::Value,
+    overrides: &[EnvOverride],
+) {
+    for o in overrides {
+        let Some((key, parents)) = o.path.split_last() else {
+            continue;
+        };
+        let mut file = file.clone();
+        let original = override_parent(&mut file, parents, false)
+            .ok()
+            .and_then(|t| t.get(key.as_str()).cloned());
+        let Ok(table) = override_parent(config, parents, false) else {
+            continue;
+        };
+        if table.get(key.as_str()) != Some(&override_value(&o.value, original.as_ref())) {
+            continue;
+        }
+        match original {
+            Some(v) => table.insert(key.clone(), v),
+            None => table.remove(key.as_str()),
+        };
+    }
+}
+
+/// Table holding the last key of an override path. A list (e.g. `projects`) is indexed by the
+/// `name` of its entries, compared case-insensitively with `-` read as `_`. With `create`,
+/// missing tables are added.
+fn override_parent<'a>(
+    config: &'a mut toml::Value,
+    parents: &[String],
+    create: bool,
+) -> Result<&'a mut toml::Table, String> {
+    let normalize = |s: &str| s.to_lowercase().replace('-', "_");
+    let mut current = config;
+    for segment in parents {
+        current = match current {
+            toml::Value::Table(table) => {
+                if create && !table.contains_key(segment.as_str()) {
+                    table.insert(segment.clone(), toml::Value::Table(Default::default()));
+                }
+                table
+                    .get_mut(segment.as_str())
+                    .ok_or_else(|| format!("no '{}' key", segment))?
+            }
+            toml::Value::Array(entries) => entries
+                .iter_mut()
+                .find(|e| {
+                    e.get("name")
+                        .and_then(|n| n.as_str())
+                        .is_some_and(|n| normalize(n) == normalize(segment))
+                })
+                .ok_or_else(|| format!("no entry named '{}'", segment))?,
+            _ => return Err(format!("'{}' is not a table or list", segment)),
+        };
+    }
+    match current {
+        toml::Value::Table(table) => Ok(table),
+        _ => Err("the key's parent is not a table".to_string()),
+    }
+}
+
+/// TOML value of an override: kept as a string when the key holds one, else parsed as a TOML
+/// value (`true`, `8080`, `["a", "b"]`) with a plain string as fallback.
+fn override_value(raw: &str, existing: Option<&toml::Value>) -> toml::Value {
+    if matches!(existing, Some(toml::Value::String(_))) {
+        return toml::Value::String(raw.to_string());
+    }
+    toml::from_str::<toml::Table>(&format!("v = {}", raw))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Write config.toml atomically (temp file + rename).
+pub fn save_config(config: &SmithConfig) -> Result<(), SmithError> {
+    let config_err =
+        |msg: &str, e: &dyn std::fmt::Display| SmithError::Config(format!("{}: {}", msg, e));
+    let dir = config_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| config_err("Failed to create config directory", &e))?;
+    let file = config_file_path()?;
+    let previous: toml::Value = fs::read_to_string(&file)
+        .ok()
+        .and_then(|raw| toml::from_str(&raw).ok())
+        .unwrap_or_else(|| toml::Value::Table(Default::default()));
+    let overrides = env_overrides(std::env::vars());
+    let content = if overrides.is_empty() {
+        toml::to_string_pretty(config)
+    } else {
+        let mut value = toml::Value::try_from(config)
+            .map_err(|e| config_err("Failed to serialize config", &e))?;
+        restore_env_overrides(&mut value, &previous, &overrides);
+        toml::to_string_pretty(&value)
+    }
+    .map_err(|e| config_err("Failed to serialize config", &e))?;
+
+    // Atomic write: write to temp file then rename. On EXDEV (cross-filesystem), fall back to copy + remove.
+    let temp_file = file.with_extension("toml.tmp");
+    fs::write(&temp_file, &content).map_err(|e| config_err("Failed to write config", &e))?;
+    if let Err(e) = fs::rename(&temp_file, &file) {
+        // EXDEV = cross-filesystem rename not supported (MSRV 1.83: avoid ErrorKind::CrossesDevices)
+        let is_cross_device = e.raw_os_error() == Some(libc::EXDEV);
+        if is_cross_device {
+            fs::copy(&temp_file, &file).map_err(|e| config_err("Failed to copy config", &e))?;
+            fs::remove_file(&temp_file)
+                .map_err(|e| config_err("Failed to remove temp config", &e))?;
+        } else {
+            return Err(config_err("Failed to finalize config", &e));
+        }
+    }
+    let changed = toml::from_str(&content)
+        .map(|current| crate::audit::changed_config_keys(&previous, &current))
+        .unwrap_or_default();
+    if !changed.is_empty() {
+        crate::audit::record(
+            "config_change",
+            &file.display().to_string(),
+            Some(changed.join(", ")),
+        );
+    }
+    Ok(())
+}
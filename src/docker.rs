@@ -5,21 +5,49 @@ use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::Path;
 use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{mpsc, Once};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::Deserialize;
 use serde_json::Value;
 
-/// Prefix for agent containers: "smith-agent-<name>". Used by agent start/stop/list.
+use crate::audit;
+use crate::config::{load_config, AgentEntry, SmithConfig};
+use crate::error::SmithError;
+use crate::logging::LogCommand;
+use crate::replay::{self, Recorded, Tape};
+use crate::simulate;
+use crate::usage;
+
+/// Prefix for agent containers: "smith-agent-<name>". Used by agent start/stop; listing
+/// goes by [`LABEL_KIND`].
 pub const AGENT_CONTAINER_PREFIX: &str = "smith-agent-";
 
 /// Prefix for spawned containers: "agent_{project}_{branch}".
 pub const SPAWN_CONTAINER_PREFIX: &str = "agent_";
 
+/// Labels on every container smith starts; listing and cleanup filter on these, not on names.
+pub const LABEL_MANAGED: &str = "smith.managed";
+/// "agent" (model profile container) or "spawned" (project/branch agent).
+pub const LABEL_KIND: &str = "smith.kind";
+pub const LABEL_AGENT: &str = "smith.agent";
+pub const LABEL_PROJECT: &str = "smith.project";
+pub const LABEL_BRANCH: &str = "smith.branch";
+pub const LABEL_PORT: &str = "smith.port";
+
+/// `--label` arguments marking a container as smith's, plus `labels`.
+pub fn label_args(kind: &str, labels: &[(&str, &str)]) -> Vec<String> {
+    [(LABEL_MANAGED, "true"), (LABEL_KIND, kind)]
+        .iter()
+        .chain(labels)
+        .flat_map(|(key, value)| ["--label".to_string(), format!("{}={}", key, value)])
+        .collect()
+}
+
 /// Port range for spawned containers: 4096-8191.
 pub const SPAWN_PORT_MIN: u16 = 4096;
 pub const SPAWN_PORT_MAX: u16 = 8191;
@@ -33,7 +61,35 @@ pub fn host_opencode_config_dir() -> Option<std::path::PathBuf> {
 }
 
 static SPAWN_RUN_CANCELLED: AtomicBool = AtomicBool::new(false);
+/// Tokens reported by prompts run in spawned containers since the last [`take_prompt_usage`].
+static PROMPT_TOKENS: AtomicU64 = AtomicU64::new(0);
+/// Provider cost of those prompts, in millionths of a dollar.
+static PROMPT_COST_MICROS: AtomicU64 = AtomicU64::new(0);
 static SPAWN_RUN_SIGINT_INIT: Once = Once::new();
+/// Limit for each prompt run in a spawned container, in seconds (0 = none).
+static PROMPT_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+
+/// Limit each prompt run in spawned containers to `secs` seconds (`--timeout`, or the project's
+/// `default_timeout`); `None` removes the limit.
+pub fn set_prompt_timeout(secs: Option<u64>) {
+    PROMPT_TIMEOUT_SECS.store(secs.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// The prompt limit, if one is set.
+pub fn prompt_timeout() -> Option<Duration> {
+    match PROMPT_TIMEOUT_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+/// Error for a prompt stopped by the prompt limit.
+fn prompt_timeout_error(limit: Duration) -> String {
+    format!(
+        "Agent prompt timed out after {}s; raise it with --timeout or the project's default_timeout",
+        limit.as_secs()
+    )
+}
 
 fn ensure_spawn_run_sigint_handler() {
     SPAWN_RUN_SIGINT_INIT.call_once(|| {
@@ -62,10 +118,8 @@ fn extract_string_field(value: Option<&Value>) -> Option<String> {
 
 fn collect_text_parts(value: &Value, out: &mut Vec<String>) {
     match value {
-        Value::String(s) => {
-            if !s.is_empty() {
-                out.push(s.to_string());
-            }
+        Value::String(s) if !s.is_empty() => {
+            out.push(s.to_string());
         }
         Value::Array(arr) => {
             for item in arr {
@@ -201,20 +255,284 @@ fn provider_api_key_env(provider: &str) -> String {
     format!("{}_API_KEY", normalized)
 }
 
+/// Capabilities kept in hardened containers unless `[hardening].cap_add` overrides them:
+/// enough for a root process to manage its own files, switch users and signal children.
+const HARDENED_CAP_ADD: &[&str] = &[
+    "CHOWN",
+    "DAC_OVERRIDE",
+    "FOWNER",
+    "SETUID",
+    "SETGID",
+    "KILL",
+];
+
+/// Extra `docker run` arguments for `hardened = true`; empty when hardening is off.
+/// With a read-only root, /tmp and /root are tmpfs. Spawned containers (`workspace`) also get
+/// a tmpfs /workspace and a volume at /state so plan state survives restarts.
+pub fn hardening_args(config: &SmithConfig, workspace: bool) -> Vec<String> {
+    if config.hardened != Some(true) {
+        return Vec::new();
+    }
+    let h = config.hardening.clone().unwrap_or_default();
+    let mut args = Vec::new();
+    if h.read_only.unwrap_or(true) {
+        args.push("--read-only".to_string());
+        args.extend([
+            "--tmpfs".to_string(),
+            "/tmp:rw,exec,size=1g".to_string(),
+            "--tmpfs".to_string(),
+            "/root:rw,exec,size=512m".to_string(),
+        ]);
+        if workspace {
+            args.extend(["-v".to_string(), "/state".to_string()]);
+        }
+    }
+    if workspace {
+        let size = h.workspace_tmpfs_size.as_deref().unwrap_or("4g");
+        args.extend([
+            "--tmpfs".to_string(),
+            format!("/workspace:rw,exec,size={}", size),
+        ]);
+    }
+    for cap in h.cap_drop.unwrap_or_else(|| vec!["ALL".to_string()]) {
+        args.extend(["--cap-drop".to_string(), cap]);
+    }
+    let cap_add = h
+        .cap_add
+        .unwrap_or_else(|| HARDENED_CAP_ADD.iter().map(|c| c.to_string()).collect());
+    for cap in cap_add {
+        args.extend(["--cap-add".to_string(), cap]);
+    }
+    if h.no_new_privileges.unwrap_or(true) {
+        args.extend([
+            "--security-opt".to_string(),
+            "no-new-privileges".to_string(),
+        ]);
+    }
+    args
+}
+
+/// `-v` arguments for an agent's `volumes` ("host:container[:ro|rw]"). The host side is an
+/// absolute path, a `~/` path or a named volume; the container side must be absolute.
+pub fn agent_volume_args(volumes: &[String]) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    for spec in volumes.iter().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let (host, container, mode) = match parts.as_slice() {
+            [host, container] => (*host, *container, None),
+            [host, container, mode] => (*host, *container, Some(*mode)),
+            _ => {
+                return Err(format!(
+                    "Invalid volume '{}': expected host:container[:ro]",
+                    spec
+                ))
+            }
+        };
+        let host = if let Some(rest) = host.strip_prefix("~/") {
+            dirs::home_dir()
+                .ok_or_else(|| format!("Invalid volume '{}': no home directory", spec))?
+                .join(rest)
+                .to_string_lossy()
+                .into_owned()
+        } else if host.starts_with('/')
+            || (!host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')))
+        {
+            host.to_string()
+        } else {
+            return Err(format!(
+                "Invalid volume '{}': host side must be an absolute path, ~/path or volume name",
+                spec
+            ));
+        };
+        if !container.starts_with('/') {
+            return Err(format!(
+                "Invalid volume '{}': container path must be absolute",
+                spec
+            ));
+        }
+        let mode = match mode {
+            None => String::new(),
+            Some(m @ ("ro" | "rw")) => format!(":{}", m),
+            Some(m) => {
+                return Err(format!(
+                    "Invalid volume '{}': unknown mode '{}' (use ro or rw)",
+                    spec, m
+                ))
+            }
+        };
+        args.extend(["-v".to_string(), format!("{}:{}{}", host, container, mode)]);
+    }
+    Ok(args)
+}
+
+/// Host address agent ports are published on unless configured otherwise: loopback, so agent
+/// servers are not reachable from the LAN.
+pub const DEFAULT_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/// Parse a `bind_address` setting (an IPv4 or IPv6 address, brackets optional).
+pub fn parse_bind_address(value: &str) -> Result<IpAddr, String> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .map_err(|_| {
+            format!(
+                "Invalid bind address '{}': expected an IP address such as 127.0.0.1 or 0.0.0.0",
+                value
+            )
+        })
+}
+
+/// Address an agent's port is published on: the profile's `bind_address`, else the global one,
+/// else [`DEFAULT_BIND_ADDRESS`]. An invalid setting is reported and falls back to the default.
+pub fn agent_bind_address(cfg: &SmithConfig, agent: Option<&AgentEntry>) -> IpAddr {
+    let Some(value) = agent
+        .and_then(|a| a.bind_address.as_deref())
+        .or(cfg.bind_address.as_deref())
+        .filter(|v| !v.trim().is_empty())
+    else {
+        return DEFAULT_BIND_ADDRESS;
+    };
+    parse_bind_address(value).unwrap_or_else(|e| {
+        tracing::warn!("{}; using {}", e, DEFAULT_BIND_ADDRESS);
+        DEFAULT_BIND_ADDRESS
+    })
+}
+
+/// Remote branch of `project` for the pipeline branch `branch` (see [`ProjectConfig::git_branch`]).
+pub fn project_git_branch(cfg: &SmithConfig, project: &str, branch: &str) -> String {
+    cfg.projects
+        .iter()
+        .find(|p| p.name == project)
+        .map_or_else(|| branch.to_string(), |p| p.git_branch(branch))
+}
+
+/// Model profile a project's spawned agents run with (its `model`), if configured.
+pub fn project_profile<'a>(cfg: &'a SmithConfig, project: &str) -> Option<&'a AgentEntry> {
+    let model = cfg
+        .projects
+        .iter()
+        .find(|p| p.name == project)?
+        .model
+        .as_deref()?;
+    cfg.agents.as_ref()?.iter().find(|a| a.name == model)
+}
+
+/// `-p` value publishing `port` on `bind` (e.g. "127.0.0.1:4096:4096").
+pub fn publish_arg(bind: IpAddr, port: u16) -> String {
+    match bind {
+        IpAddr::V4(ip) => format!("{}:{}:{}", ip, port, port),
+        IpAddr::V6(ip) => format!("[{}]:{}:{}", ip, port, port),
+    }
+}
+
+/// Address to reach an agent published on `bind`: loopback when it listens on every interface.
+pub fn probe_address(bind: IpAddr) -> IpAddr {
+    match bind {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    }
+}
+
+/// Whether `port` can be published: nothing on the host listens on it on any interface.
+pub fn host_port_free(port: u16) -> bool {
+    std::net::TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+/// Best-effort description of what holds `port`: the container publishing it, else the
+/// listening process (from `ss`, or `lsof` where `ss` is missing).
+pub fn port_owner(port: u16) -> String {
+    let container = Command::new("docker")
+        .args([
+            "ps",
+            "--filter",
+            &format!("publish={}", port),
+            "--format",
+            "{{.Names}}",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .next()
+                .map(|n| n.trim().to_string())
+        })
+        .filter(|n| !n.is_empty());
+    if let Some(name) = container {
+        return format!("container {}", name);
+    }
+    let ss = Command::new("ss")
+        .args(["-Hltnp", &format!("sport = :{}", port)])
+        .output()
+        .ok()
+        .and_then(|o| parse_ss_owner(&String::from_utf8_lossy(&o.stdout)));
+    if let Some(owner) = ss {
+        return owner;
+    }
+    Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fpc"])
+        .output()
+        .ok()
+        .and_then(|o| parse_lsof_owner(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_else(|| "another process".to_string())
+}
+
+/// `users:(("python3",pid=4242,fd=3))` in `ss -p` output -> "python3 (pid 4242)".
+fn parse_ss_owner(output: &str) -> Option<String> {
+    let users = output.split("users:((").nth(1)?;
+    let mut fields = users.split(',');
+    let name = fields.next()?.trim_matches('"');
+    let pid = fields.find_map(|f| f.strip_prefix("pid="))?;
+    Some(format!("{} (pid {})", name, pid))
+}
+
+/// `-Fpc` output of lsof: a `p<pid>` line followed by a `c<command>` line.
+fn parse_lsof_owner(output: &str) -> Option<String> {
+    let pid = output.lines().find_map(|l| l.strip_prefix('p'))?;
+    let name = output.lines().find_map(|l| l.strip_prefix('c'))?;
+    Some(format!("{} (pid {})", name, pid))
+}
+
+/// First port after `port` that is free on the host and not in `reserved` (ports configured
+/// for other agents).
+pub fn next_free_port(port: u16, reserved: &[u16]) -> Option<u16> {
+    (port.checked_add(1)?..=u16::MAX).find(|p| !reserved.contains(p) && host_port_free(*p))
+}
+
 /// Start an agent container running OpenCode in server mode. Exposes `port` on the host.
 /// Uses `--entrypoint opencode` so the container runs exactly `opencode serve --hostname 0.0.0.0 --port N`.
 /// If a container with this name already exists, tries to start it (e.g. after stop).
 /// If provider is Some, passes through the corresponding API key env var from host to container.
 /// If base_url is Some, passes OPENCODE_BASE_URL env var to container.
-pub fn start_agent_container(
+/// `hardening` is extra run arguments from [`hardening_args`].
+pub async fn start_agent_container(
     agent_name: &str,
     image: &str,
     port: u16,
     provider: Option<&str>,
     base_url: Option<&str>,
+    env_passthrough: &[String],
+    hardening: &[String],
 ) -> Result<(), String> {
     let name = agent_container_name(agent_name);
     let port_str = port.to_string();
+    let cfg = load_config().unwrap_or_default();
+    let publish = publish_arg(
+        agent_bind_address(
+            &cfg,
+            cfg.agents
+                .as_ref()
+                .and_then(|agents| agents.iter().find(|a| a.name == agent_name)),
+        ),
+        port,
+    );
 
     let mut args = vec![
         "run".to_string(),
@@ -224,8 +542,12 @@ pub fn start_agent_container(
         "--name".to_string(),
         name.clone(),
         "-p".to_string(),
-        format!("{}:{}", port, port),
+        publish.clone(),
     ];
+    args.extend(label_args(
+        "agent",
+        &[(LABEL_AGENT, agent_name), (LABEL_PORT, &port_str)],
+    ));
 
     if let Some(cfg_dir) = host_opencode_config_dir().filter(|p| p.exists()) {
         args.extend([
@@ -239,12 +561,17 @@ pub fn start_agent_container(
         args.push("-e".to_string());
         args.push(env_var);
     }
+    // `-e NAME` forwards the host value when it is set.
+    for var in env_passthrough {
+        args.extend(["-e".to_string(), var.clone()]);
+    }
 
     if let Some(url) = base_url {
         args.push("-e".to_string());
         args.push(format!("OPENCODE_BASE_URL={}", url));
     }
 
+    args.extend(hardening.iter().cloned());
     args.extend([
         "--entrypoint".to_string(),
         "opencode".to_string(),
@@ -256,16 +583,22 @@ pub fn start_agent_container(
         port_str.clone(),
     ]);
 
-    let run = Command::new("docker")
+    if let Err(e) = api::ensure_image(image).await {
+        eprintln!("  Warning: {}", e);
+    }
+    let run = tokio::process::Command::new("docker")
         .args(&args)
+        .logged()
         .output()
+        .await
         .map_err(|e| format!("Failed to run container: {}", e))?;
     if run.status.success() {
+        audit::record("container_start", &name, Some(format!("image {}", image)));
         return Ok(());
     }
     let stderr = String::from_utf8_lossy(&run.stderr);
     if stderr.contains("already in use") || stderr.contains("Conflict") {
-        let _ = Command::new("docker").args(["rm", "-f", &name]).output();
+        let _ = api::remove_container(&name, true).await;
 
         let mut args2 = vec![
             "run".to_string(),
@@ -275,8 +608,12 @@ pub fn start_agent_container(
             "--name".to_string(),
             name.clone(),
             "-p".to_string(),
-            format!("{}:{}", port, port),
+            publish,
         ];
+        args2.extend(label_args(
+            "agent",
+            &[(LABEL_AGENT, agent_name), (LABEL_PORT, &port_str)],
+        ));
 
         if let Some(cfg_dir) = host_opencode_config_dir().filter(|p| p.exists()) {
             args2.extend([
@@ -290,7 +627,12 @@ pub fn start_agent_container(
             args2.push("-e".to_string());
             args2.push(env_var);
         }
+        // `-e NAME` forwards the host value when it is set.
+        for var in env_passthrough {
+            args2.extend(["-e".to_string(), var.clone()]);
+        }
 
+        args2.extend(hardening.iter().cloned());
         args2.extend([
             "--entrypoint".to_string(),
             "opencode".to_string(),
@@ -302,11 +644,14 @@ pub fn start_agent_container(
             port_str.clone(),
         ]);
 
-        let run2 = Command::new("docker")
+        let run2 = tokio::process::Command::new("docker")
             .args(&args2)
+            .logged()
             .output()
+            .await
             .map_err(|e| format!("Failed to run container: {}", e))?;
         if run2.status.success() {
+            audit::record("container_start", &name, Some(format!("image {}", image)));
             return Ok(());
         }
         return Err(format!(
@@ -322,56 +667,246 @@ pub fn start_agent_container(
     ))
 }
 
-/// Quick one-shot check: is the agent health endpoint reachable on the given port?
-/// Single request, 2s timeout. Used by status to show warning when container is up but port unreachable.
-pub fn check_agent_reachable(port: u16) -> bool {
-    let url = format!("http://127.0.0.1:{}/global/health", port);
-    let output = Command::new("curl")
-        .args(["-sf", "--max-time", "2", &url])
-        .output();
-    match output {
-        Ok(out) if out.status.success() => {
-            let body = String::from_utf8_lossy(&out.stdout);
-            body.to_lowercase().contains("healthy")
+/// HTTP GET against 127.0.0.1:`port`. `timeout` bounds connecting, each read and the whole
+/// exchange. Returns (status code, body). Enough for local health probes.
+pub async fn http_get_local(
+    port: u16,
+    path: &str,
+    timeout: Duration,
+) -> Result<(u16, String), String> {
+    http_get(IpAddr::V4(Ipv4Addr::LOCALHOST), port, path, timeout).await
+}
+
+/// [`http_get_local`] against `host` instead of loopback.
+pub async fn http_get(
+    host: IpAddr,
+    port: u16,
+    path: &str,
+    timeout: Duration,
+) -> Result<(u16, String), String> {
+    let addr = SocketAddr::new(host, port);
+    // Probes target the host or a container directly, never through HTTP(S)_PROXY.
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .connect_timeout(timeout)
+        .read_timeout(timeout)
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("failed to create HTTP client: {}", e))?;
+    let response = client
+        .get(format!("http://{}{}", addr, path))
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                format!("request to {} timed out after {:?}", addr, timeout)
+            } else if e.is_connect() {
+                format!("connect to {} failed: {}", addr, e)
+            } else {
+                format!("request to {} failed: {}", addr, e)
+            }
+        })?;
+    let status = response.status().as_u16();
+    let body = response.text().await.map_err(|e| {
+        if e.is_timeout() {
+            format!("request to {} timed out after {:?}", addr, timeout)
+        } else {
+            format!("reading response from {} failed: {}", addr, e)
+        }
+    })?;
+    Ok((status, body))
+}
+
+/// How smith decides an agent is up: GET `path` answering 2xx with a body that contains `matches`
+/// (case-insensitive; empty matches any body) or, with `command`, that shell command exiting 0
+/// inside the agent's container. Defaults to OpenCode's `/global/health`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthCheck {
+    pub path: String,
+    pub matches: String,
+    pub command: Option<String>,
+    /// Address HTTP probes connect to (see [`probe_address`])
+    pub host: IpAddr,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        HealthCheck {
+            path: "/global/health".to_string(),
+            matches: "healthy".to_string(),
+            command: None,
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
         }
-        _ => false,
     }
 }
 
-/// Test that an OpenCode server is responding at the given host port (e.g. after start).
-/// Uses GET /global/health; returns Ok if we get a 200 and body contains "healthy".
+impl HealthCheck {
+    /// The agent's `health_command` or `health_path`/`health_match`, falling back to the defaults.
+    pub fn for_agent(agent: Option<&AgentEntry>) -> Self {
+        let default = HealthCheck::default();
+        let path = agent
+            .and_then(|a| a.health_path.as_deref())
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                if p.starts_with('/') {
+                    p.to_string()
+                } else {
+                    format!("/{}", p)
+                }
+            })
+            .unwrap_or(default.path);
+        let matches = agent
+            .and_then(|a| a.health_match.clone())
+            .unwrap_or(default.matches);
+        let command = agent
+            .and_then(|a| a.health_command.as_deref())
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(String::from);
+        let host = probe_address(agent_bind_address(
+            &load_config().unwrap_or_default(),
+            agent,
+        ));
+        HealthCheck {
+            path,
+            matches,
+            command,
+            host,
+        }
+    }
+
+    /// Health check of a project's spawned agent: that of its model profile.
+    pub fn for_project(project: &str) -> Self {
+        let cfg = load_config().ok();
+        HealthCheck::for_agent(cfg.as_ref().and_then(|cfg| project_profile(cfg, project)))
+    }
+
+    pub fn passes(&self, status: u16, body: &str) -> bool {
+        (200..300).contains(&status) && body.to_lowercase().contains(&self.matches.to_lowercase())
+    }
+
+    /// What is checked, for messages: the command or the URL.
+    pub fn describe(&self, port: u16) -> String {
+        match &self.command {
+            Some(command) => format!("`{}`", command),
+            None => format!("http://{}{}", SocketAddr::new(self.host, port), self.path),
+        }
+    }
+
+    /// One check of the agent in `container`, served on host `port`; Err says what failed.
+    pub async fn probe(&self, container: &str, port: u16, timeout: Duration) -> Result<(), String> {
+        if let Some(command) = &self.command {
+            return exec_health(container, command, timeout).await;
+        }
+        match http_get(self.host, port, &self.path, timeout).await {
+            Ok((status, body)) if self.passes(status, &body) => Ok(()),
+            Ok((status, body)) if (200..300).contains(&status) => Err(format!(
+                "response did not contain '{}': {}",
+                self.matches,
+                body.trim()
+            )),
+            Ok((status, _)) => Err(format!("HTTP error: status {}", status)),
+            Err(e) => Err(format!("connection error: {}", e)),
+        }
+    }
+}
+
+/// Run a health command in `container`; healthy when it exits 0 within `timeout`.
+async fn exec_health(container: &str, command: &str, timeout: Duration) -> Result<(), String> {
+    let mut cmd = Command::new("docker");
+    cmd.args(["exec", container, "sh", "-c", command]).logged();
+    let run = tokio::task::spawn_blocking(move || cmd.recorded_output());
+    let output = match tokio::time::timeout(timeout, run).await {
+        Ok(Ok(result)) => result.map_err(|e| format!("failed to run docker exec: {}", e))?,
+        Ok(Err(e)) => return Err(format!("health command failed: {}", e)),
+        Err(_) => return Err(format!("timed out after {}s", timeout.as_secs())),
+    };
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = stderr
+        .lines()
+        .chain(stdout.lines())
+        .rfind(|l| !l.trim().is_empty())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    Err(match output.status.code() {
+        Some(code) if detail.is_empty() => format!("exited with code {}", code),
+        Some(code) => format!("exited with code {}: {}", code, detail),
+        None => "killed".to_string(),
+    })
+}
+
+/// Quick one-shot check: is the agent in `container` (on host `port`) healthy?
+/// Single check, 2s timeout. Used by status to show warning when container is up but unhealthy.
+pub async fn check_agent_reachable(container: &str, port: u16, health: &HealthCheck) -> bool {
+    health
+        .probe(container, port, Duration::from_secs(2))
+        .await
+        .is_ok()
+}
+
+/// Test that an agent server is healthy (e.g. after start): its health command succeeds, or its
+/// health path answers a 2xx whose body contains the expected text.
 /// Retries up to 8 times with 2s delay (server may need a few seconds to start).
-pub fn test_agent_server(port: u16) -> Result<(), String> {
-    let url = format!("http://127.0.0.1:{}/global/health", port);
+pub async fn test_agent_server(
+    container: &str,
+    port: u16,
+    health: &HealthCheck,
+) -> Result<(), String> {
     let mut last_err = String::new();
     for attempt in 0..8 {
-        let output = Command::new("curl")
-            .args(["-sf", "--max-time", "5", &url])
-            .output();
-        match output {
-            Ok(out) if out.status.success() => {
-                let body = String::from_utf8_lossy(&out.stdout);
-                if body.to_lowercase().contains("healthy") {
-                    return Ok(());
-                }
-                last_err = format!("response did not contain 'healthy': {}", body.trim());
-            }
-            Ok(out) => {
-                let stderr = String::from_utf8_lossy(&out.stderr);
-                last_err = format!("HTTP/connection error: {}", stderr.trim());
-            }
-            Err(e) => last_err = format!("curl failed: {}", e),
+        match health.probe(container, port, Duration::from_secs(5)).await {
+            Ok(()) => return Ok(()),
+            Err(e) => last_err = e,
         }
         if attempt < 7 {
-            std::thread::sleep(std::time::Duration::from_secs(2));
+            tokio::time::sleep(Duration::from_secs(2)).await;
         }
     }
     Err(format!(
-        "Health check failed after retries: server at {} - {}",
-        url, last_err
+        "Health check failed after retries: {} - {}",
+        health.describe(port),
+        last_err
     ))
 }
 
+/// Tag of the cached setup layer for a project `script` on top of `base_image`:
+/// "smith/setup:<hash>", where the hash (FNV-1a, stable across builds) covers both inputs.
+pub fn setup_image_tag(base_image: &str, script: &str) -> String {
+    format!("smith/setup:{:016x}", image_inputs_hash(base_image, script))
+}
+
+/// Tag of the image `smith project bake` builds: "smith/project-<name>:<hash>" with the same
+/// (image, script) hash, so a bake goes stale (and is ignored) when either input changes.
+pub fn baked_image_tag(project: &str, base_image: &str, script: &str) -> String {
+    let name: String = project
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!(
+        "smith/project-{}:{:016x}",
+        name.trim_matches('-'),
+        image_inputs_hash(base_image, script)
+    )
+}
+
+/// FNV-1a over the base image and script (stable across builds, unlike DefaultHasher).
+fn image_inputs_hash(base_image: &str, script: &str) -> u64 {
+    crate::util::fnv1a_64(&[base_image.as_bytes(), &[0], script.as_bytes()].concat())
+}
+
 /// Tag for the built agent image (smith/<name>:latest). Smith-managed wrapper of the source image.
 pub fn agent_built_image_tag(agent_name: &str) -> String {
     let safe: String = agent_name
@@ -394,60 +929,54 @@ pub fn agent_built_image_tag(agent_name: &str) -> String {
 }
 
 /// Return true if a Docker image with the given reference exists locally.
-pub fn image_exists(image_ref: &str) -> Result<bool, String> {
-    let output = Command::new("docker")
-        .args(["image", "inspect", image_ref])
-        .output()
-        .map_err(|e| format!("Failed to inspect image: {}", e))?;
-    Ok(output.status.success())
+pub fn image_exists(image_ref: &str) -> Result<bool, SmithError> {
+    replay::value(&format!("image_exists {}", image_ref), || {
+        api::block_on(api::image_exists(image_ref)).map_err(|e| e.to_string())
+    })
+    .and_then(|exists| exists)
+    .map_err(SmithError::Docker)
 }
 
-/// List agent names that currently have a running container (smith-agent-*).
-pub fn list_running_agent_containers() -> Result<Vec<String>, String> {
-    let output = Command::new("docker")
-        .args([
-            "ps",
-            "--filter",
-            &format!("name={}", AGENT_CONTAINER_PREFIX),
-            "--format",
-            "{{.Names}}",
-        ])
-        .output()
-        .map_err(|e| format!("Failed to list containers: {}", e))?;
-    if !output.status.success() {
-        let err = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to list containers: {}", err));
+/// `docker exec` arguments running `cmd` in an agent's container; `tty` adds `-it` so
+/// interactive programs (shells, editors) work.
+pub fn agent_exec_args(agent_name: &str, cmd: &[String], tty: bool) -> Vec<String> {
+    let mut args = vec!["exec".to_string()];
+    if tty {
+        args.push("-it".to_string());
     }
-    let names: Vec<String> = String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(|s| {
-            s.trim()
-                .strip_prefix(AGENT_CONTAINER_PREFIX)
-                .unwrap_or(s.trim())
-                .to_string()
-        })
+    args.push(agent_container_name(agent_name));
+    args.extend(cmd.iter().cloned());
+    args
+}
+
+/// Image id (sha256 digest of the image config) of a local image; None when it does not exist.
+pub fn image_digest(image_ref: &str) -> Result<Option<String>, SmithError> {
+    api::block_on(api::image_id(image_ref))
+}
+
+/// List agent names that currently have a running container (smith-agent-*).
+pub fn list_running_agent_containers() -> Result<Vec<String>, SmithError> {
+    let names: Vec<String> = api::block_on(api::list_containers("agent", false))?
+        .iter()
+        .filter_map(|summary| api::summary_label(summary, LABEL_AGENT))
         .filter(|s| !s.is_empty())
         .collect();
     Ok(names)
 }
 
 /// Return true if a container with the given name exists (running or stopped).
-pub fn container_exists(container_name: &str) -> Result<bool, String> {
-    let output = Command::new("docker")
-        .args(["inspect", container_name])
-        .output()
-        .map_err(|e| format!("Failed to inspect container: {}", e))?;
-    Ok(output.status.success())
+pub fn container_exists(container_name: &str) -> Result<bool, SmithError> {
+    Ok(api::block_on(api::inspect_container(container_name))?.is_some())
 }
 
 /// Stop an agent's container by agent name.
-pub fn stop_agent_container(agent_name: &str) -> Result<(), String> {
+pub fn stop_agent_container(agent_name: &str) -> Result<(), SmithError> {
     let name = agent_container_name(agent_name);
     stop_container(&name)
 }
 
 /// Stop all running smith-agent-* containers. Returns list of agent names stopped.
-pub fn stop_all_agent_containers() -> Result<Vec<String>, String> {
+pub fn stop_all_agent_containers() -> Result<Vec<String>, SmithError> {
     let running = list_running_agent_containers()?;
     for name in &running {
         let _ = stop_agent_container(name);
@@ -455,75 +984,84 @@ pub fn stop_all_agent_containers() -> Result<Vec<String>, String> {
     Ok(running)
 }
 
-/// Check if Docker is available and running.
-pub fn check_docker_available() -> Result<(), String> {
-    let version_output = Command::new("docker")
-        .arg("--version")
-        .output()
-        .map_err(|e| format!("Docker command not found: {}", e))?;
-
-    if !version_output.status.success() {
-        return Err("Docker command failed to execute".to_string());
-    }
-
-    let info_output = Command::new("docker")
-        .arg("info")
-        .output()
-        .map_err(|e| format!("Failed to check Docker daemon: {}", e))?;
-
-    if !info_output.status.success() {
-        let error = String::from_utf8_lossy(&info_output.stderr);
-        if error.contains("Cannot connect") || error.contains("Is the docker daemon running") {
-            return Err("Docker daemon is not running".to_string());
-        }
-        return Err(format!("Docker daemon check failed: {}", error));
-    }
-
-    Ok(())
+/// Check that the Docker daemon answers a ping through the API.
+pub fn check_docker_available() -> Result<(), SmithError> {
+    api::block_on(api::ping())
 }
 
 /// Stop a container by name.
-pub fn stop_container(container_name: &str) -> Result<(), String> {
-    let output = Command::new("docker")
-        .arg("stop")
-        .arg(container_name)
-        .output()
-        .map_err(|e| format!("Failed to stop container: {}", e))?;
-
-    if output.status.success() {
-        Ok(())
-    } else {
-        let error = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Failed to stop container: {}", error))
+pub fn stop_container(container_name: &str) -> Result<(), SmithError> {
+    if simulate::active() {
+        return simulate::stop_container(container_name).map_err(SmithError::Docker);
     }
+    api::block_on(api::stop_container(container_name))?;
+    audit::record("container_stop", container_name, None);
+    Ok(())
 }
 
 /// Restart a container by name.
-pub fn restart_container(container_name: &str) -> Result<(), String> {
-    let output = Command::new("docker")
-        .arg("restart")
-        .arg(container_name)
-        .output()
-        .map_err(|e| format!("Failed to restart container: {}", e))?;
+pub fn restart_container(container_name: &str) -> Result<(), SmithError> {
+    api::block_on(api::restart_container(container_name))?;
+    audit::record("container_restart", container_name, None);
+    Ok(())
+}
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        let error = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Failed to restart container: {}", error))
-    }
+/// Running state of a container, or Ok(None) when it does not exist.
+pub fn container_running(container_name: &str) -> Result<Option<bool>, SmithError> {
+    replay::value(&format!("container_running {}", container_name), || {
+        api::block_on(api::container_running(container_name)).map_err(|e| e.to_string())
+    })
+    .and_then(|running| running)
+    .map_err(SmithError::Docker)
+}
+
+/// Force-remove a local image. Returns Ok(false) when it did not exist.
+pub fn remove_image(image_ref: &str) -> Result<bool, SmithError> {
+    api::block_on(api::remove_image(image_ref))
 }
 
 mod agent_runtime;
+pub(crate) mod api;
+mod cache;
+mod engine;
+mod kubernetes;
 mod model_runtime;
+mod network;
+mod policy;
+mod pool;
+mod session;
 
 pub use agent_runtime::{
-    ensure_spawn_dir, ensure_spawn_state_dir, list_spawn_plan_dirs, list_spawned_containers,
-    prune_spawned_containers, read_spawn_file, remove_spawn_dir, restart_spawned_container,
-    run_prompt_in_spawned_container, run_prompt_in_spawned_container_with_options, run_spawn_shell,
-    spawn_container_name, spawn_container_port, spawn_file_exists, start_spawned_container,
-    stop_spawned_container, write_spawn_file,
+    clone_filter_arg, ensure_spawn_dir, ensure_spawn_state_dir, list_spawn_plan_dirs,
+    list_spawned_containers, persistent_workspace_args, prune_spawned_containers, read_spawn_file,
+    remove_spawn_dir, restart_spawned_container, run_prompt_in_spawned_container,
+    run_prompt_in_spawned_container_with_options, run_spawn_shell, scope_prompt,
+    spawn_container_name, spawn_container_port, spawn_file_exists, spawn_logs_command,
+    spawned_container_running, start_spawned_container, stop_all_spawned_containers,
+    stop_spawned_container, take_prompt_usage, validate_clone_filter, write_spawn_file, LogOptions,
+    PromptUsage, SpawnInfo, CLONE_FILTERS,
 };
+pub use cache::{
+    dep_cache_volume, dep_caches, remove_volume, smith_images, workspace_volumes, CacheEntry,
+};
+pub use engine::{
+    configure_engine_socket, engine_info, host_gateway_address, ollama_base_url, EngineInfo,
+};
+pub use kubernetes::{spawn_pod_name, BACKENDS};
 pub use model_runtime::{
     is_ollama_running, start_ollama_container, stop_ollama_container, OLLAMA_PORT,
 };
+pub use network::{
+    egress_allowlist, isolate_spawned_network, restore_spawned_network, NETWORK_MODES,
+};
+pub use policy::{
+    check_spawned_policy, diff_line_count, enforce_spawned_paths, path_matches, policy_violations,
+    project_policy, protected_changes, scan_spawned_secrets, spawned_changed_paths,
+    validate_policy, PATH_ACTIONS,
+};
+pub use pool::{
+    claim_pool_container, is_pool_branch, list_pool_containers, load_pool_claims,
+    pool_checkout_script, remove_pool_container, rename_claims, rename_pool_claims,
+    split_pool_containers, PoolClaim, POOL_BRANCH_PREFIX,
+};
+pub use session::{message_body, message_text, session_error_message, streamed_text};
@@ -1,4 +1,5 @@
 use super::*;
+use std::time::Duration;
 
 /// Sanitize project or branch name for use in container name.
 /// Docker names allow [a-zA-Z0-9][a-zA-Z0-9_.-]*. Replaces disallowed chars with -.
@@ -24,6 +25,55 @@ pub fn spawn_container_name(project: &str, branch: &str) -> String {
     format!("agent_{}_{}", proj, bran)
 }
 
+/// `clone_filter` values besides `blob:limit=<size>`: blobless and treeless partial clones.
+pub const CLONE_FILTERS: &[&str] = &["blob:none", "tree:0"];
+
+/// Check a project's `clone_filter`: "blob:none", "tree:0" or "blob:limit=<n>[k|m|g]".
+pub fn validate_clone_filter(filter: &str) -> Result<String, String> {
+    let normalized = filter.trim().to_lowercase();
+    let limit_ok = normalized
+        .strip_prefix("blob:limit=")
+        .map(|n| n.trim_end_matches(['k', 'm', 'g']))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    if CLONE_FILTERS.contains(&normalized.as_str()) || limit_ok {
+        Ok(normalized)
+    } else {
+        Err(format!(
+            "Unsupported clone_filter '{}'; expected {} or blob:limit=<size>",
+            filter,
+            CLONE_FILTERS.join(", ")
+        ))
+    }
+}
+
+/// ` --filter=<f>` for the workspace clone of a project with `clone_filter` (empty without one).
+/// The clone is partial: git fetches the blobs (and trees) it left out from origin on demand.
+pub fn clone_filter_arg(filter: Option<&str>) -> Result<String, String> {
+    match filter.filter(|f| !f.trim().is_empty()) {
+        Some(f) => Ok(format!(" --filter={}", validate_clone_filter(f)?)),
+        None => Ok(String::new()),
+    }
+}
+
+/// `docker run` arguments that keep a spawned agent's /workspace and /state in named volumes
+/// (`smith-ws-<container>-workspace` / `-state`, created on first use), so a later container
+/// for the same project/branch fetches and rebases the previous checkout instead of cloning.
+pub async fn persistent_workspace_args(project: &str, branch: &str) -> Result<Vec<String>, String> {
+    let base = format!(
+        "{}{}",
+        cache::WORKSPACE_VOLUME_PREFIX,
+        spawn_container_name(project, branch)
+    );
+    let mut args = Vec::new();
+    for dir in ["workspace", "state"] {
+        let volume = format!("{}-{}", base, dir);
+        cache::ensure_smith_volume(&volume).await?;
+        args.extend(["-v".to_string(), format!("{}:/{}", volume, dir)]);
+    }
+    args.extend(["-e".to_string(), "SMITH_REUSE_WORKSPACE=1".to_string()]);
+    Ok(args)
+}
+
 /// Generate port for a spawned agent using hash-based allocation.
 /// Uses project + branch to generate deterministic port in range [4096, 8191].
 /// If port is in use, caller should try next available port.
@@ -36,13 +86,16 @@ pub fn spawn_container_port(project: &str, branch: &str) -> u16 {
 }
 
 /// Find next available port in spawn range, starting from the given port.
-pub fn spawn_find_available_port(start_port: u16) -> Result<u16, String> {
+pub async fn spawn_find_available_port(
+    start_port: u16,
+    health: &HealthCheck,
+) -> Result<u16, String> {
     for port in start_port..=SPAWN_PORT_MAX {
-        let url = format!("http://127.0.0.1:{}/global/health", port);
-        let output = Command::new("curl")
-            .args(["-sf", "--max-time", "1", &url])
-            .output();
-        if !output.map(|o| o.status.success()).unwrap_or(false) {
+        let in_use = http_get(health.host, port, &health.path, Duration::from_secs(1))
+            .await
+            .map(|(status, _)| (200..300).contains(&status))
+            .unwrap_or(false);
+        if !in_use {
             return Ok(port);
         }
     }
@@ -61,102 +114,176 @@ pub struct SpawnInfo {
     pub image: String,
 }
 
-/// List all spawned containers (smith::*).
+/// List all spawned agents (smith::*): claimed warm pool containers under their branch, the
+/// unclaimed ones left out (see `smith pool status`).
 pub fn list_spawned_containers() -> Result<Vec<SpawnInfo>, String> {
-    let output = Command::new("docker")
-        .args([
-            "ps",
-            "-a",
-            "--filter",
-            &format!("name={}", SPAWN_CONTAINER_PREFIX),
-            "--format",
-            "{{.Names}}|{{.ID}}|{{.Status}}|{{.Image}}",
-        ])
-        .output()
-        .map_err(|e| format!("Failed to list containers: {}", e))?;
-
-    if !output.status.success() {
-        let err = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to list containers: {}", err));
-    }
+    let all = list_all_spawned_containers()?;
+    Ok(pool::split_pool_containers(all, &pool::load_pool_claims()).0)
+}
 
+/// Every spawned container and pod as labelled, warm pool containers included.
+pub(super) fn list_all_spawned_containers() -> Result<Vec<SpawnInfo>, String> {
     let mut results = Vec::new();
-    for line in String::from_utf8_lossy(&output.stdout).lines() {
-        let parts: Vec<&str> = line.split("|").collect();
-        if parts.len() < 4 {
+    for summary in api::block_on(api::list_containers("spawned", true))? {
+        let (Some(container_name), Some(project), Some(branch)) = (
+            api::summary_name(&summary),
+            api::summary_label(&summary, LABEL_PROJECT),
+            api::summary_label(&summary, LABEL_BRANCH),
+        ) else {
             continue;
-        }
-        let container_name = parts[0].trim();
-        let container_id = parts[1].trim();
-        let status = parts[2].trim();
-        let image = parts[3].trim();
-
-        // Parse project and branch from name: "agent_{project}_{branch}"
-        if let Some(stripped) = container_name.strip_prefix(SPAWN_CONTAINER_PREFIX) {
-            let segments: Vec<&str> = stripped.splitn(2, '_').collect();
-            if segments.len() >= 2 {
-                let project = segments[0].to_string();
-                let branch = segments[1..].join("_"); // Handle branches with _ in name
-
-                // Find port from container config or skip
-                let port = get_container_port(container_name).unwrap_or(0);
-
-                results.push(SpawnInfo {
-                    project,
-                    branch,
-                    container_name: container_name.to_string(),
-                    container_id: container_id.to_string(),
-                    port,
-                    status: status.to_string(),
-                    image: image.to_string(),
-                });
-            }
-        }
+        };
+        let port = api::summary_label(&summary, LABEL_PORT)
+            .and_then(|p| p.parse().ok())
+            .or_else(|| get_container_port(&container_name).ok())
+            .unwrap_or(0);
+        results.push(SpawnInfo {
+            project,
+            branch,
+            container_id: summary.id.unwrap_or_default().chars().take(12).collect(),
+            status: summary.status.unwrap_or_default(),
+            image: summary.image.unwrap_or_default(),
+            container_name,
+            port,
+        });
     }
+    results.extend(kubernetes::list_spawned_pods());
 
     Ok(results)
 }
 
 /// Get the host port mapped for a container.
-fn get_container_port(container_name: &str) -> Result<u16, String> {
-    let output = Command::new("docker")
-        .args(["port", container_name])
-        .output()
-        .map_err(|e| format!("Failed to get port: {}", e))?;
-
-    let port_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    // Output format: "4954/tcp -> 0.0.0.0:4954" - extract the host port
-    if let Some(colon_pos) = port_str.rfind(':') {
-        let host_port = &port_str[colon_pos + 1..];
-        return host_port
-            .parse::<u16>()
-            .map_err(|_| format!("Failed to parse port from: {}", port_str));
-    }
-    // Fallback: try to parse as just a number
-    port_str
-        .parse::<u16>()
-        .map_err(|_| format!("Failed to parse port from: {}", port_str))
+fn get_container_port(container_name: &str) -> Result<u16, SmithError> {
+    api::block_on(api::container_host_port(container_name))
 }
 
-/// Stop a spawned container by project and branch.
-pub fn stop_spawned_container(project: &str, branch: &str) -> Result<(), String> {
+/// Stop a spawned container by project and branch (deletes the pod on the Kubernetes backend).
+pub fn stop_spawned_container(project: &str, branch: &str) -> Result<(), SmithError> {
+    if let Some(k) = kubernetes::kube_target(project) {
+        let pod = kubernetes::spawn_pod_name(project, branch);
+        kubernetes::delete_spawned_pod(&k, &pod).map_err(SmithError::Docker)?;
+        audit::record("container_stop", &pod, Some("kubernetes pod".to_string()));
+        return Ok(());
+    }
     let name = spawn_container_name(project, branch);
     stop_container(&name)
 }
 
+/// Stop every running spawned Docker container (Kubernetes pods are left alone).
+pub fn stop_all_spawned_containers() -> Result<Vec<String>, SmithError> {
+    let running: Vec<String> = api::block_on(api::list_containers("spawned", false))?
+        .iter()
+        .filter_map(api::summary_name)
+        .collect();
+    for name in &running {
+        let _ = stop_container(name);
+    }
+    Ok(running)
+}
+
 /// Restart a spawned container by project and branch.
-pub fn restart_spawned_container(project: &str, branch: &str) -> Result<(), String> {
+pub fn restart_spawned_container(project: &str, branch: &str) -> Result<(), SmithError> {
+    if kubernetes::kube_target(project).is_some() {
+        return Err(SmithError::Docker(format!(
+            "Restart is not supported for Kubernetes agent pods; run `smith agent stop --project {} --branch {}` and start it again",
+            project, branch
+        )));
+    }
     let name = spawn_container_name(project, branch);
     restart_container(&name)
 }
 
+/// What `smith agent logs` reads: shared by `docker logs` and `kubectl logs`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogOptions {
+    pub follow: bool,
+    /// Only lines newer than this: a duration ("10m") or, on Docker, a timestamp
+    pub since: Option<String>,
+    /// Only lines older than this (Docker only; `kubectl logs` has no equivalent)
+    pub until: Option<String>,
+    /// Lines from the end to start with: a number or "all"
+    pub tail: Option<String>,
+    pub timestamps: bool,
+}
+
+impl LogOptions {
+    /// Flags for `docker logs` / `kubectl logs`, before the container or pod name.
+    pub fn args(&self, kubernetes: bool) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.follow {
+            args.push("--follow".to_string());
+        }
+        if let Some(since) = &self.since {
+            args.push(format!("--since={}", since));
+        }
+        if let Some(until) = self.until.as_ref().filter(|_| !kubernetes) {
+            args.push(format!("--until={}", until));
+        }
+        if let Some(tail) = &self.tail {
+            let tail = if kubernetes && tail == "all" {
+                "-1"
+            } else {
+                tail
+            };
+            args.push(format!("--tail={}", tail));
+        }
+        if self.timestamps {
+            args.push("--timestamps".to_string());
+        }
+        args
+    }
+}
+
+/// `docker logs` for a spawned container, or `kubectl logs` for its pod on the Kubernetes backend.
+pub fn spawn_logs_command(project: &str, branch: &str, options: &LogOptions) -> Command {
+    match kubernetes::kube_target(project) {
+        Some(k) => kubernetes::logs_command(
+            &k,
+            &kubernetes::spawn_pod_name(project, branch),
+            &options.args(true),
+        ),
+        None => {
+            let mut cmd = Command::new("docker");
+            cmd.arg("logs")
+                .args(options.args(false))
+                .arg(spawn_container_name(project, branch));
+            cmd
+        }
+    }
+}
+
+/// `docker exec <container>` or, on the Kubernetes backend, `kubectl exec <pod> --`;
+/// append the command to run.
+fn spawn_exec(project: &str, branch: &str) -> Command {
+    match kubernetes::kube_target(project) {
+        Some(k) => {
+            kubernetes::exec_command(&k, &kubernetes::spawn_pod_name(project, branch), false)
+        }
+        None => {
+            let mut cmd = Command::new("docker");
+            cmd.args(["exec", &spawn_container_name(project, branch)]);
+            cmd
+        }
+    }
+}
+
+/// Whether the spawned container (or pod) is running; None when it does not exist.
+pub fn spawned_container_running(project: &str, branch: &str) -> Result<Option<bool>, SmithError> {
+    match kubernetes::kube_target(project) {
+        Some(k) => kubernetes::pod_phase(&k, &kubernetes::spawn_pod_name(project, branch))
+            .map(|phase| phase.map(|p| p == "Running"))
+            .map_err(SmithError::Docker),
+        None => container_running(&spawn_container_name(project, branch)),
+    }
+}
+
 /// Ensure a directory exists in a spawned container.
 pub fn ensure_spawn_dir(project: &str, branch: &str, dir_path: &str) -> Result<(), String> {
     let name = spawn_container_name(project, branch);
     let command = format!("mkdir -p '{}'", dir_path.replace('\'', "'\"'\"'"));
-    let output = Command::new("docker")
-        .args(["exec", &name, "sh", "-lc", &command])
-        .output()
+    let output = spawn_exec(project, branch)
+        .args(["sh", "-lc", &command])
+        .logged()
+        .recorded_output()
         .map_err(|e| format!("Failed to ensure '{}' in container: {}", dir_path, e))?;
 
     if output.status.success() {
@@ -176,9 +303,10 @@ pub fn ensure_spawn_dir(project: &str, branch: &str, dir_path: &str) -> Result<(
 pub fn remove_spawn_dir(project: &str, branch: &str, dir_path: &str) -> Result<(), String> {
     let name = spawn_container_name(project, branch);
     let command = format!("rm -rf '{}'", dir_path.replace('\'', "'\"'\"'"));
-    let output = Command::new("docker")
-        .args(["exec", &name, "sh", "-lc", &command])
-        .output()
+    let output = spawn_exec(project, branch)
+        .args(["sh", "-lc", &command])
+        .logged()
+        .recorded_output()
         .map_err(|e| format!("Failed to remove '{}' in container: {}", dir_path, e))?;
 
     if output.status.success() {
@@ -198,9 +326,10 @@ pub fn remove_spawn_dir(project: &str, branch: &str, dir_path: &str) -> Result<(
 pub fn ensure_spawn_state_dir(project: &str, branch: &str) -> Result<(), String> {
     let name = spawn_container_name(project, branch);
     let command = "mkdir -p /workspace; if [ -L /state ]; then target=$(readlink /state || true); if [ \"$target\" = \"/workspace/state\" ]; then mkdir -p /workspace/state /state; cp -a /workspace/state/. /state/ 2>/dev/null || true; rm -f /state; mkdir -p /state; fi; fi; mkdir -p /state";
-    let output = Command::new("docker")
-        .args(["exec", &name, "sh", "-lc", command])
-        .output()
+    let output = spawn_exec(project, branch)
+        .args(["sh", "-lc", command])
+        .logged()
+        .recorded_output()
         .map_err(|e| format!("Failed to initialize /state in container: {}", e))?;
 
     if output.status.success() {
@@ -217,11 +346,11 @@ pub fn ensure_spawn_state_dir(project: &str, branch: &str) -> Result<(), String>
 
 /// Return true when a file exists in the spawned container.
 pub fn spawn_file_exists(project: &str, branch: &str, file_path: &str) -> Result<bool, String> {
-    let name = spawn_container_name(project, branch);
     let command = format!("test -f '{}'", file_path.replace('\'', "'\"'\"'"));
-    let output = Command::new("docker")
-        .args(["exec", &name, "sh", "-lc", &command])
-        .output()
+    let output = spawn_exec(project, branch)
+        .args(["sh", "-lc", &command])
+        .logged()
+        .recorded_output()
         .map_err(|e| format!("Failed checking file in container: {}", e))?;
 
     Ok(output.status.success())
@@ -229,16 +358,14 @@ pub fn spawn_file_exists(project: &str, branch: &str, file_path: &str) -> Result
 
 /// List plan run directories under /state in a spawned container.
 pub fn list_spawn_plan_dirs(project: &str, branch: &str) -> Result<Vec<String>, String> {
-    let name = spawn_container_name(project, branch);
-    let output = Command::new("docker")
+    let output = spawn_exec(project, branch)
         .args([
-            "exec",
-            &name,
             "sh",
             "-lc",
             "for d in /state/plan-*; do [ -d \"$d\" ] && basename \"$d\"; done; true",
         ])
-        .output()
+        .logged()
+        .recorded_output()
         .map_err(|e| format!("Failed listing plan directories: {}", e))?;
 
     if !output.status.success() {
@@ -261,11 +388,11 @@ pub fn list_spawn_plan_dirs(project: &str, branch: &str) -> Result<Vec<String>,
 
 /// Read a UTF-8 file from a spawned container.
 pub fn read_spawn_file(project: &str, branch: &str, file_path: &str) -> Result<String, String> {
-    let name = spawn_container_name(project, branch);
     let command = format!("cat '{}'", file_path.replace('\'', "'\"'\"'"));
-    let output = Command::new("docker")
-        .args(["exec", &name, "sh", "-lc", &command])
-        .output()
+    let output = spawn_exec(project, branch)
+        .args(["sh", "-lc", &command])
+        .logged()
+        .recorded_output()
         .map_err(|e| format!("Failed reading '{}' in container: {}", file_path, e))?;
 
     if !output.status.success() {
@@ -296,6 +423,40 @@ pub fn write_spawn_file(
         .unwrap_or("/");
     ensure_spawn_dir(project, branch, parent)?;
 
+    if let Some(k) = kubernetes::kube_target(project) {
+        let pod = kubernetes::spawn_pod_name(project, branch);
+        let command = format!("cat > '{}'", file_path.replace('\'', "'\"'\"'"));
+        let mut exec = kubernetes::exec_command(&k, &pod, true);
+        exec.args(["sh", "-c", &command])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let mut tape = Tape::new(&exec);
+        let mut child = exec
+            .logged()
+            .recorded_spawn()
+            .map_err(|e| format!("Failed to copy file into pod: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(content.as_bytes())
+                .map_err(|e| format!("Failed to copy file into pod: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to copy file into pod: {}", e))?;
+        tape.line(true, String::from_utf8_lossy(&output.stderr).trim_end());
+        tape.finish(output.status.code());
+        if output.status.success() {
+            return Ok(());
+        }
+        return Err(format!(
+            "Failed writing '{}' in pod '{}': {}",
+            file_path,
+            pod,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
     let mut tmp_path = std::env::temp_dir();
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -315,7 +476,8 @@ pub fn write_spawn_file(
         .arg("cp")
         .arg(tmp_path.as_os_str())
         .arg(&destination)
-        .output();
+        .logged()
+        .recorded_output();
 
     let _ = fs::remove_file(&tmp_path);
 
@@ -336,9 +498,10 @@ pub fn write_spawn_file(
 /// Execute a shell command in a spawned container and return stdout.
 pub fn run_spawn_shell(project: &str, branch: &str, script: &str) -> Result<String, String> {
     let name = spawn_container_name(project, branch);
-    let output = Command::new("docker")
-        .args(["exec", &name, "sh", "-lc", script])
-        .output()
+    let output = spawn_exec(project, branch)
+        .args(["sh", "-lc", script])
+        .logged()
+        .recorded_output()
         .map_err(|e| format!("Failed running command in spawned container: {}", e))?;
 
     if output.status.success() {
@@ -367,6 +530,17 @@ pub fn run_spawn_shell(project: &str, branch: &str, script: &str) -> Result<Stri
     }
 }
 
+/// `prompt` for a project covering only `scope` of the repository: the agent is told to keep
+/// its work in that directory.
+pub fn scope_prompt(scope: Option<&str>, prompt: &str) -> String {
+    match scope {
+        Some(dir) => format!(
+            "Scope: this project is the `{dir}` directory of the repository (/workspace/{dir}). Keep changes, and run builds and tests, within it unless the task requires otherwise.\n\n{prompt}"
+        ),
+        None => prompt.to_string(),
+    }
+}
+
 /// Run a prompt in a spawned container and stream raw output to the terminal.
 pub fn run_prompt_in_spawned_container(
     project: &str,
@@ -377,7 +551,9 @@ pub fn run_prompt_in_spawned_container(
     run_prompt_in_spawned_container_with_options(project, branch, prompt, verbose, None, None)
 }
 
-/// Run a prompt in a spawned container with optional model and prompt-prefix overrides.
+/// Run a prompt in a spawned container with optional model and prompt-prefix overrides: over
+/// the agent's HTTP session API when it is reachable, else with `opencode run` in the container.
+/// Each prompt's provider, outcome and latency are added to the usage file.
 pub fn run_prompt_in_spawned_container_with_options(
     project: &str,
     branch: &str,
@@ -386,12 +562,89 @@ pub fn run_prompt_in_spawned_container_with_options(
     model: Option<&str>,
     prompt_prefix: Option<&str>,
 ) -> Result<(), String> {
+    let cfg = load_config().unwrap_or_default();
+    let started = std::time::Instant::now();
+    let result = run_prompt(&cfg, project, branch, prompt, verbose, model, prompt_prefix);
+    let cancelled = result
+        .as_ref()
+        .err()
+        .is_some_and(|e| e == "Cancelled by user.");
+    if !cancelled && !simulate::active() && !replay::replaying() {
+        let profile = project_profile(&cfg, project);
+        let record = usage::UsageRecord {
+            at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            project: project.to_string(),
+            agent: profile.map(|p| p.name.clone()),
+            provider: usage::provider_of(
+                model,
+                profile.and_then(|p| p.provider.as_deref()),
+                profile.and_then(|p| p.model.as_deref()),
+            ),
+            outcome: usage::classify(result.as_ref().err().map(String::as_str)).to_string(),
+            latency_ms: started.elapsed().as_millis() as u64,
+        };
+        if let Err(e) = usage::append(&record) {
+            tracing::debug!("usage not recorded: {}", e);
+        }
+    }
+    result
+}
+
+fn run_prompt(
+    cfg: &SmithConfig,
+    project: &str,
+    branch: &str,
+    prompt: &str,
+    verbose: bool,
+    model: Option<&str>,
+    prompt_prefix: Option<&str>,
+) -> Result<(), String> {
+    let scope = cfg
+        .projects
+        .iter()
+        .find(|p| p.name == project)
+        .and_then(|p| p.scope());
+    let prompt = &scope_prompt(scope, prompt);
+    if let Some(base) = session::session_base_url(project, branch) {
+        match api::block_on(session::prompt(
+            &base,
+            prompt,
+            model,
+            prompt_prefix,
+            verbose,
+        )) {
+            Ok(answer) => {
+                if !verbose && !answer.trim().is_empty() {
+                    println!("{}", answer.trim());
+                }
+                return Ok(());
+            }
+            Err(session::SessionError::Failed(e)) => return Err(e),
+            Err(session::SessionError::Unavailable(e)) => {
+                tracing::debug!(
+                    "session API at {} unavailable ({}); using opencode run",
+                    base,
+                    e
+                )
+            }
+        }
+    }
     let name = spawn_container_name(project, branch);
+    let mut command = match kubernetes::kube_target(project) {
+        Some(k) => {
+            kubernetes::exec_command(&k, &kubernetes::spawn_pod_name(project, branch), false)
+        }
+        None => {
+            let mut cmd = Command::new("docker");
+            cmd.arg("exec")
+                .args(network::egress_proxy_exec_env(&name))
+                .args(["-w", "/", &name]);
+            cmd
+        }
+    };
     let mut args = vec![
-        "exec".to_string(),
-        "-w".to_string(),
-        "/".to_string(),
-        name,
         "opencode".to_string(),
         "run".to_string(),
         "--dir".to_string(),
@@ -420,12 +673,15 @@ pub fn run_prompt_in_spawned_container_with_options(
     ensure_spawn_run_sigint_handler();
     SPAWN_RUN_CANCELLED.store(false, Ordering::SeqCst);
 
-    let mut child = Command::new("docker")
+    command
         .args(&args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
+        .stderr(Stdio::piped());
+    let mut tape = Tape::new(&command);
+    let mut child = command
+        .logged()
+        .recorded_spawn()
         .map_err(|e| format!("Failed to run prompt in spawned container: {}", e))?;
 
     let stdout = child
@@ -463,6 +719,8 @@ pub fn run_prompt_in_spawned_container_with_options(
     let mut rendered_answer = String::new();
     let mut fallback_stdout = String::new();
     let mut error_context = String::new();
+    let started = std::time::Instant::now();
+    let timeout = prompt_timeout();
 
     loop {
         if SPAWN_RUN_CANCELLED.load(Ordering::SeqCst) {
@@ -472,9 +730,17 @@ pub fn run_prompt_in_spawned_container_with_options(
             let _ = stderr_thread.join();
             return Err("Cancelled by user.".to_string());
         }
+        if let Some(limit) = timeout.filter(|limit| started.elapsed() >= *limit) {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = stdout_thread.join();
+            let _ = stderr_thread.join();
+            return Err(prompt_timeout_error(limit));
+        }
 
         match rx.recv_timeout(std::time::Duration::from_millis(120)) {
             Ok((source, line)) => {
+                tape.line(matches!(source, StreamSource::Stderr), &line);
                 if line.trim().is_empty() {
                     continue;
                 }
@@ -497,6 +763,12 @@ pub fn run_prompt_in_spawned_container_with_options(
                                 }
                             }
 
+                            PROMPT_TOKENS.fetch_add(event_tokens(&value), Ordering::Relaxed);
+                            if let Some(cost) = value["part"]["cost"].as_f64() {
+                                PROMPT_COST_MICROS
+                                    .fetch_add((cost * 1e6).round() as u64, Ordering::Relaxed);
+                            }
+
                             let mut parts = Vec::new();
                             collect_text_parts(&value, &mut parts);
                             for part in parts {
@@ -567,6 +839,7 @@ pub fn run_prompt_in_spawned_container_with_options(
     let status = child
         .wait()
         .map_err(|e| format!("Failed waiting for prompt command: {}", e))?;
+    tape.finish(status.code());
 
     let _ = stdout_thread.join();
     let _ = stderr_thread.join();
@@ -602,24 +875,44 @@ pub fn run_prompt_in_spawned_container_with_options(
     Err(classify_spawn_run_error(&raw, status.code()))
 }
 
+/// Input, output and reasoning tokens of an `opencode run --format json` step_finish event.
+fn event_tokens(event: &Value) -> u64 {
+    let tokens = &event["part"]["tokens"];
+    ["input", "output", "reasoning"]
+        .iter()
+        .filter_map(|k| tokens[k].as_u64())
+        .sum()
+}
+
+/// Tokens and provider cost of spawned-container prompts.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PromptUsage {
+    pub tokens: u64,
+    /// USD (0 when the provider reports no cost).
+    pub cost: f64,
+}
+
+/// Usage of spawned-container prompts since the previous call (resets the counters).
+pub fn take_prompt_usage() -> PromptUsage {
+    PromptUsage {
+        tokens: PROMPT_TOKENS.swap(0, Ordering::Relaxed),
+        cost: PROMPT_COST_MICROS.swap(0, Ordering::Relaxed) as f64 / 1e6,
+    }
+}
+
 /// Prune (remove) all stopped spawned containers.
 pub fn prune_spawned_containers() -> Result<Vec<String>, String> {
     let containers = list_spawned_containers()?;
     let mut removed = Vec::new();
 
     for container in containers {
-        if container.status.to_lowercase().contains("exited")
+        if (container.status.to_lowercase().contains("exited")
             || container.status.to_lowercase().contains("created")
-            || container.status.to_lowercase().contains("dead")
+            || container.status.to_lowercase().contains("dead"))
+            && api::block_on(api::remove_container(&container.container_name, false)).is_ok()
         {
-            let output = Command::new("docker")
-                .args(["rm", &container.container_name])
-                .output()
-                .map_err(|e| format!("Failed to remove container: {}", e))?;
-
-            if output.status.success() {
-                removed.push(container.container_name);
-            }
+            audit::record("container_remove", &container.container_name, None);
+            removed.push(container.container_name);
         }
     }
 
@@ -628,8 +921,9 @@ pub fn prune_spawned_containers() -> Result<Vec<String>, String> {
 
 /// Start a spawned container for a project/branch.
 /// Clones fresh repo into container workspace and starts opencode serve.
+/// `hardening` is extra run arguments from [`hardening_args`].
 #[allow(clippy::too_many_arguments)]
-pub fn start_spawned_container(
+pub async fn start_spawned_container(
     project: &str,
     branch: &str,
     port: u16,
@@ -639,36 +933,42 @@ pub fn start_spawned_container(
     commit_name: Option<&str>,
     commit_email: Option<&str>,
     env_vars: &[(String, String)],
+    hardening: &[String],
 ) -> Result<u16, String> {
     let container_name = spawn_container_name(project, branch);
+    if simulate::active() {
+        simulate::start_container(&container_name, repo_url, branch, commit_name, commit_email)?;
+        return Ok(port);
+    }
+    let kube = kubernetes::kube_target(project);
+    let health = HealthCheck::for_project(project);
+    let cfg = load_config().unwrap_or_default();
+    let bind = agent_bind_address(&cfg, project_profile(&cfg, project));
+
+    // Check if container already exists (pods are checked by start_spawned_pod)
+    if kube.is_none() {
+        if let Some(running) = api::container_running(&container_name).await? {
+            if running {
+                // Container already running, return its port
+                let actual_port = api::container_host_port(&container_name).await?;
+                return Ok(actual_port);
+            }
 
-    // Check if container already exists
-    if container_exists(&container_name)? {
-        // Check if it's running
-        let output = Command::new("docker")
-            .args(["inspect", &container_name, "-f", "{{.State.Running}}"])
-            .output()
-            .map_err(|e| format!("Failed to inspect container: {}", e))?;
-
-        if output.status.success()
-            && String::from_utf8_lossy(&output.stdout)
-                .trim()
-                .contains("true")
-        {
-            // Container already running, return its port
-            let actual_port = get_container_port(&container_name)?;
-            return Ok(actual_port);
+            // Container exists but not running - remove it so we can start fresh
+            let _ = api::remove_container(&container_name, true).await;
         }
-
-        // Container exists but not running - remove it so we can start fresh
-        let _ = Command::new("docker")
-            .args(["rm", "-f", &container_name])
-            .output();
     }
 
-    // Ensure port is available
-    let final_port = if check_agent_reachable(port) {
-        spawn_find_available_port(port)?
+    // Ensure port is available (a pod has its own network namespace)
+    // (probed over HTTP: the container does not exist yet)
+    let port_probe = HealthCheck {
+        command: None,
+        ..health.clone()
+    };
+    let final_port = if kube.is_some() {
+        port
+    } else if check_agent_reachable(&container_name, port, &port_probe).await {
+        spawn_find_available_port(port, &port_probe).await?
     } else {
         port
     };
@@ -690,8 +990,14 @@ pub fn start_spawned_container(
     };
 
     // Build startup script that clones repo and starts opencode serve
-    let branch_escaped = branch.replace('\'', "'\"'\"'");
+    let branch_escaped = project_git_branch(&cfg, project, branch).replace('\'', "'\"'\"'");
     let repo_escaped = repo_url.replace('\'', "'\"'\"'");
+    let clone_filter = clone_filter_arg(
+        cfg.projects
+            .iter()
+            .find(|p| p.name == project)
+            .and_then(|p| p.clone_filter.as_deref()),
+    )?;
     let startup_script = format!(
         r#"set -e
 # Install git and openssh-client
@@ -710,6 +1016,12 @@ if [ -f /root/.ssh/id_rsa ]; then
     export GIT_SSH_COMMAND="ssh -i /root/.ssh/id_rsa -o StrictHostKeyChecking=no"
 fi
 
+# Keep cargo's registry in the dependency cache volume
+{dep_cache_setup}
+
+# Enforce the project's policy (denied commands and hosts)
+{policy_setup}
+
 # Create state/workspace directories and prepare repo (idempotent on container restart)
 mkdir -p /workspace
 if [ -L /state ]; then
@@ -729,7 +1041,7 @@ else
         # Preserve pre-existing files; avoid failing restart on non-empty workspace
         cd /workspace
     else
-        git clone '{repo}' /workspace
+        git clone{clone_filter} '{repo}' /workspace
         cd /workspace
     fi
 fi
@@ -754,6 +1066,14 @@ if git rev-parse --is-inside-work-tree >/dev/null 2>&1; then
         git checkout -b '{branch}'
     fi
 
+    # Reused workspace: replay local work onto the branch's latest remote state
+    if [ -n "${{SMITH_REUSE_WORKSPACE:-}}" ] && git rev-parse --verify 'origin/{branch}' >/dev/null 2>&1; then
+        git rebase 'origin/{branch}' >/dev/null 2>&1 || {{
+            git rebase --abort 2>/dev/null || true
+            echo "Warning: could not rebase reused workspace onto origin/{branch}; keeping local state"
+        }}
+    fi
+
     # Ensure git config for commits in this session
     # Use project-configured identity, or fallback to default
     {git_name}
@@ -765,11 +1085,29 @@ fi
 # Start opencode serve
 exec opencode serve --hostname 0.0.0.0 --port {port}"#,
         repo = repo_escaped,
+        clone_filter = clone_filter,
         branch = branch_escaped,
         port = final_port,
         git_name = git_name_cmd,
-        git_email = git_email_cmd
+        git_email = git_email_cmd,
+        dep_cache_setup = cache::DEP_CACHE_SETUP,
+        policy_setup = policy::POLICY_SETUP
     );
+    let mut env_vars = env_vars.to_vec();
+    env_vars.extend(policy::policy_env(project)?);
+
+    if let Some(k) = kube {
+        return kubernetes::start_spawned_pod(
+            &k,
+            project,
+            branch,
+            final_port,
+            image,
+            &startup_script,
+            ssh_key,
+            &env_vars,
+        );
+    }
 
     // Build docker run command
     let mut args = vec![
@@ -780,8 +1118,16 @@ exec opencode serve --hostname 0.0.0.0 --port {port}"#,
         "--name".to_string(),
         container_name.clone(),
         "-p".to_string(),
-        format!("{}:{}", final_port, final_port),
+        publish_arg(bind, final_port),
     ];
+    args.extend(label_args(
+        "spawned",
+        &[
+            (LABEL_PROJECT, project),
+            (LABEL_BRANCH, branch),
+            (LABEL_PORT, &final_port.to_string()),
+        ],
+    ));
 
     if let Some(cfg_dir) = host_opencode_config_dir().filter(|p| p.exists()) {
         args.extend([
@@ -790,7 +1136,7 @@ exec opencode serve --hostname 0.0.0.0 --port {port}"#,
         ]);
     }
 
-    for (key, value) in env_vars {
+    for (key, value) in &env_vars {
         args.extend(["-e".to_string(), format!("{}={}", key, value)]);
     }
 
@@ -819,6 +1165,9 @@ exec opencode serve --hostname 0.0.0.0 --port {port}"#,
         }
     }
 
+    args.extend(cache::dep_cache_args(project).await?);
+    args.extend(hardening.iter().cloned());
+
     // Add image and startup script (image must come before command in docker run)
     args.extend([
         "--entrypoint".to_string(),
@@ -828,9 +1177,14 @@ exec opencode serve --hostname 0.0.0.0 --port {port}"#,
         startup_script,
     ]);
 
-    let output = Command::new("docker")
+    if let Err(e) = api::ensure_image(image).await {
+        eprintln!("  Warning: {}", e);
+    }
+    let output = tokio::process::Command::new("docker")
         .args(&args)
+        .logged()
         .output()
+        .await
         .map_err(|e| format!("Failed to run container: {}", e))?;
 
     if !output.status.success() {
@@ -841,8 +1195,14 @@ exec opencode serve --hostname 0.0.0.0 --port {port}"#,
         ));
     }
 
+    audit::record(
+        "container_start",
+        &container_name,
+        Some(format!("image {}", image)),
+    );
+
     // Wait for server to be ready
-    test_agent_server(final_port)?;
+    test_agent_server(&container_name, final_port, &health).await?;
 
     Ok(final_port)
 }
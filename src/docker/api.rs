@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::OnceLock;
+
+use bollard::container::{
+    Config, CreateContainerOptions, InspectContainerOptions, ListContainersOptions,
+    RemoveContainerOptions, RenameContainerOptions, RestartContainerOptions, StartContainerOptions,
+    StopContainerOptions, TopOptions,
+};
+use bollard::errors::Error as BollardError;
+use bollard::image::{CreateImageOptions, RemoveImageOptions};
+use bollard::models::{
+    ContainerInspectResponse, ContainerSummary, EndpointSettings, HostConfig,
+    SystemDataUsageResponse,
+};
+use bollard::network::{
+    ConnectNetworkOptions, CreateNetworkOptions, DisconnectNetworkOptions, InspectNetworkOptions,
+};
+use bollard::volume::{CreateVolumeOptions, RemoveVolumeOptions};
+use bollard::Docker;
+
+use futures_util::StreamExt;
+
+use crate::error::SmithError;
+
+/// Run an async Docker call to completion from synchronous code.
+/// Inside the multi-thread runtime the worker is handed off with `block_in_place` so other tasks
+/// keep running. A current-thread runtime cannot be blocked (and `block_in_place` panics there), so
+/// the call runs on a scoped thread with its own runtime instead.
+pub(crate) fn block_on<F>(fut: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(fut))
+        }
+        Ok(_) => std::thread::scope(|scope| {
+            scope
+                .spawn(|| dedicated_runtime().block_on(fut))
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        }),
+        Err(_) => dedicated_runtime().block_on(fut),
+    }
+}
+
+fn dedicated_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime for Docker API call")
+}
+
+/// Client shared by every API call in this invocation, so batch commands (status over many
+/// agents, list/prune loops, pipeline stages) reuse one connection pool instead of reconnecting.
+static CLIENT: OnceLock<Docker> = OnceLock::new();
+
+/// Connect to the local Docker daemon (DOCKER_HOST or the default unix socket).
+/// Inside the main (multi-thread) runtime the client is created once and shared; the temporary
+/// current-thread runtimes `block_on` builds get a fresh client, since pooled connections die with them.
+pub(super) fn client() -> Result<Docker, SmithError> {
+    if let Some(docker) = CLIENT.get() {
+        return Ok(docker.clone());
+    }
+    let docker = Docker::connect_with_local_defaults()
+        .map_err(|e| SmithError::Docker(format!("Failed to connect to Docker daemon: {}", e)))?;
+    let main_runtime = tokio::runtime::Handle::try_current()
+        .is_ok_and(|h| h.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread);
+    if main_runtime {
+        let _ = CLIENT.set(docker.clone());
+    }
+    Ok(docker)
+}
+
+/// Classify the engine from `/info` and `/version`: Podman names itself in the version
+/// components, rootless daemons report `name=rootless` in their security options.
+pub(super) async fn engine_info() -> Result<super::EngineInfo, SmithError> {
+    let docker = client()?;
+    let info = docker
+        .info()
+        .await
+        .map_err(|e| api_error("Failed to query engine info", e))?;
+    let version = docker
+        .version()
+        .await
+        .map_err(|e| api_error("Failed to query engine version", e))?;
+    let podman = version
+        .components
+        .unwrap_or_default()
+        .iter()
+        .any(|c| c.name.to_lowercase().contains("podman"));
+    let rootless = info
+        .security_options
+        .unwrap_or_default()
+        .iter()
+        .any(|o| o.contains("name=rootless"));
+    let desktop = info
+        .operating_system
+        .is_some_and(|os| os.contains("Docker Desktop"));
+    Ok(super::EngineInfo {
+        podman,
+        rootless,
+        desktop,
+    })
+}
+
+/// Gateway address of the default `bridge` network (None when it has none configured).
+pub(super) async fn bridge_gateway() -> Result<Option<String>, SmithError> {
+    let docker = client()?;
+    let network = docker
+        .inspect_network("bridge", None::<InspectNetworkOptions<String>>)
+        .await
+        .map_err(|e| api_error("Failed to inspect bridge network", e))?;
+    Ok(network
+        .ipam
+        .and_then(|ipam| ipam.config)
+        .unwrap_or_default()
+        .into_iter()
+        .find_map(|c| c.gateway.filter(|g| !g.is_empty())))
+}
+
+/// Return true if the network exists.
+pub(super) async fn network_exists(name: &str) -> Result<bool, SmithError> {
+    tracing::debug!("docker api: inspect network {}", name);
+    let docker = client()?;
+    match docker
+        .inspect_network(name, None::<InspectNetworkOptions<String>>)
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(e) if is_not_found(&e) => Ok(false),
+        Err(e) => Err(api_error("Failed to inspect network", e)),
+    }
+}
+
+/// Create a bridge network; `internal` ones have no route out of the host.
+pub(super) async fn create_network(name: &str, internal: bool) -> Result<(), SmithError> {
+    tracing::debug!("docker api: create network {} internal={}", name, internal);
+    let docker = client()?;
+    let options = CreateNetworkOptions {
+        name,
+        internal,
+        ..Default::default()
+    };
+    docker
+        .create_network(options)
+        .await
+        .map(|_| ())
+        .map_err(|e| api_error("Failed to create network", e))
+}
+
+/// Remove a network. Missing networks are not an error.
+pub(super) async fn remove_network(name: &str) -> Result<(), SmithError> {
+    tracing::debug!("docker api: remove network {}", name);
+    let docker = client()?;
+    match docker.remove_network(name).await {
+        Ok(()) => Ok(()),
+        Err(e) if is_not_found(&e) => Ok(()),
+        Err(e) => Err(api_error("Failed to remove network", e)),
+    }
+}
+
+/// Attach a container to a network, reachable there under `aliases` besides its name.
+pub(super) async fn connect_network(
+    network: &str,
+    container: &str,
+    aliases: &[&str],
+) -> Result<(), SmithError> {
+    tracing::debug!("docker api: connect {} to network {}", container, network);
+    let docker = client()?;
+    let options = ConnectNetworkOptions {
+        container,
+        endpoint_config: EndpointSettings {
+            aliases: (!aliases.is_empty()).then(|| aliases.iter().map(|a| a.to_string()).collect()),
+            ..Default::default()
+        },
+    };
+    docker
+        .connect_network(network, options)
+        .await
+        .map_err(|e| api_error("Failed to connect container to network", e))
+}
+
+/// Detach a container from a network; `force` also works when the container is stopped.
+pub(super) async fn disconnect_network(
+    network: &str,
+    container: &str,
+    force: bool,
+) -> Result<(), SmithError> {
+    tracing::debug!(
+        "docker api: disconnect {} from network {}",
+        container,
+        network
+    );
+    let docker = client()?;
+    let options = DisconnectNetworkOptions { container, force };
+    docker
+        .disconnect_network(network, options)
+        .await
+        .map_err(|e| api_error("Failed to disconnect container from network", e))
+}
+
+/// Create and start a detached container running `cmd` in `image` (pulled when missing).
+/// `extra_hosts` are `host:ip` entries added to its /etc/hosts.
+pub(super) async fn run_container(
+    name: &str,
+    image: &str,
+    env: Vec<String>,
+    cmd: Vec<String>,
+    extra_hosts: Vec<String>,
+) -> Result<(), SmithError> {
+    ensure_image(image).await?;
+    tracing::debug!("docker api: create container {} from {}", name, image);
+    let docker = client()?;
+    let config = Config {
+        image: Some(image.to_string()),
+        env: Some(env),
+        cmd: Some(cmd),
+        host_config: Some(HostConfig {
+            extra_hosts: Some(extra_hosts),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let options = CreateContainerOptions {
+        name,
+        platform: None,
+    };
+    docker
+        .create_container(Some(options), config)
+        .await
+        .map_err(|e| api_error("Failed to create container", e))?;
+    docker
+        .start_container(name, None::<StartContainerOptions<String>>)
+        .await
+        .map_err(|e| api_error("Failed to start container", e))
+}
+
+/// Wait until a process named `process` runs in the container, polling its process list.
+pub(super) async fn wait_for_process(
+    container: &str,
+    process: &str,
+    timeout: std::time::Duration,
+) -> Result<(), SmithError> {
+    let docker = client()?;
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let top = docker
+            .top_processes(container, Some(TopOptions { ps_args: "-ef" }))
+            .await;
+        let running = top.is_ok_and(|top| {
+            top.processes.unwrap_or_default().iter().any(|row| {
+                row.last()
+                    .and_then(|cmd| cmd.split_whitespace().next())
+                    .is_some_and(|exe| exe.rsplit('/').next() == Some(process))
+            })
+        });
+        if running {
+            return Ok(());
+        }
+        if tokio::time::Instant::now() > deadline {
+            return Err(SmithError::Docker(format!(
+                "{} did not start in container {} within {}s",
+                process,
+                container,
+                timeout.as_secs()
+            )));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+}
+
+/// True when the daemon answered with 404 (no such container/image).
+pub(super) fn is_not_found(err: &BollardError) -> bool {
+    matches!(
+        err,
+        BollardError::DockerResponseServerError {
+            status_code: 404,
+            ..
+        }
+    )
+}
+
+/// Format a Docker API error with the action that failed.
+pub(super) fn api_error(action: &str, err: BollardError) -> SmithError {
+    SmithError::Docker(match err {
+        BollardError::DockerResponseServerError {
+            status_code,
+            message,
+        } => format!("{}: {} (HTTP {})", action, message.trim(), status_code),
+        other => format!("{}: {}", action, other),
+    })
+}
+
+/// Inspect a container. Returns Ok(None) when it does not exist.
+pub(super) async fn inspect_container(
+    name: &str,
+) -> Result<Option<ContainerInspectResponse>, SmithError> {
+    tracing::debug!("docker api: inspect container {}", name);
+    let docker = client()?;
+    match docker
+        .inspect_container(name, None::<InspectContainerOptions>)
+        .await
+    {
+        Ok(info) => Ok(Some(info)),
+        Err(e) if is_not_found(&e) => Ok(None),
+        Err(e) => Err(api_error("Failed to inspect container", e)),
+    }
+}
+
+/// Running state of a container, or Ok(None) when it does not exist.
+pub(super) async fn container_running(name: &str) -> Result<Option<bool>, SmithError> {
+    Ok(inspect_container(name)
+        .await?
+        .map(|info| info.state.and_then(|state| state.running).unwrap_or(false)))
+}
+
+/// List the containers smith started of `kind` (see [`super::LABEL_KIND`]).
+pub(super) async fn list_containers(
+    kind: &str,
+    all: bool,
+) -> Result<Vec<ContainerSummary>, SmithError> {
+    tracing::debug!("docker api: list containers kind={} all={}", kind, all);
+    let docker = client()?;
+    let mut filters = HashMap::new();
+    filters.insert(
+        "label".to_string(),
+        vec![
+            format!("{}=true", super::LABEL_MANAGED),
+            format!("{}={}", super::LABEL_KIND, kind),
+        ],
+    );
+    let options = ListContainersOptions {
+        all,
+        filters,
+        ..Default::default()
+    };
+    docker
+        .list_containers(Some(options))
+        .await
+        .map_err(|e| api_error("Failed to list containers", e))
+}
+
+/// Primary name of a listed container without Docker's leading '/'.
+pub(super) fn summary_name(summary: &ContainerSummary) -> Option<String> {
+    summary
+        .names
+        .as_ref()?
+        .first()
+        .map(|n| n.trim_start_matches('/').to_string())
+}
+
+/// Value of a label on a listed container.
+pub(super) fn summary_label(summary: &ContainerSummary, key: &str) -> Option<String> {
+    summary.labels.as_ref()?.get(key).cloned()
+}
+
+/// First host port published by a container (lowest container port wins).
+pub(super) async fn container_host_port(name: &str) -> Result<u16, SmithError> {
+    let info = inspect_container(name)
+        .await?
+        .ok_or_else(|| SmithError::Docker(format!("Container '{}' does not exist", name)))?;
+    let ports = info
+        .network_settings
+        .and_then(|settings| settings.ports)
+        .unwrap_or_default();
+    let mut keys: Vec<&String> = ports.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|key| ports.get(key).cloned().flatten())
+        .flatten()
+        .find_map(|binding| binding.host_port.and_then(|p| p.parse::<u16>().ok()))
+        .ok_or_else(|| SmithError::Docker(format!("Container '{}' has no published port", name)))
+}
+
+/// Stop a container with Docker's default grace period.
+pub(super) async fn stop_container(name: &str) -> Result<(), SmithError> {
+    tracing::debug!("docker api: stop container {}", name);
+    let docker = client()?;
+    docker
+        .stop_container(name, None::<StopContainerOptions>)
+        .await
+        .map_err(|e| api_error("Failed to stop container", e))
+}
+
+/// Restart a container.
+pub(super) async fn restart_container(name: &str) -> Result<(), SmithError> {
+    tracing::debug!("docker api: restart container {}", name);
+    let docker = client()?;
+    docker
+        .restart_container(name, None::<RestartContainerOptions>)
+        .await
+        .map_err(|e| api_error("Failed to restart container", e))
+}
+
+/// Remove a container; `force` also kills it when running. Missing containers are not an error.
+pub(super) async fn remove_container(name: &str, force: bool) -> Result<(), SmithError> {
+    tracing::debug!("docker api: remove container {} force={}", name, force);
+    let docker = client()?;
+    let options = RemoveContainerOptions {
+        force,
+        ..Default::default()
+    };
+    match docker.remove_container(name, Some(options)).await {
+        Ok(()) => Ok(()),
+        Err(e) if is_not_found(&e) => Ok(()),
+        Err(e) => Err(api_error("Failed to remove container", e)),
+    }
+}
+
+/// Rename a container.
+pub(super) async fn rename_container(name: &str, new_name: &str) -> Result<(), SmithError> {
+    tracing::debug!("docker api: rename container {} to {}", name, new_name);
+    let docker = client()?;
+    docker
+        .rename_container(name, RenameContainerOptions { name: new_name })
+        .await
+        .map_err(|e| api_error("Failed to rename container", e))
+}
+
+/// Create a named volume with `labels` unless it already exists (existing labels are kept).
+pub(super) async fn ensure_volume(
+    name: &str,
+    labels: HashMap<String, String>,
+) -> Result<(), SmithError> {
+    let docker = client()?;
+    match docker.inspect_volume(name).await {
+        Ok(_) => return Ok(()),
+        Err(e) if is_not_found(&e) => {}
+        Err(e) => return Err(api_error("Failed to inspect volume", e)),
+    }
+    tracing::debug!("docker api: create volume {}", name);
+    let options = CreateVolumeOptions {
+        name: name.to_string(),
+        labels,
+        ..Default::default()
+    };
+    docker
+        .create_volume(options)
+        .await
+        .map(|_| ())
+        .map_err(|e| api_error("Failed to create volume", e))
+}
+
+/// Remove a named volume; fails while a container still uses it.
+pub(super) async fn remove_volume(name: &str) -> Result<(), SmithError> {
+    tracing::debug!("docker api: remove volume {}", name);
+    let docker = client()?;
+    match docker
+        .remove_volume(name, Some(RemoveVolumeOptions { force: false }))
+        .await
+    {
+        Ok(()) => Ok(()),
+        Err(e) if is_not_found(&e) => Ok(()),
+        Err(e) => Err(api_error("Failed to remove volume", e)),
+    }
+}
+
+/// Disk usage of images, containers and volumes (`docker system df -v`).
+pub(super) async fn disk_usage() -> Result<SystemDataUsageResponse, SmithError> {
+    tracing::debug!("docker api: system df");
+    client()?
+        .df()
+        .await
+        .map_err(|e| api_error("Failed to read Docker disk usage", e))
+}
+
+/// Return true if the image exists locally.
+pub(super) async fn image_exists(image_ref: &str) -> Result<bool, SmithError> {
+    tracing::debug!("docker api: inspect image {}", image_ref);
+    let docker = client()?;
+    match docker.inspect_image(image_ref).await {
+        Ok(_) => Ok(true),
+        Err(e) if is_not_found(&e) => Ok(false),
+        Err(e) => Err(api_error("Failed to inspect image", e)),
+    }
+}
+
+/// Content-addressed id (sha256:...) of a local image, or Ok(None) when it does not exist.
+pub(super) async fn image_id(image_ref: &str) -> Result<Option<String>, SmithError> {
+    tracing::debug!("docker api: inspect image {}", image_ref);
+    let docker = client()?;
+    match docker.inspect_image(image_ref).await {
+        Ok(info) => Ok(info.id),
+        Err(e) if is_not_found(&e) => Ok(None),
+        Err(e) => Err(api_error("Failed to inspect image", e)),
+    }
+}
+
+/// Force-remove a local image. Returns Ok(false) when it did not exist.
+pub(super) async fn remove_image(image_ref: &str) -> Result<bool, SmithError> {
+    tracing::debug!("docker api: remove image {}", image_ref);
+    let docker = client()?;
+    let options = RemoveImageOptions {
+        force: true,
+        ..Default::default()
+    };
+    match docker.remove_image(image_ref, Some(options), None).await {
+        Ok(_) => Ok(true),
+        Err(e) if is_not_found(&e) => Ok(false),
+        Err(e) => Err(api_error("Failed to remove image", e)),
+    }
+}
+
+/// Pull an image, printing per-layer progress as it changes.
+pub(super) async fn pull_image(image_ref: &str) -> Result<(), SmithError> {
+    tracing::debug!("docker api: pull image {}", image_ref);
+    let docker = client()?;
+    let options = CreateImageOptions {
+        from_image: image_ref.to_string(),
+        ..Default::default()
+    };
+    println!("  Pulling image '{}'...", image_ref);
+    let mut stream = docker.create_image(Some(options), None, None);
+    let mut last_status: HashMap<String, String> = HashMap::new();
+    while let Some(item) = stream.next().await {
+        let info = item.map_err(|e| api_error("Failed to pull image", e))?;
+        if let Some(err) = info.error {
+            return Err(SmithError::Docker(format!(
+                "Failed to pull image '{}': {}",
+                image_ref, err
+            )));
+        }
+        let Some(status) = info.status else {
+            continue;
+        };
+        let id = info.id.unwrap_or_default();
+        if last_status.get(&id) == Some(&status) {
+            continue;
+        }
+        if id.is_empty() {
+            println!("    {}", status);
+        } else {
+            println!("    {}: {}", id, status);
+        }
+        last_status.insert(id, status);
+    }
+    Ok(())
+}
+
+/// Pull the image unless it is already present locally.
+pub(super) async fn ensure_image(image_ref: &str) -> Result<(), SmithError> {
+    if image_exists(image_ref).await? {
+        return Ok(());
+    }
+    pull_image(image_ref).await
+}
+
+/// Ping the daemon; distinguishes "not reachable" from other API failures.
+pub(super) async fn ping() -> Result<(), SmithError> {
+    let docker = client()?;
+    match docker.ping().await {
+        Ok(_) => Ok(()),
+        Err(BollardError::DockerResponseServerError {
+            status_code,
+            message,
+        }) => Err(SmithError::Docker(format!(
+            "Docker daemon check failed: {} (HTTP {})",
+            message.trim(),
+            status_code
+        ))),
+        Err(_) => Err(SmithError::Docker(
+            "Docker daemon is not running".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,169 @@
+use super::*;
+use std::collections::HashMap;
+
+/// Repository prefix of images smith builds (see [`agent_built_image_tag`]).
+const SMITH_IMAGE_PREFIX: &str = "smith/";
+
+/// Name prefix of the per-project dependency cache volumes ("smith-deps-web").
+const DEP_CACHE_PREFIX: &str = "smith-deps-";
+
+/// Name prefix of persistent branch workspace volumes ("smith-ws-web_main-workspace").
+pub(super) const WORKSPACE_VOLUME_PREFIX: &str = "smith-ws-";
+
+/// Label recording when a smith volume was created (unix seconds).
+pub(super) const VOLUME_CREATED_LABEL: &str = "smith.created";
+
+/// Mount point of the dependency cache volume inside agent containers.
+const DEP_CACHE_MOUNT: &str = "/cache";
+
+/// Package manager cache locations redirected into the volume. Cargo's registry lives under
+/// CARGO_HOME next to the toolchain, so the startup script links it in instead (see
+/// [`DEP_CACHE_SETUP`]); the shared target dir lets branches reuse each other's builds.
+const DEP_CACHE_ENV: &[(&str, &str)] = &[
+    ("CARGO_TARGET_DIR", "/cache/cargo/target"),
+    ("npm_config_cache", "/cache/npm"),
+    ("YARN_CACHE_FOLDER", "/cache/yarn"),
+    ("PIP_CACHE_DIR", "/cache/pip"),
+    ("GOMODCACHE", "/cache/go/mod"),
+    ("GOCACHE", "/cache/go/build"),
+];
+
+/// Startup script snippet pointing `$CARGO_HOME/registry` at the cache volume when it is mounted.
+pub(crate) const DEP_CACHE_SETUP: &str = r#"if [ -n "${SMITH_DEP_CACHE:-}" ]; then
+    cargo_home="${CARGO_HOME:-$HOME/.cargo}"
+    if [ -d "$cargo_home" ] && [ ! -L "$cargo_home/registry" ]; then
+        mkdir -p "$SMITH_DEP_CACHE/cargo/registry" \
+            && rm -rf "$cargo_home/registry" \
+            && ln -s "$SMITH_DEP_CACHE/cargo/registry" "$cargo_home/registry" || true
+    fi
+fi"#;
+
+/// One prunable item of a cache category, with its size on disk.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub name: String,
+    pub size: u64,
+    /// Unix seconds when the entry was created (0 when unknown).
+    pub created: u64,
+    /// Used by a running container; never pruned.
+    pub in_use: bool,
+}
+
+/// Images built by smith (`smith/<agent>:latest`).
+pub fn smith_images() -> Result<Vec<CacheEntry>, SmithError> {
+    let usage = api::block_on(api::disk_usage())?;
+    Ok(usage
+        .images
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|image| {
+            let size = image.size.max(0) as u64;
+            let created = image.created.max(0) as u64;
+            let in_use = image.containers > 0;
+            image
+                .repo_tags
+                .into_iter()
+                .filter(|tag| tag.starts_with(SMITH_IMAGE_PREFIX))
+                .map(move |name| CacheEntry {
+                    name,
+                    size,
+                    created,
+                    in_use,
+                })
+        })
+        .collect())
+}
+
+/// Dependency cache volume of a project ("smith-deps-<project>"), shared by all its branches.
+pub fn dep_cache_volume(project: &str) -> String {
+    let name: String = project
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{}{}", DEP_CACHE_PREFIX, name)
+}
+
+/// `docker run` arguments mounting the project's dependency cache volume (created on first use)
+/// and pointing package managers at it. Empty when the project sets `dep_cache = false`.
+pub(crate) async fn dep_cache_args(project: &str) -> Result<Vec<String>, String> {
+    let enabled = crate::config::load_config()
+        .ok()
+        .and_then(|cfg| cfg.projects.into_iter().find(|p| p.name == project))
+        .and_then(|p| p.dep_cache)
+        .unwrap_or(true);
+    if !enabled {
+        return Ok(Vec::new());
+    }
+    let volume = dep_cache_volume(project);
+    ensure_smith_volume(&volume).await?;
+    let mut args = vec![
+        "-v".to_string(),
+        format!("{}:{}", volume, DEP_CACHE_MOUNT),
+        "-e".to_string(),
+        format!("SMITH_DEP_CACHE={}", DEP_CACHE_MOUNT),
+    ];
+    for (key, value) in DEP_CACHE_ENV {
+        args.extend(["-e".to_string(), format!("{}={}", key, value)]);
+    }
+    Ok(args)
+}
+
+/// Create a named volume stamped with its creation time, unless it already exists.
+pub(super) async fn ensure_smith_volume(name: &str) -> Result<(), String> {
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let labels = HashMap::from([(VOLUME_CREATED_LABEL.to_string(), created.to_string())]);
+    api::ensure_volume(name, labels)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Per-project dependency cache volumes; in use while an agent container mounts them.
+pub fn dep_caches() -> Result<Vec<CacheEntry>, SmithError> {
+    volumes_with_prefix(DEP_CACHE_PREFIX)
+}
+
+/// Persistent branch workspace and state volumes (`persist_workspace`, `run develop --reuse-workspace`).
+pub fn workspace_volumes() -> Result<Vec<CacheEntry>, SmithError> {
+    volumes_with_prefix(WORKSPACE_VOLUME_PREFIX)
+}
+
+fn volumes_with_prefix(prefix: &str) -> Result<Vec<CacheEntry>, SmithError> {
+    let usage = api::block_on(api::disk_usage())?;
+    let mut entries: Vec<CacheEntry> = usage
+        .volumes
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|v| v.name.starts_with(prefix))
+        .map(|v| {
+            let (size, refs) = v
+                .usage_data
+                .map_or((0, 0), |u| (u.size.max(0) as u64, u.ref_count));
+            CacheEntry {
+                created: v
+                    .labels
+                    .get(VOLUME_CREATED_LABEL)
+                    .and_then(|c| c.parse().ok())
+                    .unwrap_or(0),
+                name: v.name,
+                size,
+                in_use: refs > 0,
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// Remove a dependency cache or workspace volume.
+pub fn remove_volume(name: &str) -> Result<(), SmithError> {
+    api::block_on(api::remove_volume(name))
+}
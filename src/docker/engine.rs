@@ -0,0 +1,92 @@
+use super::*;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Socket of a rootful Docker daemon; when it is missing smith looks for a rootless one.
+const ROOTFUL_SOCKET: &str = "/var/run/docker.sock";
+
+/// Docker bridge gateway used when the bridge network cannot be inspected.
+const DEFAULT_BRIDGE_GATEWAY: &str = "172.17.0.1";
+
+/// Host loopback as seen from rootless Docker containers (slirp4netns / RootlessKit).
+const ROOTLESS_HOST_LOOPBACK: &str = "10.0.2.2";
+
+/// What kind of container engine answers on the Docker API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EngineInfo {
+    /// Podman's Docker-compatible API (usually via the podman-docker shim).
+    pub podman: bool,
+    /// Daemon runs without root: no `sudo systemctl`, per-user socket and service.
+    pub rootless: bool,
+    /// Docker Desktop (macOS/Windows/Linux VM), where `host.docker.internal` is built in.
+    pub desktop: bool,
+}
+
+static ENGINE: OnceLock<EngineInfo> = OnceLock::new();
+
+/// Engine kind, detected once per process. Defaults (rootful Docker) when the daemon is unreachable.
+pub fn engine_info() -> EngineInfo {
+    *ENGINE.get_or_init(|| {
+        let detected = api::block_on(api::engine_info()).unwrap_or_default();
+        tracing::debug!("container engine: {:?}", detected);
+        detected
+    })
+}
+
+/// Per-user sockets of rootless Docker and Podman, in the order they are tried.
+fn rootless_socket_candidates() -> Vec<PathBuf> {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            // SAFETY: getuid has no preconditions and cannot fail.
+            PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() }))
+        });
+    vec![
+        runtime_dir.join("docker.sock"),
+        runtime_dir.join("podman").join("podman.sock"),
+        PathBuf::from("/run/podman/podman.sock"),
+    ]
+}
+
+/// Point DOCKER_HOST at a rootless Docker or Podman socket when no daemon listens on the
+/// rootful socket and the user has not chosen one (DOCKER_HOST / DOCKER_CONTEXT).
+/// Both the `docker` CLI calls and the API client pick it up. Returns the socket used.
+pub fn configure_engine_socket() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux")
+        || std::env::var_os("DOCKER_HOST").is_some()
+        || std::env::var_os("DOCKER_CONTEXT").is_some()
+        || Path::new(ROOTFUL_SOCKET).exists()
+    {
+        return None;
+    }
+    let socket = rootless_socket_candidates()
+        .into_iter()
+        .find(|p| p.exists())?;
+    std::env::set_var("DOCKER_HOST", format!("unix://{}", socket.display()));
+    tracing::info!("using container engine socket {}", socket.display());
+    Some(socket)
+}
+
+/// Address containers use to reach services published on the host (e.g. Ollama):
+/// `host.containers.internal` on Podman, the slirp host loopback on rootless Docker,
+/// `host.docker.internal` on Docker Desktop, and the bridge gateway on rootful Linux Docker.
+pub fn host_gateway_address() -> String {
+    let engine = engine_info();
+    if engine.podman {
+        "host.containers.internal".to_string()
+    } else if engine.desktop || !cfg!(target_os = "linux") {
+        "host.docker.internal".to_string()
+    } else if engine.rootless {
+        ROOTLESS_HOST_LOOPBACK.to_string()
+    } else {
+        api::block_on(api::bridge_gateway())
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| DEFAULT_BRIDGE_GATEWAY.to_string())
+    }
+}
+
+/// Base URL of the Ollama API as seen from inside agent containers.
+pub fn ollama_base_url() -> String {
+    format!("http://{}:{}", host_gateway_address(), OLLAMA_PORT)
+}
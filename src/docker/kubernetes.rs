@@ -0,0 +1,351 @@
+use super::agent_runtime::SpawnInfo;
+use super::*;
+use crate::config::{load_config, KubernetesConfig};
+use serde_json::json;
+
+/// Accepted values for a project's `backend` setting (unset = docker).
+pub const BACKENDS: &[&str] = &["docker", "kubernetes"];
+
+/// Label on every pod and secret smith creates; selects them for listing.
+const SPAWN_LABEL: &str = "smith.dev/spawn";
+
+/// Label tying a pod's secrets to it, so they are deleted together.
+const POD_LABEL: &str = "smith.dev/pod";
+
+/// Name of the agent container inside the pod (used by `kubectl exec -c`).
+const AGENT_CONTAINER: &str = "agent";
+
+/// How long to wait for image pull, clone and `opencode serve` before giving up.
+const POD_READY_TIMEOUT: &str = "600s";
+
+/// Cluster settings when `project` runs on the Kubernetes backend; None = Docker.
+pub(crate) fn kube_target(project: &str) -> Option<KubernetesConfig> {
+    let cfg = load_config().ok()?;
+    let uses_kubernetes = cfg
+        .projects
+        .iter()
+        .any(|p| p.name == project && p.backend.as_deref() == Some("kubernetes"));
+    uses_kubernetes.then(|| cfg.kubernetes.unwrap_or_default())
+}
+
+/// Cluster settings when any project uses the Kubernetes backend (for listing pods).
+fn kubernetes_in_use() -> Option<KubernetesConfig> {
+    let cfg = load_config().ok()?;
+    cfg.projects
+        .iter()
+        .any(|p| p.backend.as_deref() == Some("kubernetes"))
+        .then(|| cfg.kubernetes.unwrap_or_default())
+}
+
+/// Readiness probe for the agent container: its health command, else its health path.
+fn readiness_probe(health: &HealthCheck, port: u16) -> Value {
+    match &health.command {
+        Some(command) => json!({
+            "exec": { "command": ["/bin/sh", "-c", command] },
+            "periodSeconds": 5,
+        }),
+        None => json!({
+            "httpGet": { "path": health.path, "port": port },
+            "periodSeconds": 5,
+        }),
+    }
+}
+
+/// Pod name for a spawned agent: the container name made DNS-1123 safe ("agent-web-main").
+pub fn spawn_pod_name(project: &str, branch: &str) -> String {
+    let name: String = spawn_container_name(project, branch)
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    name[..name.len().min(63)].trim_end_matches('-').to_string()
+}
+
+/// `kubectl` with the configured kubeconfig, context and namespace.
+fn kubectl(k: &KubernetesConfig) -> Command {
+    let mut cmd = Command::new("kubectl");
+    if let Some(path) = &k.kubeconfig {
+        cmd.args(["--kubeconfig", path]);
+    }
+    if let Some(context) = &k.context {
+        cmd.args(["--context", context]);
+    }
+    if let Some(namespace) = &k.namespace {
+        cmd.args(["-n", namespace]);
+    }
+    cmd
+}
+
+fn kubectl_output(k: &KubernetesConfig, args: &[&str]) -> Result<String, String> {
+    let out = kubectl(k)
+        .args(args)
+        .logged()
+        .recorded_output()
+        .map_err(|e| format!("Failed to run kubectl {}: {}", args.join(" "), e))?;
+    if out.status.success() {
+        Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "kubectl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
+
+/// `kubectl apply -f -` with the manifest on stdin.
+fn kubectl_apply(k: &KubernetesConfig, manifest: &Value) -> Result<(), String> {
+    let mut child = kubectl(k)
+        .args(["apply", "-f", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .logged()
+        .spawn()
+        .map_err(|e| format!("Failed to run kubectl apply: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(manifest.to_string().as_bytes())
+            .map_err(|e| format!("Failed to send manifest to kubectl: {}", e))?;
+    }
+    let out = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run kubectl apply: {}", e))?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "kubectl apply failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
+
+/// `kubectl exec [-i] <pod> -c agent --`; append the command to run.
+pub(crate) fn exec_command(k: &KubernetesConfig, pod: &str, stdin: bool) -> Command {
+    let mut cmd = kubectl(k);
+    cmd.arg("exec");
+    if stdin {
+        cmd.arg("-i");
+    }
+    cmd.args([pod, "-c", AGENT_CONTAINER, "--"]);
+    cmd
+}
+
+/// `kubectl logs <flags> <pod> -c agent`.
+pub(crate) fn logs_command(k: &KubernetesConfig, pod: &str, flags: &[String]) -> Command {
+    let mut cmd = kubectl(k);
+    cmd.arg("logs")
+        .args(flags)
+        .args([pod, "-c", AGENT_CONTAINER]);
+    cmd
+}
+
+/// Pod phase ("Running", "Pending", ...); None when the pod does not exist.
+pub(crate) fn pod_phase(k: &KubernetesConfig, pod: &str) -> Result<Option<String>, String> {
+    let phase = kubectl_output(
+        k,
+        &[
+            "get",
+            "pod",
+            pod,
+            "--ignore-not-found",
+            "-o",
+            "jsonpath={.status.phase}",
+        ],
+    )?;
+    Ok((!phase.is_empty()).then_some(phase))
+}
+
+/// Create the agent pod (plus secrets for env and SSH key) and wait until `opencode serve`
+/// answers its readiness probe. Workspace and /state are emptyDir volumes, so they survive
+/// container restarts but not pod deletion. Returns the in-pod server port.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn start_spawned_pod(
+    k: &KubernetesConfig,
+    project: &str,
+    branch: &str,
+    port: u16,
+    image: &str,
+    startup_script: &str,
+    ssh_key: Option<&Path>,
+    env_vars: &[(String, String)],
+) -> Result<u16, String> {
+    let pod = spawn_pod_name(project, branch);
+    match pod_phase(k, &pod)?.as_deref() {
+        Some("Running") | Some("Pending") => {
+            wait_for_pod(k, &pod)?;
+            return Ok(port);
+        }
+        Some(_) => delete_spawned_pod(k, &pod)?,
+        None => {}
+    }
+
+    let labels = json!({ SPAWN_LABEL: "true", POD_LABEL: pod });
+    let env: serde_json::Map<String, Value> = env_vars
+        .iter()
+        .map(|(key, value)| (key.clone(), Value::from(value.as_str())))
+        .collect();
+    let mut items = vec![json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": { "name": format!("{}-env", pod), "labels": labels },
+        "type": "Opaque",
+        "stringData": env,
+    })];
+    let mut volumes = vec![
+        json!({ "name": "workspace", "emptyDir": {} }),
+        json!({ "name": "state", "emptyDir": {} }),
+    ];
+    let mut mounts = vec![
+        json!({ "name": "workspace", "mountPath": "/workspace" }),
+        json!({ "name": "state", "mountPath": "/state" }),
+    ];
+    // Secret volumes are read-only, so the key is copied to where the startup script expects it.
+    let mut script = String::new();
+    if let Some(key_path) = ssh_key.filter(|p| p.exists()) {
+        let key = fs::read_to_string(key_path)
+            .map_err(|e| format!("Failed to read SSH key {}: {}", key_path.display(), e))?;
+        items.push(json!({
+            "apiVersion": "v1",
+            "kind": "Secret",
+            "metadata": { "name": format!("{}-ssh", pod), "labels": labels },
+            "type": "Opaque",
+            "stringData": { "id_rsa": key },
+        }));
+        volumes.push(json!({
+            "name": "ssh",
+            "secret": { "secretName": format!("{}-ssh", pod), "defaultMode": 0o400 },
+        }));
+        mounts.push(json!({ "name": "ssh", "mountPath": "/etc/smith-ssh", "readOnly": true }));
+        script.push_str(
+            "mkdir -p /root/.ssh && cp /etc/smith-ssh/id_rsa /root/.ssh/id_rsa && chmod 600 /root/.ssh/id_rsa\n",
+        );
+    }
+    script.push_str(startup_script);
+
+    let mut resources = serde_json::Map::new();
+    for (key, value) in [("cpu", &k.cpu), ("memory", &k.memory)] {
+        if let Some(value) = value {
+            resources.insert(key.to_string(), Value::from(value.as_str()));
+        }
+    }
+    items.push(json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": {
+            "name": pod,
+            "labels": labels,
+            "annotations": {
+                "smith.dev/project": project,
+                "smith.dev/branch": branch,
+                "smith.dev/port": port.to_string(),
+            },
+        },
+        "spec": {
+            "restartPolicy": "Always",
+            "containers": [{
+                "name": AGENT_CONTAINER,
+                "image": image,
+                "command": ["/bin/sh", "-c", script],
+                "workingDir": "/",
+                "envFrom": [{ "secretRef": { "name": format!("{}-env", pod) } }],
+                "ports": [{ "containerPort": port }],
+                "readinessProbe": readiness_probe(&HealthCheck::for_project(project), port),
+                "resources": { "requests": resources, "limits": resources },
+                "volumeMounts": mounts,
+            }],
+            "volumes": volumes,
+        },
+    }));
+    kubectl_apply(
+        k,
+        &json!({ "apiVersion": "v1", "kind": "List", "items": items }),
+    )?;
+    audit::record(
+        "container_start",
+        &pod,
+        Some(format!("kubernetes pod, image {}", image)),
+    );
+    wait_for_pod(k, &pod)?;
+    Ok(port)
+}
+
+fn wait_for_pod(k: &KubernetesConfig, pod: &str) -> Result<(), String> {
+    let timeout = format!("--timeout={}", POD_READY_TIMEOUT);
+    kubectl_output(
+        k,
+        &[
+            "wait",
+            "--for=condition=Ready",
+            &format!("pod/{}", pod),
+            &timeout,
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("Agent pod {} did not become ready: {}", pod, e))
+}
+
+/// Delete an agent pod and its secrets.
+pub(crate) fn delete_spawned_pod(k: &KubernetesConfig, pod: &str) -> Result<(), String> {
+    let selector = format!("{}={}", POD_LABEL, pod);
+    kubectl_output(
+        k,
+        &[
+            "delete",
+            "pod,secret",
+            "-l",
+            &selector,
+            "--ignore-not-found",
+        ],
+    )
+    .map(|_| ())
+}
+
+/// Agent pods created by smith, shaped like spawned containers for `agent list`.
+pub(crate) fn list_spawned_pods() -> Vec<SpawnInfo> {
+    let Some(k) = kubernetes_in_use() else {
+        return Vec::new();
+    };
+    let selector = format!("{}=true", SPAWN_LABEL);
+    let Ok(raw) = kubectl_output(&k, &["get", "pods", "-l", &selector, "-o", "json"]) else {
+        return Vec::new();
+    };
+    let Ok(list) = serde_json::from_str::<Value>(&raw) else {
+        return Vec::new();
+    };
+    list["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pod| {
+            let annotations = &pod["metadata"]["annotations"];
+            let project = annotations["smith.dev/project"].as_str()?;
+            let branch = annotations["smith.dev/branch"].as_str()?;
+            Some(SpawnInfo {
+                project: project.to_string(),
+                branch: branch.to_string(),
+                container_name: spawn_container_name(project, branch),
+                container_id: pod["metadata"]["uid"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .chars()
+                    .take(12)
+                    .collect(),
+                port: annotations["smith.dev/port"]
+                    .as_str()
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(0),
+                status: format!(
+                    "{} (kubernetes)",
+                    pod["status"]["phase"].as_str().unwrap_or("Unknown")
+                ),
+                image: pod["spec"]["containers"][0]["image"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect()
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::time::Duration;
 
 /// Container name for the Ollama service.
 pub const OLLAMA_CONTAINER_NAME: &str = "smith-ollama";
@@ -8,19 +9,13 @@ pub const OLLAMA_PORT: u16 = 11434;
 
 /// Start an Ollama container for local model serving.
 /// Returns the port the Ollama API is available on.
-pub fn start_ollama_container(model: &str, gpu: bool) -> Result<u16, String> {
+pub async fn start_ollama_container(model: &str, gpu: bool) -> Result<u16, String> {
     let container_name = OLLAMA_CONTAINER_NAME;
 
     // Check if already running
-    let inspect = Command::new("docker")
-        .args(["inspect", container_name])
-        .output();
-
-    if let Ok(output) = inspect {
-        if output.status.success() {
-            println!("  Ollama container already running");
-            return Ok(OLLAMA_PORT);
-        }
+    if let Ok(Some(_)) = api::inspect_container(container_name).await {
+        println!("  Ollama container already running");
+        return Ok(OLLAMA_PORT);
     }
 
     // Build docker run command
@@ -50,33 +45,39 @@ pub fn start_ollama_container(model: &str, gpu: bool) -> Result<u16, String> {
     }
     println!();
 
-    let output = Command::new("docker")
+    api::ensure_image("ollama/ollama").await?;
+    let output = tokio::process::Command::new("docker")
         .args(&args)
+        .logged()
         .output()
+        .await
         .map_err(|e| format!("Failed to start Ollama container: {}", e))?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
         return Err(format!("Failed to start Ollama container: {}", error));
     }
+    audit::record(
+        "container_start",
+        container_name,
+        Some("image ollama/ollama".to_string()),
+    );
 
     // Wait for Ollama to be ready
     println!("  Waiting for Ollama to be ready...");
     let max_attempts = 30;
     for attempt in 1..=max_attempts {
-        let curl = Command::new("curl")
-            .args(["-s", &format!("http://localhost:{}/api/tags", OLLAMA_PORT)])
-            .output();
-
-        if let Ok(out) = curl {
-            if out.status.success() {
+        if let Ok((status, _)) =
+            http_get_local(OLLAMA_PORT, "/api/tags", Duration::from_secs(2)).await
+        {
+            if (200..300).contains(&status) {
                 println!("  Ollama is ready!");
                 return Ok(OLLAMA_PORT);
             }
         }
 
         if attempt < max_attempts {
-            std::thread::sleep(std::time::Duration::from_secs(2));
+            tokio::time::sleep(Duration::from_secs(2)).await;
         }
     }
 
@@ -84,20 +85,15 @@ pub fn start_ollama_container(model: &str, gpu: bool) -> Result<u16, String> {
 }
 
 /// Stop the Ollama container.
-pub fn stop_ollama_container() -> Result<(), String> {
+pub fn stop_ollama_container() -> Result<(), SmithError> {
     let container_name = OLLAMA_CONTAINER_NAME;
 
     // Check if exists
-    let inspect = Command::new("docker")
-        .args(["inspect", container_name])
-        .output();
-
-    if let Ok(output) = inspect {
-        if !output.status.success() {
-            // Container doesn't exist, nothing to stop
-            return Ok(());
-        }
-    } else {
+    if !matches!(
+        api::block_on(api::inspect_container(container_name)),
+        Ok(Some(_))
+    ) {
+        // Container doesn't exist, nothing to stop
         return Ok(());
     }
 
@@ -107,11 +103,8 @@ pub fn stop_ollama_container() -> Result<(), String> {
 
 /// Check if Ollama container is running.
 pub fn is_ollama_running() -> bool {
-    let output = Command::new("docker")
-        .args(["inspect", OLLAMA_CONTAINER_NAME, "-f", "{{.State.Running}}"])
-        .output();
-
-    output
-        .map(|o| o.status.success() && String::from_utf8_lossy(&o.stdout).contains("true"))
-        .unwrap_or(false)
+    matches!(
+        api::block_on(api::container_running(OLLAMA_CONTAINER_NAME)),
+        Ok(Some(true))
+    )
 }
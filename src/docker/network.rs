@@ -0,0 +1,252 @@
+use super::*;
+use std::sync::Mutex;
+
+/// Accepted values for a project's `network` setting (unset = normal bridge networking).
+pub const NETWORK_MODES: &[&str] = &["none", "restricted"];
+
+/// Extra hosts reachable in "restricted" mode besides the provider and the repo's git host.
+const RESTRICTED_REGISTRY_HOSTS: &[&str] = &[
+    "crates.io",
+    "static.crates.io",
+    "index.crates.io",
+    "registry.npmjs.org",
+    "registry.yarnpkg.com",
+    "pypi.org",
+    "files.pythonhosted.org",
+    "proxy.golang.org",
+    "sum.golang.org",
+];
+
+/// Port the egress proxy listens on inside the isolated network.
+const EGRESS_PROXY_PORT: u16 = 8888;
+
+/// Spawned containers currently attached to their isolated network (prompts get proxy env).
+static ISOLATED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn isolated_network_name(container_name: &str) -> String {
+    format!("{}-isolated", container_name)
+}
+
+fn egress_proxy_name(container_name: &str) -> String {
+    format!("{}-egress", container_name)
+}
+
+/// Host part of an http(s) URL or scp-style git remote (`git@host:owner/repo`).
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+    let host = rest.split(['/', ':']).next()?.trim();
+    (!host.is_empty() && !url.starts_with('/') && !url.starts_with('.')).then(|| host.to_string())
+}
+
+/// API host for a provider name or `provider/model` id.
+fn provider_host(provider_or_model: &str) -> Option<&'static str> {
+    let provider = provider_or_model
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    Some(match provider.as_str() {
+        "anthropic" => "api.anthropic.com",
+        "openai" => "api.openai.com",
+        "openrouter" => "openrouter.ai",
+        "google" => "generativelanguage.googleapis.com",
+        "groq" => "api.groq.com",
+        "mistral" => "api.mistral.ai",
+        "xai" => "api.x.ai",
+        "deepseek" => "api.deepseek.com",
+        "github-copilot" => "api.githubcopilot.com",
+        "opencode" => "opencode.ai",
+        "ollama" => "host.docker.internal",
+        _ => return None,
+    })
+}
+
+/// Hosts the egress proxy allows for `mode`: the provider endpoints of the given models,
+/// providers and base URLs, plus (restricted) the repo's git host and package registries.
+pub fn egress_allowlist(
+    mode: &str,
+    providers: &[&str],
+    base_urls: &[&str],
+    repo_url: &str,
+) -> Result<Vec<String>, String> {
+    if !NETWORK_MODES.contains(&mode) {
+        return Err(format!(
+            "Invalid network mode '{}' (expected one of: {})",
+            mode,
+            NETWORK_MODES.join(", ")
+        ));
+    }
+    // opencode fetches its model catalog on start.
+    let mut hosts = vec!["models.dev".to_string()];
+    hosts.extend(
+        providers
+            .iter()
+            .filter_map(|p| provider_host(p))
+            .map(str::to_string),
+    );
+    hosts.extend(base_urls.iter().filter_map(|u| url_host(u)));
+    if mode == "restricted" {
+        hosts.extend(url_host(repo_url));
+        hosts.extend(RESTRICTED_REGISTRY_HOSTS.iter().map(|h| h.to_string()));
+    }
+    hosts.sort();
+    hosts.dedup();
+    Ok(hosts)
+}
+
+/// One Docker API step of (un)isolating a container, recorded as `api network <step>` (and
+/// skipped in a simulation, which has no networks).
+fn network_step<F>(step: &str, call: F) -> Result<(), SmithError>
+where
+    F: std::future::Future<Output = Result<(), SmithError>> + Send,
+{
+    replay::value(&format!("network {}", step), || {
+        api::block_on(call).map_err(|e| e.to_string())
+    })
+    .and_then(|done| done)
+    .map_err(SmithError::Docker)
+}
+
+/// Move a spawned container onto an internal network whose only way out is an allowlisting
+/// HTTP(S) proxy. Prompts run while isolated get HTTP(S)_PROXY pointed at it.
+pub fn isolate_spawned_network(
+    project: &str,
+    branch: &str,
+    allow_hosts: &[String],
+) -> Result<(), SmithError> {
+    if kubernetes::kube_target(project).is_some() {
+        return Err(SmithError::Docker(format!(
+            "Network isolation is not available on the Kubernetes backend; use a NetworkPolicy for project '{}' or unset `network`",
+            project
+        )));
+    }
+    let container = spawn_container_name(project, branch);
+    let network = isolated_network_name(&container);
+    let proxy = egress_proxy_name(&container);
+    restore_spawned_network(project, branch)?;
+
+    network_step(
+        &format!("create {}", network),
+        api::create_network(&network, true),
+    )?;
+    let filter = allow_hosts
+        .iter()
+        .map(|h| format!("(^|\\.){}$", h.replace('.', "\\.")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let script = format!(
+        "apk add --no-cache tinyproxy >/dev/null && \
+         printf '%s\\n' \"$SMITH_EGRESS_ALLOW\" > /etc/tinyproxy/filter && \
+         printf 'Port {port}\\nListen 0.0.0.0\\nTimeout 600\\nFilter \"/etc/tinyproxy/filter\"\\nFilterType ere\\nFilterURLs Off\\nFilterDefaultDeny Yes\\nConnectPort 443\\nConnectPort 80\\n' > /etc/tinyproxy/smith.conf && \
+         exec tinyproxy -d -c /etc/tinyproxy/smith.conf",
+        port = EGRESS_PROXY_PORT
+    );
+    network_step(
+        &format!("run {}", proxy),
+        api::run_container(
+            &proxy,
+            "alpine:3",
+            vec![format!("SMITH_EGRESS_ALLOW={}", filter)],
+            vec!["sh".to_string(), "-c".to_string(), script],
+            vec!["host.docker.internal:host-gateway".to_string()],
+        ),
+    )?;
+    network_step(
+        &format!("connect {} {}", network, proxy),
+        api::connect_network(&network, &proxy, &["smith-egress"]),
+    )?;
+
+    if let Err(e) = network_step(
+        &format!("wait {}", proxy),
+        api::wait_for_process(&proxy, "tinyproxy", Duration::from_secs(60)),
+    ) {
+        let _ = restore_spawned_network(project, branch);
+        return Err(e);
+    }
+
+    let moved = network_step(
+        &format!("connect {} {}", network, container),
+        api::connect_network(&network, &container, &[]),
+    )
+    .and_then(|_| {
+        network_step(
+            &format!("disconnect bridge {}", container),
+            api::disconnect_network("bridge", &container, false),
+        )
+    });
+    if let Err(e) = moved {
+        let _ = restore_spawned_network(project, branch);
+        return Err(e);
+    }
+    ISOLATED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(container);
+    Ok(())
+}
+
+/// Put a spawned container back on the default bridge and remove its isolated network and proxy.
+/// Safe to call when the container was never isolated (also recovers from an interrupted run).
+pub fn restore_spawned_network(project: &str, branch: &str) -> Result<(), SmithError> {
+    if kubernetes::kube_target(project).is_some() {
+        return Ok(());
+    }
+    let container = spawn_container_name(project, branch);
+    let network = isolated_network_name(&container);
+    let proxy = egress_proxy_name(&container);
+    ISOLATED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|c| *c != container);
+    let exists = replay::value(&format!("network_exists {}", network), || {
+        api::block_on(api::network_exists(&network)).map_err(|e| e.to_string())
+    })
+    .and_then(|exists| exists)
+    .map_err(SmithError::Docker)?;
+    if !exists {
+        return Ok(());
+    }
+    let networks: Vec<String> = replay::value(&format!("networks {}", container), || {
+        api::block_on(api::inspect_container(&container))
+            .ok()
+            .flatten()
+            .and_then(|info| info.network_settings?.networks)
+            .map(|networks| networks.into_keys().collect())
+            .unwrap_or_default()
+    })
+    .map_err(SmithError::Docker)?;
+    if !networks.iter().any(|n| n == "bridge") {
+        network_step(
+            &format!("connect bridge {}", container),
+            api::connect_network("bridge", &container, &[]),
+        )?;
+    }
+    let _ = network_step(
+        &format!("disconnect {} {}", network, container),
+        api::disconnect_network(&network, &container, true),
+    );
+    let _ = network_step(
+        &format!("rm {}", proxy),
+        api::remove_container(&proxy, true),
+    );
+    network_step(&format!("rm {}", network), api::remove_network(&network))
+}
+
+/// `docker exec -e` arguments that route a prompt through the egress proxy while isolated.
+pub(crate) fn egress_proxy_exec_env(container_name: &str) -> Vec<String> {
+    let isolated = ISOLATED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .any(|c| c == container_name);
+    if !isolated {
+        return Vec::new();
+    }
+    let proxy = format!("http://smith-egress:{}", EGRESS_PROXY_PORT);
+    ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"]
+        .iter()
+        .flat_map(|k| ["-e".to_string(), format!("{}={}", k, proxy)])
+        .chain(["-e".to_string(), "NO_PROXY=localhost,127.0.0.1".to_string()])
+        .collect()
+}
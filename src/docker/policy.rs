@@ -0,0 +1,305 @@
+use super::*;
+use crate::config::{load_config, PolicyConfig};
+use crate::secrets::{self, SecretFinding};
+
+/// Accepted values for `policy.path_action` (unset = abort).
+pub const PATH_ACTIONS: &[&str] = &["abort", "revert"];
+
+/// Violations recorded inside the agent container by the denied-command wrappers.
+const POLICY_LOG: &str = "/state/policy-violations.log";
+
+/// Startup snippet enforcing a project's policy (from `SMITH_POLICY_*`, see [`policy_env`]):
+/// each denied command is shadowed on PATH by a wrapper that refuses and logs the attempt,
+/// and denied hosts resolve to an unroutable address. Wrappers live under /tmp so they also
+/// work with a read-only root. `opencode serve` inherits the PATH, so agent tools do too.
+pub(crate) const POLICY_SETUP: &str = r#"if [ -n "${SMITH_POLICY_DENY_COMMANDS:-}" ]; then
+    mkdir -p /tmp/smith-policy/bin
+    for cmd in $SMITH_POLICY_DENY_COMMANDS; do
+        printf '#!/bin/sh\necho "smith policy: %s is denied for this project" >&2\necho "denied command: %s $*" >> /state/policy-violations.log\nexit 126\n' "$cmd" "$cmd" > "/tmp/smith-policy/bin/$cmd"
+        chmod 755 "/tmp/smith-policy/bin/$cmd"
+    done
+    export PATH="/tmp/smith-policy/bin:$PATH"
+fi
+if [ -n "${SMITH_POLICY_DENY_HOSTS:-}" ]; then
+    for host in $SMITH_POLICY_DENY_HOSTS; do
+        grep -q "^0.0.0.0 $host\$" /etc/hosts 2>/dev/null || printf '0.0.0.0 %s\n:: %s\n' "$host" "$host" >> /etc/hosts || true
+    done
+fi"#;
+
+/// Policy of `project`, if it has a non-empty one.
+pub fn project_policy(project: &str) -> Option<PolicyConfig> {
+    load_config()
+        .ok()?
+        .projects
+        .into_iter()
+        .find(|p| p.name == project)?
+        .policy
+        .filter(|p| *p != PolicyConfig::default())
+}
+
+/// Reject command and host names that could not be enforced literally (or would break the script).
+pub fn validate_policy(policy: &PolicyConfig) -> Result<(), String> {
+    let safe = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'))
+    };
+    for cmd in &policy.denied_commands {
+        if !safe(cmd) {
+            return Err(format!(
+                "Invalid denied command '{}' (expected a bare command name like curl)",
+                cmd
+            ));
+        }
+    }
+    if let Some(action) = policy
+        .path_action
+        .as_deref()
+        .filter(|a| !PATH_ACTIONS.contains(a))
+    {
+        return Err(format!(
+            "Invalid policy path_action '{}' (expected one of: {})",
+            action,
+            PATH_ACTIONS.join(", ")
+        ));
+    }
+    for host in &policy.denied_hosts {
+        if !safe(host) {
+            return Err(format!(
+                "Invalid denied host '{}' (expected a hostname like pastebin.com)",
+                host
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Environment read by [`POLICY_SETUP`] for `project`'s policy.
+pub(crate) fn policy_env(project: &str) -> Result<Vec<(String, String)>, String> {
+    let Some(policy) = project_policy(project) else {
+        return Ok(Vec::new());
+    };
+    validate_policy(&policy)?;
+    let mut env = Vec::new();
+    if !policy.denied_commands.is_empty() {
+        env.push((
+            "SMITH_POLICY_DENY_COMMANDS".to_string(),
+            policy.denied_commands.join(" "),
+        ));
+    }
+    if !policy.denied_hosts.is_empty() {
+        env.push((
+            "SMITH_POLICY_DENY_HOSTS".to_string(),
+            policy.denied_hosts.join(" "),
+        ));
+    }
+    Ok(env)
+}
+
+/// Added plus removed lines in `git diff --numstat` output (binary files count as one line).
+pub fn diff_line_count(numstat: &str) -> u64 {
+    numstat
+        .lines()
+        .filter_map(|line| {
+            let mut cols = line.split('\t');
+            let added = cols.next()?;
+            let removed = cols.next()?;
+            Some(match (added.parse::<u64>(), removed.parse::<u64>()) {
+                (Ok(a), Ok(r)) => a + r,
+                _ => 1,
+            })
+        })
+        .sum()
+}
+
+/// Whether `path` (relative to the repo root) matches a glob. `*` and `?` stay within one path
+/// segment and `**` spans segments; a pattern without `/` matches the file name at any depth,
+/// and a pattern ending in `/` matches everything below that directory.
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./");
+    if let Some(dir) = pattern.strip_suffix('/') {
+        return path_matches(&format!("{}/**", dir), path);
+    }
+    if !pattern.contains('/') {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        return glob(pattern.as_bytes(), name.as_bytes());
+    }
+    glob(pattern.as_bytes(), path.as_bytes())
+}
+
+fn glob(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+            rest.is_empty()
+                || (0..=text.len())
+                    .any(|i| (i == 0 || text[i - 1] == b'/') && glob(rest, &text[i..]))
+        }
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob(rest, &text[i..])),
+        [b'?', rest @ ..] => {
+            matches!(text.first(), Some(c) if *c != b'/') && glob(rest, &text[1..])
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob(rest, &text[1..]),
+    }
+}
+
+/// Changed files the policy does not let the agent modify.
+pub fn protected_changes(policy: &PolicyConfig, changed: &[&str]) -> Vec<String> {
+    changed
+        .iter()
+        .filter(|path| {
+            policy.protected_paths.iter().any(|p| path_matches(p, path))
+                || (!policy.allowed_paths.is_empty()
+                    && !policy.allowed_paths.iter().any(|p| path_matches(p, path)))
+        })
+        .map(|path| path.to_string())
+        .collect()
+}
+
+/// Violations of `policy`, given the container's violation log, the pending diff's numstat
+/// and the changed file names.
+pub fn policy_violations(
+    policy: &PolicyConfig,
+    violation_log: &str,
+    numstat: &str,
+    changed: &[&str],
+) -> Vec<String> {
+    let mut violations: Vec<String> = violation_log
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+    violations.dedup();
+    violations.extend(
+        protected_changes(policy, changed)
+            .into_iter()
+            .map(|path| format!("protected path modified: {}", path)),
+    );
+    if let Some(max) = policy.max_diff_lines {
+        let lines = diff_line_count(numstat);
+        if lines > max {
+            violations.push(format!(
+                "diff too large: {} changed lines (max_diff_lines = {})",
+                lines, max
+            ));
+        }
+    }
+    violations
+}
+
+/// Check the spawned agent's pending changes and recorded attempts against `project`'s policy.
+/// Returns the violations (empty when the project has no policy or nothing was violated).
+pub fn check_spawned_policy(project: &str, branch: &str) -> Result<Vec<String>, String> {
+    let Some(policy) = project_policy(project) else {
+        return Ok(Vec::new());
+    };
+    let numstat = run_spawn_shell(
+        project,
+        branch,
+        "cd /workspace && git add -A -N . && git diff HEAD --numstat",
+    )?;
+    let log = run_spawn_shell(
+        project,
+        branch,
+        &format!("cat {} 2>/dev/null || true", POLICY_LOG),
+    )?;
+    let changed = spawned_changed_paths(project, branch)?;
+    let changed: Vec<&str> = changed.iter().map(String::as_str).collect();
+    Ok(policy_violations(&policy, &log, &numstat, &changed))
+}
+
+/// Files with pending changes in the spawned agent's workspace (renames as delete + add).
+pub fn spawned_changed_paths(project: &str, branch: &str) -> Result<Vec<String>, String> {
+    let out = run_spawn_shell(
+        project,
+        branch,
+        "cd /workspace && git add -A -N . && git diff HEAD --name-only --no-renames",
+    )?;
+    Ok(out
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Apply `project`'s path rules to the spawned agent's pending changes. With
+/// `path_action = "revert"` the offending files are restored (new files removed) and returned;
+/// otherwise any offending file is an error naming them.
+pub fn enforce_spawned_paths(project: &str, branch: &str) -> Result<Vec<String>, String> {
+    let Some(policy) = project_policy(project)
+        .filter(|p| !p.protected_paths.is_empty() || !p.allowed_paths.is_empty())
+    else {
+        return Ok(Vec::new());
+    };
+    validate_policy(&policy)?;
+    let changed = spawned_changed_paths(project, branch)?;
+    let changed: Vec<&str> = changed.iter().map(String::as_str).collect();
+    let offending = protected_changes(&policy, &changed);
+    if offending.is_empty() {
+        return Ok(offending);
+    }
+    if policy.path_action.as_deref() != Some("revert") {
+        return Err(format!(
+            "Agent modified protected paths: {} (see protected_paths/allowed_paths in the project policy)",
+            offending.join(", ")
+        ));
+    }
+    let script = offending
+        .iter()
+        .map(|path| {
+            let quoted = format!("'{}'", path.replace('\'', "'\"'\"'"));
+            format!(
+                "git checkout HEAD -- {q} 2>/dev/null || {{ git rm -q --cached -f -- {q} >/dev/null 2>&1; rm -f -- {q}; }}",
+                q = quoted
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    run_spawn_shell(project, branch, &format!("cd /workspace\n{}", script))?;
+    Ok(offending)
+}
+
+/// Scan the spawned agent's pending diff for credentials with the built-in rules, plus gitleaks
+/// (which must be installed in the agent image) when `gitleaks` is set.
+pub fn scan_spawned_secrets(
+    project: &str,
+    branch: &str,
+    gitleaks: bool,
+) -> Result<Vec<SecretFinding>, String> {
+    let diff = run_spawn_shell(
+        project,
+        branch,
+        "cd /workspace && git add -A -N . && git diff HEAD -U0 --no-color",
+    )?;
+    let mut findings = secrets::scan_diff(&diff);
+    if gitleaks {
+        let report = run_spawn_shell(
+            project,
+            branch,
+            "cd /workspace && command -v gitleaks >/dev/null || { echo SMITH_NO_GITLEAKS; exit 0; }; \
+             git add -A && gitleaks protect --staged --no-banner --redact --exit-code 0 \
+             --report-format json --report-path /tmp/smith-gitleaks.json >/dev/null 2>&1; \
+             cat /tmp/smith-gitleaks.json",
+        )?;
+        if report.contains("SMITH_NO_GITLEAKS") {
+            return Err(format!(
+                "gitleaks is not installed in the agent image for '{}'; install it in the project script or set secret_scan to builtin",
+                project
+            ));
+        }
+        for finding in secrets::parse_gitleaks_report(&report)? {
+            if !findings
+                .iter()
+                .any(|f| f.file == finding.file && f.line == finding.line)
+            {
+                findings.push(finding);
+            }
+        }
+    }
+    Ok(findings)
+}
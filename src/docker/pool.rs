@@ -0,0 +1,162 @@
+//! Warm pool of pre-provisioned spawned agents (`smith pool`): containers started ahead of time
+//! for a project on a placeholder branch (`smith-pool-<n>`), with the image, setup script, clone
+//! and agent server ready. A pipeline run claims one by renaming it to its project/branch
+//! container name and checking out the branch. Labels cannot change after creation, so the
+//! claimed branch is kept in `pool.json` in the config directory (by container id) and applied
+//! when spawned agents are listed.
+
+use super::*;
+use crate::config::config_dir;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Branch placeholder of warm pool containers ("smith-pool-1").
+pub const POOL_BRANCH_PREFIX: &str = "smith-pool-";
+
+/// Whether `branch` is a pool placeholder rather than a real branch.
+pub fn is_pool_branch(branch: &str) -> bool {
+    branch
+        .strip_prefix(POOL_BRANCH_PREFIX)
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// A pool container handed to a run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PoolClaim {
+    /// Short (12 character) container id
+    pub id: String,
+    pub project: String,
+    pub branch: String,
+}
+
+fn claims_path() -> Result<PathBuf, String> {
+    config_dir()
+        .map(|dir| dir.join("pool.json"))
+        .map_err(|e| e.to_string())
+}
+
+/// Recorded claims; empty when there are none or the file is unreadable.
+pub fn load_pool_claims() -> Vec<PoolClaim> {
+    claims_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_pool_claims(claims: &[PoolClaim]) -> Result<(), String> {
+    let path = claims_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let body = serde_json::to_string_pretty(claims).map_err(|e| e.to_string())?;
+    fs::write(&path, body).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Move recorded claims of project `old` to `new`. Returns how many were updated.
+pub fn rename_pool_claims(old: &str, new: &str) -> Result<usize, String> {
+    let mut claims = load_pool_claims();
+    let count = rename_claims(&mut claims, old, new);
+    if count > 0 {
+        save_pool_claims(&claims)?;
+    }
+    Ok(count)
+}
+
+/// Claims with `project` changed from `old` to `new`; returns how many changed.
+pub fn rename_claims(claims: &mut [PoolClaim], old: &str, new: &str) -> usize {
+    let mut count = 0;
+    for claim in claims.iter_mut().filter(|c| c.project == old) {
+        claim.project = new.to_string();
+        count += 1;
+    }
+    count
+}
+
+/// Apply recorded claims to listed spawned containers: claimed pool containers get their real
+/// branch, unclaimed ones (the warm pool itself) are split off. Returns (agents, warm pool).
+pub fn split_pool_containers(
+    containers: Vec<SpawnInfo>,
+    claims: &[PoolClaim],
+) -> (Vec<SpawnInfo>, Vec<SpawnInfo>) {
+    let mut agents = Vec::new();
+    let mut warm = Vec::new();
+    for mut c in containers {
+        if !is_pool_branch(&c.branch) {
+            agents.push(c);
+            continue;
+        }
+        match claims.iter().find(|claim| claim.id == c.container_id) {
+            Some(claim) => {
+                c.branch = claim.branch.clone();
+                agents.push(c);
+            }
+            None => warm.push(c),
+        }
+    }
+    (agents, warm)
+}
+
+/// Warm (unclaimed) pool containers, running or not.
+pub fn list_pool_containers() -> Result<Vec<SpawnInfo>, String> {
+    let all = agent_runtime::list_all_spawned_containers()?;
+    Ok(split_pool_containers(all, &load_pool_claims()).1)
+}
+
+/// Hand a running warm container of `project` to `branch`: rename it to the branch's spawned
+/// agent name and record the claim. Returns false when the pool has none. The caller checks
+/// out the branch (see [`pool_checkout_script`]).
+pub fn claim_pool_container(project: &str, branch: &str) -> Result<bool, SmithError> {
+    if simulate::active() || replay::replaying() {
+        return Ok(false);
+    }
+    let all = agent_runtime::list_all_spawned_containers().map_err(SmithError::Docker)?;
+    let mut claims = load_pool_claims();
+    // Drop claims of containers that are gone.
+    claims.retain(|claim| all.iter().any(|c| c.container_id == claim.id));
+    let warm = split_pool_containers(all, &claims).1;
+    let Some(container) = warm
+        .into_iter()
+        .find(|c| c.project == project && c.status.starts_with("Up"))
+    else {
+        return Ok(false);
+    };
+    let target = spawn_container_name(project, branch);
+    // A stopped container left under the target name would block the rename.
+    let _ = api::block_on(api::remove_container(&target, false));
+    api::block_on(api::rename_container(&container.container_name, &target)).map_err(|e| {
+        SmithError::Docker(format!(
+            "Failed to claim pool container {}: {}",
+            container.container_name, e
+        ))
+    })?;
+    claims.push(PoolClaim {
+        id: container.container_id,
+        project: project.to_string(),
+        branch: branch.to_string(),
+    });
+    save_pool_claims(&claims).map_err(SmithError::Config)?;
+    audit::record(
+        "pool_claim",
+        &target,
+        Some(format!("from {}", container.container_name)),
+    );
+    Ok(true)
+}
+
+/// Shell script switching a claimed pool container from its placeholder branch to `git_branch`:
+/// the remote branch when it exists, else a new branch from the clone's HEAD, as a fresh
+/// spawned agent would.
+pub fn pool_checkout_script(git_branch: &str) -> String {
+    format!(
+        "cd /workspace && placeholder=$(git rev-parse --abbrev-ref HEAD) && (git fetch origin 2>/dev/null || true) && if git rev-parse --verify 'origin/{branch}' >/dev/null 2>&1; then git checkout -B '{branch}' 'origin/{branch}'; else git checkout -B '{branch}'; fi && if [ \"$placeholder\" != '{branch}' ]; then git branch -D \"$placeholder\" >/dev/null 2>&1 || true; fi",
+        branch = git_branch.replace('\'', "'\"'\"'")
+    )
+}
+
+/// Remove a warm pool container, stopping it first when running.
+pub fn remove_pool_container(name: &str) -> Result<(), String> {
+    api::block_on(api::remove_container(name, true)).map_err(|e| e.to_string())?;
+    audit::record("container_remove", name, Some("warm pool".to_string()));
+    Ok(())
+}
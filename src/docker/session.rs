@@ -0,0 +1,248 @@
+//! Prompts over the OpenCode HTTP session API of a spawned agent's `opencode serve`: one
+//! session per prompt, live output from the `/event` stream, errors from the assistant
+//! message, and cancellation (Ctrl-C or the prompt timeout) with `/session/<id>/abort`.
+
+use super::*;
+use crate::logging::{read_json, LogRequest};
+use std::collections::HashMap;
+
+/// Why a session prompt did not produce an answer.
+#[derive(Debug, PartialEq)]
+pub(crate) enum SessionError {
+    /// The server could not be used (not reachable, or no session API); run the prompt another way
+    Unavailable(String),
+    /// The prompt ran and failed, or was cancelled
+    Failed(String),
+}
+
+/// Base URL of the spawned agent's server when prompts can go over HTTP: a Docker container
+/// reachable from the host. Kubernetes pods, egress-isolated containers (no host route, and the
+/// server is not behind the proxy), and simulated, recorded or replayed runs use `opencode run`.
+pub(crate) fn session_base_url(project: &str, branch: &str) -> Option<String> {
+    if simulate::active() || replay::replaying() || replay::recording() {
+        return None;
+    }
+    if kubernetes::kube_target(project).is_some() {
+        return None;
+    }
+    let name = spawn_container_name(project, branch);
+    if !network::egress_proxy_exec_env(&name).is_empty() {
+        return None;
+    }
+    let port = api::block_on(api::container_host_port(&name)).ok()?;
+    let host = HealthCheck::for_project(project).host;
+    Some(format!("http://{}", SocketAddr::new(host, port)))
+}
+
+/// Send `prompt` in a new session and wait for the answer. With `verbose`, text and reasoning
+/// are printed as they stream in. Usage is added to the prompt counters.
+pub(crate) async fn prompt(
+    base: &str,
+    prompt: &str,
+    model: Option<&str>,
+    system: Option<&str>,
+    verbose: bool,
+) -> Result<String, SessionError> {
+    let client = reqwest::Client::new();
+    let created = client
+        .post(format!("{}/session?directory=/", base))
+        .timeout(Duration::from_secs(10))
+        .json(&serde_json::json!({ "title": "smith" }))
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SessionError::Unavailable(e.to_string()))?;
+    if !created.status().is_success() {
+        return Err(SessionError::Unavailable(format!(
+            "POST /session returned {}",
+            created.status()
+        )));
+    }
+    let session: Value = read_json(created)
+        .await
+        .map_err(SessionError::Unavailable)?;
+    let id = session["id"]
+        .as_str()
+        .ok_or_else(|| SessionError::Unavailable("session has no id".to_string()))?
+        .to_string();
+
+    let events =
+        verbose.then(|| tokio::spawn(stream_output(client.clone(), base.to_string(), id.clone())));
+
+    ensure_spawn_run_sigint_handler();
+    SPAWN_RUN_CANCELLED.store(false, Ordering::SeqCst);
+    let request = client
+        .post(format!("{}/session/{}/message?directory=/", base, id))
+        .json(&message_body(prompt, model, system))
+        .logged()
+        .send();
+    tokio::pin!(request);
+    let started = std::time::Instant::now();
+    let timeout = prompt_timeout();
+    let mut tick = tokio::time::interval(Duration::from_millis(120));
+    let response = loop {
+        tokio::select! {
+            response = &mut request => break response,
+            _ = tick.tick() => {
+                let stopped = if SPAWN_RUN_CANCELLED.load(Ordering::SeqCst) {
+                    Some("Cancelled by user.".to_string())
+                } else {
+                    timeout
+                        .filter(|limit| started.elapsed() >= *limit)
+                        .map(prompt_timeout_error)
+                };
+                if let Some(reason) = stopped {
+                    let _ = client
+                        .post(format!("{}/session/{}/abort?directory=/", base, id))
+                        .timeout(Duration::from_secs(10))
+                        .logged()
+                        .send()
+                        .await;
+                    if let Some(events) = &events {
+                        events.abort();
+                    }
+                    return Err(SessionError::Failed(reason));
+                }
+            }
+        }
+    };
+    if let Some(events) = events {
+        events.abort();
+        println!();
+    }
+
+    let response =
+        response.map_err(|e| SessionError::Failed(format!("Agent request failed: {}", e)))?;
+    let status = response.status();
+    let message: Value = read_json(response).await.map_err(|e| {
+        SessionError::Failed(format!(
+            "Agent request failed: unreadable response ({}): {}",
+            status, e
+        ))
+    })?;
+    if !status.is_success() {
+        return Err(SessionError::Failed(if message["name"].is_string() {
+            session_error_message(&message)
+        } else {
+            format!("Agent request failed: server returned {}", status)
+        }));
+    }
+    let info = &message["info"];
+    PROMPT_TOKENS.fetch_add(message_tokens(info), Ordering::Relaxed);
+    if let Some(cost) = info["cost"].as_f64() {
+        PROMPT_COST_MICROS.fetch_add((cost * 1e6).round() as u64, Ordering::Relaxed);
+    }
+    if let Some(error) = info.get("error").filter(|e| !e.is_null()) {
+        return Err(SessionError::Failed(session_error_message(error)));
+    }
+    Ok(message_text(&message))
+}
+
+/// Request body for `POST /session/<id>/message`.
+pub fn message_body(prompt: &str, model: Option<&str>, system: Option<&str>) -> Value {
+    let mut body = serde_json::json!({
+        "parts": [{ "type": "text", "text": prompt }],
+    });
+    if let Some((provider, model)) = model
+        .map(str::trim)
+        .and_then(|m| m.split_once('/'))
+        .filter(|(p, m)| !p.is_empty() && !m.is_empty())
+    {
+        body["model"] = serde_json::json!({ "providerID": provider, "modelID": model });
+    }
+    if let Some(system) = system.filter(|s| !s.trim().is_empty()) {
+        body["system"] = Value::from(system);
+    }
+    body
+}
+
+/// The answer: the message's text parts, in order.
+pub fn message_text(message: &Value) -> String {
+    message["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|part| part["type"] == "text" && part["synthetic"] != true)
+        .filter_map(|part| part["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+/// Input, output and reasoning tokens of an assistant message.
+fn message_tokens(info: &Value) -> u64 {
+    ["input", "output", "reasoning"]
+        .iter()
+        .filter_map(|k| info["tokens"][k].as_u64())
+        .sum()
+}
+
+/// Readable error for a failed assistant message (`{ name, data: { message, statusCode } }`),
+/// using the same wording as `opencode run` failures for rate limits and authentication.
+pub fn session_error_message(error: &Value) -> String {
+    let name = error["name"].as_str().unwrap_or("UnknownError");
+    let message = error["data"]["message"]
+        .as_str()
+        .or_else(|| error["message"].as_str())
+        .unwrap_or_default();
+    if name == "MessageAbortedError" {
+        return "Cancelled by user.".to_string();
+    }
+    let status = error["data"]["statusCode"].as_u64();
+    if name == "ProviderAuthError" || status == Some(401) {
+        return classify_spawn_run_error("authentication", None);
+    }
+    let mut raw = format!("{}: {}", name, message);
+    if let Some(code) = status {
+        raw.push_str(&format!(" (status code {})", code));
+    }
+    if has_hard_failure_signal(&raw) {
+        classify_spawn_run_error(&raw, None)
+    } else {
+        format!("Agent request failed: {}", raw.trim_end_matches(": "))
+    }
+}
+
+/// Print the session's text and reasoning as it streams in over `/event`, until aborted.
+async fn stream_output(client: reqwest::Client, base: String, session: String) {
+    let Ok(mut response) = client
+        .get(format!("{}/event?directory=/", base))
+        .logged()
+        .send()
+        .await
+    else {
+        return;
+    };
+    let mut printed: HashMap<String, usize> = HashMap::new();
+    let mut buffer = String::new();
+    while let Ok(Some(chunk)) = response.chunk().await {
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(end) = buffer.find('\n') {
+            let line: String = buffer.drain(..=end).collect();
+            if let Some((part_id, text)) = streamed_text(&line, &session) {
+                let seen = printed.entry(part_id).or_default();
+                if let Some(new) = text.get(*seen..) {
+                    print!("{}", new);
+                    let _ = std::io::stdout().flush();
+                    *seen = text.len();
+                }
+            }
+        }
+    }
+}
+
+/// Part id and full text so far of a `message.part.updated` SSE line for `session`'s text or
+/// reasoning.
+pub fn streamed_text(line: &str, session: &str) -> Option<(String, String)> {
+    let event: Value = serde_json::from_str(line.strip_prefix("data:")?.trim()).ok()?;
+    if event["type"] != "message.part.updated" {
+        return None;
+    }
+    let part = &event["properties"]["part"];
+    if part["sessionID"] != session || !(part["type"] == "text" || part["type"] == "reasoning") {
+        return None;
+    }
+    Some((
+        part["id"].as_str()?.to_string(),
+        part["text"].as_str()?.to_string(),
+    ))
+}
@@ -0,0 +1,42 @@
+use serde_json::json;
+
+/// Typed error for smith operations. Converts into `String` so modules that still
+/// return `Result<_, String>` can propagate it with `?`.
+#[derive(Debug, thiserror::Error)]
+pub enum SmithError {
+    /// Reading, parsing or writing smith configuration.
+    #[error("{0}")]
+    Config(String),
+    /// Docker daemon/API or container lifecycle failure.
+    #[error("{0}")]
+    Docker(String),
+    /// Git forge (GitHub, Gitea) API or authentication failure.
+    #[error("{0}")]
+    Forge(String),
+    /// Invalid user input or configuration values.
+    #[error("{0}")]
+    Validation(String),
+}
+
+impl SmithError {
+    /// Stable machine-readable code for this error category.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SmithError::Config(_) => "config_error",
+            SmithError::Docker(_) => "docker_error",
+            SmithError::Forge(_) => "forge_error",
+            SmithError::Validation(_) => "validation_error",
+        }
+    }
+
+    /// JSON error object for `--json` output: {"error": {"code": ..., "message": ...}}.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({ "error": { "code": self.code(), "message": self.to_string() } })
+    }
+}
+
+impl From<SmithError> for String {
+    fn from(err: SmithError) -> Self {
+        err.to_string()
+    }
+}
@@ -0,0 +1,220 @@
+//! Lifecycle event bus: pipeline code emits typed events and every configured
+//! `[[event_subscribers]]` entry receives the ones it asked for.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{config_dir, load_config, EventSubscriber};
+use crate::docker::api::block_on;
+use crate::error::SmithError;
+use crate::logging::LogRequest;
+
+/// Sink kinds accepted in `event_subscribers[].sink`.
+pub const EVENT_SINKS: &[&str] = &["log", "webhook", "notify"];
+
+/// Typed lifecycle events. Serialized with an `event` tag (e.g. `"event": "pr_opened"`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SmithEvent {
+    RunStarted {
+        stage: String,
+        project: String,
+        branch: String,
+        run_id: String,
+    },
+    StageCompleted {
+        stage: String,
+        project: String,
+        branch: String,
+        run_id: String,
+        /// Final manifest state ("completed" or "failed")
+        state: String,
+        duration_secs: u64,
+    },
+    PushPerformed {
+        project: String,
+        branch: String,
+        commit: String,
+    },
+    PrOpened {
+        project: Option<String>,
+        branch: String,
+        url: String,
+    },
+}
+
+impl SmithEvent {
+    /// Event name as used in subscriber filters.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SmithEvent::RunStarted { .. } => "run_started",
+            SmithEvent::StageCompleted { .. } => "stage_completed",
+            SmithEvent::PushPerformed { .. } => "push_performed",
+            SmithEvent::PrOpened { .. } => "pr_opened",
+        }
+    }
+
+    /// One-line human summary (used for desktop notifications).
+    pub fn summary(&self) -> String {
+        match self {
+            SmithEvent::RunStarted {
+                stage,
+                project,
+                branch,
+                ..
+            } => format!("{} started for {}/{}", stage, project, branch),
+            SmithEvent::StageCompleted {
+                stage,
+                project,
+                branch,
+                state,
+                duration_secs,
+                ..
+            } => format!(
+                "{} {} for {}/{} in {}s",
+                stage, state, project, branch, duration_secs
+            ),
+            SmithEvent::PushPerformed {
+                project,
+                branch,
+                commit,
+            } => format!(
+                "Pushed {} to {}/{}",
+                &commit[..commit.len().min(12)],
+                project,
+                branch
+            ),
+            SmithEvent::PrOpened { branch, url, .. } => {
+                format!("Pull request for {}: {}", branch, url)
+            }
+        }
+    }
+
+    /// JSON payload delivered to sinks: the tagged event plus an `at` unix timestamp.
+    pub fn to_payload(&self) -> Value {
+        let mut payload = serde_json::to_value(self).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut payload {
+            let at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            map.insert("at".to_string(), Value::from(at));
+        }
+        payload
+    }
+}
+
+/// True when the subscriber wants this event (an empty filter means every event).
+pub fn subscribed(subscriber: &EventSubscriber, event: &SmithEvent) -> bool {
+    subscriber.events.is_empty() || subscriber.events.iter().any(|e| e == event.name())
+}
+
+/// Deliver an event to every matching subscriber.
+/// Sink failures only warn: events never fail the run that emitted them.
+pub fn emit(event: &SmithEvent) {
+    crate::audit::record_event(event);
+    let Ok(cfg) = load_config() else {
+        return;
+    };
+    if cfg.event_subscribers.is_empty() {
+        return;
+    }
+    let payload = event.to_payload();
+    for subscriber in cfg
+        .event_subscribers
+        .iter()
+        .filter(|s| subscribed(s, event))
+    {
+        if let Err(e) = deliver(subscriber, event, &payload) {
+            eprintln!(
+                "  Warning: {} event sink '{}' failed: {}",
+                event.name(),
+                subscriber.sink,
+                e
+            );
+        }
+    }
+}
+
+fn deliver(
+    subscriber: &EventSubscriber,
+    event: &SmithEvent,
+    payload: &Value,
+) -> Result<(), SmithError> {
+    match subscriber.sink.as_str() {
+        "log" => append_log(subscriber.target.as_deref(), payload),
+        "webhook" => {
+            let url = subscriber.target.as_deref().ok_or_else(|| {
+                SmithError::Config("webhook event sink requires `target` (URL)".to_string())
+            })?;
+            block_on(post_webhook(url, payload))
+        }
+        "notify" => notify(&event.summary()),
+        other => Err(SmithError::Config(format!(
+            "Unknown event sink '{}' (expected one of: {})",
+            other,
+            EVENT_SINKS.join(", ")
+        ))),
+    }
+}
+
+/// Append the payload as one JSON line (default file: <config dir>/events.jsonl).
+fn append_log(target: Option<&str>, payload: &Value) -> Result<(), SmithError> {
+    let path = match target {
+        Some(path) => PathBuf::from(path),
+        None => config_dir()?.join("events.jsonl"),
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| SmithError::Config(format!("Failed to open {}: {}", path.display(), e)))?;
+    writeln!(file, "{}", payload)
+        .map_err(|e| SmithError::Config(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+async fn post_webhook(url: &str, payload: &Value) -> Result<(), SmithError> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("User-Agent", "agent-smith")
+        .timeout(Duration::from_secs(10))
+        .json(payload)
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Config(format!("Failed to POST {}: {}", url, e)))?;
+    if !response.status().is_success() {
+        return Err(SmithError::Config(format!(
+            "Webhook {} returned {}",
+            url,
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+/// Desktop notification via notify-send (Linux) or osascript (macOS).
+fn notify(message: &str) -> Result<(), SmithError> {
+    let status = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"smith\"",
+            message.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        Command::new("osascript").args(["-e", &script]).status()
+    } else {
+        Command::new("notify-send")
+            .args(["smith", message])
+            .status()
+    };
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => Err(SmithError::Config(format!("notifier exited with {}", s))),
+        Err(e) => Err(SmithError::Config(format!("notifier unavailable: {}", e))),
+    }
+}
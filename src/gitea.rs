@@ -0,0 +1,356 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::SmithError;
+use crate::github::{with_retry, MergedBranch, SMITH_PR_BODY_MARKER};
+use crate::logging::{read_json, LogRequest};
+
+/// Gitea/Forgejo PR response
+#[derive(Debug, Serialize, Deserialize)]
+struct PullRequest {
+    number: u64,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+    head: BranchRef,
+    base: BranchRef,
+    #[serde(default)]
+    merged: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BranchRef {
+    #[serde(rename = "ref")]
+    ref_name: String,
+}
+
+/// Create PR request payload
+#[derive(Debug, Serialize)]
+struct CreatePRRequest {
+    title: String,
+    body: String,
+    head: String,
+    base: String,
+}
+
+/// Edit PR request payload (title, body and/or state)
+#[derive(Debug, Serialize)]
+struct EditPRRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<String>,
+}
+
+/// API root for a Gitea/Forgejo instance (e.g. https://git.example.com -> .../api/v1).
+fn api_root(base_url: &str) -> String {
+    let trimmed = base_url.trim().trim_end_matches('/');
+    if trimmed.ends_with("/api/v1") {
+        trimmed.to_string()
+    } else {
+        format!("{}/api/v1", trimmed)
+    }
+}
+
+/// Find existing open PR for a branch
+async fn find_existing_pr(
+    base_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+) -> Result<Option<PullRequest>, SmithError> {
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/repos/{}/{}/pulls?state=open&limit=50",
+        api_root(base_url),
+        owner,
+        repo
+    );
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("token {}", token))
+        .header("Accept", "application/json")
+        .header("User-Agent", "agent-smith")
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to query Gitea API: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "Gitea API error ({}): {}",
+            status, error_text
+        )));
+    }
+
+    let prs: Vec<PullRequest> = read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse Gitea API response: {}", e)))?;
+
+    Ok(prs.into_iter().find(|pr| pr.head.ref_name == branch))
+}
+
+/// Create a new pull request
+async fn create_pr(
+    base_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    payload: &CreatePRRequest,
+) -> Result<String, SmithError> {
+    let client = reqwest::Client::new();
+    let url = format!("{}/repos/{}/{}/pulls", api_root(base_url), owner, repo);
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("token {}", token))
+        .header("Accept", "application/json")
+        .header("User-Agent", "agent-smith")
+        .json(payload)
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to create PR: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "Failed to create PR ({}): {}",
+            status, error_text
+        )));
+    }
+
+    let pr: PullRequest = read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse PR response: {}", e)))?;
+
+    Ok(pr.html_url)
+}
+
+/// Edit an existing pull request (title, body or state)
+async fn edit_pr(
+    base_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    pr_number: u64,
+    payload: &EditPRRequest,
+) -> Result<String, SmithError> {
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/repos/{}/{}/pulls/{}",
+        api_root(base_url),
+        owner,
+        repo,
+        pr_number
+    );
+
+    let response = client
+        .patch(&url)
+        .header("Authorization", format!("token {}", token))
+        .header("Accept", "application/json")
+        .header("User-Agent", "agent-smith")
+        .json(payload)
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to update PR: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "Failed to update PR ({}): {}",
+            status, error_text
+        )));
+    }
+
+    let pr: PullRequest = read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse PR response: {}", e)))?;
+
+    Ok(pr.html_url)
+}
+
+/// Create or update a pull request on a Gitea/Forgejo instance.
+/// Only creates one PR per branch (updates existing if found).
+pub async fn create_or_update_pr(
+    base_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+    base: &str,
+    title: &str,
+) -> Result<String, SmithError> {
+    let existing =
+        with_retry(|| async move { find_existing_pr(base_url, token, owner, repo, branch).await })
+            .await;
+
+    if let Ok(Some(existing_pr)) = existing {
+        println!(
+            "  Found existing PR #{} for branch '{}', updating...",
+            existing_pr.number, branch
+        );
+        let payload = EditPRRequest {
+            title: Some(title.to_string()),
+            body: None,
+            state: None,
+        };
+        let payload = &payload;
+        return with_retry(|| async move {
+            edit_pr(base_url, token, owner, repo, existing_pr.number, payload).await
+        })
+        .await;
+    }
+
+    if let Err(e) = existing {
+        println!("  Could not check for existing PRs: {}", e);
+        println!("  Attempting to create new pull request...");
+    } else {
+        println!("  Creating new pull request for branch '{}'...", branch);
+    }
+    let payload = CreatePRRequest {
+        title: title.to_string(),
+        body: format!("{} for branch `{}`", SMITH_PR_BODY_MARKER, branch),
+        head: branch.to_string(),
+        base: base.to_string(),
+    };
+    let payload = &payload;
+    with_retry(|| async move { create_pr(base_url, token, owner, repo, payload).await }).await
+}
+
+/// Close an open pull request for the given head branch and base branch, if one exists.
+/// Returns Ok(Some(url)) when closed, Ok(None) when no matching open PR exists.
+pub async fn close_pr_for_branch(
+    base_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+    base: &str,
+    close_note: &str,
+) -> Result<Option<String>, SmithError> {
+    let existing =
+        with_retry(|| async move { find_existing_pr(base_url, token, owner, repo, branch).await })
+            .await?;
+
+    let Some(existing_pr) = existing else {
+        return Ok(None);
+    };
+
+    if existing_pr.base.ref_name != base {
+        return Err(SmithError::Forge(format!(
+            "Found open PR #{} for '{}' but base is '{}' (expected '{}')",
+            existing_pr.number, branch, existing_pr.base.ref_name, base
+        )));
+    }
+
+    let payload = EditPRRequest {
+        title: None,
+        body: Some(close_note.to_string()),
+        state: Some("closed".to_string()),
+    };
+    let payload = &payload;
+    let url = with_retry(|| async move {
+        edit_pr(base_url, token, owner, repo, existing_pr.number, payload).await
+    })
+    .await?;
+    Ok(Some(url))
+}
+
+/// List head branches of the most recent merged pull requests (up to 50 closed PRs).
+pub async fn list_merged_pr_branches(
+    base_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+) -> Result<Vec<MergedBranch>, SmithError> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls?state=closed&limit=50",
+        api_root(base_url),
+        owner,
+        repo
+    );
+    let prs: Vec<PullRequest> = with_retry(|| {
+        let url = url.clone();
+        async move {
+            let response = reqwest::Client::new()
+                .get(&url)
+                .header("Authorization", format!("token {}", token))
+                .header("Accept", "application/json")
+                .header("User-Agent", "agent-smith")
+                .logged()
+                .send()
+                .await
+                .map_err(|e| SmithError::Forge(format!("Failed to query Gitea API: {}", e)))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(SmithError::Forge(format!(
+                    "Gitea API error ({}): {}",
+                    status, error_text
+                )));
+            }
+            read_json(response).await.map_err(|e| {
+                SmithError::Forge(format!("Failed to parse Gitea API response: {}", e))
+            })
+        }
+    })
+    .await?;
+    Ok(prs
+        .into_iter()
+        .filter(|pr| pr.merged)
+        .map(|pr| MergedBranch {
+            smith_created: pr
+                .body
+                .as_deref()
+                .is_some_and(|b| b.contains(SMITH_PR_BODY_MARKER)),
+            branch: pr.head.ref_name,
+            pr_number: pr.number,
+        })
+        .collect())
+}
+
+/// Delete a remote branch. Returns Ok(false) when the branch no longer exists.
+pub async fn delete_branch(
+    base_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+) -> Result<bool, SmithError> {
+    let url = format!(
+        "{}/repos/{}/{}/branches/{}",
+        api_root(base_url),
+        owner,
+        repo,
+        branch
+    );
+    let response = reqwest::Client::new()
+        .delete(&url)
+        .header("Authorization", format!("token {}", token))
+        .header("User-Agent", "agent-smith")
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to delete branch: {}", e)))?;
+    match response.status().as_u16() {
+        204 => Ok(true),
+        404 => Ok(false),
+        _ => {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            Err(SmithError::Forge(format!(
+                "Failed to delete branch '{}' ({}): {}",
+                branch, status, error_text
+            )))
+        }
+    }
+}
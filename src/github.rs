@@ -1,28 +1,36 @@
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use crate::error::SmithError;
+use crate::logging::{read_json, LogRequest};
+
 /// Max retries for GitHub API calls (rate limit / transient errors).
 const GITHUB_API_MAX_RETRIES: u32 = 3;
 /// Initial backoff duration; doubles each retry.
 const GITHUB_API_INITIAL_BACKOFF_MS: u64 = 1000;
+/// Marker in PR bodies written by smith; used to recognise smith branches during cleanup.
+pub const SMITH_PR_BODY_MARKER: &str = "Automated PR created by Agent Smith";
+/// Default REST API root (GitHub Enterprise Server uses https://<host>/api/v3).
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
 
 /// Run an async closure with retry and exponential backoff. Retries on 429 (rate limit),
 /// 503 (unavailable), and transient reqwest errors.
-async fn with_retry<F, Fut, T>(mut f: F) -> Result<T, String>
+pub(crate) async fn with_retry<F, Fut, T>(mut f: F) -> Result<T, SmithError>
 where
     F: FnMut() -> Fut,
-    Fut: std::future::Future<Output = Result<T, String>>,
+    Fut: std::future::Future<Output = Result<T, SmithError>>,
 {
     let mut backoff_ms = GITHUB_API_INITIAL_BACKOFF_MS;
     for attempt in 0..=GITHUB_API_MAX_RETRIES {
         match f().await {
             Ok(t) => return Ok(t),
             Err(e) => {
-                let retryable = e.contains("429")
-                    || e.contains("503")
-                    || e.contains("Failed to query")
-                    || e.contains("Failed to create")
-                    || e.contains("Failed to update");
+                let message = e.to_string();
+                let retryable = message.contains("429")
+                    || message.contains("503")
+                    || message.contains("Failed to query")
+                    || message.contains("Failed to create")
+                    || message.contains("Failed to update");
                 if retryable && attempt < GITHUB_API_MAX_RETRIES {
                     tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
                     backoff_ms = (backoff_ms * 2).min(30_000);
@@ -47,7 +55,7 @@ pub struct RepoInfo {
 /// - https://github.com/owner/repo
 /// - git@github.com:owner/repo.git
 /// - git@github.com:owner/repo
-pub fn extract_repo_info(url: &str) -> Result<RepoInfo, String> {
+pub fn extract_repo_info(url: &str) -> Result<RepoInfo, SmithError> {
     // Remove .git suffix if present
     let url = url.trim_end_matches(".git");
 
@@ -55,12 +63,18 @@ pub fn extract_repo_info(url: &str) -> Result<RepoInfo, String> {
     if url.starts_with("git@") {
         let parts: Vec<&str> = url.split(':').collect();
         if parts.len() != 2 {
-            return Err(format!("Invalid SSH URL format: {}", url));
+            return Err(SmithError::Validation(format!(
+                "Invalid SSH URL format: {}",
+                url
+            )));
         }
         let repo_part = parts[1];
         let repo_parts: Vec<&str> = repo_part.split('/').collect();
         if repo_parts.len() < 2 {
-            return Err(format!("Invalid repository path in SSH URL: {}", url));
+            return Err(SmithError::Validation(format!(
+                "Invalid repository path in SSH URL: {}",
+                url
+            )));
         }
         let owner = repo_parts[repo_parts.len() - 2].to_string();
         let name = repo_parts[repo_parts.len() - 1].to_string();
@@ -71,31 +85,44 @@ pub fn extract_repo_info(url: &str) -> Result<RepoInfo, String> {
     if url.contains("github.com") {
         let parts: Vec<&str> = url.split("github.com").collect();
         if parts.len() != 2 {
-            return Err(format!("Invalid GitHub URL format: {}", url));
+            return Err(SmithError::Validation(format!(
+                "Invalid GitHub URL format: {}",
+                url
+            )));
         }
         let path = parts[1].trim_start_matches('/').trim_end_matches('/');
         let path_parts: Vec<&str> = path.split('/').collect();
         if path_parts.len() < 2 {
-            return Err(format!("Invalid repository path in GitHub URL: {}", url));
+            return Err(SmithError::Validation(format!(
+                "Invalid repository path in GitHub URL: {}",
+                url
+            )));
         }
         let owner = path_parts[0].to_string();
         let name = path_parts[1].to_string();
         return Ok(RepoInfo { owner, name });
     }
 
-    Err(format!("Unsupported repository URL format: {}", url))
+    Err(SmithError::Validation(format!(
+        "Unsupported repository URL format: {}",
+        url
+    )))
 }
 
 /// GitHub PR response
 #[derive(Debug, Serialize, Deserialize)]
 struct PullRequest {
     number: u64,
+    #[serde(default)]
+    node_id: String,
     html_url: String,
     title: String,
     body: Option<String>,
     head: BranchRef,
     base: BranchRef,
     state: String,
+    #[serde(default)]
+    merged_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -128,15 +155,20 @@ struct ClosePRRequest {
 
 /// Find existing PR for a branch
 async fn find_existing_pr(
+    api_url: &str,
     token: &str,
     owner: &str,
     repo: &str,
     branch: &str,
-) -> Result<Option<PullRequest>, String> {
+) -> Result<Option<PullRequest>, SmithError> {
     let client = reqwest::Client::new();
     let url = format!(
-        "https://api.github.com/repos/{}/{}/pulls?head={}:{}&state=open",
-        owner, repo, owner, branch
+        "{}/repos/{}/{}/pulls?head={}:{}&state=open",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo,
+        owner,
+        branch
     );
 
     let response = client
@@ -144,20 +176,23 @@ async fn find_existing_pr(
         .header("Authorization", format!("token {}", token))
         .header("Accept", "application/vnd.github.v3+json")
         .header("User-Agent", "agent-smith")
+        .logged()
         .send()
         .await
-        .map_err(|e| format!("Failed to query GitHub API: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to query GitHub API: {}", e)))?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("GitHub API error ({}): {}", status, error_text));
+        return Err(SmithError::Forge(format!(
+            "GitHub API error ({}): {}",
+            status, error_text
+        )));
     }
 
-    let prs: Vec<PullRequest> = response
-        .json()
+    let prs: Vec<PullRequest> = read_json(response)
         .await
-        .map_err(|e| format!("Failed to parse GitHub API response: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to parse GitHub API response: {}", e)))?;
 
     // Find PR with matching head branch
     for pr in prs {
@@ -169,25 +204,30 @@ async fn find_existing_pr(
     Ok(None)
 }
 
+fn new_pr_payload(branch: &str, base: &str, title: &str) -> CreatePRRequest {
+    CreatePRRequest {
+        title: title.to_string(),
+        body: format!("{} for branch `{}`", SMITH_PR_BODY_MARKER, branch),
+        head: branch.to_string(),
+        base: base.to_string(),
+    }
+}
+
 /// Create a new pull request
 async fn create_pr(
+    api_url: &str,
     token: &str,
     owner: &str,
     repo: &str,
-    branch: &str,
-    base: &str,
-    title: &str,
-    body: &str,
-) -> Result<String, String> {
+    payload: &CreatePRRequest,
+) -> Result<String, SmithError> {
     let client = reqwest::Client::new();
-    let url = format!("https://api.github.com/repos/{}/{}/pulls", owner, repo);
-
-    let payload = CreatePRRequest {
-        title: title.to_string(),
-        body: body.to_string(),
-        head: branch.to_string(),
-        base: base.to_string(),
-    };
+    let url = format!(
+        "{}/repos/{}/{}/pulls",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo
+    );
 
     let response = client
         .post(&url)
@@ -195,38 +235,45 @@ async fn create_pr(
         .header("Accept", "application/vnd.github.v3+json")
         .header("User-Agent", "agent-smith")
         .header("Content-Type", "application/json")
-        .json(&payload)
+        .json(payload)
+        .logged()
         .send()
         .await
-        .map_err(|e| format!("Failed to create PR: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to create PR: {}", e)))?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to create PR ({}): {}", status, error_text));
+        return Err(SmithError::Forge(format!(
+            "Failed to create PR ({}): {}",
+            status, error_text
+        )));
     }
 
-    let pr: PullRequest = response
-        .json()
+    let pr: PullRequest = read_json(response)
         .await
-        .map_err(|e| format!("Failed to parse PR response: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to parse PR response: {}", e)))?;
 
     Ok(pr.html_url)
 }
 
 /// Update an existing pull request
 async fn update_pr(
+    api_url: &str,
     token: &str,
     owner: &str,
     repo: &str,
     pr_number: u64,
     title: Option<&str>,
     body: Option<&str>,
-) -> Result<String, String> {
+) -> Result<String, SmithError> {
     let client = reqwest::Client::new();
     let url = format!(
-        "https://api.github.com/repos/{}/{}/pulls/{}",
-        owner, repo, pr_number
+        "{}/repos/{}/{}/pulls/{}",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo,
+        pr_number
     );
 
     let payload = UpdatePRRequest {
@@ -241,30 +288,42 @@ async fn update_pr(
         .header("User-Agent", "agent-smith")
         .header("Content-Type", "application/json")
         .json(&payload)
+        .logged()
         .send()
         .await
-        .map_err(|e| format!("Failed to update PR: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to update PR: {}", e)))?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to update PR ({}): {}", status, error_text));
+        return Err(SmithError::Forge(format!(
+            "Failed to update PR ({}): {}",
+            status, error_text
+        )));
     }
 
-    let pr: PullRequest = response
-        .json()
+    let pr: PullRequest = read_json(response)
         .await
-        .map_err(|e| format!("Failed to parse PR response: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to parse PR response: {}", e)))?;
 
     Ok(pr.html_url)
 }
 
 /// Close an existing pull request.
-async fn close_pr(token: &str, owner: &str, repo: &str, pr_number: u64) -> Result<String, String> {
+async fn close_pr(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    pr_number: u64,
+) -> Result<String, SmithError> {
     let client = reqwest::Client::new();
     let url = format!(
-        "https://api.github.com/repos/{}/{}/pulls/{}",
-        owner, repo, pr_number
+        "{}/repos/{}/{}/pulls/{}",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo,
+        pr_number
     );
 
     let payload = ClosePRRequest {
@@ -278,20 +337,23 @@ async fn close_pr(token: &str, owner: &str, repo: &str, pr_number: u64) -> Resul
         .header("User-Agent", "agent-smith")
         .header("Content-Type", "application/json")
         .json(&payload)
+        .logged()
         .send()
         .await
-        .map_err(|e| format!("Failed to close PR: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to close PR: {}", e)))?;
 
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Failed to close PR ({}): {}", status, error_text));
+        return Err(SmithError::Forge(format!(
+            "Failed to close PR ({}): {}",
+            status, error_text
+        )));
     }
 
-    let pr: PullRequest = response
-        .json()
+    let pr: PullRequest = read_json(response)
         .await
-        .map_err(|e| format!("Failed to parse PR response: {}", e))?;
+        .map_err(|e| SmithError::Forge(format!("Failed to parse PR response: {}", e)))?;
 
     Ok(pr.html_url)
 }
@@ -300,15 +362,17 @@ async fn close_pr(token: &str, owner: &str, repo: &str, pr_number: u64) -> Resul
 /// Only creates one PR per branch (updates existing if found).
 /// Uses retry with backoff for rate limits and transient errors.
 pub async fn create_or_update_pr(
+    api_url: &str,
     token: &str,
     owner: &str,
     repo: &str,
     branch: &str,
     base: &str,
     title: &str,
-) -> Result<String, String> {
+) -> Result<String, SmithError> {
     let existing =
-        with_retry(|| async move { find_existing_pr(token, owner, repo, branch).await }).await;
+        with_retry(|| async move { find_existing_pr(api_url, token, owner, repo, branch).await })
+            .await;
 
     match existing {
         Ok(Some(existing_pr)) => {
@@ -317,34 +381,33 @@ pub async fn create_or_update_pr(
                 existing_pr.number, branch
             );
             with_retry(|| async move {
-                update_pr(token, owner, repo, existing_pr.number, Some(title), None).await
+                update_pr(
+                    api_url,
+                    token,
+                    owner,
+                    repo,
+                    existing_pr.number,
+                    Some(title),
+                    None,
+                )
+                .await
             })
             .await
         }
         Ok(None) => {
             println!("  Creating new pull request for branch '{}'...", branch);
-            let body = format!(
-                "Automated PR created by Agent Smith for branch `{}`",
-                branch
-            );
-            with_retry(|| {
-                let b = body.clone();
-                async move { create_pr(token, owner, repo, branch, base, title, &b).await }
-            })
-            .await
+            let payload = new_pr_payload(branch, base, title);
+            let payload = &payload;
+            with_retry(|| async move { create_pr(api_url, token, owner, repo, payload).await })
+                .await
         }
         Err(e) => {
             println!("  Could not check for existing PRs: {}", e);
             println!("  Attempting to create new pull request...");
-            let body = format!(
-                "Automated PR created by Agent Smith for branch `{}`",
-                branch
-            );
-            with_retry(|| {
-                let b = body.clone();
-                async move { create_pr(token, owner, repo, branch, base, title, &b).await }
-            })
-            .await
+            let payload = new_pr_payload(branch, base, title);
+            let payload = &payload;
+            with_retry(|| async move { create_pr(api_url, token, owner, repo, payload).await })
+                .await
         }
     }
 }
@@ -352,29 +415,32 @@ pub async fn create_or_update_pr(
 /// Close an open pull request for the given head branch and base branch, if one exists.
 /// Returns Ok(Some(url)) when closed, Ok(None) when no matching open PR exists.
 pub async fn close_pr_for_branch(
+    api_url: &str,
     token: &str,
     owner: &str,
     repo: &str,
     branch: &str,
     base: &str,
     close_note: &str,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, SmithError> {
     let existing =
-        with_retry(|| async move { find_existing_pr(token, owner, repo, branch).await }).await?;
+        with_retry(|| async move { find_existing_pr(api_url, token, owner, repo, branch).await })
+            .await?;
 
     let Some(existing_pr) = existing else {
         return Ok(None);
     };
 
     if existing_pr.base.ref_name != base {
-        return Err(format!(
+        return Err(SmithError::Forge(format!(
             "Found open PR #{} for '{}' but base is '{}' (expected '{}')",
             existing_pr.number, branch, existing_pr.base.ref_name, base
-        ));
+        )));
     }
 
     let updated_url = with_retry(|| async move {
         update_pr(
+            api_url,
             token,
             owner,
             repo,
@@ -386,7 +452,881 @@ pub async fn close_pr_for_branch(
     })
     .await?;
 
-    let _ = with_retry(|| async move { close_pr(token, owner, repo, existing_pr.number).await })
+    let _ =
+        with_retry(
+            || async move { close_pr(api_url, token, owner, repo, existing_pr.number).await },
+        )
         .await?;
     Ok(Some(updated_url))
 }
+
+/// Installation access token minted for a GitHub App.
+pub struct AppInstallationToken {
+    pub token: String,
+    /// Unix timestamp after which the token should be treated as expired.
+    pub expires_at_unix: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct InstallationResponse {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccessTokenResponse {
+    token: String,
+    expires_at: String,
+}
+
+/// Build an RS256-signed app JWT (valid ~9 minutes) from the app's PEM private key.
+fn app_jwt(app_id: &str, private_key_path: &str, now_unix: u64) -> Result<String, SmithError> {
+    let pem = std::fs::read(private_key_path).map_err(|e| {
+        SmithError::Config(format!(
+            "Failed to read GitHub App private key '{}': {}",
+            private_key_path, e
+        ))
+    })?;
+    let key = jsonwebtoken::EncodingKey::from_rsa_pem(&pem).map_err(|e| {
+        SmithError::Config(format!(
+            "Invalid GitHub App private key '{}' (expected an RSA PEM): {}",
+            private_key_path, e
+        ))
+    })?;
+    let claims = serde_json::json!({
+        "iat": now_unix.saturating_sub(60),
+        "exp": now_unix + 540,
+        "iss": app_id,
+    });
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+        &claims,
+        &key,
+    )
+    .map_err(|e| SmithError::Forge(format!("Failed to sign GitHub App JWT: {}", e)))
+}
+
+/// "2026-10-17T12:34:56Z" (GitHub's `expires_at`) to unix seconds.
+pub fn parse_github_timestamp(stamp: &str) -> Option<u64> {
+    if !stamp.ends_with('Z') || stamp.len() < 20 || stamp.as_bytes()[10] != b'T' {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| stamp.get(range)?.parse::<u32>().ok();
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = crate::budget::days_from_civil(i64::from(year), month, day);
+    u64::try_from(days * 86_400 + i64::from(hour * 3_600 + minute * 60 + second)).ok()
+}
+
+/// Mint an installation access token for a GitHub App installed on owner/repo.
+/// Installation tokens are valid for one hour; the expiry GitHub reports is returned.
+pub async fn create_app_installation_token(
+    api_url: &str,
+    app_id: &str,
+    private_key_path: &str,
+    owner: &str,
+    repo: &str,
+    now_unix: u64,
+) -> Result<AppInstallationToken, SmithError> {
+    let jwt = app_jwt(app_id, private_key_path, now_unix)?;
+    let client = reqwest::Client::new();
+    let api_url = api_url.trim_end_matches('/');
+
+    let response = client
+        .get(format!("{}/repos/{}/{}/installation", api_url, owner, repo))
+        .header("Authorization", format!("Bearer {}", jwt))
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "agent-smith")
+        .logged()
+        .send()
+        .await
+        .map_err(|e| {
+            SmithError::Forge(format!("Failed to query GitHub App installation: {}", e))
+        })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "GitHub App {} is not installed on {}/{} ({}): {}",
+            app_id, owner, repo, status, error_text
+        )));
+    }
+    let installation: InstallationResponse = read_json(response).await.map_err(|e| {
+        SmithError::Forge(format!("Failed to parse GitHub App installation: {}", e))
+    })?;
+
+    let response = client
+        .post(format!(
+            "{}/app/installations/{}/access_tokens",
+            api_url, installation.id
+        ))
+        .header("Authorization", format!("Bearer {}", jwt))
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "agent-smith")
+        .logged()
+        .send()
+        .await
+        .map_err(|e| {
+            SmithError::Forge(format!(
+                "Failed to create GitHub App installation token: {}",
+                e
+            ))
+        })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "Failed to create GitHub App installation token ({}): {}",
+            status, error_text
+        )));
+    }
+    let access: AccessTokenResponse = read_json(response).await.map_err(|e| {
+        SmithError::Forge(format!(
+            "Failed to parse GitHub App installation token: {}",
+            e
+        ))
+    })?;
+
+    let expires_at_unix = parse_github_timestamp(&access.expires_at).ok_or_else(|| {
+        SmithError::Forge(format!(
+            "Failed to parse GitHub App installation token expiry '{}'",
+            access.expires_at
+        ))
+    })?;
+    Ok(AppInstallationToken {
+        token: access.token,
+        expires_at_unix,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoPermissionsResponse {
+    #[serde(default)]
+    private: bool,
+    #[serde(default)]
+    permissions: Option<RepoPermissions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoPermissions {
+    #[serde(default)]
+    push: bool,
+}
+
+/// Verify the token can see owner/repo and push to it before attempting PR operations.
+/// Returns an actionable message naming the missing scope/permission on failure.
+pub async fn preflight_pr_access(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+) -> Result<(), SmithError> {
+    let client = reqwest::Client::new();
+    let url = format!("{}/repos/{}/{}", api_url.trim_end_matches('/'), owner, repo);
+
+    let response = with_retry(|| {
+        let client = client.clone();
+        let url = url.clone();
+        async move {
+            client
+                .get(&url)
+                .header("Authorization", format!("token {}", token))
+                .header("Accept", "application/vnd.github.v3+json")
+                .header("User-Agent", "agent-smith")
+                .logged()
+                .send()
+                .await
+                .map_err(|e| SmithError::Forge(format!("Failed to query GitHub API: {}", e)))
+        }
+    })
+    .await?;
+
+    let status = response.status();
+    // Classic PATs report their scopes; fine-grained and app tokens do not send this header.
+    let scopes: Option<Vec<String>> = response
+        .headers()
+        .get("x-oauth-scopes")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        });
+
+    match status.as_u16() {
+        401 => {
+            return Err(SmithError::Forge(
+                "Token was rejected (401): it is invalid, expired or revoked; generate a new one"
+                    .to_string(),
+            ))
+        }
+        403 | 404 => {
+            return Err(SmithError::Forge(format!(
+                "Token cannot access {}/{} ({}): grant it access to this repository \
+                 (classic PAT: 'repo' scope; fine-grained PAT or GitHub App: add the repository \
+                 with 'Metadata: read')",
+                owner, repo, status
+            )))
+        }
+        _ if !status.is_success() => {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(SmithError::Forge(format!(
+                "GitHub API error ({}): {}",
+                status, error_text
+            )));
+        }
+        _ => {}
+    }
+
+    let info: RepoPermissionsResponse = read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse GitHub API response: {}", e)))?;
+
+    if let Some(scopes) = scopes {
+        let has_repo = scopes.iter().any(|s| s == "repo");
+        let has_public_repo = scopes.iter().any(|s| s == "public_repo");
+        if !has_repo && (info.private || !has_public_repo) {
+            let needed = if info.private { "repo" } else { "public_repo" };
+            return Err(SmithError::Forge(format!(
+                "Token is missing the '{}' scope required to open pull requests on {}/{} (current scopes: {})",
+                needed,
+                owner,
+                repo,
+                if scopes.is_empty() {
+                    "none".to_string()
+                } else {
+                    scopes.join(", ")
+                }
+            )));
+        }
+    }
+
+    if let Some(permissions) = info.permissions {
+        if !permissions.push {
+            return Err(SmithError::Forge(format!(
+                "Token has read-only access to {}/{}: grant 'Contents: write' and \
+                 'Pull requests: write' (or use an account with push access)",
+                owner, repo
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Completed check run to publish on a commit.
+pub struct CheckRunReport {
+    pub name: String,
+    pub head_sha: String,
+    /// success | failure | neutral
+    pub conclusion: String,
+    pub title: String,
+    /// Markdown summary shown at the top of the check
+    pub summary: String,
+    /// Markdown details (may be empty)
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRunResponse {
+    html_url: String,
+}
+
+/// Create a completed check run. GitHub only accepts check runs from GitHub App tokens.
+pub async fn create_check_run(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    report: &CheckRunReport,
+) -> Result<String, SmithError> {
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/repos/{}/{}/check-runs",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo
+    );
+    let payload = serde_json::json!({
+        "name": report.name,
+        "head_sha": report.head_sha,
+        "status": "completed",
+        "conclusion": report.conclusion,
+        "output": {
+            "title": report.title,
+            "summary": report.summary,
+            "text": report.text,
+        },
+    });
+
+    let response = with_retry(|| {
+        let client = client.clone();
+        let url = url.clone();
+        let payload = payload.clone();
+        async move {
+            let response = client
+                .post(&url)
+                .header("Authorization", format!("token {}", token))
+                .header("Accept", "application/vnd.github.v3+json")
+                .header("User-Agent", "agent-smith")
+                .json(&payload)
+                .logged()
+                .send()
+                .await
+                .map_err(|e| SmithError::Forge(format!("Failed to create check run: {}", e)))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(SmithError::Forge(format!(
+                    "Failed to create check run ({}): {}",
+                    status, error_text
+                )));
+            }
+            Ok(response)
+        }
+    })
+    .await?;
+
+    let check: CheckRunResponse = read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse check run response: {}", e)))?;
+    Ok(check.html_url)
+}
+
+#[derive(Debug, Deserialize)]
+struct GistResponse {
+    html_url: String,
+}
+
+/// Create a secret (unlisted) gist holding one file; returns its URL. Needs a user token with
+/// the `gist` scope: GitHub App installation tokens cannot create gists.
+pub async fn create_gist(
+    api_url: &str,
+    token: &str,
+    description: &str,
+    filename: &str,
+    content: &str,
+) -> Result<String, SmithError> {
+    let client = reqwest::Client::new();
+    let url = format!("{}/gists", api_url.trim_end_matches('/'));
+    let payload = serde_json::json!({
+        "description": description,
+        "public": false,
+        "files": { filename: { "content": content } },
+    });
+
+    let response = with_retry(|| {
+        let client = client.clone();
+        let url = url.clone();
+        let payload = payload.clone();
+        async move {
+            let response = client
+                .post(&url)
+                .header("Authorization", format!("token {}", token))
+                .header("Accept", "application/vnd.github.v3+json")
+                .header("User-Agent", "agent-smith")
+                .json(&payload)
+                .logged()
+                .send()
+                .await
+                .map_err(|e| SmithError::Forge(format!("Failed to create gist: {}", e)))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(SmithError::Forge(format!(
+                    "Failed to create gist ({}): {}",
+                    status, error_text
+                )));
+            }
+            Ok(response)
+        }
+    })
+    .await?;
+
+    let gist: GistResponse = read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse gist response: {}", e)))?;
+    Ok(gist.html_url)
+}
+
+/// Label put on issues smith opens from review findings.
+pub const SMITH_ISSUE_LABEL: &str = "smith";
+
+/// An issue (pull requests excluded).
+#[derive(Debug, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub html_url: String,
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+/// Stable hash of an issue title, ignoring case and runs of whitespace; findings whose title
+/// hashes match an open issue's are not filed again.
+pub fn title_hash(title: &str) -> String {
+    let normalized = title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    format!("{:016x}", crate::util::fnv1a_64(normalized.as_bytes()))
+}
+
+/// Web root for a REST API root (api.github.com -> github.com, GHE /api/v3 -> the host).
+pub fn web_url(api_url: &str) -> String {
+    let api_url = api_url.trim_end_matches('/');
+    if api_url == DEFAULT_GITHUB_API_URL {
+        return "https://github.com".to_string();
+    }
+    api_url
+        .strip_suffix("/api/v3")
+        .unwrap_or(api_url)
+        .to_string()
+}
+
+/// Open issues carrying `label` (at most 1000).
+pub async fn list_open_issues(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    label: &str,
+) -> Result<Vec<Issue>, SmithError> {
+    let client = reqwest::Client::new();
+    let mut issues = Vec::new();
+    for page in 1..=10 {
+        let url = format!(
+            "{}/repos/{}/{}/issues?state=open&labels={}&per_page=100&page={}",
+            api_url.trim_end_matches('/'),
+            owner,
+            repo,
+            label,
+            page
+        );
+        let batch: Vec<Issue> = with_retry(|| {
+            let client = client.clone();
+            let url = url.clone();
+            async move {
+                let response = client
+                    .get(&url)
+                    .header("Authorization", format!("token {}", token))
+                    .header("Accept", "application/vnd.github.v3+json")
+                    .header("User-Agent", "agent-smith")
+                    .logged()
+                    .send()
+                    .await
+                    .map_err(|e| SmithError::Forge(format!("Failed to query issues: {}", e)))?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let error_text = response.text().await.unwrap_or_default();
+                    return Err(SmithError::Forge(format!(
+                        "Failed to query issues ({}): {}",
+                        status, error_text
+                    )));
+                }
+                read_json(response).await.map_err(|e| {
+                    SmithError::Forge(format!("Failed to parse issues response: {}", e))
+                })
+            }
+        })
+        .await?;
+        let last = batch.len() < 100;
+        issues.extend(batch.into_iter().filter(|i| i.pull_request.is_none()));
+        if last {
+            break;
+        }
+    }
+    Ok(issues)
+}
+
+/// Open an issue; returns it. Only 429/503 answers are retried.
+pub async fn create_issue(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    title: &str,
+    body: &str,
+    labels: &[String],
+) -> Result<Issue, SmithError> {
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/repos/{}/{}/issues",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo
+    );
+    let payload = serde_json::json!({ "title": title, "body": body, "labels": labels });
+    // Not `with_retry`: a failed or dropped create may still have opened the issue, so only
+    // answers that say it was not processed (429, 503) are retried.
+    let mut backoff_ms = GITHUB_API_INITIAL_BACKOFF_MS;
+    let mut attempt = 0;
+    let response = loop {
+        let response = client
+            .post(&url)
+            .header("Authorization", format!("token {}", token))
+            .header("Accept", "application/vnd.github.v3+json")
+            .header("User-Agent", "agent-smith")
+            .json(&payload)
+            .logged()
+            .send()
+            .await
+            .map_err(|e| SmithError::Forge(format!("Failed to create issue: {}", e)))?;
+        let status = response.status();
+        if matches!(status.as_u16(), 429 | 503) && attempt < GITHUB_API_MAX_RETRIES {
+            attempt += 1;
+            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+            backoff_ms = (backoff_ms * 2).min(30_000);
+            continue;
+        }
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(SmithError::Forge(format!(
+                "Failed to create issue ({}): {}",
+                status, error_text
+            )));
+        }
+        break response;
+    };
+    read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse issue response: {}", e)))
+}
+
+/// Result of waiting for CI on a ref.
+pub struct CiOutcome {
+    pub passed: bool,
+    /// True when no checks or statuses were reported for the ref.
+    pub no_checks: bool,
+    pub failed_checks: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRunsResponse {
+    #[serde(default)]
+    check_runs: Vec<CheckRunState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRunState {
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CombinedStatusResponse {
+    #[serde(default)]
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitStatus {
+    context: String,
+    state: String,
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(url: &str, token: &str) -> Result<T, SmithError> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("Authorization", format!("token {}", token))
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "agent-smith")
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to query GitHub API: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "GitHub API error ({}): {}",
+            status, error_text
+        )));
+    }
+    read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse GitHub API response: {}", e)))
+}
+
+/// Seconds to keep waiting for checks to appear before assuming the repo has no CI.
+const CI_NO_CHECKS_GRACE_SECS: u64 = 60;
+const CI_POLL_INTERVAL_SECS: u64 = 15;
+
+/// Poll check runs and commit statuses for `git_ref` until all complete or `timeout` elapses.
+pub async fn wait_for_ci(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    git_ref: &str,
+    timeout: Duration,
+) -> Result<CiOutcome, SmithError> {
+    let api_url = api_url.trim_end_matches('/');
+    let checks_url = format!(
+        "{}/repos/{}/{}/commits/{}/check-runs?per_page=100",
+        api_url, owner, repo, git_ref
+    );
+    let status_url = format!(
+        "{}/repos/{}/{}/commits/{}/status",
+        api_url, owner, repo, git_ref
+    );
+    let started = std::time::Instant::now();
+    let mut last_pending = usize::MAX;
+
+    loop {
+        let checks: CheckRunsResponse =
+            with_retry(|| async { get_json(&checks_url, token).await }).await?;
+        let statuses: CombinedStatusResponse =
+            with_retry(|| async { get_json(&status_url, token).await }).await?;
+
+        let mut pending: Vec<String> = Vec::new();
+        let mut failed: Vec<String> = Vec::new();
+        for run in &checks.check_runs {
+            if run.status != "completed" {
+                pending.push(run.name.clone());
+                continue;
+            }
+            match run.conclusion.as_deref() {
+                Some("success") | Some("neutral") | Some("skipped") => {}
+                other => failed.push(format!("{} ({})", run.name, other.unwrap_or("unknown"))),
+            }
+        }
+        for status in &statuses.statuses {
+            match status.state.as_str() {
+                "success" => {}
+                "pending" => pending.push(status.context.clone()),
+                other => failed.push(format!("{} ({})", status.context, other)),
+            }
+        }
+
+        let total = checks.check_runs.len() + statuses.statuses.len();
+        let elapsed = started.elapsed();
+        if total == 0 && elapsed.as_secs() >= CI_NO_CHECKS_GRACE_SECS {
+            return Ok(CiOutcome {
+                passed: true,
+                no_checks: true,
+                failed_checks: Vec::new(),
+            });
+        }
+        if total > 0 && pending.is_empty() {
+            return Ok(CiOutcome {
+                passed: failed.is_empty(),
+                no_checks: false,
+                failed_checks: failed,
+            });
+        }
+        if elapsed >= timeout {
+            return Err(SmithError::Forge(format!(
+                "Timed out after {}s waiting for CI; still pending: {}",
+                timeout.as_secs(),
+                pending.join(", ")
+            )));
+        }
+        if pending.len() != last_pending && total > 0 {
+            println!(
+                "  Waiting for CI: {} of {} check(s) pending",
+                pending.len(),
+                total
+            );
+            last_pending = pending.len();
+        }
+        tokio::time::sleep(Duration::from_secs(CI_POLL_INTERVAL_SECS)).await;
+    }
+}
+
+/// Open pull request identifiers needed for follow-up operations (merge, auto-merge).
+pub struct OpenPullRequest {
+    pub number: u64,
+    pub node_id: String,
+    pub html_url: String,
+}
+
+/// Look up the open pull request for a head branch.
+pub async fn find_open_pr(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+) -> Result<Option<OpenPullRequest>, SmithError> {
+    let existing =
+        with_retry(|| async move { find_existing_pr(api_url, token, owner, repo, branch).await })
+            .await?;
+    Ok(existing.map(|pr| OpenPullRequest {
+        number: pr.number,
+        node_id: pr.node_id,
+        html_url: pr.html_url,
+    }))
+}
+
+/// GraphQL endpoint for a REST API root (api.github.com -> /graphql, GHE /api/v3 -> /api/graphql).
+fn graphql_url(api_url: &str) -> String {
+    let api_url = api_url.trim_end_matches('/');
+    match api_url.strip_suffix("/v3") {
+        Some(root) => format!("{}/graphql", root),
+        None => format!("{}/graphql", api_url),
+    }
+}
+
+/// Enable GitHub auto-merge on a pull request; GitHub merges it once required checks pass.
+/// `merge_method` is one of squash|merge|rebase.
+pub async fn enable_auto_merge(
+    api_url: &str,
+    token: &str,
+    pr_node_id: &str,
+    merge_method: &str,
+) -> Result<(), SmithError> {
+    let payload = serde_json::json!({
+        "query": "mutation($id: ID!, $method: PullRequestMergeMethod!) { enablePullRequestAutoMerge(input: {pullRequestId: $id, mergeMethod: $method}) { clientMutationId } }",
+        "variables": { "id": pr_node_id, "method": merge_method.to_uppercase() },
+    });
+    let response = reqwest::Client::new()
+        .post(graphql_url(api_url))
+        .header("Authorization", format!("bearer {}", token))
+        .header("User-Agent", "agent-smith")
+        .json(&payload)
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to enable auto-merge: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "Failed to enable auto-merge ({}): {}",
+            status, error_text
+        )));
+    }
+    let body: serde_json::Value = read_json(response)
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to parse auto-merge response: {}", e)))?;
+    if let Some(errors) = body.get("errors").and_then(|v| v.as_array()) {
+        let messages: Vec<&str> = errors
+            .iter()
+            .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+            .collect();
+        if !messages.is_empty() {
+            return Err(SmithError::Forge(format!(
+                "Failed to enable auto-merge: {}",
+                messages.join("; ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Merge a pull request immediately with the given method (squash|merge|rebase).
+pub async fn merge_pr(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    pr_number: u64,
+    merge_method: &str,
+) -> Result<(), SmithError> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls/{}/merge",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo,
+        pr_number
+    );
+    let response = reqwest::Client::new()
+        .put(&url)
+        .header("Authorization", format!("token {}", token))
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "agent-smith")
+        .json(&serde_json::json!({ "merge_method": merge_method }))
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to merge PR: {}", e)))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(SmithError::Forge(format!(
+            "Failed to merge PR ({}): {}",
+            status, error_text
+        )));
+    }
+    Ok(())
+}
+
+/// Branch whose pull request has been merged.
+pub struct MergedBranch {
+    pub branch: String,
+    pub pr_number: u64,
+    /// True when the PR was opened by smith (body carries SMITH_PR_BODY_MARKER).
+    pub smith_created: bool,
+}
+
+/// List head branches of the most recent merged pull requests (up to 100 closed PRs).
+pub async fn list_merged_pr_branches(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+) -> Result<Vec<MergedBranch>, SmithError> {
+    let url = format!(
+        "{}/repos/{}/{}/pulls?state=closed&per_page=100",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo
+    );
+    let prs: Vec<PullRequest> = with_retry(|| async { get_json(&url, token).await }).await?;
+    Ok(prs
+        .into_iter()
+        .filter(|pr| pr.merged_at.is_some())
+        .map(|pr| MergedBranch {
+            smith_created: pr
+                .body
+                .as_deref()
+                .is_some_and(|b| b.contains(SMITH_PR_BODY_MARKER)),
+            branch: pr.head.ref_name,
+            pr_number: pr.number,
+        })
+        .collect())
+}
+
+/// Delete a remote branch. Returns Ok(false) when the branch no longer exists.
+pub async fn delete_branch(
+    api_url: &str,
+    token: &str,
+    owner: &str,
+    repo: &str,
+    branch: &str,
+) -> Result<bool, SmithError> {
+    let url = format!(
+        "{}/repos/{}/{}/git/refs/heads/{}",
+        api_url.trim_end_matches('/'),
+        owner,
+        repo,
+        branch
+    );
+    let response = reqwest::Client::new()
+        .delete(&url)
+        .header("Authorization", format!("token {}", token))
+        .header("Accept", "application/vnd.github.v3+json")
+        .header("User-Agent", "agent-smith")
+        .logged()
+        .send()
+        .await
+        .map_err(|e| SmithError::Forge(format!("Failed to delete branch: {}", e)))?;
+    match response.status().as_u16() {
+        204 => Ok(true),
+        404 | 422 => Ok(false),
+        _ => {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            Err(SmithError::Forge(format!(
+                "Failed to delete branch '{}' ({}): {}",
+                branch, status, error_text
+            )))
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Run history: one JSON line per finished pipeline run in `<config dir>/runs.jsonl`,
+//! kept on the host so it outlives spawned containers and their `/state` manifests.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_dir;
+use crate::error::SmithError;
+
+/// Summary of one finished plan, develop or release run.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunRecord {
+    pub stage: String,
+    pub project: String,
+    pub branch: String,
+    pub run_id: String,
+    /// Final manifest state ("completed" or "failed")
+    pub state: String,
+    /// Model profile (agent) the project ran with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    pub started_at: u64,
+    pub finished_at: u64,
+    #[serde(default)]
+    pub tokens: u64,
+    /// Provider cost in USD as reported by opencode (0 when unknown).
+    #[serde(default)]
+    pub cost: f64,
+}
+
+impl RunRecord {
+    pub fn duration_secs(&self) -> u64 {
+        self.finished_at.saturating_sub(self.started_at)
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.state == "completed"
+    }
+}
+
+/// Path of the run history file.
+pub fn history_path() -> Result<PathBuf, SmithError> {
+    config_dir().map(|dir| dir.join("runs.jsonl"))
+}
+
+/// Append a finished run to the history.
+pub fn append(record: &RunRecord) -> Result<(), SmithError> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            SmithError::Config(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    let line = serde_json::to_string(record)
+        .map_err(|e| SmithError::Config(format!("Failed to serialize run record: {}", e)))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| SmithError::Config(format!("Failed to open {}: {}", path.display(), e)))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| SmithError::Config(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// All recorded runs, oldest first; unreadable lines are skipped. Empty when there is no history yet.
+pub fn load() -> Result<Vec<RunRecord>, SmithError> {
+    let path = history_path()?;
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(SmithError::Config(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    Ok(raw
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Move every recorded run of project `old` to `new`. Returns how many runs were updated.
+pub fn rename_project(old: &str, new: &str) -> Result<usize, SmithError> {
+    rename_project_in_file(&history_path()?, old, new)
+}
+
+/// Rewrite the JSON lines file at `path` with `project` changed from `old` to `new`; returns
+/// how many lines changed.
+pub fn rename_project_in_file(path: &Path, old: &str, new: &str) -> Result<usize, SmithError> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(SmithError::Config(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    let (renamed, count) = rename_project_lines(&raw, old, new);
+    if count > 0 {
+        let tmp = path.with_extension("jsonl.tmp");
+        fs::write(&tmp, renamed)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                SmithError::Config(format!("Failed to write {}: {}", path.display(), e))
+            })?;
+    }
+    Ok(count)
+}
+
+/// History lines with `project` changed from `old` to `new`; other lines are kept as they are.
+pub fn rename_project_lines(raw: &str, old: &str, new: &str) -> (String, usize) {
+    let mut count = 0;
+    let mut out = String::with_capacity(raw.len());
+    for line in raw.lines() {
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(mut record) if record["project"] == old => {
+                record["project"] = serde_json::Value::from(new);
+                out.push_str(&record.to_string());
+                count += 1;
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    (out, count)
+}
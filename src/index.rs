@@ -0,0 +1,354 @@
+//! Code index: repository files split into line chunks and embedded through an
+//! OpenAI-compatible `/embeddings` endpoint, stored per project in
+//! `<cache dir>/index/<project>.json`. `smith agent run` retrieves the chunks closest to a
+//! prompt and adds them to it, so agents on repos too big for their context start from the
+//! relevant code. Reindexing keeps the chunks of files whose blob is unchanged and re-embeds
+//! only the rest.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::{cache_dir, IndexConfig};
+use crate::error::SmithError;
+use crate::logging::{read_json, LogRequest};
+
+pub const DEFAULT_EMBEDDING_URL: &str = "http://localhost:11434/v1";
+pub const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+const DEFAULT_CHUNK_LINES: usize = 60;
+const DEFAULT_TOP_K: usize = 6;
+const DEFAULT_MAX_FILE_KB: u64 = 256;
+
+/// Chunks sent per embeddings request.
+const EMBED_BATCH: usize = 32;
+
+/// Files that are text but not worth retrieving.
+const SKIPPED_FILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "go.sum",
+];
+
+/// Extensions of binary and generated files.
+const SKIPPED_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "ico", "webp", "pdf", "zip", "gz", "tgz", "xz", "jar", "wasm",
+    "so", "dylib", "dll", "exe", "bin", "woff", "woff2", "ttf", "otf", "mp3", "mp4", "map",
+];
+
+/// `[index]` with the defaults filled in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSettings {
+    pub embedding_url: String,
+    pub embedding_model: String,
+    pub api_key_env: Option<String>,
+    pub chunk_lines: usize,
+    pub top_k: usize,
+    pub max_file_kb: u64,
+}
+
+impl IndexSettings {
+    pub fn from_config(config: Option<&IndexConfig>) -> Self {
+        let config = config.cloned().unwrap_or_default();
+        Self {
+            embedding_url: config
+                .embedding_url
+                .unwrap_or_else(|| DEFAULT_EMBEDDING_URL.to_string()),
+            embedding_model: config
+                .embedding_model
+                .unwrap_or_else(|| DEFAULT_EMBEDDING_MODEL.to_string()),
+            api_key_env: config.api_key_env,
+            chunk_lines: config.chunk_lines.unwrap_or(DEFAULT_CHUNK_LINES).max(1),
+            top_k: config.top_k.unwrap_or(DEFAULT_TOP_K),
+            max_file_kb: config.max_file_kb.unwrap_or(DEFAULT_MAX_FILE_KB),
+        }
+    }
+}
+
+/// Lines `start_line..=end_line` (1-based) of a file and their embedding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Chunk {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+    #[serde(default)]
+    pub vector: Vec<f32>,
+}
+
+/// An indexed file: the blob it was read from and its chunks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IndexedFile {
+    pub blob: String,
+    pub chunks: Vec<Chunk>,
+}
+
+/// A project's index, built from one commit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CodeIndex {
+    pub project: String,
+    /// Ref the index was built from, as given to `smith index build --ref`
+    pub git_ref: String,
+    pub commit: String,
+    /// Embedding model; a prompt is only matched against an index of the same model
+    pub model: String,
+    /// Lines per chunk the index was built with (0 for indexes from before it was recorded)
+    #[serde(default)]
+    pub chunk_lines: usize,
+    pub built_at: u64,
+    /// Repository-relative path to file
+    pub files: BTreeMap<String, IndexedFile>,
+}
+
+/// A chunk retrieved for a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit<'a> {
+    pub path: &'a str,
+    pub chunk: &'a Chunk,
+    pub score: f32,
+}
+
+impl CodeIndex {
+    /// Whether chunks of unchanged files can be kept when reindexing with `settings`.
+    pub fn matches(&self, settings: &IndexSettings) -> bool {
+        self.model == settings.embedding_model && self.chunk_lines == settings.chunk_lines
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.files.values().map(|f| f.chunks.len()).sum()
+    }
+
+    /// The `k` chunks most similar to `query`, best first.
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<Hit<'_>> {
+        let mut hits: Vec<Hit> = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.chunks.iter().map(move |chunk| Hit {
+                    path,
+                    chunk,
+                    score: cosine(query, &chunk.vector),
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(k);
+        hits
+    }
+
+    pub fn save(&self) -> Result<PathBuf, SmithError> {
+        let path = index_path(&self.project)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                SmithError::Config(format!("Failed to create {}: {}", parent.display(), e))
+            })?;
+        }
+        let json = serde_json::to_string(self)
+            .map_err(|e| SmithError::Config(format!("Failed to serialize index: {}", e)))?;
+        fs::write(&path, json).map_err(|e| {
+            SmithError::Config(format!("Failed to write {}: {}", path.display(), e))
+        })?;
+        Ok(path)
+    }
+}
+
+/// Where the index of `project` is stored.
+pub fn index_path(project: &str) -> Result<PathBuf, SmithError> {
+    cache_dir().map(|dir| dir.join("index").join(format!("{}.json", project)))
+}
+
+/// Move the index of project `old` to `new`. Returns false when `old` has no index.
+pub fn rename_project(old: &str, new: &str) -> Result<bool, SmithError> {
+    move_index(&index_path(old)?, &index_path(new)?, new)
+}
+
+/// Move the index file at `from` to `to`, recording `project` as its new owner.
+pub fn move_index(from: &Path, to: &Path, project: &str) -> Result<bool, SmithError> {
+    let Ok(content) = fs::read_to_string(from) else {
+        return Ok(false);
+    };
+    let mut index: Value = serde_json::from_str(&content)
+        .map_err(|e| SmithError::Config(format!("Failed to parse {}: {}", from.display(), e)))?;
+    index["project"] = Value::from(project);
+    fs::write(to, index.to_string())
+        .and_then(|_| fs::remove_file(from))
+        .map_err(|e| SmithError::Config(format!("Failed to write {}: {}", to.display(), e)))?;
+    Ok(true)
+}
+
+/// The index of `project`; `None` when it has not been built.
+pub fn load(project: &str) -> Result<Option<CodeIndex>, SmithError> {
+    let path = index_path(project)?;
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| SmithError::Config(format!("Failed to parse {}: {}", path.display(), e)))
+}
+
+/// Whether a file of `size` bytes at `path` should be indexed at all.
+pub fn is_indexable(path: &str, size: u64, max_file_kb: u64) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default();
+    size > 0
+        && size <= max_file_kb * 1024
+        && !SKIPPED_FILES.contains(&name)
+        && !SKIPPED_EXTENSIONS.contains(&extension.as_str())
+        && !name.ends_with(".min.js")
+}
+
+/// `content` as text; `None` for binary content (NUL bytes or invalid UTF-8).
+pub fn as_text(content: &[u8]) -> Option<&str> {
+    if content.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(content).ok()
+}
+
+/// `text` split into chunks of `chunk_lines` lines, without embeddings; blank chunks are dropped.
+pub fn chunk_file(text: &str, chunk_lines: usize) -> Vec<Chunk> {
+    let size = chunk_lines.max(1);
+    let lines: Vec<&str> = text.lines().collect();
+    lines
+        .chunks(size)
+        .enumerate()
+        .filter(|(_, lines)| lines.iter().any(|l| !l.trim().is_empty()))
+        .map(|(i, lines)| Chunk {
+            start_line: i * size + 1,
+            end_line: i * size + lines.len(),
+            text: lines.join("\n"),
+            vector: Vec::new(),
+        })
+        .collect()
+}
+
+/// Cosine similarity; 0 for vectors of different lengths or zero vectors.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// Retrieved chunks as a prompt preamble; empty without hits.
+pub fn format_context(hits: &[Hit]) -> String {
+    if hits.is_empty() {
+        return String::new();
+    }
+    let mut context = String::from(
+        "Possibly relevant code from the repository (retrieved by smith index; verify against the workspace):\n",
+    );
+    for hit in hits {
+        context.push_str(&format!(
+            "\n--- {}:{}-{} ---\n{}\n",
+            hit.path, hit.chunk.start_line, hit.chunk.end_line, hit.chunk.text
+        ));
+    }
+    context
+}
+
+/// Embeddings of `texts`, in order.
+pub async fn embed(settings: &IndexSettings, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let url = format!(
+        "{}/embeddings",
+        settings.embedding_url.trim_end_matches('/')
+    );
+    let api_key = match &settings.api_key_env {
+        Some(var) => Some(
+            std::env::var(var)
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .ok_or_else(|| format!("Missing host env '{}' for the embedding endpoint", var))?,
+        ),
+        None => None,
+    };
+    let client = reqwest::Client::new();
+    let mut vectors = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(EMBED_BATCH) {
+        let mut request = client
+            .post(&url)
+            .header("User-Agent", "agent-smith")
+            .timeout(Duration::from_secs(120))
+            .json(&json!({ "model": settings.embedding_model, "input": batch }));
+        if let Some(key) = &api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .logged()
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach embedding endpoint {}: {}", url, e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(format!(
+                "Embedding endpoint error ({}): {}",
+                status, error_text
+            ));
+        }
+        let body: Value = read_json(response).await?;
+        let mut data: Vec<(u64, Vec<f32>)> = body["data"]
+            .as_array()
+            .ok_or("Embedding response has no data")?
+            .iter()
+            .map(|item| {
+                let vector = item["embedding"]
+                    .as_array()
+                    .map(|v| v.iter().filter_map(|x| x.as_f64()).map(|x| x as f32))
+                    .ok_or("Embedding response item has no embedding")?
+                    .collect();
+                Ok((item["index"].as_u64().unwrap_or(0), vector))
+            })
+            .collect::<Result<_, String>>()?;
+        if data.len() != batch.len() {
+            return Err(format!(
+                "Embedding endpoint returned {} embeddings for {} inputs",
+                data.len(),
+                batch.len()
+            ));
+        }
+        data.sort_by_key(|(i, _)| *i);
+        vectors.extend(data.into_iter().map(|(_, v)| v));
+    }
+    Ok(vectors)
+}
+
+/// `prompt` preceded by the chunks of `index` closest to it; `prompt` unchanged when nothing
+/// matches.
+pub async fn augment_prompt(
+    index: &CodeIndex,
+    settings: &IndexSettings,
+    prompt: &str,
+) -> Result<String, String> {
+    if index.model != settings.embedding_model {
+        return Err(format!(
+            "index of '{}' was built with {}, but [index] uses {}; rebuild it with `smith index build {}`",
+            index.project, index.model, settings.embedding_model, index.project
+        ));
+    }
+    let query = embed(settings, &[prompt.to_string()])
+        .await?
+        .pop()
+        .unwrap_or_default();
+    let context = format_context(&index.search(&query, settings.top_k));
+    if context.is_empty() {
+        return Ok(prompt.to_string());
+    }
+    Ok(format!("{}\n---\n\n{}", context, prompt))
+}
@@ -0,0 +1,26 @@
+//! smith core: configuration, Docker runtime and forge clients shared by the CLI
+//! and anything else that wants to embed smith without shelling out to it.
+
+pub mod audit;
+pub mod budget;
+pub mod commit_lint;
+pub mod config;
+pub mod docker;
+pub mod error;
+pub mod events;
+pub mod gitea;
+pub mod github;
+pub mod history;
+pub mod index;
+pub mod logging;
+pub mod nodes;
+pub mod opencode;
+pub mod preset;
+pub mod proxy;
+pub mod replay;
+pub mod schedule;
+pub mod secrets;
+pub mod simulate;
+pub mod toolchain;
+pub mod usage;
+pub mod util;
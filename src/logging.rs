@@ -0,0 +1,189 @@
+//! Leveled diagnostics: `-v` info, `-vv` debug (docker commands, HTTP requests),
+//! `-vvv` trace (full request/response payloads). Secrets are redacted before logging.
+
+use std::process::Command;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::de::DeserializeOwned;
+use tracing::level_filters::LevelFilter;
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Install the stderr tracing subscriber for a `-v` count (0 = warnings only).
+pub fn init(verbosity: u8, color: bool) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(color)
+        .without_time()
+        .with_target(false)
+        .try_init();
+}
+
+/// The `-v` count the subscriber was installed with.
+pub fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// True at `-v` and above: commands show their detailed (formerly `--verbose`) output.
+pub fn verbose() -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= 1
+}
+
+/// Words that mark an environment variable or header as secret.
+const SECRET_MARKERS: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "API_KEY", "PRIVATE_KEY"];
+
+/// Prefixes of well-known credential formats (GitHub tokens, OpenAI/Anthropic keys).
+const SECRET_PREFIXES: &[&str] = &["ghp_", "gho_", "ghs_", "ghu_", "ghr_", "github_pat_", "sk-"];
+
+/// Mask secrets in free text: `KEY=value` pairs and JSON fields whose name looks secret,
+/// values after `Bearer`/`token`, and known credential prefixes.
+pub fn redact(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut mask_next = false;
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let trailing = &piece[word.len()..];
+        let lower = word.to_ascii_lowercase();
+        if mask_next && !word.is_empty() && lower != "bearer" && lower != "token" {
+            out.push_str("***");
+            out.push_str(trailing);
+            mask_next = false;
+            continue;
+        }
+        mask_next = lower == "bearer"
+            || lower == "token"
+            || lower == "authorization:"
+            || (word.ends_with("\":") && is_secret_name(word));
+        out.push_str(&redact_word(word));
+        out.push_str(trailing);
+    }
+    out
+}
+
+/// True when a variable, header or field name looks like it holds a credential.
+pub fn is_secret_name(name: &str) -> bool {
+    let upper = name.trim_matches(|c: char| !c.is_alphanumeric() && c != '_');
+    let upper = upper.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|m| upper.contains(m))
+}
+
+/// Replace every scalar whose key looks secret (tokens, keys, passwords) with "***", recursively.
+pub fn redact_toml(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, v) in table.iter_mut() {
+                if is_secret_name(key) && !v.is_table() && !v.is_array() {
+                    *v = toml::Value::String("***".to_string());
+                } else {
+                    redact_toml(v);
+                }
+            }
+        }
+        toml::Value::Array(items) => items.iter_mut().for_each(redact_toml),
+        _ => {}
+    }
+}
+
+fn redact_word(word: &str) -> String {
+    let bare = word.trim_matches(|c: char| c == '"' || c == '\'' || c == ',');
+    if SECRET_PREFIXES.iter().any(|p| bare.starts_with(p)) && bare.len() > 8 {
+        return word.replace(bare, "***");
+    }
+    // KEY=value (env/args)
+    if let Some((name, value)) = word.split_once('=') {
+        if is_secret_name(name) && !value.is_empty() {
+            return format!("{}=***", name);
+        }
+    }
+    // "key":"value" (compact JSON)
+    let mut out = String::new();
+    let mut rest = word;
+    while let Some(pos) = rest.find("\":\"") {
+        let (head, tail) = rest.split_at(pos);
+        let name = head.rsplit('"').next().unwrap_or_default();
+        out.push_str(head);
+        out.push_str("\":\"");
+        let tail = &tail[3..];
+        let end = tail.find('"').unwrap_or(tail.len());
+        if is_secret_name(name) {
+            out.push_str("***");
+        } else {
+            out.push_str(&tail[..end]);
+        }
+        rest = &tail[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Program and arguments as a copy-pasteable shell line.
+pub fn display_command(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|part| {
+            let part = part.to_string_lossy();
+            if part.is_empty() || part.contains(char::is_whitespace) {
+                format!("'{}'", part.replace('\'', "'\"'\"'"))
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Log an external command at debug level before it runs: `Command::new(..).args(..).logged().output()`.
+pub trait LogCommand {
+    fn logged(&mut self) -> &mut Self;
+}
+
+impl LogCommand for Command {
+    fn logged(&mut self) -> &mut Self {
+        tracing::debug!("exec: {}", redact(&display_command(self)));
+        self
+    }
+}
+
+impl LogCommand for tokio::process::Command {
+    fn logged(&mut self) -> &mut Self {
+        tracing::debug!("exec: {}", redact(&display_command(self.as_std())));
+        self
+    }
+}
+
+/// Log an HTTP request before sending: method and URL at debug, body at trace.
+pub trait LogRequest {
+    fn logged(self) -> Self;
+}
+
+impl LogRequest for reqwest::RequestBuilder {
+    fn logged(self) -> Self {
+        let Some(request) = self.try_clone().and_then(|b| b.build().ok()) else {
+            return self;
+        };
+        tracing::debug!("http: {} {}", request.method(), request.url());
+        if let Some(body) = request.body().and_then(|b| b.as_bytes()) {
+            tracing::trace!(
+                "http request body: {}",
+                redact(&String::from_utf8_lossy(body))
+            );
+        }
+        self
+    }
+}
+
+/// Read a JSON response body, logging it (redacted) at trace level.
+pub async fn read_json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, String> {
+    let status = response.status();
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    tracing::trace!("http response {}: {}", status, redact(&body));
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
@@ -0,0 +1,223 @@
+//! Remote smith nodes (`smith node`): machines reached over SSH that run their own smith and
+//! Docker. `--node` schedules agent starts and pipeline runs onto them by name or by labels and
+//! free capacity; status commands aggregate what the nodes report.
+
+use std::process::{Command, Output};
+
+use crate::config::NodeConfig;
+use crate::docker::{
+    SpawnInfo, LABEL_BRANCH, LABEL_KIND, LABEL_MANAGED, LABEL_PORT, LABEL_PROJECT,
+};
+use crate::logging::LogCommand;
+
+/// What a node reported when probed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeStatus {
+    /// smith version on the node, or why the node could not be reached
+    pub version: Result<String, String>,
+    /// Running spawned agents on the node
+    pub agents: usize,
+}
+
+impl NodeStatus {
+    pub fn reachable(&self) -> bool {
+        self.version.is_ok()
+    }
+}
+
+/// SSH destination and port of a node endpoint ("user@host", "host" or "ssh://user@host:port").
+pub fn parse_endpoint(endpoint: &str) -> Result<(String, Option<u16>), String> {
+    let endpoint = endpoint.trim();
+    if endpoint.contains("://") && !endpoint.starts_with("ssh://") {
+        return Err(format!(
+            "Unsupported node endpoint '{}': nodes are reached over SSH (user@host or ssh://user@host:port)",
+            endpoint
+        ));
+    }
+    let rest = endpoint.strip_prefix("ssh://").unwrap_or(endpoint);
+    let rest = rest.trim_end_matches('/');
+    let (destination, port) = match rest.rsplit_once(':') {
+        Some((host, port)) if endpoint.starts_with("ssh://") => {
+            let port = port
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid port in node endpoint '{}'", endpoint))?;
+            (host, Some(port))
+        }
+        _ => (rest, None),
+    };
+    let host = destination.rsplit('@').next().unwrap_or_default();
+    if host.is_empty() || destination.contains(char::is_whitespace) || destination.contains('/') {
+        return Err(format!("Invalid node endpoint '{}'", endpoint));
+    }
+    // ssh would read a leading '-' as an option (e.g. -oProxyCommand=...).
+    if destination.starts_with('-') || host.starts_with('-') {
+        return Err(format!(
+            "Invalid node endpoint '{}': it must not start with '-'",
+            endpoint
+        ));
+    }
+    Ok((destination.to_string(), port))
+}
+
+/// Node name derived from an endpoint: its host, without the user and domain (IPs are kept whole).
+pub fn default_node_name(endpoint: &str) -> Result<String, String> {
+    let (destination, _) = parse_endpoint(endpoint)?;
+    let host = destination.rsplit('@').next().unwrap_or(&destination);
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return Ok(host.to_string());
+    }
+    Ok(host.split('.').next().unwrap_or(host).to_string())
+}
+
+/// `ssh` to the node running `script` in its login user's shell. Never prompts for passwords.
+pub fn ssh_command(node: &NodeConfig, script: &str) -> Result<Command, String> {
+    ssh(node, script, false)
+}
+
+/// `smith <args>` on the node, with a terminal allocated when `tty` (for prompts and colors).
+pub fn remote_smith(node: &NodeConfig, args: &[String], tty: bool) -> Result<Command, String> {
+    let script = std::iter::once("smith".to_string())
+        .chain(args.iter().map(|a| quote(a)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    ssh(node, &script, tty)
+}
+
+fn ssh(node: &NodeConfig, script: &str, tty: bool) -> Result<Command, String> {
+    let (destination, port) = parse_endpoint(&node.endpoint)?;
+    let mut cmd = Command::new("ssh");
+    if tty {
+        cmd.arg("-t");
+    }
+    cmd.args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=5"]);
+    if let Some(port) = port {
+        cmd.args(["-p", &port.to_string()]);
+    }
+    // `--` ends ssh's options, so the destination is never read as one.
+    cmd.arg("--").arg(destination).arg(script);
+    Ok(cmd)
+}
+
+fn quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@,+".contains(c))
+    {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\"'\"'"))
+}
+
+fn run(node: &NodeConfig, script: &str) -> Result<Output, String> {
+    let output = ssh_command(node, script)?
+        .logged()
+        .output()
+        .map_err(|e| format!("Failed to run ssh: {}", e))?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(match output.status.code() {
+            Some(255) => format!("unreachable: {}", stderr.trim()),
+            _ => stderr.trim().to_string(),
+        })
+    }
+}
+
+/// Ask the node for its smith version and how many spawned agents it is running.
+pub fn probe(node: &NodeConfig) -> NodeStatus {
+    let script = format!(
+        "command -v smith >/dev/null || {{ echo 'smith is not installed (or not on PATH for SSH sessions)' >&2; exit 127; }}; \
+         smith version && docker ps -q --filter label={}=true --filter label={}=spawned | wc -l",
+        LABEL_MANAGED, LABEL_KIND
+    );
+    match run(node, &script) {
+        Ok(output) => parse_probe(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => NodeStatus {
+            version: Err(e),
+            agents: 0,
+        },
+    }
+}
+
+/// Probe output: the version line, then the running agent count.
+pub fn parse_probe(stdout: &str) -> NodeStatus {
+    let mut lines = stdout.lines().map(str::trim).filter(|l| !l.is_empty());
+    let version = lines
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| "no answer from smith".to_string());
+    let agents = lines.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+    NodeStatus { version, agents }
+}
+
+/// Spawned agents on the node, as `smith agent list` shows local ones.
+pub fn list_agents(node: &NodeConfig) -> Result<Vec<SpawnInfo>, String> {
+    let script = format!(
+        "docker ps -a --filter label={}=true --filter label={}=spawned --format '{{{{.Names}}}}\t{{{{.Label \"{}\"}}}}\t{{{{.Label \"{}\"}}}}\t{{{{.Label \"{}\"}}}}\t{{{{.Status}}}}\t{{{{.ID}}}}\t{{{{.Image}}}}'",
+        LABEL_MANAGED, LABEL_KIND, LABEL_PROJECT, LABEL_BRANCH, LABEL_PORT
+    );
+    let output = run(node, &script)?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let f: Vec<&str> = line.split('\t').collect();
+            let [name, project, branch, port, status, id, image] = f[..] else {
+                return None;
+            };
+            Some(SpawnInfo {
+                project: project.to_string(),
+                branch: branch.to_string(),
+                container_name: name.to_string(),
+                container_id: id.to_string(),
+                port: port.parse().unwrap_or(0),
+                status: status.to_string(),
+                image: image.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Whether the node carries every `key=value` in `wanted`.
+pub fn has_labels(node: &NodeConfig, wanted: &[String]) -> bool {
+    wanted.iter().all(|w| node.labels.iter().any(|l| l == w))
+}
+
+/// Pick a node for new work: reachable, carrying `wanted` labels and below its capacity; the
+/// least loaded (by share of capacity, then agent count) wins, config order breaking ties.
+pub fn select_node<'a>(
+    nodes: &'a [NodeConfig],
+    statuses: &[NodeStatus],
+    wanted: &[String],
+) -> Result<&'a NodeConfig, String> {
+    let load = |node: &NodeConfig, status: &NodeStatus| match node.capacity {
+        Some(0) => f64::INFINITY,
+        Some(cap) => status.agents as f64 / cap as f64,
+        None => 0.0,
+    };
+    nodes
+        .iter()
+        .zip(statuses)
+        .filter(|(node, status)| {
+            has_labels(node, wanted)
+                && status.reachable()
+                && node.capacity.is_none_or(|cap| (status.agents as u32) < cap)
+        })
+        .min_by(|(a, sa), (b, sb)| {
+            load(a, sa)
+                .total_cmp(&load(b, sb))
+                .then(sa.agents.cmp(&sb.agents))
+        })
+        .map(|(node, _)| node)
+        .ok_or_else(|| {
+            if wanted.is_empty() {
+                "No reachable node has free capacity (see `smith node list`)".to_string()
+            } else {
+                format!(
+                    "No reachable node with labels {} has free capacity (see `smith node list`)",
+                    wanted.join(", ")
+                )
+            }
+        })
+}
@@ -0,0 +1,253 @@
+//! `opencode.jsonc` of a model profile's image: the model, small model and the profile's
+//! structured `[agents.opencode]` settings (providers, MCP servers, permissions), checked
+//! before they reach OpenCode so a typo fails `smith model config edit` instead of the agent.
+//! Spawned project agents get the profile's and project's MCP servers through
+//! `OPENCODE_CONFIG_CONTENT`.
+
+use serde_json::{json, Map, Value};
+
+use std::collections::BTreeMap;
+
+use crate::config::{McpServer, OpencodeSettings};
+
+/// Environment variable OpenCode reads extra inline config from, merged over its config files.
+pub const CONFIG_CONTENT_ENV: &str = "OPENCODE_CONFIG_CONTENT";
+
+/// Tools OpenCode takes a permission for.
+pub const PERMISSION_TOOLS: &[&str] = &[
+    "edit",
+    "bash",
+    "webfetch",
+    "doom_loop",
+    "external_directory",
+];
+
+/// Permission values.
+pub const PERMISSION_VALUES: &[&str] = &["allow", "ask", "deny"];
+
+/// Problems with `settings`, one readable line each; empty when valid.
+pub fn validate(settings: &OpencodeSettings) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (id, provider) in &settings.provider {
+        let Some(table) = provider.as_table() else {
+            problems.push(format!("provider.{}: must be a table", id));
+            continue;
+        };
+        match table.get("options").map(|o| o.as_table()) {
+            Some(None) => problems.push(format!("provider.{}.options: must be a table", id)),
+            Some(Some(options)) => {
+                if let Some(url) = options.get("baseURL") {
+                    if !url.as_str().is_some_and(is_http_url) {
+                        problems.push(format!(
+                            "provider.{}.options.baseURL: must be an http(s) URL",
+                            id
+                        ));
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+    problems.extend(validate_mcp(&settings.mcp));
+    for (tool, value) in &settings.permission {
+        if !PERMISSION_TOOLS.contains(&tool.as_str()) {
+            problems.push(format!(
+                "permission.{}: unknown tool (expected one of {})",
+                tool,
+                PERMISSION_TOOLS.join(", ")
+            ));
+            continue;
+        }
+        let valid = |v: &toml::Value| v.as_str().is_some_and(|s| PERMISSION_VALUES.contains(&s));
+        let ok = match value {
+            toml::Value::Table(patterns) if tool == "bash" || tool == "edit" => {
+                patterns.values().all(valid)
+            }
+            v => valid(v),
+        };
+        if !ok {
+            problems.push(format!(
+                "permission.{}: must be allow, ask or deny{}",
+                tool,
+                if tool == "bash" || tool == "edit" {
+                    ", or a table of patterns to those"
+                } else {
+                    ""
+                }
+            ));
+        }
+    }
+    problems
+}
+
+/// Problems with MCP server definitions; empty when valid.
+pub fn validate_mcp(servers: &BTreeMap<String, McpServer>) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (name, server) in servers {
+        match (server.command.is_empty(), &server.url) {
+            (true, None) => problems.push(format!("mcp.{}: needs a command or a url", name)),
+            (false, Some(_)) => {
+                problems.push(format!("mcp.{}: has both a command and a url", name))
+            }
+            (true, Some(url)) => {
+                if !is_http_url(url) {
+                    problems.push(format!("mcp.{}.url: must be an http(s) URL", name));
+                }
+                if !server.environment.is_empty() {
+                    problems.push(format!(
+                        "mcp.{}.environment: only local (command) servers take an environment",
+                        name
+                    ));
+                }
+            }
+            (false, None) => {
+                if !server.headers.is_empty() {
+                    problems.push(format!(
+                        "mcp.{}.headers: only remote (url) servers take headers",
+                        name
+                    ));
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// OpenCode config object for a profile; `None` when there is nothing to set.
+pub fn config_value(
+    model: Option<&str>,
+    small_model: Option<&str>,
+    settings: Option<&OpencodeSettings>,
+) -> Option<Value> {
+    let mut config = Map::new();
+    if let Some(m) = model {
+        config.insert("model".to_string(), json!(m));
+    }
+    if let Some(sm) = small_model {
+        config.insert("small_model".to_string(), json!(sm));
+    }
+    if let Some(settings) = settings {
+        let table = |entries: Map<String, Value>| (!entries.is_empty()).then_some(entries);
+        let provider = settings
+            .provider
+            .iter()
+            .map(|(id, v)| (id.clone(), serde_json::to_value(v).unwrap_or(Value::Null)))
+            .collect();
+        if let Some(provider) = table(provider) {
+            config.insert("provider".to_string(), Value::Object(provider));
+        }
+        let mcp = mcp_value(&settings.mcp);
+        if let Some(mcp) = table(mcp) {
+            config.insert("mcp".to_string(), Value::Object(mcp));
+        }
+        let permission = settings
+            .permission
+            .iter()
+            .map(|(tool, v)| (tool.clone(), serde_json::to_value(v).unwrap_or(Value::Null)))
+            .collect();
+        if let Some(permission) = table(permission) {
+            config.insert("permission".to_string(), Value::Object(permission));
+        }
+    }
+    if config.is_empty() {
+        return None;
+    }
+    config.insert(
+        "$schema".to_string(),
+        json!("https://opencode.ai/config.json"),
+    );
+    Some(Value::Object(config))
+}
+
+/// OpenCode `mcp` entries for `servers`.
+fn mcp_value(servers: &BTreeMap<String, McpServer>) -> Map<String, Value> {
+    servers
+        .iter()
+        .map(|(name, server)| {
+            let mut entry = Map::new();
+            if let Some(url) = &server.url {
+                entry.insert("type".to_string(), json!("remote"));
+                entry.insert("url".to_string(), json!(url));
+                if !server.headers.is_empty() {
+                    entry.insert("headers".to_string(), json!(server.headers));
+                }
+            } else {
+                entry.insert("type".to_string(), json!("local"));
+                entry.insert("command".to_string(), json!(server.command));
+                if !server.environment.is_empty() {
+                    entry.insert("environment".to_string(), json!(server.environment));
+                }
+            }
+            entry.insert("enabled".to_string(), json!(server.enabled.unwrap_or(true)));
+            (name.clone(), Value::Object(entry))
+        })
+        .collect()
+}
+
+/// MCP servers of a spawned agent: the model profile's, then the project's (a project server
+/// replaces the profile's of the same name).
+pub fn merged_mcp_servers(
+    profile: Option<&OpencodeSettings>,
+    project: Option<&BTreeMap<String, McpServer>>,
+) -> BTreeMap<String, McpServer> {
+    let mut servers = profile.map(|s| s.mcp.clone()).unwrap_or_default();
+    servers.extend(project.cloned().unwrap_or_default());
+    servers
+}
+
+/// Replace `$NAME` environment and header values with the host variable `lookup` returns, so
+/// secrets stay out of the config. Other values are kept as they are.
+pub fn resolve_mcp_secrets(
+    servers: &mut BTreeMap<String, McpServer>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    for (name, server) in servers.iter_mut() {
+        for (key, value) in server
+            .environment
+            .iter_mut()
+            .chain(server.headers.iter_mut())
+        {
+            let Some(var) = value.strip_prefix('$') else {
+                continue;
+            };
+            *value = lookup(var)
+                .filter(|v| !v.trim().is_empty())
+                .ok_or_else(|| {
+                    format!(
+                        "Missing host env '{}' for MCP server '{}' ({})",
+                        var, name, key
+                    )
+                })?;
+        }
+    }
+    Ok(())
+}
+
+/// `OPENCODE_CONFIG_CONTENT` value adding `servers` to an agent's OpenCode config; `None`
+/// when there are none.
+pub fn mcp_config_content(servers: &BTreeMap<String, McpServer>) -> Option<String> {
+    if servers.is_empty() {
+        return None;
+    }
+    Some(json!({ "mcp": mcp_value(servers) }).to_string())
+}
+
+/// `opencode.jsonc` text for the model profile `name`; `None` when there is nothing to set.
+pub fn render(
+    name: &str,
+    model: Option<&str>,
+    small_model: Option<&str>,
+    settings: Option<&OpencodeSettings>,
+) -> Option<String> {
+    let config = config_value(model, small_model, settings)?;
+    Some(format!(
+        "// Generated by smith from model profile '{}'; change it with `smith model config edit {}`.\n{}\n",
+        name,
+        name,
+        serde_json::to_string_pretty(&config).unwrap_or_default()
+    ))
+}
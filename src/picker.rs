@@ -0,0 +1,106 @@
+//! Interactive picker for commands run on a terminal without a name they need: the candidates
+//! are listed, and typing part of a name (its letters in order, as in skim or fzf) narrows the
+//! list until one is left, or a number chooses from it.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Most candidates listed at once; narrow the list to see the rest.
+const SHOWN: usize = 20;
+
+/// True when a picker can be shown: stdin and stdout are both a terminal.
+pub fn interactive() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+/// How well `query` matches `candidate`, lower is better: the length of the shortest stretch
+/// holding the query's characters in order, then where it starts. `None` when they do not all
+/// appear in order. Case-insensitive.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<(usize, usize)> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let text: Vec<char> = candidate.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return Some((0, 0));
+    }
+    let mut best: Option<(usize, usize)> = None;
+    for start in (0..text.len()).filter(|&i| text[i] == query[0]) {
+        let mut matched = 1;
+        let mut end = start;
+        for (i, c) in text.iter().enumerate().skip(start + 1) {
+            if matched == query.len() {
+                break;
+            }
+            if *c == query[matched] {
+                matched += 1;
+                end = i;
+            }
+        }
+        if matched == query.len() {
+            let score = (end + 1 - start, start);
+            if best.is_none_or(|b| score < b) {
+                best = Some(score);
+            }
+        }
+    }
+    best
+}
+
+/// Candidates matching `query`, best first; ties keep their order.
+pub fn filter<'a>(query: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+    let mut scored: Vec<((usize, usize), &'a str)> = candidates
+        .iter()
+        .filter_map(|c| fuzzy_score(query, c).map(|score| (score, *c)))
+        .collect();
+    scored.sort_by_key(|(score, _)| *score);
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Ask for one of `candidates` (`what` names them, e.g. "project"). `None` when there are
+/// none, or the user enters nothing.
+pub fn pick(what: &str, candidates: &[String]) -> Option<String> {
+    match candidates {
+        [] => return None,
+        [only] => {
+            println!("Using {} '{}' (the only one)", what, only);
+            return Some(only.clone());
+        }
+        _ => {}
+    }
+    let mut shown: Vec<&str> = candidates.iter().map(String::as_str).collect();
+    let stdin = io::stdin();
+    loop {
+        println!(
+            "Select a {} (type to filter, a number to choose, enter to cancel):",
+            what
+        );
+        for (i, name) in shown.iter().take(SHOWN).enumerate() {
+            println!("  {:>2}) {}", i + 1, name);
+        }
+        if shown.len() > SHOWN {
+            println!("  ... {} more", shown.len() - SHOWN);
+        }
+        print!("> ");
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            return None;
+        }
+        let input = line.trim();
+        if input.is_empty() {
+            return None;
+        }
+        if let Ok(n) = input.parse::<usize>() {
+            if (1..=shown.len().min(SHOWN)).contains(&n) {
+                return Some(shown[n - 1].to_string());
+            }
+        }
+        let narrowed = filter(input, &shown);
+        match narrowed.as_slice() {
+            [] => println!("No {} matches '{}'", what, input),
+            [one] => {
+                println!("Using {} '{}'", what, one);
+                return Some(one.to_string());
+            }
+            _ => shown = narrowed,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Built-in pipeline presets for `smith run develop`: named bundles of the steps a run takes and
+//! the role prompts added to them, picked with `--preset` or a project's `preset`.
+
+/// A develop pipeline preset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub summary: &'static str,
+    /// Run the self-check step after each develop pass
+    pub self_check: bool,
+    /// Run the validate (assurance) step; without it the first develop pass is committed
+    pub validate: bool,
+    /// After the assurance step, smith runs the project's `validate_commands` itself; a failing
+    /// command is a blocking finding whatever the agent reported
+    pub run_validate_commands: bool,
+    /// Show the diff and ask before committing and pushing
+    pub review_before_push: bool,
+    /// Added to the develop step's role prompt
+    pub execute_prompt: Option<&'static str>,
+    /// Added to the self-check step's role prompt
+    pub check_prompt: Option<&'static str>,
+    /// Added to the validate step's role prompt
+    pub validate_prompt: Option<&'static str>,
+}
+
+/// Preset of runs that name none: every step, as configured.
+pub const DEFAULT_PRESET: &str = "standard";
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "standard",
+        summary: "develop, self-check and validate with the project's roles",
+        self_check: true,
+        validate: true,
+        run_validate_commands: false,
+        review_before_push: false,
+        execute_prompt: None,
+        check_prompt: None,
+        validate_prompt: None,
+    },
+    Preset {
+        name: "strict",
+        summary: "standard, plus validate_commands run by smith and a diff review before push",
+        self_check: true,
+        validate: true,
+        run_validate_commands: true,
+        review_before_push: true,
+        execute_prompt: Some(
+            "Add or update tests for every behaviour you change, and run the project's validation commands until they pass.",
+        ),
+        check_prompt: Some(
+            "Treat a failing validation command, a missing test or a change outside the plan as a failure.",
+        ),
+        validate_prompt: Some(
+            "Review strictly: an untested change, a deviation from the plan or a failing validation command is blocking.",
+        ),
+    },
+    Preset {
+        name: "fast",
+        summary: "one develop pass, committed without self-check or validation",
+        self_check: false,
+        validate: false,
+        run_validate_commands: false,
+        review_before_push: false,
+        execute_prompt: Some(
+            "Make the smallest change that completes the task; skip refactors and optional polish.",
+        ),
+        check_prompt: None,
+        validate_prompt: None,
+    },
+];
+
+/// Names of the built-in presets.
+pub fn names() -> Vec<&'static str> {
+    PRESETS.iter().map(|p| p.name).collect()
+}
+
+/// The preset called `name` (case-insensitive).
+pub fn find(name: &str) -> Result<&'static Preset, String> {
+    let wanted = name.trim().to_lowercase();
+    PRESETS
+        .iter()
+        .find(|p| p.name == wanted)
+        .ok_or_else(|| format!("Unknown preset '{}'; expected {}", name, names().join(", ")))
+}
+
+/// Preset of a run: `--preset`, else the project's `preset`, else the default.
+pub fn resolve(flag: Option<&str>, project: Option<&str>) -> Result<&'static Preset, String> {
+    find(flag.or(project).unwrap_or(DEFAULT_PRESET))
+}
+
+/// `role_prompt` with the preset's `extra` instructions appended.
+pub fn with_prompt(role_prompt: Option<&str>, extra: Option<&str>) -> Option<String> {
+    match (role_prompt, extra) {
+        (Some(role), Some(extra)) => Some(format!("{}\n\n{}", role, extra)),
+        (role, extra) => role.or(extra).map(String::from),
+    }
+}
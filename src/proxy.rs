@@ -0,0 +1,310 @@
+//! Authenticated reverse proxy for agent servers (`smith agent expose`): a client proves it
+//! holds the access token, optionally over TLS, and its connection is then piped to the agent.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+/// Cookie set after a browser signs in with `?token=`, so later requests need no token in the URL.
+pub const TOKEN_COOKIE: &str = "smith_token";
+
+/// Largest request head read before the token is checked.
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Where to forward authorized connections and how clients authenticate.
+pub struct ExposeOptions {
+    pub upstream: SocketAddr,
+    pub token: String,
+    /// TLS terminator; `None` serves plain HTTP
+    pub tls: Option<TlsAcceptor>,
+}
+
+/// Outcome of checking a request head against the access token.
+#[derive(Debug, PartialEq)]
+pub enum Access {
+    /// Bearer header or cookie matches: forward the connection
+    Granted,
+    /// `?token=` matches: redirect to this location and set the cookie
+    SignIn(String),
+    Denied,
+}
+
+/// Check the request head for the token: `Authorization: Bearer <token>`, the
+/// [`TOKEN_COOKIE`] cookie, or a `token` query parameter (browsers).
+pub fn authorize(head: &str, token: &str) -> Access {
+    let mut lines = head.lines();
+    let target = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/");
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            if let Some(presented) = value.strip_prefix("Bearer ") {
+                if same_token(presented.trim(), token) {
+                    return Access::Granted;
+                }
+            }
+        } else if name.eq_ignore_ascii_case("cookie") {
+            let presented = value
+                .split(';')
+                .filter_map(|c| c.trim().split_once('='))
+                .any(|(k, v)| k == TOKEN_COOKIE && same_token(v, token));
+            if presented {
+                return Access::Granted;
+            }
+        }
+    }
+    let Some((path, query)) = target.split_once('?') else {
+        return Access::Denied;
+    };
+    let (matched, rest): (Vec<&str>, Vec<&str>) = query.split('&').partition(|pair| {
+        pair.strip_prefix("token=")
+            .is_some_and(|v| same_token(v, token))
+    });
+    if matched.is_empty() {
+        return Access::Denied;
+    }
+    if rest.is_empty() {
+        Access::SignIn(path.to_string())
+    } else {
+        Access::SignIn(format!("{}?{}", path, rest.join("&")))
+    }
+}
+
+/// Compare without stopping at the first differing byte.
+fn same_token(presented: &str, token: &str) -> bool {
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Accept connections on `listener` until it fails, serving each on its own task.
+pub async fn serve(listener: TcpListener, options: Arc<ExposeOptions>) -> Result<(), String> {
+    loop {
+        let (stream, peer) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("Failed to accept connection: {}", e))?;
+        let options = options.clone();
+        tokio::spawn(async move {
+            let result = match &options.tls {
+                Some(acceptor) => match acceptor.accept(stream).await {
+                    Ok(tls) => handle_connection(tls, &options).await,
+                    Err(e) => Err(format!("TLS handshake failed: {}", e)),
+                },
+                None => handle_connection(stream, &options).await,
+            };
+            if let Err(e) = result {
+                tracing::debug!("expose: {}: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Authenticate the first request on the connection, then pipe the whole connection (later
+/// keep-alive requests, SSE and WebSocket upgrades included) to the upstream agent.
+async fn handle_connection<S>(mut client: S, options: &ExposeOptions) -> Result<(), String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    let end = loop {
+        let n = client
+            .read(&mut buf)
+            .await
+            .map_err(|e| format!("read failed: {}", e))?;
+        if n == 0 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buf[..n]);
+        if let Some(pos) = head.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if head.len() > MAX_HEAD {
+            return respond(&mut client, "431 Request Header Fields Too Large", &[]).await;
+        }
+    };
+    match authorize(&String::from_utf8_lossy(&head[..end]), &options.token) {
+        Access::Granted => {
+            let mut upstream = match TcpStream::connect(options.upstream).await {
+                Ok(upstream) => upstream,
+                Err(e) => {
+                    respond(&mut client, "502 Bad Gateway", &[]).await?;
+                    return Err(format!("agent at {} unreachable: {}", options.upstream, e));
+                }
+            };
+            upstream
+                .write_all(&head)
+                .await
+                .map_err(|e| format!("forward failed: {}", e))?;
+            tokio::io::copy_bidirectional(&mut client, &mut upstream)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("connection closed: {}", e))
+        }
+        Access::SignIn(location) => {
+            let secure = if options.tls.is_some() {
+                "; Secure"
+            } else {
+                ""
+            };
+            let cookie = format!(
+                "Set-Cookie: {}={}; Path=/; HttpOnly; SameSite=Strict{}",
+                TOKEN_COOKIE, options.token, secure
+            );
+            let location = format!("Location: {}", location);
+            respond(&mut client, "302 Found", &[&location, &cookie]).await
+        }
+        Access::Denied => {
+            respond(
+                &mut client,
+                "401 Unauthorized",
+                &["WWW-Authenticate: Bearer realm=\"smith\""],
+            )
+            .await
+        }
+    }
+}
+
+async fn respond<S>(client: &mut S, status: &str, headers: &[&str]) -> Result<(), String>
+where
+    S: AsyncWrite + Unpin,
+{
+    let mut response = format!("HTTP/1.1 {}\r\n", status);
+    for header in headers {
+        response.push_str(header);
+        response.push_str("\r\n");
+    }
+    response.push_str("Content-Length: 0\r\nConnection: close\r\n\r\n");
+    client
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+    let _ = client.shutdown().await;
+    Ok(())
+}
+
+/// TLS acceptor for a PEM certificate chain and private key.
+pub fn tls_acceptor(cert: &Path, key: &Path) -> Result<TlsAcceptor, String> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    };
+    let cert_pem = read(cert)?;
+    let chain = CertificateDer::pem_slice_iter(&cert_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid certificate {}: {}", cert.display(), e))?;
+    if chain.is_empty() {
+        return Err(format!("No certificate found in {}", cert.display()));
+    }
+    let key = PrivateKeyDer::from_pem_slice(&read(key)?)
+        .map_err(|e| format!("Invalid private key {}: {}", key.display(), e))?;
+    let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .and_then(|b| b.with_no_client_auth().with_single_cert(chain, key))
+        .map_err(|e| format!("Invalid TLS configuration: {}", e))?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Self-signed certificate and key in `dir` (`cert.pem`, `key.pem`), created with openssl on
+/// first use for `localhost`, the loopback addresses and `hosts`.
+pub fn self_signed_cert(dir: &Path, hosts: &[String]) -> Result<(PathBuf, PathBuf), String> {
+    let cert = dir.join("cert.pem");
+    let key = dir.join("key.pem");
+    if cert.exists() && key.exists() {
+        return Ok((cert, key));
+    }
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let mut names = vec![
+        "DNS:localhost".to_string(),
+        "IP:127.0.0.1".to_string(),
+        "IP:::1".to_string(),
+    ];
+    for host in hosts {
+        let kind = if host.parse::<std::net::IpAddr>().is_ok() {
+            "IP"
+        } else {
+            "DNS"
+        };
+        names.push(format!("{}:{}", kind, host));
+    }
+    // Create the key file owner-only before openssl writes the private key into it (a leftover
+    // key is replaced, since the mode only applies to new files).
+    let _ = std::fs::remove_file(&key);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&key)
+        .map_err(|e| format!("Failed to create {}: {}", key.display(), e))?;
+    let output = Command::new("openssl")
+        .args(["req", "-x509", "-newkey", "ec", "-pkeyopt"])
+        .arg("ec_paramgen_curve:prime256v1")
+        .args(["-nodes", "-days", "365", "-subj", "/CN=smith agent expose"])
+        .arg("-addext")
+        .arg(format!("subjectAltName={}", names.join(",")))
+        .arg("-keyout")
+        .arg(&key)
+        .arg("-out")
+        .arg(&cert)
+        .output()
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&key);
+            format!(
+                "Failed to run openssl (install it or pass --tls-cert/--tls-key): {}",
+                e
+            )
+        })?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&key);
+        return Err(format!(
+            "openssl failed to create a certificate: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok((cert, key))
+}
+
+/// SHA-256 fingerprint of a PEM certificate, for clients to pin or compare.
+pub fn cert_fingerprint(cert: &Path) -> Option<String> {
+    let output = Command::new("openssl")
+        .args(["x509", "-noout", "-fingerprint", "-sha256", "-in"])
+        .arg(cert)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    text.trim()
+        .split_once('=')
+        .map(|(_, fingerprint)| fingerprint.to_string())
+}
+
+/// Random access token: 32 bytes from the OS, hex-encoded.
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut bytes))
+        .map_err(|e| format!("Failed to generate a token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
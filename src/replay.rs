@@ -0,0 +1,382 @@
+//! Record/replay of a run's external interactions: `docker exec`/`kubectl exec` commands,
+//! Docker API lookups and agent (opencode) output. `--record <file>` appends each interaction
+//! as one JSON line; `--replay <file>` answers the same calls, in order, from the recording,
+//! so the orchestration logic runs deterministically without Docker or provider API keys.
+//! Child smith processes (matrix variants) append to their parent's recording between marker
+//! lines, and on replay get that segment of the recording as their own.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::logging::{display_command, redact};
+use crate::simulate;
+
+/// One recorded call and its result.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Interaction {
+    /// Redacted command line, or `api <call>` for Docker API lookups.
+    pub command: String,
+    /// Exit code; -1 when the process was killed.
+    #[serde(default)]
+    pub status: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stdout: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+}
+
+enum Mode {
+    Off,
+    Record(PathBuf),
+    Replay(VecDeque<Interaction>),
+}
+
+static MODE: Mutex<Mode> = Mutex::new(Mode::Off);
+
+/// Set for a child smith process that records into its parent's file: `--record` appends
+/// instead of truncating.
+pub const RECORD_APPEND_ENV: &str = "SMITH_RECORD_APPEND";
+
+/// Recorded commands around a child smith process's interactions.
+const CHILD_BEGIN: &str = "smith child begin";
+const CHILD_END: &str = "smith child end";
+
+/// How a child smith process shares this process's recording or replay.
+#[derive(Debug, PartialEq)]
+pub enum ChildBackend {
+    Off,
+    /// Append to the recording at this path.
+    Record(PathBuf),
+    /// Replay the child's segment of the recording, written to this file.
+    Replay(PathBuf),
+}
+
+impl ChildBackend {
+    /// Global flags that put the child in the same mode.
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            ChildBackend::Off => Vec::new(),
+            ChildBackend::Record(path) => vec!["--record".into(), path.display().to_string()],
+            ChildBackend::Replay(path) => vec!["--replay".into(), path.display().to_string()],
+        }
+    }
+}
+
+/// Record every interaction from now on, truncating `path` (unless [`RECORD_APPEND_ENV`] is set).
+pub fn start_recording(path: &Path) -> Result<(), String> {
+    if std::env::var_os(RECORD_APPEND_ENV).is_none() {
+        fs::write(path, "").map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    }
+    set_mode(Mode::Record(path.to_path_buf()));
+    Ok(())
+}
+
+/// Answer interactions from the recording at `path` from now on.
+pub fn start_replay(path: &Path) -> Result<(), String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let interactions = raw
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("{} line {}: {}", path.display(), i + 1, e))
+        })
+        .collect::<Result<Vec<Interaction>, String>>()?;
+    replay_from(interactions);
+    Ok(())
+}
+
+/// Answer interactions from `interactions` (in order) from now on.
+pub fn replay_from(interactions: Vec<Interaction>) {
+    set_mode(Mode::Replay(interactions.into()));
+}
+
+/// Stop recording or replaying; returns the recorded interactions that were not replayed.
+pub fn stop() -> Vec<Interaction> {
+    match std::mem::replace(&mut *lock(), Mode::Off) {
+        Mode::Replay(rest) => rest.into(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn replaying() -> bool {
+    matches!(*lock(), Mode::Replay(_))
+}
+
+pub fn recording() -> bool {
+    matches!(*lock(), Mode::Record(_))
+}
+
+/// Start a child smith process's share of the recording or replay: a begin marker when
+/// recording, the recorded child segment (in a temporary file) when replaying. Finish it with
+/// [`end_child`].
+pub fn begin_child() -> Result<ChildBackend, String> {
+    let mut mode = lock();
+    match &mut *mode {
+        Mode::Off => Ok(ChildBackend::Off),
+        Mode::Record(path) => {
+            let path = path.clone();
+            drop(mode);
+            save(&marker(CHILD_BEGIN));
+            Ok(ChildBackend::Record(path))
+        }
+        Mode::Replay(queue) => {
+            let segment = child_segment(queue)?;
+            drop(mode);
+            let path = std::env::temp_dir().join(format!(
+                "smith-replay-{}-{}.jsonl",
+                std::process::id(),
+                unique()
+            ));
+            let mut raw = String::new();
+            for interaction in &segment {
+                raw.push_str(&serde_json::to_string(interaction).map_err(|e| e.to_string())?);
+                raw.push('\n');
+            }
+            fs::write(&path, raw)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            Ok(ChildBackend::Replay(path))
+        }
+    }
+}
+
+/// Finish a child's share started by [`begin_child`].
+pub fn end_child(child: ChildBackend) {
+    match child {
+        ChildBackend::Off => {}
+        ChildBackend::Record(_) => save(&marker(CHILD_END)),
+        ChildBackend::Replay(path) => {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn marker(command: &str) -> Interaction {
+    Interaction {
+        command: command.to_string(),
+        status: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+    }
+}
+
+/// Take the next child segment (between begin and end markers) off the replay queue.
+fn child_segment(queue: &mut VecDeque<Interaction>) -> Result<Vec<Interaction>, String> {
+    match queue.pop_front() {
+        Some(i) if i.command == CHILD_BEGIN => {}
+        Some(i) => {
+            return Err(format!(
+                "replay: recorded `{}`, now a child smith run",
+                i.command
+            ))
+        }
+        None => return Err("replay: recording exhausted at a child smith run".to_string()),
+    }
+    let mut segment = Vec::new();
+    while let Some(i) = queue.pop_front() {
+        if i.command == CHILD_END {
+            return Ok(segment);
+        }
+        segment.push(i);
+    }
+    Err("replay: recording ends inside a child smith run".to_string())
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Mode> {
+    MODE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_mode(mode: Mode) {
+    *lock() = mode;
+}
+
+/// Next recorded interaction, which is expected to be `command`.
+fn next(command: &str) -> io::Result<Interaction> {
+    let mut mode = lock();
+    let Mode::Replay(queue) = &mut *mode else {
+        return Err(io::Error::other("not replaying"));
+    };
+    let interaction = queue
+        .pop_front()
+        .ok_or_else(|| io::Error::other(format!("replay: recording exhausted at `{}`", command)))?;
+    if interaction.command != command {
+        tracing::debug!(
+            "replay: recorded `{}`, now `{}`",
+            interaction.command,
+            command
+        );
+    }
+    Ok(interaction)
+}
+
+fn save(interaction: &Interaction) {
+    let Mode::Record(path) = &*lock() else {
+        return;
+    };
+    let result = serde_json::to_string(interaction)
+        .map_err(io::Error::other)
+        .and_then(|line| {
+            let mut file = OpenOptions::new().append(true).open(path)?;
+            writeln!(file, "{}", line)
+        });
+    if let Err(e) = result {
+        tracing::warn!("failed to record to {}: {}", path.display(), e);
+    }
+}
+
+pub(crate) fn exit_status(code: i32) -> ExitStatus {
+    if code < 0 {
+        ExitStatus::from_raw(libc::SIGKILL)
+    } else {
+        ExitStatus::from_raw((code & 0xff) << 8)
+    }
+}
+
+/// `Command` execution that goes through the recording layer.
+pub trait Recorded {
+    /// `output()`, recorded or answered from the recording.
+    fn recorded_output(&mut self) -> io::Result<Output>;
+    /// `spawn()` of a streaming command; on replay the child replays the recorded stdout and
+    /// stderr. Record its output with a [`Tape`].
+    fn recorded_spawn(&mut self) -> io::Result<Child>;
+}
+
+impl Recorded for Command {
+    fn recorded_output(&mut self) -> io::Result<Output> {
+        if simulate::active() {
+            return simulate::output(self);
+        }
+        let command = redact(&display_command(self));
+        if replaying() {
+            let recorded = next(&command)?;
+            return Ok(Output {
+                status: exit_status(recorded.status),
+                stdout: recorded.stdout.into_bytes(),
+                stderr: recorded.stderr.into_bytes(),
+            });
+        }
+        let output = self.output()?;
+        save(&Interaction {
+            command,
+            status: output.status.code().unwrap_or(-1),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+        Ok(output)
+    }
+
+    fn recorded_spawn(&mut self) -> io::Result<Child> {
+        if simulate::active() {
+            return simulate::spawn(self);
+        }
+        if !replaying() {
+            return self.spawn();
+        }
+        let recorded = next(&redact(&display_command(self)))?;
+        let dir = std::env::temp_dir();
+        let stem = format!("smith-replay-{}-{}", std::process::id(), unique());
+        let stdout_path = dir.join(format!("{}.out", stem));
+        let stderr_path = dir.join(format!("{}.err", stem));
+        fs::write(&stdout_path, &recorded.stdout)?;
+        fs::write(&stderr_path, &recorded.stderr)?;
+        Command::new("sh")
+            .args([
+                "-c",
+                "cat \"$1\"; cat \"$2\" >&2; rm -f \"$1\" \"$2\"; [ \"$3\" -ge 0 ] || kill -9 $$; exit \"$3\"",
+                "sh",
+            ])
+            .arg(&stdout_path)
+            .arg(&stderr_path)
+            .arg(recorded.status.to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+    }
+}
+
+fn unique() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Collects a streaming command's output while it is read; saved as one interaction when
+/// dropped (status -1 unless [`Tape::finish`] set it).
+pub struct Tape {
+    interaction: Option<Interaction>,
+}
+
+impl Tape {
+    /// Start a tape for `cmd`; a no-op unless recording.
+    pub fn new(cmd: &Command) -> Self {
+        let recording = matches!(*lock(), Mode::Record(_));
+        Tape {
+            interaction: recording.then(|| Interaction {
+                command: redact(&display_command(cmd)),
+                status: -1,
+                stdout: String::new(),
+                stderr: String::new(),
+            }),
+        }
+    }
+
+    pub fn line(&mut self, stderr: bool, line: &str) {
+        if let Some(i) = &mut self.interaction {
+            let out = if stderr { &mut i.stderr } else { &mut i.stdout };
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+
+    pub fn finish(&mut self, status: Option<i32>) {
+        if let Some(i) = &mut self.interaction {
+            i.status = status.unwrap_or(-1);
+        }
+    }
+}
+
+impl Drop for Tape {
+    fn drop(&mut self) {
+        if let Some(interaction) = self.interaction.take() {
+            save(&interaction);
+        }
+    }
+}
+
+/// A lookup that is not a command (e.g. a Docker API call), recorded as JSON under `api <call>`.
+pub fn value<T: Serialize + DeserializeOwned>(
+    call: &str,
+    f: impl FnOnce() -> T,
+) -> Result<T, String> {
+    if let Some(answer) = simulate::value(call).filter(|_| simulate::active()) {
+        return serde_json::from_str(&answer)
+            .map_err(|e| format!("simulate: invalid value for `{}`: {}", call, e));
+    }
+    let command = format!("api {}", call);
+    if replaying() {
+        let recorded = next(&command).map_err(|e| e.to_string())?;
+        return serde_json::from_str(&recorded.stdout)
+            .map_err(|e| format!("replay: invalid recorded value for `{}`: {}", command, e));
+    }
+    let result = f();
+    if let Ok(stdout) = serde_json::to_string(&result) {
+        save(&Interaction {
+            command,
+            status: 0,
+            stdout,
+            stderr: String::new(),
+        });
+    }
+    Ok(result)
+}
@@ -0,0 +1,161 @@
+//! Run windows: when a project's `smith run` pipelines may start (`[projects.schedule]`), in the
+//! host's local time, and whether they may start on a metered connection.
+
+use crate::config::ScheduleConfig;
+use std::process::Command;
+
+/// Pipelines a schedule can cover (`schedule.pipelines`).
+pub const SCHEDULE_PIPELINES: &[&str] = &["plan", "develop", "release", "review", "resolve"];
+
+/// Accepted values for `schedule.metered`.
+pub const METERED_MODES: &[&str] = &["allow", "deny"];
+
+const DAYS: &[&str] = &["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const DAY_NAMES: &[&str] = &[
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Parse "HH:MM-HH:MM" into start and end minutes of the day. An end before the start wraps past
+/// midnight; equal start and end cover the whole day.
+pub fn parse_window(window: &str) -> Result<(u32, u32), String> {
+    let parse = |t: &str| -> Option<u32> {
+        let (h, m) = t.trim().split_once(':')?;
+        let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+        (h < 24 && m < 60 && t.trim().len() == 5).then_some(h * 60 + m)
+    };
+    window
+        .split_once('-')
+        .and_then(|(start, end)| Some((parse(start)?, parse(end)?)))
+        .ok_or_else(|| format!("invalid run window '{}'; expected HH:MM-HH:MM", window))
+}
+
+/// Weekday index (Monday = 0) of "mon".."sun" or a full day name; anything else is an error.
+pub fn parse_day(day: &str) -> Result<u32, String> {
+    let day = day.trim().to_lowercase();
+    DAYS.iter()
+        .zip(DAY_NAMES)
+        .position(|(short, full)| day == *short || day == *full)
+        .map(|i| i as u32)
+        .ok_or_else(|| {
+            format!(
+                "invalid day '{}'; expected one of {} (or the full name)",
+                day,
+                DAYS.join(", ")
+            )
+        })
+}
+
+/// Check every field of a schedule.
+pub fn validate(schedule: &ScheduleConfig) -> Result<(), String> {
+    for window in &schedule.windows {
+        parse_window(window)?;
+    }
+    for day in &schedule.days {
+        parse_day(day)?;
+    }
+    for pipeline in &schedule.pipelines {
+        if !SCHEDULE_PIPELINES.contains(&pipeline.as_str()) {
+            return Err(format!(
+                "invalid schedule pipeline '{}'; expected one of {}",
+                pipeline,
+                SCHEDULE_PIPELINES.join(", ")
+            ));
+        }
+    }
+    if let Some(mode) = schedule.metered.as_deref() {
+        if !METERED_MODES.contains(&mode) {
+            return Err(format!(
+                "invalid schedule metered '{}'; expected one of {}",
+                mode,
+                METERED_MODES.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether the schedule covers runs of `pipeline` (no `pipelines` = every pipeline).
+pub fn applies(schedule: &ScheduleConfig, pipeline: &str) -> bool {
+    schedule.pipelines.is_empty() || schedule.pipelines.iter().any(|p| p == pipeline)
+}
+
+/// Whether a run may start at `minute` of `weekday` (Monday = 0). A window that wraps past
+/// midnight belongs to the day it starts on. No windows means any time of an allowed day.
+pub fn open_at(schedule: &ScheduleConfig, weekday: u32, minute: u32) -> Result<bool, String> {
+    let days = schedule
+        .days
+        .iter()
+        .map(|d| parse_day(d))
+        .collect::<Result<Vec<_>, _>>()?;
+    let day_allowed = |day: u32| days.is_empty() || days.contains(&(day % 7));
+    if schedule.windows.is_empty() {
+        return Ok(day_allowed(weekday));
+    }
+    for window in &schedule.windows {
+        let (start, end) = parse_window(window)?;
+        let open = if start == end {
+            day_allowed(weekday)
+        } else if start < end {
+            day_allowed(weekday) && (start..end).contains(&minute)
+        } else {
+            (minute >= start && day_allowed(weekday)) || (minute < end && day_allowed(weekday + 6))
+        };
+        if open {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Minutes from `minute` of `weekday` until the schedule next opens (None if it never does).
+pub fn minutes_until_open(schedule: &ScheduleConfig, weekday: u32, minute: u32) -> Option<u32> {
+    (1..=7 * 24 * 60).find(|step| {
+        let t = minute + step;
+        open_at(schedule, (weekday + t / 1440) % 7, t % 1440).unwrap_or(false)
+    })
+}
+
+/// Local weekday (Monday = 0) and minute of the day at unix time `now`.
+pub fn local_time(now: u64) -> (u32, u32) {
+    let t = now as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+        let days = now / 86_400;
+        return (((days + 3) % 7) as u32, ((now % 86_400) / 60) as u32);
+    }
+    (
+        ((tm.tm_wday + 6) % 7) as u32,
+        (tm.tm_hour * 60 + tm.tm_min) as u32,
+    )
+}
+
+/// Whether NetworkManager reports the host's connection as metered (None when it cannot tell).
+pub fn connection_metered() -> Option<bool> {
+    let out = Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    parse_metered(&String::from_utf8_lossy(&out.stdout))
+}
+
+/// NetworkManager's `Metered` property ("u 1"): 1 yes, 3 guessed yes, 2 no, 4 guessed no, 0 unknown.
+pub fn parse_metered(property: &str) -> Option<bool> {
+    match property.trim().strip_prefix("u ")?.trim() {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,243 @@
+//! Secret detection for generated diffs: pattern rules for well-known credential formats plus
+//! an entropy check on secret-looking assignments. Only added lines are scanned.
+
+use serde::{Deserialize, Serialize};
+
+/// Accepted values for a project's `secret_scan` setting (unset = builtin).
+pub const SECRET_SCAN_MODES: &[&str] = &["builtin", "gitleaks", "off"];
+
+/// Minimum Shannon entropy (bits per char) for an assigned value to count as a secret.
+const MIN_ENTROPY: f64 = 3.5;
+
+/// Minimum length of an assigned value checked for entropy.
+const MIN_SECRET_LEN: usize = 16;
+
+/// Assignment names that suggest a credential (compared case-insensitively).
+const SECRET_NAME_MARKERS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "api_key",
+    "apikey",
+    "private_key",
+    "access_key",
+];
+
+/// Values that are clearly placeholders, not credentials.
+const PLACEHOLDER_MARKERS: &[&str] = &[
+    "${",
+    "{{",
+    "<",
+    "example",
+    "xxxx",
+    "changeme",
+    "placeholder",
+    "redacted",
+    "dummy",
+    "***",
+];
+
+/// One suspected credential in a diff.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SecretFinding {
+    /// Rule that matched (e.g. "github-token", "high-entropy-assignment").
+    pub rule: String,
+    pub file: String,
+    /// Line number in the new version of the file (0 when unknown).
+    pub line: u64,
+    /// The matched line with the secret masked.
+    pub excerpt: String,
+}
+
+/// Known credential formats: (rule id, prefix, minimum length of the token after the prefix).
+const TOKEN_RULES: &[(&str, &str, usize)] = &[
+    ("github-token", "ghp_", 36),
+    ("github-token", "gho_", 36),
+    ("github-token", "ghs_", 36),
+    ("github-token", "ghu_", 36),
+    ("github-token", "ghr_", 36),
+    ("github-token", "github_pat_", 22),
+    ("anthropic-key", "sk-ant-", 32),
+    ("openai-key", "sk-proj-", 32),
+    ("openai-key", "sk-", 32),
+    ("aws-access-key", "AKIA", 16),
+    ("aws-access-key", "ASIA", 16),
+    ("google-api-key", "AIza", 35),
+    ("slack-token", "xoxb-", 20),
+    ("slack-token", "xoxp-", 20),
+    ("stripe-key", "sk_live_", 20),
+    ("stripe-key", "rk_live_", 20),
+    ("gitlab-token", "glpat-", 20),
+];
+
+fn token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-')
+}
+
+/// Scan the added lines of a unified diff (`git diff`) for credentials.
+pub fn scan_diff(diff: &str) -> Vec<SecretFinding> {
+    let mut findings = Vec::new();
+    let mut file = String::new();
+    let mut line_no: u64 = 0;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            file = path.strip_prefix("b/").unwrap_or(path).to_string();
+            continue;
+        }
+        if line.starts_with("@@") {
+            // "@@ -a,b +c,d @@": the next added or context line is line c.
+            line_no = line
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(0);
+            continue;
+        }
+        if line.starts_with("---") || line.starts_with('-') || line.starts_with('\\') {
+            continue;
+        }
+        if let Some(added) = line.strip_prefix('+') {
+            if let Some(rule) = scan_line(added) {
+                findings.push(SecretFinding {
+                    rule: rule.to_string(),
+                    file: file.clone(),
+                    line: line_no,
+                    excerpt: mask_line(added),
+                });
+            }
+        }
+        if line.starts_with('+') || line.starts_with(' ') {
+            line_no += 1;
+        }
+    }
+    findings
+}
+
+/// `text` made safe to share: a line a rule matches is replaced by a marker naming the rule,
+/// and secret-looking values elsewhere (`KEY=value`, bearer tokens) are masked as in logs.
+pub fn redact_text(text: &str) -> String {
+    text.lines()
+        .map(|line| match scan_line(line) {
+            Some(rule) => format!("[redacted: {}]", rule),
+            None => crate::logging::redact(line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Rule id matching one line of text, if any.
+pub fn scan_line(text: &str) -> Option<&'static str> {
+    if text.contains("-----BEGIN") && text.contains("PRIVATE KEY-----") {
+        return Some("private-key");
+    }
+    for word in text.split(|c: char| !token_char(c)) {
+        for (rule, prefix, min_len) in TOKEN_RULES {
+            if let Some(rest) = word.strip_prefix(prefix) {
+                let upper_only = prefix.starts_with("AKIA") || prefix.starts_with("ASIA");
+                let valid = rest.len() >= *min_len
+                    && (!upper_only
+                        || rest
+                            .chars()
+                            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+                if valid && !is_placeholder(rest) {
+                    return Some(rule);
+                }
+            }
+        }
+    }
+    assigned_secret(text).then_some("high-entropy-assignment")
+}
+
+/// `name = "value"` / `name: value` where the name looks like a credential and the value is long,
+/// random-looking and not a placeholder.
+fn assigned_secret(text: &str) -> bool {
+    let Some((name, value)) = text.split_once(['=', ':']) else {
+        return false;
+    };
+    let name = name.to_lowercase();
+    if !SECRET_NAME_MARKERS.iter().any(|m| name.contains(m)) {
+        return false;
+    }
+    let value = value
+        .trim()
+        .trim_end_matches([',', ';'])
+        .trim_matches(|c| c == '"' || c == '\'' || c == '`');
+    value.len() >= MIN_SECRET_LEN
+        && !value.contains(char::is_whitespace)
+        && !is_placeholder(value)
+        && shannon_entropy(value) >= MIN_ENTROPY
+}
+
+fn is_placeholder(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    PLACEHOLDER_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Shannon entropy of `value` in bits per character.
+pub fn shannon_entropy(value: &str) -> f64 {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+    let mut counts: Vec<(char, usize)> = Vec::new();
+    for c in &chars {
+        match counts.iter_mut().find(|(k, _)| k == c) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((*c, 1)),
+        }
+    }
+    let len = chars.len() as f64;
+    counts
+        .iter()
+        .map(|(_, n)| {
+            let p = *n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// The line with every long token masked, keeping four leading characters for context.
+fn mask_line(text: &str) -> String {
+    let mut out = String::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, out: &mut String| {
+        if word.len() >= MIN_SECRET_LEN {
+            out.extend(word.chars().take(4));
+            out.push_str("***");
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    };
+    for c in text.trim().chars() {
+        if token_char(c) {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut word, &mut out);
+    out.chars().take(120).collect()
+}
+
+/// Findings from a gitleaks JSON report (`--report-format json`).
+pub fn parse_gitleaks_report(raw: &str) -> Result<Vec<SecretFinding>, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(Vec::new());
+    }
+    let items: Vec<serde_json::Value> =
+        serde_json::from_str(raw).map_err(|e| format!("Invalid gitleaks report: {}", e))?;
+    Ok(items
+        .iter()
+        .map(|item| SecretFinding {
+            rule: format!("gitleaks:{}", item["RuleID"].as_str().unwrap_or("unknown")),
+            file: item["File"].as_str().unwrap_or_default().to_string(),
+            line: item["StartLine"].as_u64().unwrap_or(0),
+            excerpt: mask_line(item["Match"].as_str().unwrap_or_default()),
+        })
+        .collect())
+}
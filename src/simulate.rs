@@ -0,0 +1,541 @@
+//! Simulation mode (`--simulate`): a mock container backend for demos and command-flow tests.
+//! Each spawned agent is a directory `<cache dir>/simulate/<container>` whose `state/` and
+//! `workspace/` stand in for /state and /workspace. Container shell commands run locally against
+//! them, clones and pushes go to a local bare repository per project repo, and the agent writes
+//! the artifacts its prompt asks for. Fixtures adjust agent answers and command results.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::audit::format_utc;
+use crate::config::cache_dir;
+use crate::replay::exit_status;
+
+/// Optional `--simulate=<file>` fixtures (TOML, YAML or JSON).
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Fixtures {
+    #[serde(default)]
+    pub answers: Vec<AnswerFixture>,
+    #[serde(default)]
+    pub commands: Vec<CommandFixture>,
+}
+
+/// Agent answer for prompts containing `match`.
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AnswerFixture {
+    #[serde(rename = "match")]
+    pub pattern: String,
+    /// Text the agent prints (and writes to requested text files).
+    pub reply: Option<String>,
+    /// Fields merged into the JSON artifact the prompt asks for (e.g. `verdict = "fail"`).
+    pub artifact: Option<Value>,
+    /// Fail the prompt with this error instead.
+    pub error: Option<String>,
+}
+
+/// Result for container shell commands containing `match` (e.g. `git push`).
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CommandFixture {
+    #[serde(rename = "match")]
+    pub pattern: String,
+    #[serde(default)]
+    pub exit_code: i32,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+}
+
+struct Simulation {
+    root: PathBuf,
+    fixtures: Fixtures,
+}
+
+static SIMULATION: Mutex<Option<Simulation>> = Mutex::new(None);
+
+/// Default root for simulated containers and remotes.
+pub fn default_root() -> Result<PathBuf, String> {
+    cache_dir()
+        .map(|dir| dir.join("simulate"))
+        .map_err(|e| e.to_string())
+}
+
+pub fn load_fixtures(path: &Path) -> Result<Fixtures, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let parsed = if path.extension().is_some_and(|e| e == "toml") {
+        toml::from_str(&raw).map_err(|e| e.to_string())
+    } else {
+        serde_yaml::from_str(&raw).map_err(|e| e.to_string())
+    };
+    parsed.map_err(|e| format!("Invalid fixtures {}: {}", path.display(), e))
+}
+
+/// Route container operations to the simulation rooted at `root` from now on.
+pub fn start(root: PathBuf, fixtures: Fixtures) -> Result<(), String> {
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+    *SIMULATION.lock().unwrap_or_else(|e| e.into_inner()) = Some(Simulation { root, fixtures });
+    Ok(())
+}
+
+pub fn stop() {
+    *SIMULATION.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub fn active() -> bool {
+    SIMULATION
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some()
+}
+
+fn with_sim<T>(f: impl FnOnce(&Simulation) -> T) -> Option<T> {
+    SIMULATION
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(f)
+}
+
+fn container_root(name: &str) -> io::Result<PathBuf> {
+    with_sim(|s| s.root.join("containers").join(name))
+        .ok_or_else(|| io::Error::other("simulation is not active"))
+}
+
+/// Host path of `path` inside simulated container `name`.
+fn host_path(name: &str, path: &str) -> io::Result<PathBuf> {
+    Ok(PathBuf::from(rewrite_paths(path, &container_root(name)?)))
+}
+
+/// Replace `/state` and `/workspace` path prefixes in a command with the container's directories.
+pub fn rewrite_paths(script: &str, container: &Path) -> String {
+    let mut out = String::with_capacity(script.len());
+    let mut rest = script;
+    'scan: while !rest.is_empty() {
+        for dir in ["/state", "/workspace"] {
+            let boundary_before = out
+                .chars()
+                .last()
+                .is_none_or(|c| !(c.is_ascii_alphanumeric() || "/._-$~".contains(c)));
+            let boundary_after = rest[dir.len().min(rest.len())..]
+                .chars()
+                .next()
+                .is_none_or(|c| !(c.is_ascii_alphanumeric() || "._-".contains(c)));
+            if boundary_before && rest.starts_with(dir) && boundary_after {
+                out.push_str(&container.join(&dir[1..]).to_string_lossy());
+                rest = &rest[dir.len()..];
+                continue 'scan;
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Whether simulated container `name` exists (it is always "running" once started).
+pub(crate) fn container_running(name: &str) -> Option<bool> {
+    container_root(name)
+        .ok()
+        .filter(|p| p.is_dir())
+        .map(|_| true)
+}
+
+/// Answer for a `replay::value` lookup, as JSON; None for lookups the simulation does not fake.
+pub(crate) fn value(call: &str) -> Option<String> {
+    let (kind, target) = call.split_once(' ')?;
+    let answer = match kind {
+        "container_running" => json!({ "Ok": container_running(target) }),
+        "image_exists" => json!({ "Ok": true }),
+        // Nothing to isolate: no networks exist, and every network step succeeds.
+        "network_exists" => json!({ "Ok": false }),
+        "networks" => json!(["bridge"]),
+        "network" => json!({ "Ok": null }),
+        _ => return None,
+    };
+    Some(answer.to_string())
+}
+
+/// "Clone" `repo` at `branch` into a new simulated container. The repo is a local bare
+/// repository standing in for the remote, seeded with one commit on first use.
+pub(crate) fn start_container(
+    name: &str,
+    repo: &str,
+    branch: &str,
+    commit_name: Option<&str>,
+    commit_email: Option<&str>,
+) -> Result<(), String> {
+    let root = container_root(name).map_err(|e| e.to_string())?;
+    let remote = with_sim(|s| s.root.join("remotes").join(remote_dir_name(repo)))
+        .ok_or("simulation is not active")?;
+    if !remote.exists() {
+        seed_remote(&remote, repo)?;
+    }
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("state"))
+        .map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+    let workspace = root.join("workspace");
+    git(
+        None,
+        &[
+            "clone",
+            "-q",
+            &remote.to_string_lossy(),
+            &workspace.to_string_lossy(),
+        ],
+    )?;
+    let ws = Some(workspace.as_path());
+    git(ws, &["config", "user.name", commit_name.unwrap_or("Smith")])?;
+    git(
+        ws,
+        &[
+            "config",
+            "user.email",
+            commit_email.unwrap_or("smith@localhost"),
+        ],
+    )?;
+    let remote_ref = format!("origin/{}", branch);
+    if git(ws, &["rev-parse", "--verify", "-q", &remote_ref]).is_ok() {
+        git(ws, &["checkout", "-q", "-B", branch, &remote_ref])?;
+    } else {
+        git(ws, &["checkout", "-q", "-B", branch])?;
+    }
+    Ok(())
+}
+
+/// Remove a simulated container.
+pub(crate) fn stop_container(name: &str) -> Result<(), String> {
+    let root = container_root(name).map_err(|e| e.to_string())?;
+    fs::remove_dir_all(&root).map_err(|e| format!("No simulated container '{}': {}", name, e))
+}
+
+fn remote_dir_name(repo: &str) -> String {
+    let safe: String = repo
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("{}.git", safe.trim_matches('-'))
+}
+
+fn seed_remote(remote: &Path, repo: &str) -> Result<(), String> {
+    let seed = remote.with_extension("seed");
+    let _ = fs::remove_dir_all(&seed);
+    fs::create_dir_all(&seed).map_err(|e| e.to_string())?;
+    fs::write(
+        seed.join("README.md"),
+        format!("# Simulated checkout of {}\n", repo),
+    )
+    .map_err(|e| e.to_string())?;
+    let s = Some(seed.as_path());
+    git(s, &["init", "-q", "-b", "main"])?;
+    git(s, &["add", "-A"])?;
+    git(
+        s,
+        &[
+            "-c",
+            "user.name=Smith",
+            "-c",
+            "user.email=smith@localhost",
+            "commit",
+            "-q",
+            "-m",
+            "Initial commit",
+        ],
+    )?;
+    git(
+        None,
+        &[
+            "clone",
+            "-q",
+            "--bare",
+            &seed.to_string_lossy(),
+            &remote.to_string_lossy(),
+        ],
+    )?;
+    let _ = fs::remove_dir_all(&seed);
+    Ok(())
+}
+
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let out = cmd
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if out.status.success() {
+        Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
+
+/// Container name and command of a `docker exec`, skipping exec options.
+fn parse_exec(args: &[String]) -> Option<(&str, &[String])> {
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if matches!(arg, "-e" | "-w" | "-u" | "--env" | "--workdir" | "--user") {
+            i += 2;
+        } else if arg.starts_with('-') {
+            i += 1;
+        } else {
+            return Some((arg, &args[i + 1..]));
+        }
+    }
+    None
+}
+
+fn command_args(cmd: &Command) -> (String, Vec<String>) {
+    (
+        cmd.get_program().to_string_lossy().into_owned(),
+        cmd.get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect(),
+    )
+}
+
+fn fixed_output(code: i32, stdout: &str, stderr: &str) -> io::Result<Output> {
+    Ok(Output {
+        status: exit_status(code),
+        stdout: stdout.as_bytes().to_vec(),
+        stderr: stderr.as_bytes().to_vec(),
+    })
+}
+
+/// Simulated `output()` of a container command.
+pub(crate) fn output(cmd: &Command) -> io::Result<Output> {
+    let (program, args) = command_args(cmd);
+    if program != "docker" {
+        return Err(io::Error::other(
+            "simulation supports the Docker backend only",
+        ));
+    }
+    match args.first().map(String::as_str) {
+        Some("exec") => {
+            let Some((name, argv)) = parse_exec(&args[1..]) else {
+                return fixed_output(1, "", "docker exec: missing container");
+            };
+            let root = container_root(name)?;
+            if !root.is_dir() {
+                return fixed_output(1, "", &format!("No such container: {}", name));
+            }
+            let joined = argv.join(" ");
+            let fixture = with_sim(|s| {
+                s.fixtures
+                    .commands
+                    .iter()
+                    .find(|f| joined.contains(&f.pattern))
+                    .cloned()
+            })
+            .flatten();
+            if let Some(f) = fixture {
+                return fixed_output(f.exit_code, &f.stdout, &f.stderr);
+            }
+            let argv: Vec<String> = argv.iter().map(|a| rewrite_paths(a, &root)).collect();
+            let (exe, rest) = argv
+                .split_first()
+                .ok_or_else(|| io::Error::other("empty command"))?;
+            let rest: Vec<&str> = rest
+                .iter()
+                .map(|a| if a == "-lc" { "-c" } else { a.as_str() })
+                .collect();
+            Command::new(exe).args(rest).current_dir(&root).output()
+        }
+        Some("cp") if args.len() == 3 => {
+            let Some((name, path)) = args[2].split_once(':') else {
+                return fixed_output(
+                    1,
+                    "",
+                    "docker cp: only copies into containers are simulated",
+                );
+            };
+            let dest = host_path(name, path)?;
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&args[1], &dest)?;
+            fixed_output(0, "", "")
+        }
+        // Other calls have nothing to act on in a simulation.
+        _ => fixed_output(0, "", ""),
+    }
+}
+
+/// Simulated `spawn()` of an agent prompt (`docker exec ... opencode run ... <prompt>`): writes
+/// the requested artifacts, then returns a child that prints the agent's JSON events.
+pub(crate) fn spawn(cmd: &Command) -> io::Result<Child> {
+    let (program, args) = command_args(cmd);
+    let exec = (program == "docker" && args.first().is_some_and(|a| a == "exec"))
+        .then(|| parse_exec(&args[1..]))
+        .flatten();
+    let Some((name, argv)) = exec.filter(|(_, argv)| argv.first().is_some_and(|a| a == "opencode"))
+    else {
+        return Err(io::Error::other(
+            "simulation only runs agent prompts in Docker containers",
+        ));
+    };
+    let prompt = argv.last().cloned().unwrap_or_default();
+    let root = container_root(name)?;
+    let fixture = with_sim(|s| {
+        s.fixtures
+            .answers
+            .iter()
+            .find(|f| prompt.contains(&f.pattern))
+            .cloned()
+    })
+    .flatten()
+    .unwrap_or_default();
+
+    if let Some(error) = &fixture.error {
+        return echo_child(&[], error, 1);
+    }
+    let reply = simulate_agent(&root, &prompt, &fixture)?;
+    let events = [
+        json!({ "type": "text", "part": { "text": reply } }).to_string(),
+        json!({
+            "type": "step_finish",
+            "part": { "tokens": { "input": prompt.len() / 4, "output": reply.len() / 4 }, "cost": 0.0 }
+        })
+        .to_string(),
+    ];
+    echo_child(&events, "", 0)
+}
+
+fn echo_child(stdout_lines: &[String], stderr: &str, code: i32) -> io::Result<Child> {
+    Command::new("sh")
+        .args([
+            "-c",
+            "printf '%s' \"$1\" >&2; code=\"$2\"; shift 2; for line; do printf '%s\\n' \"$line\"; done; exit \"$code\"",
+            "sh",
+            stderr,
+            &code.to_string(),
+        ])
+        .args(stdout_lines)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+}
+
+/// Do what the prompt asks: write each requested artifact and, when asked for code changes,
+/// change a file in the workspace. Returns the agent's printed answer.
+fn simulate_agent(root: &Path, prompt: &str, fixture: &AnswerFixture) -> io::Result<String> {
+    let reply = fixture
+        .reply
+        .clone()
+        .unwrap_or_else(|| "chore: simulated change".to_string());
+    let template = prompt_template(prompt);
+    for path in prompt_artifacts(prompt) {
+        let dest = PathBuf::from(rewrite_paths(&path, root));
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = if path.ends_with(".json") {
+            let stem = Path::new(&path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let mut artifact = template
+                .as_ref()
+                .map(|t| fill_template(t, "", &stem))
+                .unwrap_or_else(|| json!({}));
+            if let (Some(obj), Some(Value::Object(extra))) =
+                (artifact.as_object_mut(), fixture.artifact.as_ref())
+            {
+                obj.extend(extra.clone());
+            }
+            serde_json::to_string_pretty(&artifact).unwrap_or_default()
+        } else {
+            format!("{}\n", reply)
+        };
+        fs::write(&dest, content)?;
+    }
+    if prompt.contains("Make code changes in /workspace") {
+        let notes = root.join("workspace").join("SIMULATED_CHANGES.md");
+        let mut text = fs::read_to_string(&notes).unwrap_or_default();
+        let task = prompt.lines().next().unwrap_or_default();
+        text.push_str(&format!("- {}\n", task.trim()));
+        fs::write(&notes, text)?;
+    }
+    Ok(reply)
+}
+
+/// Absolute paths the prompt asks to write ("... to /state/x.json", "... at /state/y.json").
+pub fn prompt_artifacts(prompt: &str) -> Vec<String> {
+    let words: Vec<&str> = prompt.split_whitespace().collect();
+    let mut paths = Vec::new();
+    for pair in words.windows(2) {
+        let path = pair[1].trim_end_matches(['.', ',', ':', ';', ')']);
+        if matches!(pair[0], "to" | "at")
+            && path.starts_with('/')
+            && [".json", ".txt", ".md"]
+                .iter()
+                .any(|ext| path.ends_with(ext))
+            && !paths.iter().any(|p| p == path)
+        {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+/// The first JSON object in the prompt (the artifact shape it asks for).
+fn prompt_template(prompt: &str) -> Option<Value> {
+    let start = prompt.find("\n{")? + 1;
+    let mut depth = 0usize;
+    for (i, c) in prompt[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return serde_json::from_str(&prompt[start..start + i + 1]).ok();
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A plausible artifact from a shape: first choice of `a|b` strings, no issues, `role` from
+/// the file name and timestamps for `ISO-8601`.
+pub fn fill_template(value: &Value, key: &str, stem: &str) -> Value {
+    match value {
+        Value::String(_) if key == "role" => Value::String(stem.to_string()),
+        Value::String(s) if s == "ISO-8601" => Value::String(format_utc(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        )),
+        Value::String(s) => Value::String(s.split('|').next().unwrap_or_default().to_string()),
+        Value::Array(_) if key.ends_with("issues") => Value::Array(Vec::new()),
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| fill_template(v, "", stem)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), fill_template(v, k, stem)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
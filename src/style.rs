@@ -0,0 +1,92 @@
+//! Terminal styling: ANSI colors and OSC 8 hyperlinks, disabled by `--no-color`, `NO_COLOR`
+//! or when stdout is not a terminal.
+
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// Decide once at startup whether output is styled.
+pub fn init(no_color: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    COLOR.store(
+        !no_color && !no_color_env && std::io::stdout().is_terminal(),
+        Ordering::Relaxed,
+    );
+}
+
+/// True when ANSI colors and hyperlinks should be written.
+pub fn color_enabled() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+/// An ANSI escape sequence; prints nothing when color is disabled.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Ansi(&'static str);
+
+impl fmt::Display for Ansi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if color_enabled() {
+            f.write_str(self.0)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Status bullet ("●") in one color; plain when color is disabled.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Bullet(Ansi);
+
+impl fmt::Display for Bullet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}●{}", self.0, ANSI_RESET)
+    }
+}
+
+/// ANSI color codes for status output (circle: red = not built, blue = built, green = online).
+pub const ANSI_RED: Ansi = Ansi("\x1b[31m");
+pub const ANSI_BLUE: Ansi = Ansi("\x1b[34m");
+pub const ANSI_GREEN: Ansi = Ansi("\x1b[32m");
+pub const ANSI_YELLOW: Ansi = Ansi("\x1b[33m");
+pub const ANSI_MAGENTA: Ansi = Ansi("\x1b[35m");
+pub const ANSI_CYAN: Ansi = Ansi("\x1b[36m");
+pub const ANSI_RESET: Ansi = Ansi("\x1b[0m");
+
+/// Colors cycled through for per-agent prefixes when several log streams are interleaved.
+pub const PREFIX_COLORS: [Ansi; 6] = [
+    ANSI_CYAN,
+    ANSI_MAGENTA,
+    ANSI_YELLOW,
+    ANSI_GREEN,
+    ANSI_BLUE,
+    ANSI_RED,
+];
+
+/// Colored bullet for status/install output.
+pub const BULLET_GREEN: Bullet = Bullet(ANSI_GREEN);
+pub const BULLET_BLUE: Bullet = Bullet(ANSI_BLUE);
+pub const BULLET_RED: Bullet = Bullet(ANSI_RED);
+pub const BULLET_YELLOW: Bullet = Bullet(ANSI_YELLOW);
+
+/// OSC 8 hyperlink so the URL is clickable in supported terminals (e.g. VS Code, iTerm2,
+/// Windows Terminal); the bare URL when styling is off.
+pub fn hyperlink(url: &str) -> String {
+    if color_enabled() {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, url)
+    } else {
+        url.to_string()
+    }
+}
+
+/// `label |` prefixes padded to the longest label, each in the next of [`PREFIX_COLORS`], for
+/// telling interleaved streams apart.
+pub fn aligned_prefixes(labels: &[String]) -> Vec<String> {
+    let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    labels
+        .iter()
+        .zip(PREFIX_COLORS.iter().cycle())
+        .map(|(label, color)| format!("{}{:<width$} |{}", color, label, ANSI_RESET))
+        .collect()
+}
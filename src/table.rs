@@ -0,0 +1,194 @@
+//! Column-aligned table output for list/status commands, with `--columns`, `--sort` and `--filter`.
+
+use std::cmp::Ordering;
+
+/// Shared table flags; flatten into a command with `#[command(flatten)]`.
+#[derive(clap::Args, Clone, Default)]
+pub struct TableArgs {
+    /// Comma-separated columns to show, in order (e.g. name,repo,model)
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+    /// Sort rows by a column; prefix with '-' for descending (e.g. -port)
+    #[arg(long, allow_hyphen_values = true)]
+    pub sort: Option<String>,
+    /// Keep rows where COLUMN contains VALUE, case-insensitive (repeatable: name=web)
+    #[arg(long)]
+    pub filter: Vec<String>,
+}
+
+/// A table of string cells keyed by lowercase column ids; headers are the uppercased ids.
+pub struct Table {
+    columns: Vec<&'static str>,
+    defaults: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// All available columns, in display order; every column is shown unless `defaults` narrows it.
+    pub fn new(columns: &[&'static str]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            defaults: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Columns shown when `--columns` is not given.
+    pub fn defaults(mut self, defaults: &[&'static str]) -> Self {
+        self.defaults = defaults.to_vec();
+        self
+    }
+
+    /// Add a row; cells are in `new()` column order. Empty cells render as "-".
+    pub fn row(&mut self, cells: Vec<String>) {
+        debug_assert_eq!(cells.len(), self.columns.len());
+        self.rows.push(
+            cells
+                .into_iter()
+                .map(|c| if c.is_empty() { "-".to_string() } else { c })
+                .collect(),
+        );
+    }
+
+    fn index(&self, column: &str) -> Result<usize, String> {
+        let column = column.trim().to_lowercase();
+        self.columns
+            .iter()
+            .position(|c| *c == column)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown column '{}' (available: {})",
+                    column,
+                    self.columns.join(", ")
+                )
+            })
+    }
+
+    /// Apply filters, sorting and column selection, then render aligned lines (header first).
+    /// Returns just the header when no rows remain.
+    pub fn render(&self, args: &TableArgs) -> Result<String, String> {
+        let shown: Vec<usize> = if args.columns.is_empty() {
+            self.defaults
+                .iter()
+                .map(|c| self.index(c))
+                .collect::<Result<_, _>>()?
+        } else {
+            args.columns
+                .iter()
+                .filter(|c| !c.trim().is_empty())
+                .map(|c| self.index(c))
+                .collect::<Result<_, _>>()?
+        };
+
+        let mut filters = Vec::new();
+        for filter in &args.filter {
+            let (column, value) = filter
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid --filter '{}', expected COLUMN=VALUE", filter))?;
+            filters.push((self.index(column)?, value.to_lowercase()));
+        }
+        let mut rows: Vec<&Vec<String>> = self
+            .rows
+            .iter()
+            .filter(|row| {
+                filters
+                    .iter()
+                    .all(|(i, value)| strip_ansi(&row[*i]).to_lowercase().contains(value))
+            })
+            .collect();
+
+        if let Some(sort) = args.sort.as_deref() {
+            let (column, descending) = match sort.strip_prefix('-') {
+                Some(column) => (column, true),
+                None => (sort, false),
+            };
+            let i = self.index(column)?;
+            rows.sort_by(|a, b| {
+                let ord = compare_cells(&strip_ansi(&a[i]), &strip_ansi(&b[i]));
+                if descending {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            });
+        }
+
+        let headers: Vec<String> = shown
+            .iter()
+            .map(|i| self.columns[*i].to_uppercase())
+            .collect();
+        let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+        for row in &rows {
+            for (w, i) in widths.iter_mut().zip(&shown) {
+                *w = (*w).max(visible_width(&row[*i]));
+            }
+        }
+
+        let mut out = String::new();
+        let mut push_line = |cells: Vec<&str>| {
+            let last = cells.len().saturating_sub(1);
+            let mut line = String::new();
+            for (n, (cell, width)) in cells.iter().zip(&widths).enumerate() {
+                line.push_str(cell);
+                if n < last {
+                    line.push_str(&" ".repeat(width - visible_width(cell) + 2));
+                }
+            }
+            out.push_str(line.trim_end());
+            out.push('\n');
+        };
+        push_line(headers.iter().map(String::as_str).collect());
+        for row in rows {
+            push_line(shown.iter().map(|i| row[*i].as_str()).collect());
+        }
+        Ok(out)
+    }
+}
+
+/// Numbers compare numerically, everything else case-insensitively.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// Remove ANSI color (CSI) and OSC 8 hyperlink escape sequences.
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: ESC [ ... final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: ESC ] ... terminated by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn visible_width(s: &str) -> usize {
+    strip_ansi(s).chars().count()
+}
@@ -0,0 +1,63 @@
+//! Project toolchain detection from manifest files (Cargo.toml, package.json, go.mod,
+//! pyproject.toml): the suggested validation commands and a setup script that installs the
+//! toolchain into the agent image when it is missing.
+
+/// A language toolchain smith knows how to set up and validate.
+#[derive(Debug, PartialEq)]
+pub struct Toolchain {
+    pub name: &'static str,
+    /// Files at the repository root that identify it
+    pub markers: &'static [&'static str],
+    /// Commands that show an edit builds and passes its tests
+    pub validate: &'static [&'static str],
+    /// Setup script (project `script`) installing the toolchain on Alpine or Debian images
+    pub setup: &'static str,
+}
+
+/// Known toolchains, in detection order: the first whose marker is present wins, so a Rust
+/// crate with a package.json for its docs is still Rust.
+pub const TOOLCHAINS: &[Toolchain] = &[
+    Toolchain {
+        name: "rust",
+        markers: &["Cargo.toml"],
+        validate: &[
+            "cargo fmt --all -- --check",
+            "cargo clippy --all-targets -- -D warnings",
+            "cargo test",
+        ],
+        setup: "command -v cargo >/dev/null || apk add --no-cache cargo rust-clippy rustfmt || (apt-get update && apt-get install -y cargo rustfmt rust-clippy)",
+    },
+    Toolchain {
+        name: "go",
+        markers: &["go.mod"],
+        validate: &["go vet ./...", "go test ./..."],
+        setup: "command -v go >/dev/null || apk add --no-cache go || (apt-get update && apt-get install -y golang)",
+    },
+    Toolchain {
+        name: "node",
+        markers: &["package.json"],
+        validate: &["npm ci", "npm test --if-present", "npm run build --if-present"],
+        setup: "command -v npm >/dev/null || apk add --no-cache nodejs npm || (apt-get update && apt-get install -y nodejs npm)",
+    },
+    Toolchain {
+        name: "python",
+        markers: &["pyproject.toml", "requirements.txt", "setup.py"],
+        validate: &["python3 -m pip install -e .", "python3 -m pytest"],
+        setup: "command -v pip3 >/dev/null || apk add --no-cache python3 py3-pip || (apt-get update && apt-get install -y python3 python3-pip python3-venv)",
+    },
+];
+
+/// Toolchain of a repository whose root holds `files` (names only).
+pub fn detect<S: AsRef<str>>(files: &[S]) -> Option<&'static Toolchain> {
+    TOOLCHAINS.iter().find(|toolchain| {
+        toolchain
+            .markers
+            .iter()
+            .any(|marker| files.iter().any(|f| f.as_ref() == *marker))
+    })
+}
+
+/// Toolchain by name (as stored in a project's `toolchain`).
+pub fn by_name(name: &str) -> Option<&'static Toolchain> {
+    TOOLCHAINS.iter().find(|t| t.name == name)
+}
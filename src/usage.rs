@@ -0,0 +1,178 @@
+//! Provider usage: one JSON line per prompt sent to a spawned agent in
+//! `<config dir>/usage.jsonl`, with the model profile, provider, outcome and latency, summed
+//! per profile and provider for `smith model status --verbose`.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_dir;
+use crate::error::SmithError;
+
+/// Outcomes of a prompt.
+pub const OUTCOME_OK: &str = "ok";
+/// Provider rate limit or quota (HTTP 429)
+pub const OUTCOME_RATE_LIMITED: &str = "rate_limited";
+/// Provider server error (HTTP 5xx)
+pub const OUTCOME_SERVER_ERROR: &str = "server_error";
+pub const OUTCOME_ERROR: &str = "error";
+
+/// One prompt sent to a spawned agent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UsageRecord {
+    pub at: u64,
+    pub project: String,
+    /// Model profile of the project, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Provider id ("unknown" when neither the model nor the profile names one)
+    pub provider: String,
+    pub outcome: String,
+    pub latency_ms: u64,
+}
+
+/// Outcome of a prompt from its error message: rate limits and 5xx responses are told apart
+/// from other failures.
+pub fn classify(error: Option<&str>) -> &'static str {
+    let Some(error) = error else {
+        return OUTCOME_OK;
+    };
+    let lower = error.to_lowercase();
+    if lower.contains("rate limit")
+        || lower.contains("freeusagelimiterror")
+        || lower.contains("status code 429")
+        || lower.contains("statuscode\":429")
+    {
+        return OUTCOME_RATE_LIMITED;
+    }
+    let server_error = ["status code 5", "statuscode\":5", "http 5"]
+        .iter()
+        .any(|marker| {
+            lower.match_indices(marker).any(|(i, _)| {
+                let code = &lower[i + marker.len()..];
+                code.len() >= 2 && code.as_bytes()[..2].iter().all(u8::is_ascii_digit)
+            })
+        });
+    if server_error {
+        OUTCOME_SERVER_ERROR
+    } else {
+        OUTCOME_ERROR
+    }
+}
+
+/// Provider of a prompt: the `provider/` prefix of the model it ran with, else the profile's
+/// provider, else the prefix of the profile's model.
+pub fn provider_of(
+    model: Option<&str>,
+    profile_provider: Option<&str>,
+    profile_model: Option<&str>,
+) -> String {
+    let prefix = |m: &str| m.split_once('/').map(|(p, _)| p.trim().to_string());
+    model
+        .and_then(prefix)
+        .or_else(|| profile_provider.map(String::from))
+        .or_else(|| profile_model.and_then(prefix))
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Path of the usage file.
+pub fn usage_path() -> Result<PathBuf, SmithError> {
+    config_dir().map(|dir| dir.join("usage.jsonl"))
+}
+
+/// Move every recorded prompt of project `old` to `new`. Returns how many were updated.
+pub fn rename_project(old: &str, new: &str) -> Result<usize, SmithError> {
+    crate::history::rename_project_in_file(&usage_path()?, old, new)
+}
+
+/// Append a prompt to the usage file.
+pub fn append(record: &UsageRecord) -> Result<(), SmithError> {
+    let path = usage_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            SmithError::Config(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    let line = serde_json::to_string(record)
+        .map_err(|e| SmithError::Config(format!("Failed to serialize usage record: {}", e)))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| SmithError::Config(format!("Failed to open {}: {}", path.display(), e)))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| SmithError::Config(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Recorded prompts since `since` (unix seconds), oldest first; unreadable lines are skipped.
+pub fn load_since(since: u64) -> Result<Vec<UsageRecord>, SmithError> {
+    let path = usage_path()?;
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<UsageRecord>(line).ok())
+        .filter(|r| r.at >= since)
+        .collect())
+}
+
+/// Prompts of one model profile and provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageStats {
+    pub agent: Option<String>,
+    pub provider: String,
+    pub requests: u64,
+    pub rate_limited: u64,
+    pub server_errors: u64,
+    /// Other failures (timeouts, authentication, agent errors)
+    pub errors: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+}
+
+impl UsageStats {
+    /// Share of requests that failed for any reason, in percent.
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        (self.rate_limited + self.server_errors + self.errors) as f64 / self.requests as f64 * 100.0
+    }
+}
+
+/// `records` summed per model profile and provider, sorted by profile then provider.
+pub fn summarize(records: &[UsageRecord]) -> Vec<UsageStats> {
+    let mut groups: Vec<(Option<&str>, &str, Vec<&UsageRecord>)> = Vec::new();
+    for record in records {
+        let key = (record.agent.as_deref(), record.provider.as_str());
+        match groups.iter_mut().find(|(a, p, _)| (*a, *p) == key) {
+            Some((_, _, group)) => group.push(record),
+            None => groups.push((key.0, key.1, vec![record])),
+        }
+    }
+    groups.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+    groups
+        .into_iter()
+        .map(|(agent, provider, group)| {
+            let count =
+                |outcome: &str| group.iter().filter(|r| r.outcome == outcome).count() as u64;
+            let mut latencies: Vec<u64> = group.iter().map(|r| r.latency_ms).collect();
+            latencies.sort_unstable();
+            let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+            UsageStats {
+                agent: agent.map(String::from),
+                provider: provider.to_string(),
+                requests: group.len() as u64,
+                rate_limited: count(OUTCOME_RATE_LIMITED),
+                server_errors: count(OUTCOME_SERVER_ERROR),
+                errors: count(OUTCOME_ERROR),
+                p50_ms: percentile(50),
+                p95_ms: percentile(95),
+            }
+        })
+        .collect()
+}
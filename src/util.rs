@@ -0,0 +1,12 @@
+//! Small helpers shared across modules.
+
+/// 64-bit FNV-1a hash of `bytes`. Unlike `DefaultHasher` it is stable across builds, so it
+/// can name things that persist (cache directories, image tags, issue markers).
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}